@@ -0,0 +1,78 @@
+//! ============================================================================
+//! ⏱️ Cart calculation benchmark (1000-line cart)
+//! ============================================================================
+//! `cargo bench --bench cart_calculation`
+//!
+//! Product discount rules are sorted once in `add_product_discount`. The
+//! "per-line sort" row re-creates the old per-calculation clone + sort so
+//! both costs show side by side.
+
+use financial_engine::prelude::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const LINES: usize = 1000;
+const RULES: i32 = 20;
+const ROUNDS: u32 = 50;
+
+fn rules() -> Vec<DiscountRule> {
+    (0..RULES)
+        .map(|i| DiscountRule {
+            id: format!("R{}", i),
+            name: format!("Rule {}", i),
+            discount_type: DiscountType::Percentage(0.5),
+            // Out of priority order on purpose
+            priority: (i * 7) % RULES,
+            conditions: Vec::new(),
+            stackable: true,
+        })
+        .collect()
+}
+
+fn cart() -> Cart {
+    let mut cart = Cart::new();
+    for _ in 0..LINES {
+        let mut item = Item::new("SKU", Money::new(100, 0), 1.0);
+        item.id = "SKU".to_string();
+        cart.add_item(item);
+    }
+    cart
+}
+
+fn time(label: &str, mut run: impl FnMut()) -> Duration {
+    run(); // warm-up
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    let per_round = start.elapsed() / ROUNDS;
+    println!("{:<32} {:>10.3} ms", label, per_round.as_secs_f64() * 1000.0);
+    per_round
+}
+
+fn main() {
+    let mut engine = MixedScenarioEngine::new();
+    engine
+        .add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: rules(),
+            stackable: true,
+            max_discount_percent: None,
+        })
+        .expect("valid discount config");
+    let cart = cart();
+    let unsorted = rules();
+
+    println!("{} lines, {} rules on one SKU, {} rounds", LINES, RULES, ROUNDS);
+    let after = time("calculate_cart (presorted)", || {
+        black_box(engine.calculate_cart(black_box(&cart), &[], None).expect("cart calculates"));
+    });
+    let sorting = time("per-line sort (removed)", || {
+        for _ in 0..LINES {
+            let mut rules = black_box(&unsorted).clone();
+            rules.sort_by(DiscountRule::precedence);
+            black_box(rules);
+        }
+    });
+    println!("{:<32} {:>10.3} ms", "calculate_cart + per-line sort", (after + sorting).as_secs_f64() * 1000.0);
+}
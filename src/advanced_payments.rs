@@ -49,9 +49,131 @@ pub struct PosTransactionRequest {
     pub payments: Vec<PaymentComponent>, // ✅ List of mix payments
 }
 
+// 4. Split Payment Limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitPaymentConfig {
+    pub max_tenders: usize,
+    /// Allowed |paid - bill| difference after rounding to the minor unit
+    pub rounding_tolerance: Decimal,
+    /// Decimal places of the currency's minor unit (LKR cents = 2)
+    pub minor_unit_scale: u32,
+    /// Which tender absorbs the rounding residue
+    pub residue_target: ResidueTarget,
+}
+
+impl Default for SplitPaymentConfig {
+    fn default() -> Self {
+        SplitPaymentConfig {
+            max_tenders: 10,
+            rounding_tolerance: Decimal::new(1, 2), // 0.01
+            minor_unit_scale: 2,
+            residue_target: ResidueTarget::Cash,
+        }
+    }
+}
+
+// 5. Rounding Residue Target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResidueTarget {
+    /// First cash tender (falls back to the largest tender)
+    Cash,
+    Largest,
+    /// Tender at this position in `payments`
+    Index(usize),
+}
+
 pub struct AdvancedPaymentEngine;
 
 impl AdvancedPaymentEngine {
+    /// Validate tenders before anything is posted
+    pub fn validate_payments(
+        req: &PosTransactionRequest,
+        config: &SplitPaymentConfig,
+    ) -> Result<(), String> {
+        Self::reconcile_amounts(req, config).map(|_| ())
+    }
+
+    /// Round each tender and the bill to the minor unit, then move the residue
+    /// (bill - rounded tenders) onto the configured tender so the sum matches exactly.
+    pub fn reconcile_amounts(
+        req: &PosTransactionRequest,
+        config: &SplitPaymentConfig,
+    ) -> Result<Vec<Decimal>, String> {
+        // Free order (zero bill): nothing to capture, and no tender may be taken
+        if req.total_amount.round_dp(config.minor_unit_scale).is_zero() {
+            if !req.payments.is_empty() {
+                return Err(format!("Order #{} is free; no payment should be taken", req.order_id));
+            }
+            return Ok(Vec::new());
+        }
+        if req.payments.is_empty() {
+            return Err(format!("Order #{} has no payments", req.order_id));
+        }
+        if req.payments.len() > config.max_tenders {
+            return Err(format!(
+                "Too many tenders: {} (max {})",
+                req.payments.len(),
+                config.max_tenders
+            ));
+        }
+        for (index, payment) in req.payments.iter().enumerate() {
+            if payment.amount <= Decimal::ZERO {
+                return Err(format!(
+                    "Payment #{} amount must be positive, got {}",
+                    index + 1,
+                    payment.amount
+                ));
+            }
+        }
+
+        let mut amounts: Vec<Decimal> = req
+            .payments
+            .iter()
+            .map(|p| p.amount.round_dp(config.minor_unit_scale))
+            .collect();
+        let bill = req.total_amount.round_dp(config.minor_unit_scale);
+        let total_paid: Decimal = amounts.iter().sum();
+        let residue = bill - total_paid;
+
+        if residue.abs() > config.rounding_tolerance {
+            return Err(format!(
+                "Payment mismatch! Bill: {}, Paid: {}",
+                req.total_amount, total_paid
+            ));
+        }
+
+        if !residue.is_zero() {
+            let largest = amounts
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, amount)| **amount)
+                .map(|(index, _)| index)
+                .unwrap_or(0);
+            let target = match config.residue_target {
+                ResidueTarget::Cash => req
+                    .payments
+                    .iter()
+                    .position(|p| matches!(p.method, PaymentMethod::Cash))
+                    .unwrap_or(largest),
+                ResidueTarget::Largest => largest,
+                ResidueTarget::Index(index) if index < amounts.len() => index,
+                ResidueTarget::Index(index) => {
+                    return Err(format!("Residue tender #{} does not exist", index + 1))
+                }
+            };
+            amounts[target] += residue;
+            if amounts[target] <= Decimal::ZERO {
+                return Err(format!(
+                    "Rounding residue {} cannot be absorbed by payment #{}",
+                    residue,
+                    target + 1
+                ));
+            }
+        }
+
+        Ok(amounts)
+    }
+
     /// Convert Mixed Payments into Double-Entry Ledger format
     pub fn build_ledger_entries(
         req: PosTransactionRequest,
@@ -60,15 +182,40 @@ impl AdvancedPaymentEngine {
         uncleared_cheques_account: Uuid, // For Cheques
         cash_account: Uuid,
         bank_account: Uuid,
+    ) -> Result<Vec<JournalEntry>, String> {
+        Self::build_ledger_entries_with_config(
+            req,
+            &SplitPaymentConfig::default(),
+            revenue_account,
+            receivable_account,
+            uncleared_cheques_account,
+            cash_account,
+            bank_account,
+        )
+    }
+
+    /// Same as `build_ledger_entries` with explicit tender limits
+    pub fn build_ledger_entries_with_config(
+        req: PosTransactionRequest,
+        config: &SplitPaymentConfig,
+        revenue_account: Uuid,
+        receivable_account: Uuid,
+        uncleared_cheques_account: Uuid,
+        cash_account: Uuid,
+        bank_account: Uuid,
     ) -> Result<Vec<JournalEntry>, String> {
         // Returns entries to be posted
+        let amounts = Self::reconcile_amounts(&req, config)?;
+        let bill = req.total_amount.round_dp(config.minor_unit_scale);
+        if bill.is_zero() {
+            // Free order: no payment captured, revenue/discount are posted with the sale
+            return Ok(Vec::new());
+        }
 
         let mut entries = Vec::new();
         let transaction_id = Uuid::new_v4();
-        let mut total_paid = Decimal::ZERO;
 
-        for payment in req.payments {
-            total_paid += payment.amount;
+        for (payment, amount) in req.payments.into_iter().zip(amounts) {
 
             // DETERMINE DEBIT ACCOUNT (Where money goes IN)
             let (target_account, description) = match payment.method {
@@ -94,28 +241,20 @@ impl AdvancedPaymentEngine {
                 id: Uuid::new_v4(),
                 transaction_id,
                 account_id: target_account,
-                debit: payment.amount,
+                debit: amount,
                 credit: Decimal::ZERO,
                 description,
                 created_at: Utc::now(),
             });
         }
 
-        // Validate Totals
-        if total_paid != req.total_amount {
-            return Err(format!(
-                "Payment mismatch! Bill: {}, Paid: {}",
-                req.total_amount, total_paid
-            ));
-        }
-
         // CREDIT ENTRY (Revenue Up) -> One single entry for Total Sale
         entries.push(JournalEntry {
             id: Uuid::new_v4(),
             transaction_id,
             account_id: revenue_account,
             debit: Decimal::ZERO,
-            credit: req.total_amount,
+            credit: bill,
             description: format!("POS Sale Order #{}", req.order_id),
             created_at: Utc::now(),
         });
@@ -123,3 +262,117 @@ impl AdvancedPaymentEngine {
         Ok(entries)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(amounts: &[Decimal], total: Decimal) -> PosTransactionRequest {
+        PosTransactionRequest {
+            order_id: "ORD-1".to_string(),
+            shop_id: Uuid::new_v4(),
+            customer_id: None,
+            total_amount: total,
+            payments: amounts
+                .iter()
+                .map(|amount| PaymentComponent {
+                    method: PaymentMethod::Cash,
+                    amount: *amount,
+                })
+                .collect(),
+        }
+    }
+
+    fn build(req: PosTransactionRequest, config: &SplitPaymentConfig) -> Result<Vec<JournalEntry>, String> {
+        let account = Uuid::new_v4();
+        AdvancedPaymentEngine::build_ledger_entries_with_config(
+            req, config, account, account, account, account, account,
+        )
+    }
+
+    #[test]
+    fn test_negative_component_rejected() {
+        let req = request(&[Decimal::new(150, 0), Decimal::new(-50, 0)], Decimal::new(100, 0));
+        let err = build(req, &SplitPaymentConfig::default()).unwrap_err();
+        assert!(err.contains("must be positive"));
+    }
+
+    #[test]
+    fn test_free_order_takes_no_payment() {
+        let free = request(&[], Decimal::ZERO);
+        assert!(AdvancedPaymentEngine::reconcile_amounts(&free, &SplitPaymentConfig::default())
+            .unwrap()
+            .is_empty());
+        assert!(build(free, &SplitPaymentConfig::default()).unwrap().is_empty());
+
+        let err = build(request(&[Decimal::new(10, 0)], Decimal::ZERO), &SplitPaymentConfig::default()).unwrap_err();
+        assert!(err.contains("no payment should be taken"));
+    }
+
+    #[test]
+    fn test_too_many_tenders_rejected() {
+        let config = SplitPaymentConfig {
+            max_tenders: 2,
+            ..Default::default()
+        };
+        let req = request(&[Decimal::new(10, 0); 3], Decimal::new(30, 0));
+        let err = build(req, &config).unwrap_err();
+        assert!(err.contains("Too many tenders"));
+    }
+
+    #[test]
+    fn test_rounding_tolerant_match_balances() {
+        // 3 x 33.333 = 99.999 against a 100.00 bill
+        let req = request(&[Decimal::new(33333, 3); 3], Decimal::new(100, 0));
+        let entries = build(req, &SplitPaymentConfig::default()).unwrap();
+
+        let debits: Decimal = entries.iter().map(|e| e.debit).sum();
+        let credits: Decimal = entries.iter().map(|e| e.credit).sum();
+        assert_eq!(debits, credits);
+        // 3 tenders + the sale: a sub-cent difference is never posted as its own line
+        assert_eq!(entries.len(), 4);
+        assert!(entries.iter().all(|e| e.debit.scale() <= 2 && e.credit.scale() <= 2));
+        assert!(entries.iter().all(|e| !e.description.starts_with("Rounding adjustment")));
+
+        let req = request(&[Decimal::new(99, 0)], Decimal::new(100, 0));
+        assert!(build(req, &SplitPaymentConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_split_hundred_three_ways_residue_to_cash() {
+        // Rs.100 / 3 = 33.333333 per tender; rounds to 33.33 x 3 = 99.99
+        let third = Decimal::new(33_333_333, 6);
+        let mut req = request(&[third; 3], Decimal::new(100, 0));
+        req.payments[0].method = PaymentMethod::Card {
+            last4: "4242".to_string(),
+            terminal_id: "T1".to_string(),
+        };
+
+        let amounts =
+            AdvancedPaymentEngine::reconcile_amounts(&req, &SplitPaymentConfig::default()).unwrap();
+        // 0.01 residue lands on the first cash tender
+        assert_eq!(
+            amounts,
+            vec![Decimal::new(3333, 2), Decimal::new(3334, 2), Decimal::new(3333, 2)]
+        );
+        assert_eq!(amounts.iter().sum::<Decimal>(), Decimal::new(10000, 2));
+
+        let entries = build(req, &SplitPaymentConfig::default()).unwrap();
+        let debits: Decimal = entries.iter().map(|e| e.debit).sum();
+        assert_eq!(debits, Decimal::new(100, 0));
+        assert_eq!(entries.last().unwrap().credit, Decimal::new(100, 0));
+    }
+
+    #[test]
+    fn test_residue_to_configured_index() {
+        let third = Decimal::new(33_333_333, 6);
+        let req = request(&[third; 3], Decimal::new(100, 0));
+        let config = SplitPaymentConfig {
+            residue_target: ResidueTarget::Index(2),
+            ..Default::default()
+        };
+
+        let amounts = AdvancedPaymentEngine::reconcile_amounts(&req, &config).unwrap();
+        assert_eq!(amounts[2], Decimal::new(3334, 2));
+    }
+}
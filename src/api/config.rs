@@ -0,0 +1,161 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::rules::mixed_scenarios::{
+    CartBundle, CartDiscountConfig, MixedScenarioEngine, ProductDiscountConfig, ProductTaxConfig, SmallOrderTax,
+    StackingPolicy, TaxRate,
+};
+use crate::storage::database::{EntitySerializer, StorageBackend};
+use crate::tax::jurisdiction::JurisdictionResolver;
+use crate::types::cart::CartLimits;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// ⚙️ Engine Configuration (එන්ජින් සැකසුම්)
+/// ============================================================================
+/// Server එක ආරම්භ වන විට බදු සහ වට්ටම් නීති storage එකෙන් හෝ JSON rules file
+/// එකකින් පූරණය කරයි. Admin endpoints මගින් වෙනස් කළ config එක storage එකේ
+/// සුරකින බැවින් restart එකකින් පසුවද එය පවතී (stored config > file).
+///
+/// 🔑 Storage key of the active configuration
+pub const ENGINE_CONFIG_KEY: &str = "engine-config";
+pub const ENGINE_CONFIG_KIND: &str = "EngineConfig";
+
+/// 📋 Taxes, discounts, bundles and tax jurisdictions a `MixedScenarioEngine` is built from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineConfig {
+    #[serde(default)]
+    pub global_taxes: Vec<TaxRate>,
+    #[serde(default)]
+    pub product_taxes: Vec<ProductTaxConfig>,
+    #[serde(default)]
+    pub product_discounts: Vec<ProductDiscountConfig>,
+    #[serde(default)]
+    pub cart_discounts: Vec<CartDiscountConfig>,
+    #[serde(default)]
+    pub cart_bundles: Vec<CartBundle>,
+    /// Which discounts may combine
+    #[serde(default)]
+    pub stacking_policy: StackingPolicy,
+    #[serde(default)]
+    pub small_order_tax: Option<SmallOrderTax>,
+    /// Home jurisdiction (used when a request names none), nexus and postal overrides
+    #[serde(default)]
+    pub jurisdictions: JurisdictionResolver,
+}
+
+impl EngineConfig {
+    pub fn from_json(json: &str) -> EngineResult<Self> {
+        serde_json::from_str(json).map_err(|e| EngineError::Validation {
+            message: format!("Invalid engine config: {}", e),
+        })
+    }
+
+    /// 📄 Rules file (JSON)
+    pub fn from_file(path: &str) -> EngineResult<Self> {
+        let json = std::fs::read_to_string(path).map_err(|e| EngineError::Storage {
+            message: format!("Cannot read engine config '{}': {}", path, e),
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// 💾 Configuration saved by `save` (None => never saved)
+    pub fn from_storage(backend: &dyn StorageBackend) -> EngineResult<Option<Self>> {
+        match backend.get(ENGINE_CONFIG_KEY)? {
+            Some(json) => EntitySerializer::from_json(&json).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Stored configuration, else the rules file, else empty
+    pub fn load(backend: &dyn StorageBackend, path: Option<&str>) -> EngineResult<Self> {
+        if let Some(config) = Self::from_storage(backend)? {
+            return Ok(config);
+        }
+        match path {
+            Some(path) => Self::from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, backend: &dyn StorageBackend) -> EngineResult<()> {
+        let json = EntitySerializer::to_versioned_json(ENGINE_CONFIG_KIND, self)?;
+        backend.set(ENGINE_CONFIG_KEY, &json)
+    }
+
+    /// 📸 Configuration currently on `engine`
+    pub fn of(engine: &MixedScenarioEngine) -> Self {
+        let snapshot = engine.snapshot();
+        EngineConfig {
+            global_taxes: snapshot.global_tax_rates,
+            product_taxes: snapshot.product_taxes.into_values().collect(),
+            product_discounts: snapshot.product_discounts.into_values().collect(),
+            cart_discounts: snapshot.cart_discounts,
+            cart_bundles: snapshot.cart_bundles,
+            stacking_policy: snapshot.stacking_policy,
+            small_order_tax: snapshot.small_order_tax,
+            jurisdictions: snapshot.jurisdictions,
+        }
+    }
+
+    /// Replace `engine`'s configuration with this one (validated by the engine)
+    pub fn apply(self, engine: &mut MixedScenarioEngine) -> EngineResult<()> {
+        engine.replace_taxes(self.global_taxes, self.product_taxes)?;
+        engine.replace_discounts(self.product_discounts, self.cart_discounts)?;
+        engine.replace_cart_bundles(self.cart_bundles)?;
+        engine.set_stacking_policy(self.stacking_policy);
+        engine.set_small_order_tax(self.small_order_tax);
+        engine.set_jurisdiction_resolver(self.jurisdictions);
+        Ok(())
+    }
+
+    /// 🏗️ Server engine: this configuration, with cart limits read from the environment
+    pub fn build(self) -> EngineResult<MixedScenarioEngine> {
+        let mut engine = MixedScenarioEngine::new();
+        engine.set_cart_limits(CartLimits::from_env());
+        self.apply(&mut engine)?;
+        Ok(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::storage::database::InMemoryStorage;
+
+    #[test]
+    fn test_config_loads_from_json_and_round_trips_through_storage() {
+        let json = r#"{
+            "global_taxes": [{"name": "VAT", "rate": 18.0, "jurisdiction": "LK", "applies_to": "All"}],
+            "jurisdictions": {"home": "LK"}
+        }"#;
+        let config = EngineConfig::from_json(json).unwrap();
+        let engine = config.clone().build().unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(100, 0), 1.0));
+        assert_eq!(engine.calculate_cart(&cart, &[], None).unwrap().total_tax, Money::new(18, 0));
+
+        // Stored config wins over the rules file
+        let storage = InMemoryStorage::new();
+        assert!(EngineConfig::load(&storage, None).unwrap().global_taxes.is_empty());
+        EngineConfig::of(&engine).save(&storage).unwrap();
+        let loaded = EngineConfig::load(&storage, Some("/nonexistent/rules.json")).unwrap();
+        assert_eq!(loaded.global_taxes.len(), 1);
+        assert_eq!(loaded.jurisdictions.home(), Some("LK"));
+        assert!(EngineConfig::from_file("/nonexistent/rules.json").is_err());
+
+        // Bundles, stacking policy and small-order tax survive a restart too
+        let json = r#"{
+            "cart_bundles": [{"id": "B1", "name": "Tea set", "items": ["Tea", "Cup"], "discount_percent": 10.0}],
+            "stacking_policy": {"max_per_line": 1},
+            "small_order_tax": {"threshold": {"amount": 10000}, "treatment": "Exempt"}
+        }"#;
+        EngineConfig::of(&EngineConfig::from_json(json).unwrap().build().unwrap()).save(&storage).unwrap();
+        let loaded = EngineConfig::load(&storage, None).unwrap();
+        assert_eq!(loaded.cart_bundles.len(), 1);
+        assert_eq!(loaded.stacking_policy.max_per_line, Some(1));
+        assert!(loaded.small_order_tax.is_some());
+
+        let invalid = r#"{"global_taxes": [{"name": "Bad", "rate": 250.0, "jurisdiction": "LK", "applies_to": "All"}]}"#;
+        assert!(EngineConfig::from_json(invalid).unwrap().build().is_err());
+    }
+}
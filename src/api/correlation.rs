@@ -0,0 +1,53 @@
+use axum::extract::Request;
+use axum::http::{HeaderMap, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Instrument;
+
+/// ============================================================================
+/// 🧵 Correlation IDs (ඉල්ලීම් හඳුනාගැනීම)
+/// ============================================================================
+/// සෑම HTTP ඉල්ලීමකටම correlation id එකක් ලැබේ - පැමිණෙන `X-Request-Id` එක
+/// (වලංගු නම්) හෝ අලුත් UUID එකක්. එය tracing span එකේ, audit entries වල,
+/// error responses වල සහ response header එකේ දිස් වේ, එබැවින් පාරිභෝගික
+/// පැමිණිල්ලක් gateway → engine → ledger හරහා සොයාගත හැක.
+///
+/// 🏷️ Header carrying the id in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Incoming ids longer than this, or with characters outside `[A-Za-z0-9-_.:]`, are replaced
+/// (they end up in logs and audit records)
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// 🔍 Correlation id of a request that passed through `correlation_middleware`
+pub fn correlation_id(headers: &HeaderMap) -> Option<&str> {
+    headers.get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok())
+}
+
+/// 🧵 Middleware: honor or assign `X-Request-Id`, run the request in a span carrying it,
+/// and echo it on the response. Handlers read it back with `correlation_id(&headers)`.
+pub async fn correlation_middleware(mut request: Request, next: Next) -> Response {
+    let id = correlation_id(request.headers())
+        .filter(|id| is_valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    // Valid by construction (checked charset or UUID)
+    let value = HeaderValue::from_str(&id).expect("correlation id is a valid header value");
+    request.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+
+    let span = tracing::info_span!(
+        "http_request",
+        correlation_id = %id,
+        method = %request.method(),
+        path = %request.uri().path()
+    );
+    let mut response = next.run(request).instrument(span).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    response
+}
@@ -1,9 +1,9 @@
 use crate::core::money::Money;
-use crate::types::cart::Cart;
+use crate::types::cart::{Cart, CartLimits};
 use crate::types::item::Item;
-use crate::types::currency::Currency;
+use crate::types::currency::{Currency, CurrencyRegistry};
 use crate::core::calculation::{CalculationEngine, CalculationResult};
-use crate::core::errors::EngineResult;
+use crate::core::errors::{EngineError, EngineResult};
 use crate::core::rounding::RoundingMode;
 
 /// ============================================================================
@@ -21,6 +21,7 @@ pub struct FinancialEngine {
     pub calculator: CalculationEngine,
     pub rounding: RoundingMode,
     pub rules: Vec<Box<dyn Rule + Send + Sync>>,
+    pub limits: CartLimits,
     
     // 🌍 Advanced Modules
     pub ledger: GeneralLedger,
@@ -35,17 +36,29 @@ impl FinancialEngine {
             calculator: CalculationEngine::new(),
             rounding: RoundingMode::Standard,
             rules: Vec::new(),
+            limits: CartLimits::from_env(),
             ledger: GeneralLedger::new(),
             inventory: InventoryManager::new(),
         }
     }
 
     /// ➕ භාණ්ඩයක් එකතු කරන්න (Add Item)
-    pub fn add_item(&mut self, name: &str, price: f64, quantity: f64) -> &mut Self {
-        let money_price = Money::from_float(price);
-        let item = Item::new(name, money_price, quantity);
-        self.cart.add_item(item);
-        self
+    /// Price is validated against the cart currency: finite, non-negative and no more
+    /// decimal places than the currency has (LKR 19.99 ok, 19.999 rejected).
+    pub fn add_item(&mut self, name: &str, price: f64, quantity: f64) -> EngineResult<&mut Self> {
+        let money_price = Money::try_from_float(price, self.cart.currency)?;
+        self.add_item_money(name, money_price, quantity)
+    }
+
+    /// ➕ `Money` මිලක් සමඟ භාණ්ඩයක් එකතු කරන්න (float නැත)
+    pub fn add_item_money(&mut self, name: &str, price: Money, quantity: f64) -> EngineResult<&mut Self> {
+        if price.is_negative() {
+            return Err(EngineError::Validation {
+                message: format!("Price of '{}' cannot be negative", name),
+            });
+        }
+        self.cart.add_item_checked(Item::new(name, price, quantity), &self.limits)?;
+        Ok(self)
     }
 
     /// ➕ රීතියක් එකතු කරන්න (Add Rule)
@@ -54,10 +67,11 @@ impl FinancialEngine {
         self
     }
 
-    /// 💱 මුදල් ඒකකය මාරු කරන්න (Set Currency)
-    pub fn set_currency(&mut self, currency: Currency) -> &mut Self {
+    /// 💱 මුදල් ඒකකය මාරු කරන්න (Set Currency) - deployment එක සහය දක්වන ඒවා පමණි
+    pub fn set_currency(&mut self, currency: Currency) -> EngineResult<&mut Self> {
+        CurrencyRegistry::configured().ensure_supported(currency)?;
         self.cart.currency = currency;
-        self
+        Ok(self)
     }
 
     /// 🔢 වට කරන ක්‍රමය වෙනස් කරන්න (Set Rounding)
@@ -81,3 +95,23 @@ impl FinancialEngine {
         &mut self.inventory
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_item_validates_price() {
+        let mut engine = FinancialEngine::new();
+        assert!(engine.add_item("Broken", f64::NAN, 1.0).is_err());
+        assert!(engine.add_item("Broken", f64::INFINITY, 1.0).is_err());
+        assert!(engine.add_item("Fuel", 19.999, 1.0).is_err());
+        assert!(engine.add_item("Refund?", -5.0, 1.0).is_err());
+        assert!(engine.cart.items.is_empty());
+
+        engine.add_item("Tea", 19.99, 2.0).unwrap();
+        engine.add_item_money("Milk", Money::new(250, 0), 1.0).unwrap();
+        assert_eq!(engine.cart.items[0].price, Money::from_cents(1999));
+        assert_eq!(engine.cart.subtotal(), Money::new(289, 98));
+    }
+}
@@ -331,6 +331,9 @@ mod tests {
             discount_total: Money::new(10, 0),
             tax_total: Money::new(9, 0),
             grand_total: Money::new(99, 0),
+            taxable_fees: Money::zero(),
+            non_taxable_fees: Money::zero(),
+            shipping_discount: Money::zero(),
         };
 
         let flutter_response: FlutterCalculationResponse = result.into();
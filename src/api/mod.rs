@@ -1,4 +1,9 @@
+pub mod config;
+#[cfg(feature = "server")]
+pub(crate) mod correlation;
 pub mod facade;
 pub mod ffi;
 pub mod rest;
+#[cfg(feature = "server")]
 pub mod routes; // Added new API routes for Microservice
+pub mod webhook;
@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 use crate::core::money::Money;
 use crate::core::errors::{EngineResult, EngineError};
+use crate::core::i18n::Locale;
+use crate::rules::mixed_scenarios::CartCalculation;
+use crate::subscription::proration::{ProrationMethod, ProrationResult};
+use crate::tax::sourcing::TaxSourcing;
+use crate::types::cart::Cart;
+use chrono::{DateTime, Utc};
 
 /// ============================================================================
 /// 🌐 REST/GraphQL API Interface (API අතුරුමුහුණත)
@@ -62,6 +68,29 @@ pub struct ApiError {
     pub message: String,
     pub field: Option<String>,
     pub details: Option<serde_json::Value>,
+    /// `X-Request-Id` of the failed request (quote it when reporting issues)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl ApiError {
+    /// ❌ Build from an engine error; `code` is `EngineError::code()`, `details.sub_code` when present
+    pub fn from_engine_error(error: &EngineError, locale: Locale) -> Self {
+        ApiError {
+            code: error.code().to_string(),
+            message: error.localized_message(locale),
+            field: None,
+            details: error
+                .sub_code()
+                .map(|sub_code| serde_json::json!({ "sub_code": sub_code })),
+            correlation_id: None,
+        }
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: Option<&str>) -> Self {
+        self.correlation_id = correlation_id.map(str::to_string);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +126,7 @@ impl<T> ApiResponse<T> {
                 message: message.to_string(),
                 field: None,
                 details: None,
+                correlation_id: Some(request_id.to_string()),
             }),
             pagination: None,
         }
@@ -129,9 +159,14 @@ pub struct ItemInput {
     pub discount_eligible: bool,
 }
 
+/// 🏷️ Current `CalculationResponse` schema version
+pub const API_SCHEMA_VERSION: u32 = 1;
+
 /// 💵 Calculation Response (ගණනය කිරීමේ ප්‍රතිචාරය)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalculationResponse {
+    /// Bumped whenever the response shape changes
+    pub schema_version: u32,
     pub subtotal: MoneyDto,
     pub discount_total: MoneyDto,
     pub tax_total: MoneyDto,
@@ -139,6 +174,122 @@ pub struct CalculationResponse {
     pub applied_discounts: Vec<AppliedDiscount>,
     pub applied_taxes: Vec<AppliedTax>,
     pub breakdown: Vec<LineItemBreakdown>,
+    pub tax_display: TaxDisplayMode,
+    /// Ex: "Prices include tax of Rs.18.00" (Inclusive mode only)
+    pub tax_note: Option<String>,
+}
+
+/// 🏷️ Tax Display Mode (බදු පෙන්වන ආකාරය)
+/// Calculation එක එකමයි; වෙනස් වන්නේ මිල ගණන් පෙන්වන ආකාරය පමණි.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TaxDisplayMode {
+    /// Net prices, tax added on top
+    #[default]
+    Exclusive,
+    /// Tax-inclusive prices with a "tax included" note
+    Inclusive,
+}
+
+impl CalculationResponse {
+    /// 🔄 Build the API response from an engine calculation
+    /// Inclusive mode grosses unit prices and line subtotals up by each line's effective tax,
+    /// while the grand total stays identical in both modes.
+    pub fn from_calculation(cart: &Cart, calculation: &CartCalculation, display: TaxDisplayMode) -> Self {
+        let currency = cart.currency.code();
+        let dto = |money: Money| MoneyDto::with_currency(money, &currency);
+
+        let mut breakdown = Vec::new();
+        let mut applied_discounts = Vec::new();
+        let mut applied_taxes: Vec<AppliedTax> = Vec::new();
+        let mut shown_subtotal = Money::zero();
+        let mut shown_discount = Money::zero();
+
+        for line in &calculation.items {
+            let item = cart.items.iter().find(|i| i.id == line.item_id);
+            let quantity = item.map(|i| i.quantity).unwrap_or(0.0);
+            let unit_price = item.map(|i| i.price).unwrap_or_else(Money::zero);
+            let net = line.base_amount - line.discount_amount;
+            let line_total = net + line.tax_amount;
+
+            let (shown_unit, line_subtotal) = match display {
+                TaxDisplayMode::Exclusive => (unit_price, line.base_amount),
+                TaxDisplayMode::Inclusive => {
+                    let ratio = if net.is_zero() {
+                        0.0
+                    } else {
+                        line.tax_amount.amount as f64 / net.amount as f64
+                    };
+                    (
+                        unit_price + unit_price.mul_ratio(ratio),
+                        line.base_amount + line.base_amount.mul_ratio(ratio),
+                    )
+                }
+            };
+            let line_discount = match display {
+                TaxDisplayMode::Exclusive => line.discount_amount,
+                // Keeps subtotal - discount == total exact after rounding
+                TaxDisplayMode::Inclusive => line_subtotal - line_total,
+            };
+
+            shown_subtotal = shown_subtotal + line_subtotal;
+            shown_discount = shown_discount + line_discount;
+
+            for detail in &line.discount_details {
+                applied_discounts.push(AppliedDiscount {
+                    code: None,
+                    name: detail.name.clone(),
+                    discount_type: detail.rule_id.clone(),
+                    amount: dto(detail.amount),
+                });
+            }
+            for detail in &line.tax_details {
+                match applied_taxes
+                    .iter_mut()
+                    .find(|t| t.name == detail.name && t.rate == detail.rate)
+                {
+                    Some(existing) => {
+                        existing.amount = dto(Money::from_cents(existing.amount.amount) + detail.amount)
+                    }
+                    None => applied_taxes.push(AppliedTax {
+                        name: detail.name.clone(),
+                        rate: detail.rate,
+                        amount: dto(detail.amount),
+                    }),
+                }
+            }
+
+            breakdown.push(LineItemBreakdown {
+                item_id: line.item_id.clone(),
+                item_name: item.map(|i| i.name.clone()).unwrap_or_default(),
+                unit_price: dto(shown_unit),
+                quantity,
+                subtotal: dto(line_subtotal),
+                discount: dto(line_discount),
+                tax: dto(line.tax_amount),
+                total: dto(line_total),
+            });
+        }
+
+        let tax_note = match display {
+            TaxDisplayMode::Exclusive => None,
+            TaxDisplayMode::Inclusive => {
+                Some(format!("Prices include tax of {}", calculation.total_tax))
+            }
+        };
+
+        CalculationResponse {
+            schema_version: API_SCHEMA_VERSION,
+            subtotal: dto(shown_subtotal),
+            discount_total: dto(shown_discount),
+            tax_total: dto(calculation.total_tax),
+            grand_total: dto(calculation.grand_total),
+            applied_discounts,
+            applied_taxes,
+            breakdown,
+            tax_display: display,
+            tax_note,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -148,6 +299,16 @@ pub struct MoneyDto {
     pub currency: String,
 }
 
+impl MoneyDto {
+    pub fn with_currency(money: Money, currency: &str) -> Self {
+        MoneyDto {
+            amount: money.amount,
+            formatted: money.to_string(),
+            currency: currency.to_string(),
+        }
+    }
+}
+
 impl From<Money> for MoneyDto {
     fn from(money: Money) -> Self {
         MoneyDto {
@@ -209,6 +370,74 @@ pub struct ReportRequest {
     pub filters: Option<serde_json::Value>,
 }
 
+/// 🔁 Subscription Change Preview Request (plan upgrade/downgrade)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionPreviewRequest {
+    pub subscription_id: Option<String>,
+    pub old_plan_amount: Money,
+    pub new_plan_amount: Money,
+    pub billing_cycle_start: DateTime<Utc>,
+    pub billing_cycle_end: DateTime<Utc>,
+    /// Defaults to now
+    pub change_date: Option<DateTime<Utc>>,
+    /// Defaults to `SecondBased`
+    pub proration_method: Option<ProrationMethod>,
+    pub currency: Option<String>,
+}
+
+/// 🔁 Subscription Change Preview Response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionPreviewResponse {
+    /// Charged today
+    pub immediate_charge: MoneyDto,
+    /// Unused portion of the old plan
+    pub credit: MoneyDto,
+    /// New plan for the rest of the cycle
+    pub prorated_charge: MoneyDto,
+    /// Credit left over after today, applied to the renewal
+    pub carried_credit: MoneyDto,
+    pub next_renewal_amount: MoneyDto,
+    pub next_renewal_date: DateTime<Utc>,
+    pub proration_factor: f64,
+    pub days_remaining: i64,
+    pub days_total: i64,
+}
+
+impl SubscriptionPreviewResponse {
+    /// `CreditNext` defers the whole net amount to the renewal; other methods charge a
+    /// positive net today and carry a negative net (downgrade) as credit.
+    pub fn from_proration(
+        result: &ProrationResult,
+        new_plan_amount: Money,
+        method: &ProrationMethod,
+        currency: &str,
+    ) -> Self {
+        let dto = |money: Money| MoneyDto::with_currency(money, currency);
+        let (immediate, carried, renewal) = match method {
+            ProrationMethod::CreditNext => (Money::zero(), Money::zero(), new_plan_amount + result.net_amount),
+            _ if result.net_amount.is_negative() => {
+                let credit = result.net_amount.abs();
+                (Money::zero(), credit, new_plan_amount - credit)
+            }
+            _ => (result.net_amount, Money::zero(), new_plan_amount),
+        };
+        // A credit larger than the renewal leaves nothing to pay (the rest stays on account)
+        let renewal = if renewal.is_negative() { Money::zero() } else { renewal };
+
+        SubscriptionPreviewResponse {
+            immediate_charge: dto(immediate),
+            credit: dto(result.credit_amount),
+            prorated_charge: dto(result.charge_amount),
+            carried_credit: dto(carried),
+            next_renewal_amount: dto(renewal),
+            next_renewal_date: result.next_billing_date,
+            proration_factor: result.proration_factor,
+            days_remaining: result.days_remaining,
+            days_total: result.days_total,
+        }
+    }
+}
+
 /// 🛒 Order Request (ඇණවුම් ඉල්ලීම)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderRequest {
@@ -218,6 +447,14 @@ pub struct OrderRequest {
     pub shipping: Option<ShippingInput>,
 }
 
+impl OrderRequest {
+    /// 📍 Jurisdiction this order is taxed in (`tax_region` is the origin)
+    pub fn tax_jurisdiction(&self, sourcing: TaxSourcing) -> Option<String> {
+        let destination = self.shipping.as_ref().map(ShippingInput::tax_region);
+        sourcing.resolve(self.calculation.tax_region.as_deref(), destination.as_deref())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomerInput {
     pub id: Option<String>,
@@ -239,6 +476,13 @@ pub struct ShippingInput {
     pub address: AddressInput,
 }
 
+impl ShippingInput {
+    /// Destination region code (Ex: "US-CA", or "LK" without a state)
+    pub fn tax_region(&self) -> String {
+        TaxSourcing::region_code(&self.address.country, self.address.state.as_deref())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddressInput {
     pub line1: String,
@@ -299,6 +543,8 @@ impl ApiEndpoints {
     pub const ORDER_CREATE: &'static str = "/api/v1/orders";
     pub const ORDER_GET: &'static str = "/api/v1/orders/:id";
     pub const ORDER_LIST: &'static str = "/api/v1/orders";
+    /// Admin bearer token required (same as `CONFIG_*`)
+    pub const ORDER_STATUS: &'static str = "/api/v1/orders/:id/status";
     
     // Refunds
     pub const REFUND_CREATE: &'static str = "/api/v1/refunds";
@@ -313,10 +559,17 @@ impl ApiEndpoints {
     pub const LEDGER_ENTRIES: &'static str = "/api/v1/ledger/entries";
     pub const LEDGER_BALANCE: &'static str = "/api/v1/ledger/balance/:account_id";
     
+    // Subscriptions
+    pub const SUBSCRIPTION_PREVIEW: &'static str = "/api/v1/subscriptions/preview";
+
     // Inventory
     pub const INVENTORY_STOCK: &'static str = "/api/v1/inventory/stock";
     pub const INVENTORY_MOVEMENT: &'static str = "/api/v1/inventory/movements";
     
+    // Admin configuration (bearer token)
+    pub const CONFIG_TAXES: &'static str = "/api/v1/config/taxes";
+    pub const CONFIG_DISCOUNTS: &'static str = "/api/v1/config/discounts";
+
     // Health & Meta
     pub const HEALTH: &'static str = "/api/v1/health";
     pub const VERSION: &'static str = "/api/v1/version";
@@ -349,6 +602,51 @@ mod tests {
         assert!(response.data.is_some());
     }
 
+    fn vat_cart() -> (Cart, CartCalculation) {
+        use crate::rules::mixed_scenarios::{MixedScenarioEngine, TaxAppliesTo, TaxRate};
+        use crate::types::item::Item;
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "ALL", TaxAppliesTo::All)).unwrap();
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(50, 0), 2.0));
+        let calculation = engine.calculate_cart(&cart, &[], None).unwrap();
+        (cart, calculation)
+    }
+
+    #[test]
+    fn test_tax_display_exclusive() {
+        let (cart, calculation) = vat_cart();
+        let response = CalculationResponse::from_calculation(&cart, &calculation, TaxDisplayMode::Exclusive);
+        let json = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json["schema_version"], API_SCHEMA_VERSION);
+        assert_eq!(json["tax_display"], "Exclusive");
+        assert!(json["tax_note"].is_null());
+        assert_eq!(json["breakdown"][0]["unit_price"]["amount"], 5000);
+        assert_eq!(json["subtotal"]["amount"], 10000);
+        assert_eq!(json["tax_total"]["amount"], 1800);
+        assert_eq!(json["grand_total"]["amount"], 11800);
+        assert_eq!(json["applied_taxes"][0]["name"], "VAT");
+        assert_eq!(json["applied_taxes"][0]["rate"], 18.0);
+        assert_eq!(json["applied_taxes"][0]["amount"]["amount"], 1800);
+    }
+
+    #[test]
+    fn test_tax_display_inclusive() {
+        let (cart, calculation) = vat_cart();
+        let response = CalculationResponse::from_calculation(&cart, &calculation, TaxDisplayMode::Inclusive);
+        let json = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json["tax_display"], "Inclusive");
+        assert_eq!(json["tax_note"], "Prices include tax of Rs.18.00");
+        assert_eq!(json["breakdown"][0]["unit_price"]["amount"], 5900);
+        assert_eq!(json["subtotal"]["amount"], 11800);
+        assert_eq!(json["discount_total"]["amount"], 0);
+        assert_eq!(json["grand_total"]["amount"], 11800);
+    }
+
     #[test]
     fn test_money_dto_conversion() {
         let money = Money::new(100, 50);
@@ -1,16 +1,47 @@
+use crate::api::config::EngineConfig;
+use crate::api::correlation::{correlation_id, correlation_middleware};
+use crate::api::rest::{
+    ApiEndpoints, ApiError, OrderRequest, SubscriptionPreviewRequest, SubscriptionPreviewResponse,
+};
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::health::{HealthStatus, StatusRegistry};
+use crate::core::i18n::Locale;
+use crate::core::metrics;
+use crate::order::{sql as order_sql, Order, OrderStatus, DEFAULT_PAGE_SIZE};
 use crate::refund::processor::RefundProcessor;
 use crate::refund::types::RefundRequest;
-use crate::rules::mixed_scenarios::{CartCalculation, MixedScenarioEngine};
+use crate::rules::mixed_scenarios::{
+    CalculationContext, CalculationOrder, CartCalculation, CartDiscountConfig, MixedScenarioEngine,
+    ProductDiscountConfig, ProductTaxConfig, TaxRate,
+};
+use crate::security::audit_trail::{AuditAction, AuditEntry, AuditSeverity, AuditTrail};
+use crate::storage::connector;
+use crate::storage::database::{InMemoryStorage, StorageBackend};
+use crate::subscription::proration::{
+    ProrationEngine, ProrationMethod, ProrationRequest, DEFAULT_FACTOR_PRECISION,
+};
+use crate::tax::jurisdiction::JurisdictionResolver;
+use crate::tax::reporting::{ReportGranularity, TaxReport, TaxTransaction};
+use crate::tax::sourcing::TaxSourcing;
 use crate::types::cart::Cart;
+use crate::types::customer::CustomerContext;
+use crate::types::currency::CurrencyRegistry;
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{Json, Path, Query, State},
+    http::{
+        header::{ACCEPT_LANGUAGE, AUTHORIZATION},
+        HeaderMap, StatusCode,
+    },
     response::IntoResponse,
     routing::{get, post},
     Json as AxumJson, Router,
 };
-use serde::Deserialize;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use tokio::sync::Semaphore;
 
 /// ============================================================================
 /// 🌐 API Routing (API මංපෙත්)
@@ -19,21 +50,159 @@ use std::sync::Arc;
 /// JSON input එකක් ගෙන එය Rust struct එකකට හරවා, එන්ජිමට යවයි.
 
 #[derive(Clone)]
-pub struct AppState {
-    pub engine: Arc<MixedScenarioEngine>,
+pub(crate) struct AppState {
+    /// Swapped whole by the admin config endpoints; requests keep the engine they started with
+    pub engine: Arc<RwLock<Arc<MixedScenarioEngine>>>,
     pub refund_processor: Arc<RefundProcessor>,
+    pub status: Arc<StatusRegistry>,
+    pub audit: Arc<Mutex<AuditTrail>>,
+    /// Recorded sales (`TaxTransaction`) the reports read, orders, engine config
+    pub storage: Arc<dyn StorageBackend>,
+    /// Bearer token for `/api/v1/config/*` and order status changes; None => those endpoints disabled
+    pub admin_token: Option<Arc<str>>,
+    /// Serializes order status changes (load → transition → SQL → storage)
+    pub order_updates: Arc<tokio::sync::Mutex<()>>,
+    /// Serializes admin config updates (copy → change → save → swap)
+    pub config_updates: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl AppState {
+    /// Engine currently serving requests
+    fn engine(&self) -> Arc<MixedScenarioEngine> {
+        self.engine.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
 }
 
 /// 📋 Calculate Request DTO
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct CalculateRequest {
     pub cart: Cart,
     pub promo_codes: Vec<String>,
     pub jurisdiction: Option<String>,
+    /// "DiscountFirst" | "TaxFirst" | "Parallel"; None => engine default
+    #[serde(default)]
+    pub calculation_order: Option<String>,
+    /// Groups and purchase history for customer discount conditions (overrides `cart.customer`)
+    #[serde(default)]
+    pub customer: Option<CustomerContext>,
+    /// Price as of this instant (previews, back-dated orders); None => now
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// ISO code the cart must be priced in (Ex: "LKR")
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl CalculateRequest {
+    /// Engine honoring this request's `calculation_order` (the shared one is cloned only if it differs)
+    fn engine(&self, shared: &Arc<MixedScenarioEngine>) -> EngineResult<Arc<MixedScenarioEngine>> {
+        let Some(order) = self.calculation_order.as_deref() else {
+            return Ok(shared.clone());
+        };
+        let order = CalculationOrder::parse(order)?;
+        if order == shared.calculation_order() {
+            return Ok(shared.clone());
+        }
+        let mut engine = (**shared).clone();
+        engine.set_calculation_order(order);
+        Ok(Arc::new(engine))
+    }
+
+    /// Engine inputs of this request (the customer rides on `priced_cart`)
+    fn context(&self) -> EngineResult<CalculationContext> {
+        let currency = match self.currency.as_deref() {
+            Some(code) => Some(CurrencyRegistry::configured().resolve(code)?),
+            None => None,
+        };
+        Ok(CalculationContext {
+            jurisdiction: self.jurisdiction.clone(),
+            customer: None,
+            timestamp: self.timestamp,
+            currency,
+        })
+    }
+
+    /// Cart carrying this request's `customer` (borrowed as-is without one)
+    fn priced_cart(&self) -> Cow<'_, Cart> {
+        match &self.customer {
+            Some(customer) => {
+                let mut cart = self.cart.clone();
+                cart.customer = Some(customer.clone());
+                Cow::Owned(cart)
+            }
+            None => Cow::Borrowed(&self.cart),
+        }
+    }
+}
+
+/// Largest batch accepted by `/calculate/batch`
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// 📋 Batch Calculate Request DTO
+#[derive(Clone, Deserialize)]
+pub struct BatchCalculateRequest {
+    pub requests: Vec<CalculateRequest>,
+}
+
+/// 📋 One batch entry's outcome (same position as its request)
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub result: Option<CartCalculation>,
+    pub error: Option<ApiError>,
+}
+
+/// 📋 Tax report query (`?from=..&to=..&granularity=quarter&format=csv`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaxReportQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// "day" | "month" | "quarter" | "year"; None => month
+    #[serde(default)]
+    pub granularity: Option<String>,
+    /// "json" (default) | "csv"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// ⚙️ Replaces every configured tax (`POST /api/v1/config/taxes`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaxConfigUpdate {
+    #[serde(default)]
+    pub global_taxes: Vec<TaxRate>,
+    #[serde(default)]
+    pub product_taxes: Vec<ProductTaxConfig>,
+    /// Home jurisdiction, nexus and overrides (None => unchanged)
+    #[serde(default)]
+    pub jurisdictions: Option<JurisdictionResolver>,
+}
+
+/// ⚙️ Replaces every configured discount (`POST /api/v1/config/discounts`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscountConfigUpdate {
+    #[serde(default)]
+    pub product_discounts: Vec<ProductDiscountConfig>,
+    #[serde(default)]
+    pub cart_discounts: Vec<CartDiscountConfig>,
+}
+
+/// 📄 Order list query (`?page=2&per_page=50`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderListQuery {
+    #[serde(default)]
+    pub page: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+}
+
+/// 🚦 Order status change (`{"status": "paid"}`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderStatusUpdate {
+    pub status: String,
 }
 
 /// 📋 Refund Request DTO
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ApiRefundRequest {
     pub original_cart: Cart,
     pub original_calculation: CartCalculation,
@@ -42,57 +211,989 @@ pub struct ApiRefundRequest {
 
 // --- Handlers ---
 
+/// ❌ Engine error -> JSON `ApiError` in the caller's language (`Accept-Language`)
+fn error_response(status: StatusCode, headers: &HeaderMap, error: &EngineError) -> axum::response::Response {
+    metrics::record_error(error);
+    let locale = Locale::resolve(headers.get(ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()));
+    let body = ApiError::from_engine_error(error, locale).with_correlation_id(correlation_id(headers));
+    (status, AxumJson(body)).into_response()
+}
+
 /// 🧮 Calculate Endpoint
 async fn calculate_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<CalculateRequest>,
 ) -> impl IntoResponse {
+    let (engine, context) = match payload.engine(&state.engine()).and_then(|engine| Ok((engine, payload.context()?))) {
+        Ok(prepared) => prepared,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    };
+
+    // Registered promo codes: expired, exhausted or below-minimum codes are an error, not ignored
+    let cart = payload.priced_cart();
+    if let Err(e) = engine.check_promo_codes_with_context(&cart, &payload.promo_codes, &context) {
+        return error_response(StatusCode::UNPROCESSABLE_ENTITY, &headers, &e);
+    }
+
     // Engine Logic (Calculate)
-    match state.engine.calculate_cart(
-        &payload.cart,
-        &payload.promo_codes,
-        payload.jurisdiction.as_deref(),
-    ) {
+    match engine.calculate_cart_with_context(&cart, &payload.promo_codes, &context) {
         Ok(result) => (StatusCode::OK, AxumJson(result)).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, format!("Error: {:?}", e)).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &headers, &e),
     }
 }
 
+/// 🧮 Calculate many carts concurrently
+/// Calculation is CPU-bound, so each cart runs on the blocking pool; a semaphore caps how many
+/// run at once. Results come back in input order and match `calculate_cart` called one by one.
+pub async fn calculate_batch(
+    engine: Arc<MixedScenarioEngine>,
+    requests: Vec<CalculateRequest>,
+    max_parallel: usize,
+    locale: Locale,
+) -> Vec<BatchItemResult> {
+    let permits = Arc::new(Semaphore::new(max_parallel.max(1)));
+    let mut handles = Vec::with_capacity(requests.len());
+
+    for request in requests {
+        let engine = engine.clone();
+        let permits = permits.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("batch semaphore is never closed");
+            tokio::task::spawn_blocking(move || {
+                let (engine, context) = (request.engine(&engine)?, request.context()?);
+                let cart = request.priced_cart();
+                engine.check_promo_codes_with_context(&cart, &request.promo_codes, &context)?;
+                engine.calculate_cart_with_context(&cart, &request.promo_codes, &context)
+            })
+            .await
+        }));
+    }
+
+    let mut results = Vec::with_capacity(handles.len());
+    for (index, handle) in handles.into_iter().enumerate() {
+        let outcome = match handle.await {
+            Ok(Ok(calculation)) => calculation,
+            // A panicking calculation only fails its own entry
+            Ok(Err(join_error)) | Err(join_error) => Err(EngineError::System {
+                message: format!("Batch calculation task failed: {}", join_error),
+            }),
+        };
+        results.push(match outcome {
+            Ok(calculation) => BatchItemResult {
+                index,
+                result: Some(calculation),
+                error: None,
+            },
+            Err(e) => BatchItemResult {
+                index,
+                result: None,
+                error: Some(ApiError::from_engine_error(&e, locale)),
+            },
+        });
+    }
+    results
+}
+
+/// 🧮 Batch Calculate Endpoint
+async fn calculate_batch_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<BatchCalculateRequest>,
+) -> impl IntoResponse {
+    if payload.requests.len() > MAX_BATCH_SIZE {
+        let error = EngineError::Validation {
+            message: format!("Batch of {} exceeds the limit of {}", payload.requests.len(), MAX_BATCH_SIZE),
+        };
+        return error_response(StatusCode::PAYLOAD_TOO_LARGE, &headers, &error);
+    }
+    let locale = Locale::resolve(headers.get(ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()));
+    let max_parallel = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let results = calculate_batch(state.engine(), payload.requests, max_parallel, locale).await;
+    (StatusCode::OK, AxumJson(results)).into_response()
+}
+
 /// 🔄 Refund Endpoint
 async fn refund_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<ApiRefundRequest>,
 ) -> impl IntoResponse {
+    // A late-refund approval is a back-office decision: honoured only with the admin token
+    if payload.refund_request.manager_approval.is_some() {
+        if let Err(e) = authorize_admin(&state, &headers) {
+            return error_response(StatusCode::UNAUTHORIZED, &headers, &e);
+        }
+    }
     // Refund Logic (Reverse Calculation)
-    match state.refund_processor.process(
+    let outcome = state.refund_processor.process(
         &payload.original_cart,
         &payload.original_calculation,
         &payload.refund_request,
-    ) {
+    );
+
+    let transaction_id = &payload.refund_request.original_transaction_id;
+    let mut entry = match &outcome {
+        Ok(result) => AuditEntry::new(
+            AuditAction::TransactionRefunded,
+            AuditSeverity::Audit,
+            "Refund",
+            &format!("Refunded {} on {}", result.refund_amount, transaction_id),
+        )
+        .with_amount(result.refund_amount),
+        Err(e) => AuditEntry::new(
+            AuditAction::TransactionRefunded,
+            AuditSeverity::Error,
+            "Refund",
+            &format!("Refund on {} rejected: {}", transaction_id, e.code()),
+        ),
+    }
+    .with_resource(transaction_id);
+    if let Some(id) = correlation_id(&headers) {
+        entry = entry.with_correlation_id(id);
+    }
+    state.audit.lock().unwrap_or_else(|e| e.into_inner()).log(entry);
+
+    match outcome {
         Ok(result) => (StatusCode::OK, AxumJson(result)).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, format!("Error: {:?}", e)).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    }
+}
+
+/// 🔁 Subscription Change Preview (nothing is charged)
+async fn subscription_preview_handler(
+    headers: HeaderMap,
+    Json(payload): Json<SubscriptionPreviewRequest>,
+) -> impl IntoResponse {
+    let method = payload.proration_method.clone().unwrap_or(ProrationMethod::SecondBased);
+    let request = ProrationRequest {
+        subscription_id: payload.subscription_id.clone().unwrap_or_default(),
+        old_plan_amount: payload.old_plan_amount,
+        new_plan_amount: payload.new_plan_amount,
+        billing_cycle_start: payload.billing_cycle_start,
+        billing_cycle_end: payload.billing_cycle_end,
+        change_date: payload.change_date.unwrap_or_else(chrono::Utc::now),
+        proration_method: method.clone(),
+        factor_precision: DEFAULT_FACTOR_PRECISION,
+    };
+    if request.billing_cycle_end <= request.billing_cycle_start {
+        let error = EngineError::Validation {
+            message: "billing_cycle_end must be after billing_cycle_start".to_string(),
+        };
+        return error_response(StatusCode::BAD_REQUEST, &headers, &error);
+    }
+
+    let currency = payload.currency.as_deref().unwrap_or("LKR");
+    if let Err(e) = CurrencyRegistry::configured().resolve(currency) {
+        return error_response(StatusCode::BAD_REQUEST, &headers, &e);
+    }
+
+    match ProrationEngine::calculate(&request) {
+        Ok(result) => {
+            let preview =
+                SubscriptionPreviewResponse::from_proration(&result, payload.new_plan_amount, &method, currency);
+            (StatusCode::OK, AxumJson(preview)).into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    }
+}
+
+/// 📑 Tax report over the recorded sales (JSON or CSV)
+async fn tax_report_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TaxReportQuery>,
+) -> impl IntoResponse {
+    let granularity = match query.granularity.as_deref().map(ReportGranularity::parse).transpose() {
+        Ok(granularity) => granularity.unwrap_or_default(),
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    };
+    let transactions = match TaxTransaction::load_all(state.storage.as_ref()) {
+        Ok(transactions) => transactions,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e),
+    };
+    let report = match TaxReport::build(&transactions, query.from, query.to, granularity) {
+        Ok(report) => report,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    };
+    match query.format.as_deref().unwrap_or("json") {
+        "csv" => match report.to_csv() {
+            Ok(csv) => ([(axum::http::header::CONTENT_TYPE, "text/csv; charset=utf-8")], csv).into_response(),
+            Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e),
+        },
+        "json" => (StatusCode::OK, AxumJson(report)).into_response(),
+        other => {
+            let error = EngineError::Validation {
+                message: format!("Unknown report format '{}' (json, csv)", other),
+            };
+            error_response(StatusCode::BAD_REQUEST, &headers, &error)
+        }
+    }
+}
+
+/// 🔐 `Authorization: Bearer <admin token>`, compared in constant time
+/// SHA-256 digests are compared, so the time does not depend on the token's length either.
+fn authorize_admin(state: &AppState, headers: &HeaderMap) -> EngineResult<()> {
+    let Some(expected) = state.admin_token.as_deref() else {
+        return Err(EngineError::Unauthorized {
+            message: "Admin API is disabled (no admin token configured)".to_string(),
+        });
+    };
+    let presented = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+    let (presented, expected) = (Sha256::digest(presented.as_bytes()), Sha256::digest(expected.as_bytes()));
+    let matches = presented.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if matches {
+        Ok(())
+    } else {
+        Err(EngineError::Unauthorized {
+            message: "Missing or invalid admin token".to_string(),
+        })
+    }
+}
+
+/// ✏️ Apply `change` to a copy of the live engine, persist its config, then swap it in.
+/// `config_updates` keeps concurrent updates from overwriting each other; the engine lock
+/// is only taken for the swap, never across the (blocking) storage write.
+async fn update_engine(
+    state: &AppState,
+    headers: &HeaderMap,
+    change: impl FnOnce(&mut MixedScenarioEngine) -> EngineResult<()>,
+) -> axum::response::Response {
+    if let Err(e) = authorize_admin(state, headers) {
+        return error_response(StatusCode::UNAUTHORIZED, headers, &e);
+    }
+    let _update = state.config_updates.lock().await;
+    let mut engine = (*state.engine()).clone();
+    if let Err(e) = change(&mut engine) {
+        return error_response(StatusCode::BAD_REQUEST, headers, &e);
+    }
+    let config = EngineConfig::of(&engine);
+    let (storage, saved) = (state.storage.clone(), config.clone());
+    let result = tokio::task::spawn_blocking(move || saved.save(storage.as_ref()))
+        .await
+        .unwrap_or_else(|e| {
+            Err(EngineError::Storage {
+                message: format!("Saving the engine config failed: {}", e),
+            })
+        });
+    if let Err(e) = result {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, headers, &e);
+    }
+    *state.engine.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(engine);
+    (StatusCode::OK, AxumJson(config)).into_response()
+}
+
+/// ⚙️ Admin: replace taxes
+async fn config_taxes_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<TaxConfigUpdate>,
+) -> impl IntoResponse {
+    update_engine(&state, &headers, |engine| {
+        engine.replace_taxes(payload.global_taxes, payload.product_taxes)?;
+        if let Some(resolver) = payload.jurisdictions {
+            engine.set_jurisdiction_resolver(resolver);
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// ⚙️ Admin: replace discounts
+async fn config_discounts_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<DiscountConfigUpdate>,
+) -> impl IntoResponse {
+    update_engine(&state, &headers, |engine| {
+        engine.replace_discounts(payload.product_discounts, payload.cart_discounts)
+    })
+    .await
+}
+
+/// SQL pool when the storage connector is up (`init_db`); orders are mirrored there
+fn sql_pool() -> Option<&'static sqlx::PgPool> {
+    connector::get_db().ok().and_then(|db| db.get_sql().ok())
+}
+
+fn order_not_found(id: &str) -> EngineError {
+    EngineError::NotFound {
+        resource: "Order".to_string(),
+        id: id.to_string(),
+    }
+}
+
+/// 🛒 Place an order: price it, persist it as pending
+async fn create_order_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<OrderRequest>,
+) -> impl IntoResponse {
+    let engine = state.engine();
+    let order = match Order::place(&engine, &payload, TaxSourcing::default(), Utc::now()) {
+        Ok(order) => order,
+        Err(e) => return error_response(StatusCode::UNPROCESSABLE_ENTITY, &headers, &e),
+    };
+    // An order that is not persisted gives its promo code uses back
+    let pool = sql_pool();
+    if let Some(pool) = pool {
+        if let Err(e) = order_sql::insert(pool, &order).await {
+            engine.release_promo_codes(&order.promo_redemptions);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e);
+        }
+    }
+    match order.store(state.storage.as_ref()) {
+        Ok(()) => (StatusCode::CREATED, AxumJson(order)).into_response(),
+        Err(e) => {
+            if let Some(pool) = pool {
+                let _ = order_sql::delete(pool, &order).await;
+            }
+            engine.release_promo_codes(&order.promo_redemptions);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e)
+        }
+    }
+}
+
+/// 🔍 Order by id
+async fn get_order_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match Order::load(state.storage.as_ref(), &id) {
+        Ok(Some(order)) => (StatusCode::OK, AxumJson(order)).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, &headers, &order_not_found(&id)),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e),
+    }
+}
+
+/// 📄 Orders, newest first
+async fn list_orders_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<OrderListQuery>,
+) -> impl IntoResponse {
+    let page = query.page.unwrap_or(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE);
+    match Order::list(state.storage.as_ref(), page, per_page) {
+        Ok(page) => (StatusCode::OK, AxumJson(page)).into_response(),
+        Err(e @ EngineError::Validation { .. }) => error_response(StatusCode::BAD_REQUEST, &headers, &e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e),
+    }
+}
+
+/// 🚦 Move an order along pending → paid → fulfilled (or cancel it); 409 on a disallowed step
+/// Admin only (payment confirmations come from the back office / payment webhook relay).
+async fn order_status_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<OrderStatusUpdate>,
+) -> impl IntoResponse {
+    if let Err(e) = authorize_admin(&state, &headers) {
+        return error_response(StatusCode::UNAUTHORIZED, &headers, &e);
+    }
+    let next = match OrderStatus::parse(&payload.status) {
+        Ok(next) => next,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &headers, &e),
+    };
+    // One status change at a time: a concurrent paid and cancelled cannot both pass `transition`
+    let _guard = state.order_updates.lock().await;
+    let mut order = match Order::load(state.storage.as_ref(), &id) {
+        Ok(Some(order)) => order,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, &headers, &order_not_found(&id)),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e),
+    };
+    let previous = order.clone();
+    if let Err(e) = order.transition(next, Utc::now()) {
+        return error_response(StatusCode::CONFLICT, &headers, &e);
+    }
+    let pool = sql_pool();
+    if let Some(pool) = pool {
+        if let Err(e) = order_sql::update_status(pool, &order).await {
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e);
+        }
+    }
+    match order.store_transition(&previous, state.storage.as_ref(), &state.engine()) {
+        Ok(()) => (StatusCode::OK, AxumJson(order)).into_response(),
+        Err(e) => {
+            // Keep the SQL mirror on the status storage still has
+            if let Some(pool) = pool {
+                let _ = order_sql::update_status(pool, &previous).await;
+            }
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, &headers, &e)
+        }
     }
 }
 
+/// 📈 Prometheus scrape endpoint (empty until `metrics::install_prometheus` runs)
+async fn metrics_handler() -> impl IntoResponse {
+    let body = metrics::prometheus().map(|handle| handle.render()).unwrap_or_default();
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
 /// 🏥 Health Check
 async fn health_check() -> &'static str {
     "Financial Engine is Running! 🚀"
 }
 
+/// 🏥 Subsystem Health (503 when any subsystem is Down)
+async fn subsystem_health(State(state): State<AppState>) -> impl IntoResponse {
+    let report = state.status.report_all();
+    let code = match report.status {
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+        HealthStatus::Up | HealthStatus::Degraded => StatusCode::OK,
+    };
+    (code, AxumJson(report)).into_response()
+}
+
 /// 🛠️ Setup Routes (Router සාදන්න)
+/// Empty engine, admin API disabled; servers use `create_configured_router`
 pub fn create_router() -> Router {
-    // Initialize Engine & Services
-    let engine = Arc::new(MixedScenarioEngine::new());
+    create_router_with_status(Arc::new(StatusRegistry::new()))
+}
+
+/// Router sharing a status registry with background subsystems
+pub fn create_router_with_status(status: Arc<StatusRegistry>) -> Router {
+    create_router_with_audit(status, Arc::new(Mutex::new(AuditTrail::new(10_000))))
+}
+
+/// Router writing request audit entries (refunds...) to a shared trail
+pub fn create_router_with_audit(status: Arc<StatusRegistry>, audit: Arc<Mutex<AuditTrail>>) -> Router {
+    build_router(
+        MixedScenarioEngine::new(),
+        status,
+        audit,
+        Arc::new(InMemoryStorage::new()),
+        None,
+    )
+}
+
+/// Router over a pre-configured engine (taxes, discounts, calculation order)
+pub fn create_router_with_engine(engine: MixedScenarioEngine) -> Router {
+    create_router_with_storage(engine, Arc::new(InMemoryStorage::new()))
+}
+
+/// Router over a pre-configured engine, reporting on the sales recorded in `storage`
+pub fn create_router_with_storage(engine: MixedScenarioEngine, storage: Arc<dyn StorageBackend>) -> Router {
+    build_router(
+        engine,
+        Arc::new(StatusRegistry::new()),
+        Arc::new(Mutex::new(AuditTrail::new(10_000))),
+        storage,
+        None,
+    )
+}
+
+/// ⚙️ Router whose engine is built from `EngineConfig::load` (stored config, else the
+/// `config_path` rules file); `admin_token` enables the `/api/v1/config/*` endpoints
+pub fn create_configured_router(
+    storage: Arc<dyn StorageBackend>,
+    config_path: Option<&str>,
+    admin_token: Option<String>,
+) -> EngineResult<Router> {
+    let engine = EngineConfig::load(storage.as_ref(), config_path)?.build()?;
+    Ok(build_router(
+        engine,
+        Arc::new(StatusRegistry::new()),
+        Arc::new(Mutex::new(AuditTrail::new(10_000))),
+        storage,
+        admin_token.filter(|token| !token.is_empty()).map(Arc::from),
+    ))
+}
+
+fn build_router(
+    engine: MixedScenarioEngine,
+    status: Arc<StatusRegistry>,
+    audit: Arc<Mutex<AuditTrail>>,
+    storage: Arc<dyn StorageBackend>,
+    admin_token: Option<Arc<str>>,
+) -> Router {
+    // Initialize Services
     let refund_processor = Arc::new(RefundProcessor::new());
 
     let state = AppState {
-        engine,
+        engine: Arc::new(RwLock::new(Arc::new(engine))),
         refund_processor,
+        status,
+        audit,
+        storage,
+        admin_token,
+        order_updates: Arc::new(tokio::sync::Mutex::new(())),
+        config_updates: Arc::new(tokio::sync::Mutex::new(())),
     };
 
     Router::new()
         .route("/", get(health_check))
+        .route("/api/v1/health", get(subsystem_health))
+        .route("/metrics", get(metrics_handler))
         .route("/api/v1/calculate", post(calculate_handler))
+        .route("/api/v1/calculate/batch", post(calculate_batch_handler))
         .route("/api/v1/refund", post(refund_handler))
+        .route(ApiEndpoints::SUBSCRIPTION_PREVIEW, post(subscription_preview_handler))
+        .route(ApiEndpoints::REPORT_TAX, get(tax_report_handler))
+        .route(ApiEndpoints::ORDER_CREATE, post(create_order_handler))
+        .route(ApiEndpoints::ORDER_LIST, get(list_orders_handler))
+        .route(ApiEndpoints::ORDER_GET, get(get_order_handler))
+        .route(ApiEndpoints::ORDER_STATUS, post(order_status_handler))
+        .route(ApiEndpoints::CONFIG_TAXES, post(config_taxes_handler))
+        .route(ApiEndpoints::CONFIG_DISCOUNTS, post(config_discounts_handler))
+        .layer(axum::middleware::from_fn(correlation_middleware))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[tokio::test]
+    async fn test_subscription_upgrade_preview() {
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        // Rs.100 => Rs.200 plan, changed exactly halfway through a 30-day cycle
+        let body = serde_json::json!({
+            "subscription_id": "sub_1",
+            "old_plan_amount": { "amount": 10000 },
+            "new_plan_amount": { "amount": 20000 },
+            "billing_cycle_start": "2024-01-01T00:00:00Z",
+            "billing_cycle_end": "2024-01-31T00:00:00Z",
+            "change_date": "2024-01-16T00:00:00Z"
+        });
+        let response = create_router()
+            .oneshot(
+                Request::post("/api/v1/subscriptions/preview")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let preview: SubscriptionPreviewResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(preview.credit.amount, 5000);
+        assert_eq!(preview.prorated_charge.amount, 10000);
+        assert_eq!(preview.immediate_charge.amount, 5000);
+        assert_eq!(preview.next_renewal_amount.amount, 20000);
+        assert_eq!(preview.next_renewal_date.to_rfc3339(), "2024-01-31T00:00:00+00:00");
+        assert_eq!(preview.proration_factor, 0.5);
+
+        // Change date outside the cycle is rejected
+        let mut invalid = body.clone();
+        invalid["change_date"] = serde_json::json!("2024-02-15T00:00:00Z");
+        let response = create_router()
+            .oneshot(
+                Request::post("/api/v1/subscriptions/preview")
+                    .header("content-type", "application/json")
+                    .body(Body::from(invalid.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_in_response_and_audit_entry() {
+        use crate::api::correlation::REQUEST_ID_HEADER;
+        use crate::refund::types::{RefundDestination, RefundReason, RefundRequest};
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(2000, 0), 1.0));
+        let calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        // Refunding more than was bought fails => error body + audit entry
+        let body = ApiRefundRequest {
+            original_cart: cart.clone(),
+            original_calculation: calculation,
+            refund_request: RefundRequest {
+                original_transaction_id: cart.id.clone(),
+                items_to_refund: vec![("Shirt".to_string(), 5.0)],
+                reason: RefundReason::Defective,
+                note: None,
+                destination: RefundDestination::OriginalTender,
+                manager_approval: None,
+            },
+        };
+
+        let audit = Arc::new(Mutex::new(AuditTrail::new(100)));
+        let router = create_router_with_audit(Arc::new(StatusRegistry::new()), audit.clone());
+        let response = router
+            .oneshot(
+                Request::post("/api/v1/refund")
+                    .header("content-type", "application/json")
+                    .header(REQUEST_ID_HEADER, "req-2483")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-2483");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ApiError = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(error.correlation_id.as_deref(), Some("req-2483"));
+
+        {
+            let trail = audit.lock().unwrap();
+            let entries = trail.get_by_action(&AuditAction::TransactionRefunded);
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].correlation_id.as_deref(), Some("req-2483"));
+            assert!(trail.verify_chain());
+        }
+
+        // A client cannot approve its own late refund
+        let mut self_approved = body;
+        self_approved.refund_request.manager_approval = Some(crate::refund::types::ManagerApproval {
+            manager_id: "MGR-7".to_string(),
+            reason: "trust me".to_string(),
+        });
+        let response = create_router_with_audit(Arc::new(StatusRegistry::new()), audit)
+            .oneshot(
+                Request::post("/api/v1/refund")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&self_approved).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_calculation_order_per_request() {
+        use crate::rules::mixed_scenarios::{DiscountRule, DiscountType, ProductDiscountConfig};
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "D10".to_string(),
+                name: "10% off".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let router = create_router_with_engine(engine);
+
+        let mut item = Item::new("Shirt", Money::new(1000, 0), 1.0);
+        item.id = "SKU".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(item);
+
+        let calculate = |order: &str| {
+            let body = serde_json::json!({
+                "cart": cart,
+                "promo_codes": [],
+                "jurisdiction": null,
+                "calculation_order": order,
+            });
+            router.clone().oneshot(
+                Request::post("/api/v1/calculate")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let total_tax = |response: axum::response::Response| async move {
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<CartCalculation>(&bytes).unwrap().total_tax
+        };
+
+        // Discount first: VAT on Rs.900; tax first: 10% off Rs.1,180 takes 10% of the VAT with it;
+        // parallel: VAT on the full Rs.1,000
+        let discount_first = total_tax(calculate("DiscountFirst").await.unwrap()).await;
+        let tax_first = total_tax(calculate("tax_first").await.unwrap()).await;
+        let parallel = total_tax(calculate("parallel").await.unwrap()).await;
+        assert_eq!(discount_first, Money::new(162, 0));
+        assert_eq!(tax_first, Money::new(162, 0));
+        assert_eq!(parallel, Money::new(180, 0));
+
+        let response = calculate("LoyaltyFirst").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_batch_preserves_order_and_matches_sequential() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let engine = Arc::new(engine);
+
+        let requests: Vec<CalculateRequest> = (1..=50)
+            .map(|n| {
+                let mut cart = Cart::new();
+                cart.add_item(Item::new("Item", Money::new(n * 10, n % 100), n as f64));
+                CalculateRequest {
+                    cart,
+                    promo_codes: Vec::new(),
+                    jurisdiction: None,
+                    calculation_order: None,
+                    customer: None,
+                    timestamp: None,
+                    currency: None,
+                }
+            })
+            .collect();
+
+        let batch = calculate_batch(engine.clone(), requests.clone(), 4, Locale::En).await;
+
+        assert_eq!(batch.len(), 50);
+        for (index, (entry, request)) in batch.iter().zip(&requests).enumerate() {
+            assert_eq!(entry.index, index);
+            let sequential = engine.calculate_cart(&request.cart, &[], None).unwrap();
+            assert_eq!(
+                serde_json::to_value(entry.result.as_ref().unwrap()).unwrap(),
+                serde_json::to_value(&sequential).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calculate_rejects_exhausted_promo_code() {
+        use crate::discount::promo::{PromoCode, PromoCodeManager, PROMO_EXHAUSTED};
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        use crate::rules::mixed_scenarios::{CartDiscountConfig, CartDiscountType, DiscountCondition};
+
+        let promos = Arc::new(PromoCodeManager::new());
+        promos.register(PromoCode::new("ONCE").with_max_total_uses(1)).unwrap();
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_cart_discount(CartDiscountConfig {
+            id: "ONCE10".to_string(),
+            name: "10% with ONCE".to_string(),
+            discount_type: CartDiscountType::Percentage(10.0),
+            conditions: vec![DiscountCondition::PromoCode("ONCE".to_string())],
+            priority: 1,
+            stackable: true,
+        }).unwrap();
+        engine.set_promo_manager(Some(promos.clone()));
+        let router = create_router_with_engine(engine);
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(1000, 0), 1.0));
+        let calculate = || {
+            let body = serde_json::json!({ "cart": cart, "promo_codes": ["once"], "jurisdiction": null });
+            router.clone().oneshot(
+                Request::post("/api/v1/calculate")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        // Entered in lower case, the code still triggers its discount
+        let response = calculate().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let priced: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(priced["total_discount"]["amount"], 10000);
+
+        promos.redeem("ONCE", None, cart.subtotal(), chrono::Utc::now()).unwrap();
+        let response = calculate().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ApiError = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(error.details.unwrap()["sub_code"], PROMO_EXHAUSTED);
+    }
+
+    #[tokio::test]
+    async fn test_tax_report_route_exports_recorded_sales() {
+        use crate::tax::reporting::TaxReport;
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use chrono::TimeZone;
+        use tower::ServiceExt;
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(500, 0), 2.0));
+        let sale = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        let storage = Arc::new(InMemoryStorage::new());
+        let at = Utc.with_ymd_and_hms(2026, 9, 14, 8, 0, 0).unwrap();
+        TaxTransaction::new("ORD-1", "LK", sale, at).store(storage.as_ref()).unwrap();
+        let router = create_router_with_storage(engine, storage);
+
+        let get = |query: &str| {
+            router.clone().oneshot(
+                Request::get(format!("{}?{}", ApiEndpoints::REPORT_TAX, query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let response = get("from=2026-09-01T00:00:00Z&to=2026-10-01T00:00:00Z").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: TaxReport = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(report.total_collected, Money::new(180, 0));
+
+        let response = get("from=2026-09-01T00:00:00Z&to=2026-10-01T00:00:00Z&format=csv&granularity=day")
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&bytes).contains("2026-09-14,LK,VAT,18,standard,1000.00,180.00,0.00,0.00"));
+
+        let response = get("from=2026-09-01T00:00:00Z&to=2026-10-01T00:00:00Z&granularity=weekly").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_admin_config_endpoints_reconfigure_live_engine() {
+        use crate::api::config::EngineConfig;
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let router = create_configured_router(storage.clone(), None, Some("s3cret".to_string())).unwrap();
+        let post = |path: &str, token: Option<&str>, body: serde_json::Value| {
+            let mut request = Request::post(path).header("content-type", "application/json");
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            router.clone().oneshot(request.body(Body::from(body.to_string())).unwrap())
+        };
+        let total_tax = || async {
+            let mut cart = Cart::new();
+            cart.add_item(Item::new("Tea", Money::new(100, 0), 1.0));
+            let body = serde_json::json!({"cart": cart, "promo_codes": [], "jurisdiction": null});
+            let response = post(ApiEndpoints::CALCULATE, None, body).await.unwrap();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<CartCalculation>(&bytes).unwrap().total_tax
+        };
+        let taxes = serde_json::json!({
+            "global_taxes": [{"name": "VAT", "rate": 18.0, "jurisdiction": "LK", "applies_to": "All"}],
+            "jurisdictions": {"home": "LK"}
+        });
+
+        assert_eq!(total_tax().await, Money::zero());
+        for token in [None, Some("wrong"), Some("s3cre"), Some("s3cret!")] {
+            let response = post(ApiEndpoints::CONFIG_TAXES, token, taxes.clone()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = post(ApiEndpoints::CONFIG_TAXES, Some("s3cret"), taxes).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(total_tax().await, Money::new(18, 0));
+
+        // An invalid discount leaves the live engine untouched
+        let bad = serde_json::json!({"cart_discounts": [{
+            "id": "X", "name": "X", "discount_type": {"Percentage": 150.0}, "priority": 1, "stackable": true
+        }]});
+        let response = post(ApiEndpoints::CONFIG_DISCOUNTS, Some("s3cret"), bad).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(total_tax().await, Money::new(18, 0));
+
+        // Persisted: a restarted server picks the edited config up
+        assert_eq!(EngineConfig::from_storage(storage.as_ref()).unwrap().unwrap().global_taxes.len(), 1);
+        // No admin token configured => admin API disabled
+        let response = create_router()
+            .oneshot(
+                Request::post(ApiEndpoints::CONFIG_TAXES)
+                    .header("content-type", "application/json")
+                    .header("authorization", "Bearer ")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_order_create_get_list_and_status_flow() {
+        use crate::order::{Order, OrderPage};
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        EngineConfig {
+            global_taxes: vec![TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)],
+            ..EngineConfig::default()
+        }
+        .save(storage.as_ref())
+        .unwrap();
+        let router = create_configured_router(storage.clone(), None, Some("s3cret".to_string())).unwrap();
+        let send = |request: Request<Body>| async {
+            let response = router.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, bytes)
+        };
+        let post = |path: String, body: serde_json::Value| {
+            Request::post(path)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let order_request = serde_json::json!({
+            "calculation": {
+                "items": [{"id": "TEA", "name": "Tea", "price": 500.0, "quantity": 2.0,
+                           "category": null, "tax_class": null, "discount_eligible": true}],
+                "customer_id": null, "discount_codes": [], "tax_region": "LK", "currency": "LKR"
+            },
+            "customer": {"id": null, "email": "a@example.com", "name": "A", "phone": null},
+            "payment": {"method": "card", "card_token": null, "billing_address": null},
+            "shipping": null
+        });
+
+        let (status, bytes) = send(post(ApiEndpoints::ORDER_CREATE.to_string(), order_request)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let order: Order = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(order.calculation.grand_total, Money::new(1180, 0));
+
+        let get = |path: String| Request::get(path).body(Body::empty()).unwrap();
+        let (status, bytes) = send(get(format!("{}/{}", ApiEndpoints::ORDER_CREATE, order.id))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<Order>(&bytes).unwrap().id, order.id);
+        let (status, _) = send(get(format!("{}/missing", ApiEndpoints::ORDER_CREATE))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, bytes) = send(get(format!("{}?page=1&per_page=10", ApiEndpoints::ORDER_LIST))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<OrderPage>(&bytes).unwrap().total, 1);
+
+        let status_path = format!("{}/{}/status", ApiEndpoints::ORDER_CREATE, order.id);
+        // Anyone may place an order; only the back office may mark it paid
+        assert_eq!(send(post(status_path.clone(), serde_json::json!({ "status": "paid" }))).await.0, StatusCode::UNAUTHORIZED);
+        let change = |status: &str| {
+            let mut request = post(status_path.clone(), serde_json::json!({ "status": status }));
+            request.headers_mut().insert(AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+            request
+        };
+        assert_eq!(send(change("fulfilled")).await.0, StatusCode::CONFLICT);
+        assert!(TaxTransaction::load(storage.as_ref(), &order.id).unwrap().is_none());
+        assert_eq!(send(change("paid")).await.0, StatusCode::OK);
+        // The paid sale reaches the tax reports
+        assert_eq!(
+            TaxTransaction::load(storage.as_ref(), &order.id).unwrap().unwrap().calculation.total_tax,
+            Money::new(180, 0)
+        );
+        assert_eq!(send(change("shipped")).await.0, StatusCode::BAD_REQUEST);
+        let (status, bytes) = send(change("fulfilled")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<Order>(&bytes).unwrap().status, crate::order::OrderStatus::Fulfilled);
+        assert_eq!(send(change("cancelled")).await.0, StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::storage::database::{EntitySerializer, StorageBackend};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// ============================================================================
+/// 📣 Webhook Delivery (webhook බෙදාහැරීම)
+/// ============================================================================
+/// අසාර්ථක webhook යැවීම් exponential backoff සමඟ සීමිත වාර ගණනක් නැවත උත්සාහ කරයි.
+/// උපරිම උත්සාහයන් අවසන් වූ පසු event එක හේතුව සමඟ dead-letter `StorageBackend`
+/// එකට යවනු ලැබේ (notification නැති නොවේ, සදාකාලිකව retry නොවේ) සහ පසුව
+/// `requeue` කළ හැක. සෑම උත්සාහයකම එකම event id එක යවන බැවින් ලබන්නාට
+/// duplicates ඉවත් කළ හැක.
+///
+/// 🏷️ Header carrying the event id receivers dedup on
+pub const EVENT_ID_HEADER: &str = "x-event-id";
+
+/// Storage key prefix of dead-lettered events
+const DEAD_LETTER_PREFIX: &str = "webhook_dlq:";
+
+/// 📨 Outbound event (the id is fixed at creation and reused by every retry and requeue)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: &str, payload: serde_json::Value) -> Self {
+        WebhookEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            event_type: event_type.to_string(),
+            payload,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 🚚 Delivers one attempt (HTTP client, queue producer...); send `event.id` as `EVENT_ID_HEADER`
+pub trait WebhookTransport: Send + Sync {
+    fn deliver(&self, event: &WebhookEvent) -> EngineResult<()>;
+}
+
+/// 🔁 Retry policy: attempt n waits `initial_backoff × 2^(n-1)`, capped at `max_backoff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (1 = after the first failure)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// 🪦 Event that exhausted its retries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub event: WebhookEvent,
+    pub attempts: u32,
+    /// Last delivery error
+    pub reason: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Result of a dispatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryOutcome {
+    Delivered { attempts: u32 },
+    DeadLettered { attempts: u32 },
+}
+
+/// 📣 Dispatcher
+pub struct WebhookDispatcher {
+    transport: Arc<dyn WebhookTransport>,
+    dead_letters: Arc<dyn StorageBackend>,
+    policy: RetryPolicy,
+    sleep: Box<dyn Fn(Duration) + Send + Sync>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(
+        transport: Arc<dyn WebhookTransport>,
+        dead_letters: Arc<dyn StorageBackend>,
+        policy: RetryPolicy,
+    ) -> EngineResult<Self> {
+        if policy.max_attempts == 0 {
+            return Err(EngineError::Validation {
+                message: "Webhook retry policy needs at least one attempt".to_string(),
+            });
+        }
+        Ok(WebhookDispatcher {
+            transport,
+            dead_letters,
+            policy,
+            sleep: Box::new(std::thread::sleep),
+        })
+    }
+
+    /// Replace how backoff waits are taken (Ex: a recorder in tests)
+    pub fn with_sleeper(mut self, sleep: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.sleep = Box::new(sleep);
+        self
+    }
+
+    /// 📤 Deliver with retries; an exhausted event is dead-lettered, not returned as an error
+    pub fn dispatch(&self, event: &WebhookEvent) -> EngineResult<DeliveryOutcome> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let error = match self.transport.deliver(event) {
+                Ok(()) => return Ok(DeliveryOutcome::Delivered { attempts }),
+                Err(e) => e,
+            };
+            if attempts >= self.policy.max_attempts {
+                let letter = DeadLetter {
+                    event: event.clone(),
+                    attempts,
+                    reason: error.to_string(),
+                    failed_at: Utc::now(),
+                };
+                self.dead_letters
+                    .set(&Self::dead_letter_key(&event.id), &EntitySerializer::to_json(&letter)?)?;
+                tracing::warn!(event_id = %event.id, attempts, reason = %letter.reason, "webhook dead-lettered");
+                return Ok(DeliveryOutcome::DeadLettered { attempts });
+            }
+            (self.sleep)(self.policy.backoff(attempts));
+        }
+    }
+
+    fn dead_letter_key(event_id: &str) -> String {
+        format!("{}{}", DEAD_LETTER_PREFIX, event_id)
+    }
+
+    /// 🪦 Dead-lettered events, oldest failure first
+    pub fn dead_letters(&self) -> EngineResult<Vec<DeadLetter>> {
+        let mut letters = Vec::new();
+        for key in self.dead_letters.keys(DEAD_LETTER_PREFIX)? {
+            if let Some(json) = self.dead_letters.get(&key)? {
+                letters.push(EntitySerializer::from_json::<DeadLetter>(&json)?);
+            }
+        }
+        letters.sort_by_key(|letter| letter.failed_at);
+        Ok(letters)
+    }
+
+    /// ♻️ Take an event off the dead-letter queue and dispatch it again (same event id)
+    pub fn requeue(&self, event_id: &str) -> EngineResult<DeliveryOutcome> {
+        let key = Self::dead_letter_key(event_id);
+        let json = self.dead_letters.get(&key)?.ok_or_else(|| EngineError::NotFound {
+            resource: "Dead-lettered webhook".to_string(),
+            id: event_id.to_string(),
+        })?;
+        let letter: DeadLetter = EntitySerializer::from_json(&json)?;
+        self.dead_letters.delete(&key)?;
+        self.dispatch(&letter.event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::database::InMemoryStorage;
+    use std::sync::Mutex;
+
+    /// Fails the first `failures` attempts, recording the event ids it saw
+    struct FlakyTransport {
+        failures: Mutex<u32>,
+        seen: Mutex<Vec<String>>,
+    }
+
+    impl FlakyTransport {
+        fn new(failures: u32) -> Arc<Self> {
+            Arc::new(FlakyTransport {
+                failures: Mutex::new(failures),
+                seen: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    impl WebhookTransport for FlakyTransport {
+        fn deliver(&self, event: &WebhookEvent) -> EngineResult<()> {
+            self.seen.lock().unwrap().push(event.id.clone());
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(EngineError::System {
+                    message: "503 Service Unavailable".to_string(),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    fn dispatcher(
+        transport: Arc<FlakyTransport>,
+        storage: Arc<InMemoryStorage>,
+    ) -> (WebhookDispatcher, Arc<Mutex<Vec<Duration>>>) {
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = waits.clone();
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(150),
+        };
+        let dispatcher = WebhookDispatcher::new(transport, storage, policy)
+            .unwrap()
+            .with_sleeper(move |wait| recorded.lock().unwrap().push(wait));
+        (dispatcher, waits)
+    }
+
+    #[test]
+    fn test_delivery_succeeds_after_one_retry() {
+        let transport = FlakyTransport::new(1);
+        let storage = Arc::new(InMemoryStorage::new());
+        let (dispatcher, waits) = dispatcher(transport.clone(), storage);
+        let event = WebhookEvent::new("order.paid", serde_json::json!({ "order_id": "ORD-1" }));
+
+        assert_eq!(dispatcher.dispatch(&event).unwrap(), DeliveryOutcome::Delivered { attempts: 2 });
+        assert_eq!(*waits.lock().unwrap(), vec![Duration::from_millis(100)]);
+        // Same id on every attempt, so the receiver can dedup
+        assert_eq!(*transport.seen.lock().unwrap(), vec![event.id.clone(), event.id.clone()]);
+        assert!(dispatcher.dead_letters().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_exhausted_delivery_is_dead_lettered_and_requeued() {
+        let transport = FlakyTransport::new(3);
+        let storage = Arc::new(InMemoryStorage::new());
+        let (dispatcher, waits) = dispatcher(transport.clone(), storage);
+        let event = WebhookEvent::new("refund.issued", serde_json::json!({ "refund_id": "RF-1" }));
+
+        assert_eq!(dispatcher.dispatch(&event).unwrap(), DeliveryOutcome::DeadLettered { attempts: 3 });
+        // 100ms, then 200ms capped at 150ms; no wait after the last attempt
+        assert_eq!(
+            *waits.lock().unwrap(),
+            vec![Duration::from_millis(100), Duration::from_millis(150)]
+        );
+        let letters = dispatcher.dead_letters().unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].event, event);
+        assert_eq!(letters[0].attempts, 3);
+        assert!(letters[0].reason.contains("503"));
+
+        // Receiver is back: requeue delivers with the original id and clears the queue
+        assert_eq!(dispatcher.requeue(&event.id).unwrap(), DeliveryOutcome::Delivered { attempts: 1 });
+        assert!(dispatcher.dead_letters().unwrap().is_empty());
+        assert!(transport.seen.lock().unwrap().iter().all(|id| *id == event.id));
+        assert!(matches!(dispatcher.requeue(&event.id), Err(EngineError::NotFound { .. })));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod logger;
+#[cfg(feature = "observability")]
 pub mod sentry; // Added Sentry integration
@@ -0,0 +1,353 @@
+//! # 🏪 Marketplace Commission & Seller Payouts (කොමිස් සහ විකුණුම්කරු ගෙවීම්)
+//! Each marketplace sale is split between the platform (commission) and the seller
+//! (payout). Commission is charged on each line's net revenue (after discounts, before
+//! tax) at the rate of the line's category (`Item` metadata `category`), the split is
+//! posted to the ledger, and seller payables are settled in periodic payout batches.
+//! The platform collects the tax and remits it, so tax is never part of a payout.
+
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use crate::rules::mixed_scenarios::CartCalculation;
+use crate::types::cart::Cart;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Item metadata key holding the commission category
+pub const CATEGORY_KEY: &str = "category";
+
+// 1. Commission rates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CommissionRate {
+    /// Percent of the line's net revenue
+    Percentage(f64),
+    /// Fixed fee per unit sold (never more than the line's net revenue)
+    PerUnit(Money),
+}
+
+/// Category => rate, with a fallback for uncategorised or unlisted items
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommissionSchedule {
+    pub default_rate: CommissionRate,
+    pub category_rates: BTreeMap<String, CommissionRate>,
+    pub rounding: RoundingMode,
+}
+
+impl CommissionSchedule {
+    pub fn new(default_rate: CommissionRate) -> EngineResult<Self> {
+        Self::check_rate("default", &default_rate)?;
+        Ok(CommissionSchedule {
+            default_rate,
+            category_rates: BTreeMap::new(),
+            rounding: RoundingMode::default(),
+        })
+    }
+
+    pub fn with_category(mut self, category: &str, rate: CommissionRate) -> EngineResult<Self> {
+        Self::check_rate(category, &rate)?;
+        self.category_rates.insert(category.to_string(), rate);
+        Ok(self)
+    }
+
+    fn check_rate(category: &str, rate: &CommissionRate) -> EngineResult<()> {
+        let valid = match rate {
+            CommissionRate::Percentage(percent) => percent.is_finite() && (0.0..=100.0).contains(percent),
+            CommissionRate::PerUnit(fee) => !fee.is_negative(),
+        };
+        if !valid {
+            return Err(EngineError::Validation {
+                message: format!("Invalid commission rate {:?} for '{}'", rate, category),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn rate_for(&self, category: Option<&str>) -> CommissionRate {
+        category
+            .and_then(|c| self.category_rates.get(c))
+            .copied()
+            .unwrap_or(self.default_rate)
+    }
+
+    // 2. Split one sale
+    /// `calculation` must be `cart` priced by the engine (lines in cart order)
+    pub fn split(
+        &self,
+        order_id: &str,
+        seller_id: &str,
+        cart: &Cart,
+        calculation: &CartCalculation,
+    ) -> EngineResult<CommissionSplit> {
+        self.split_at(order_id, seller_id, cart, calculation, Utc::now())
+    }
+
+    /// `split` for a sale made at `sold_at` (Ex: imported orders, tests)
+    pub fn split_at(
+        &self,
+        order_id: &str,
+        seller_id: &str,
+        cart: &Cart,
+        calculation: &CartCalculation,
+        sold_at: DateTime<Utc>,
+    ) -> EngineResult<CommissionSplit> {
+        if cart.items.len() != calculation.items.len() {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Order '{}' has {} cart items but {} calculated lines",
+                    order_id,
+                    cart.items.len(),
+                    calculation.items.len()
+                ),
+            });
+        }
+
+        let mut lines = Vec::with_capacity(cart.items.len());
+        for (item, line) in cart.items.iter().zip(&calculation.items) {
+            let category = item.metadata.get(CATEGORY_KEY).cloned();
+            let net = line.net_revenue();
+            let commission = match self.rate_for(category.as_deref()) {
+                CommissionRate::Percentage(percent) => net.percentage_of_rounded(percent, self.rounding),
+                CommissionRate::PerUnit(fee) => fee.mul_ratio_rounded(item.quantity, self.rounding).min(net),
+            };
+            lines.push(LineCommission {
+                item_id: line.item_id.clone(),
+                category,
+                net,
+                commission,
+            });
+        }
+
+        let net_revenue = lines.iter().fold(Money::zero(), |acc, l| acc + l.net);
+        let commission = lines.iter().fold(Money::zero(), |acc, l| acc + l.commission);
+        let tax = calculation.grand_total - net_revenue;
+        Ok(CommissionSplit {
+            order_id: order_id.to_string(),
+            seller_id: seller_id.to_string(),
+            sold_at,
+            lines,
+            gross: calculation.grand_total,
+            tax,
+            commission,
+            seller_net: net_revenue - commission,
+            paid_out: false,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineCommission {
+    pub item_id: String,
+    pub category: Option<String>,
+    /// Net revenue the commission was charged on
+    pub net: Money,
+    pub commission: Money,
+}
+
+/// 🧾 Platform / seller split of one order (gross == commission + seller_net + tax)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommissionSplit {
+    pub order_id: String,
+    pub seller_id: String,
+    pub sold_at: DateTime<Utc>,
+    pub lines: Vec<LineCommission>,
+    pub gross: Money,
+    pub tax: Money,
+    pub commission: Money,
+    pub seller_net: Money,
+    /// Already included in a payout batch - later batches skip it
+    #[serde(default)]
+    pub paid_out: bool,
+}
+
+// 3. Ledger posting
+/// 🏦 Accounts the split posts to
+#[derive(Debug, Clone)]
+pub struct CommissionAccounts {
+    /// Debited with what the customer paid
+    pub receivable: String,
+    /// Credited with the commission
+    pub platform_revenue: String,
+    /// Credited with the seller's net (owed until paid out)
+    pub seller_payable: String,
+    /// Credited with the tax the platform remits
+    pub tax_payable: String,
+    /// Credited when a payout batch is paid
+    pub payout_clearing: String,
+}
+
+impl CommissionSplit {
+    pub fn post(&self, ledger: &mut GeneralLedger, accounts: &CommissionAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new(&format!("Marketplace sale {}", self.order_id))
+            .debit(&accounts.receivable, self.gross);
+        // No zero-amount lines (Ex: a commission-free category)
+        for (account, amount) in [
+            (&accounts.platform_revenue, self.commission),
+            (&accounts.seller_payable, self.seller_net),
+            (&accounts.tax_payable, self.tax),
+        ] {
+            if !amount.is_zero() {
+                transaction = transaction.credit(account, amount);
+            }
+        }
+        transaction.metadata.insert("order_id".to_string(), self.order_id.clone());
+        transaction.metadata.insert("seller_id".to_string(), self.seller_id.clone());
+        ledger.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+}
+
+// 4. Payout batches
+/// 💸 What one seller is paid for a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SellerPayout {
+    pub seller_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub order_ids: Vec<String>,
+    pub gross_sales: Money,
+    pub commission: Money,
+    pub amount: Money,
+}
+
+pub struct PayoutBatch;
+
+impl PayoutBatch {
+    /// Aggregate unpaid splits sold in `[from, to)` into one payout per seller (sorted by
+    /// seller id) and mark them `paid_out`, so building the same period again pays nothing twice
+    pub fn build(splits: &mut [CommissionSplit], from: DateTime<Utc>, to: DateTime<Utc>) -> EngineResult<Vec<SellerPayout>> {
+        if from >= to {
+            return Err(EngineError::Validation {
+                message: format!("Payout period start {} must be before its end {}", from, to),
+            });
+        }
+        let mut by_seller: HashMap<String, SellerPayout> = HashMap::new();
+        for split in splits.iter_mut().filter(|s| !s.paid_out && s.sold_at >= from && s.sold_at < to) {
+            split.paid_out = true;
+            let payout = by_seller.entry(split.seller_id.clone()).or_insert_with(|| SellerPayout {
+                seller_id: split.seller_id.clone(),
+                from,
+                to,
+                order_ids: Vec::new(),
+                gross_sales: Money::zero(),
+                commission: Money::zero(),
+                amount: Money::zero(),
+            });
+            payout.order_ids.push(split.order_id.clone());
+            payout.gross_sales = payout.gross_sales + split.gross;
+            payout.commission = payout.commission + split.commission;
+            payout.amount = payout.amount + split.seller_net;
+        }
+        let mut payouts: Vec<SellerPayout> = by_seller.into_values().collect();
+        payouts.sort_by(|a, b| a.seller_id.cmp(&b.seller_id));
+        Ok(payouts)
+    }
+}
+
+impl SellerPayout {
+    /// Settle the payable: Dr seller payable, Cr payout clearing (bank)
+    pub fn post(&self, ledger: &mut GeneralLedger, accounts: &CommissionAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new(&format!("Seller payout {}", self.seller_id))
+            .debit(&accounts.seller_payable, self.amount)
+            .credit(&accounts.payout_clearing, self.amount);
+        transaction.metadata.insert("seller_id".to_string(), self.seller_id.clone());
+        transaction.metadata.insert("orders".to_string(), self.order_ids.len().to_string());
+        ledger.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use chrono::TimeZone;
+
+    fn accounts() -> CommissionAccounts {
+        CommissionAccounts {
+            receivable: "1100".to_string(),
+            platform_revenue: "4100".to_string(),
+            seller_payable: "2300".to_string(),
+            tax_payable: "2200".to_string(),
+            payout_clearing: "1000".to_string(),
+        }
+    }
+
+    fn ledger() -> GeneralLedger {
+        let mut ledger = GeneralLedger::new();
+        for (id, kind) in [
+            ("1000", AccountType::Asset),
+            ("1100", AccountType::Asset),
+            ("2200", AccountType::Liability),
+            ("2300", AccountType::Liability),
+            ("4100", AccountType::Income),
+        ] {
+            ledger.add_account(Account::new(id, id, kind));
+        }
+        ledger
+    }
+
+    #[test]
+    fn test_commission_split_posts_balanced_and_batches_payouts() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let schedule = CommissionSchedule::new(CommissionRate::Percentage(15.0))
+            .unwrap()
+            .with_category("books", CommissionRate::PerUnit(Money::new(20, 0)))
+            .unwrap();
+        assert!(CommissionSchedule::new(CommissionRate::Percentage(120.0)).is_err());
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Headphones", Money::new(2000, 0), 1.0));
+        cart.add_item(Item::new("Novel", Money::new(500, 0), 2.0).with_metadata(CATEGORY_KEY, "books"));
+        let calculation = engine.calculate_cart(&cart, &[], None).unwrap();
+
+        let at = |day| Utc.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap();
+        let split = schedule.split_at("ORD-1", "SELLER-A", &cart, &calculation, at(3)).unwrap();
+        assert_eq!(split.sold_at, at(3));
+        // 15% of 2,000 + 2 x Rs.20 on books
+        assert_eq!(split.commission, Money::new(340, 0));
+        assert_eq!(split.seller_net, Money::new(2660, 0));
+        assert_eq!(split.tax, Money::new(540, 0));
+        assert_eq!(split.commission + split.seller_net + split.tax, split.gross);
+
+        let mut ledger = ledger();
+        let accounts = accounts();
+        let posted = split.post(&mut ledger, &accounts).unwrap();
+        assert!(posted.is_balanced());
+        assert_eq!(ledger.balance("4100"), Some(Money::zero() - Money::new(340, 0)));
+        assert_eq!(ledger.balance("2300"), Some(Money::zero() - Money::new(2660, 0)));
+
+        // Second order for the same seller, one for another seller, one outside the period
+        let mut second = split.clone();
+        second.order_id = "ORD-2".to_string();
+        second.sold_at = at(20);
+        let mut other = split.clone();
+        other.seller_id = "SELLER-B".to_string();
+        let mut late = split.clone();
+        late.sold_at = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+
+        let mut splits = [split, second, other, late];
+        let payouts = PayoutBatch::build(&mut splits, at(1), at(31)).unwrap();
+        assert_eq!(payouts.len(), 2);
+        // Rebuilding the same period pays nobody twice; the late order is still unpaid
+        assert!(PayoutBatch::build(&mut splits, at(1), at(31)).unwrap().is_empty());
+        assert!(!splits[3].paid_out);
+        assert_eq!(payouts[0].seller_id, "SELLER-A");
+        assert_eq!(payouts[0].order_ids, vec!["ORD-1", "ORD-2"]);
+        assert_eq!(payouts[0].commission, Money::new(680, 0));
+        assert_eq!(payouts[0].amount, Money::new(5320, 0));
+
+        // Paying out the first order's seller net clears what it was owed
+        let payout = SellerPayout { amount: Money::new(2660, 0), ..payouts[0].clone() };
+        assert!(payout.post(&mut ledger, &accounts).unwrap().is_balanced());
+        assert_eq!(ledger.balance("2300"), Some(Money::zero()));
+
+        // A commission-free sale posts no zero platform revenue line
+        let free = CommissionSchedule::new(CommissionRate::Percentage(0.0)).unwrap();
+        let posted = free.split("ORD-3", "SELLER-A", &cart, &calculation).unwrap().post(&mut ledger, &accounts).unwrap();
+        assert!(posted.entries.iter().all(|e| e.account_id != "4100"));
+    }
+}
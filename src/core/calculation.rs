@@ -1,6 +1,9 @@
 use crate::core::money::Money;
 use crate::core::errors::{EngineResult, EngineError};
+use crate::core::formula::{BucketAmounts, TotalBucket, TotalFormula};
+use crate::core::rounding::RoundingMode;
 use crate::types::cart::Cart;
+use crate::rules::traits::{DiscountTarget, DiscountValue};
 
 /// ============================================================================
 /// 🧮 Calculation Engine (ගණනය කිරීමේ යන්ත්‍රය)
@@ -9,12 +12,29 @@ use crate::types::cart::Cart;
 /// මෙය pipeline එකක් ලෙස ක්‍රියා කරයි.
 
 pub struct CalculationEngine {
-    // Configuration fields usually go here (e.g. RoundingMode)
+    formula: TotalFormula,
+    /// Engine-side percentages (targeted discounts); rules round their own actions
+    rounding: RoundingMode,
 }
 
 impl CalculationEngine {
     pub fn new() -> Self {
-        CalculationEngine {}
+        CalculationEngine {
+            formula: TotalFormula::standard(),
+            rounding: RoundingMode::default(),
+        }
+    }
+
+    /// Legally required rounding (Ex: `Bankers` / half-even)
+    pub fn with_rounding(mut self, mode: RoundingMode) -> Self {
+        self.rounding = mode;
+        self
+    }
+
+    /// Region-specific grand total composition
+    pub fn with_formula(mut self, formula: TotalFormula) -> Self {
+        self.formula = formula;
+        self
     }
 
     /// 🚀 ගණනය කරන්න (Calculate)
@@ -24,56 +44,132 @@ impl CalculationEngine {
         let subtotal = cart.subtotal();
 
         // 2. රීති ක්‍රියාත්මක කිරීම (Rules Execution)
-        let mut discount_total = Money::zero();
-        let mut tax_total = Money::zero();
-        let mut fees_total = Money::zero();
-
-        // Sort rules by priority (High to Low)
-        // Note: In a real engine, we might want to clone the rules or sort indices to avoid mutating the input ref locally if needed,
-        // but here we iterate. To strictly follow priority, we should collect and sort.
-        // For now, let's assume the caller passes them sorted or we iterate simply.
-        // A better approach: 
-        let mut sorted_rules: Vec<&Box<dyn crate::rules::traits::Rule + Send + Sync>> = rules.iter().collect();
-        sorted_rules.sort_by(|a, b| b.priority().cmp(&a.priority()));
+        // Sort rules by priority (High to Low), ties by name (deterministic whatever the input order)
+        let mut sorted_rules: Vec<&(dyn crate::rules::traits::Rule + Send + Sync)> =
+            rules.iter().map(|rule| rule.as_ref()).collect();
+        sorted_rules.sort_by(|a, b| b.priority().cmp(&a.priority()).then_with(|| a.name().cmp(b.name())));
+
+        let mut totals = Self::apply_rules(cart, &sorted_rules)?;
+        let shipping_discount = totals.resolve_targeted(cart.discountable_subtotal(), self.rounding);
+        // Percentage taxes once every fee is known: taxable fees join the goods in the base
+        let tax_base = cart.taxable_subtotal() + totals.taxable_fees;
+        for rate in std::mem::take(&mut totals.percentage_taxes) {
+            totals.tax = totals.tax + tax_base.percentage_of_rounded(rate, self.rounding);
+        }
+        let discount_total = totals.discount;
+        let tax_total = totals.tax;
+        let fees_total = totals.fees + totals.taxable_fees;
+
+        // 3. අවසාන එකතුව (Total Calculation)
+        // Default: Total = Subtotal - Discounts + Taxes + Fees
+        let amounts = BucketAmounts::new(subtotal, discount_total, tax_total)
+            .with(TotalBucket::Fee, fees_total);
+        let total = self.formula.evaluate(&amounts)?.grand_total;
+
+        // Example error check
+        if total.is_negative() {
+             return Err(EngineError::Calculation {
+                 code: "NEGATIVE_TOTAL".to_string(),
+                 message: "Total cannot be negative".to_string(),
+             });
+        }
+
+        Ok(CalculationResult {
+            subtotal,
+            discount_total,
+            tax_total,
+            grand_total: total,
+            taxable_fees: totals.taxable_fees,
+            non_taxable_fees: totals.fees,
+            shipping_discount,
+        })
+    }
 
+    /// Run the rules once (priority order, stopping after a non-stackable one)
+    fn apply_rules(
+        cart: &Cart,
+        sorted_rules: &[&(dyn crate::rules::traits::Rule + Send + Sync)],
+    ) -> EngineResult<RuleTotals> {
+        let mut totals = RuleTotals::default();
         for rule in sorted_rules {
             if rule.can_apply(cart) {
                 let actions = rule.apply(cart)?;
                 for action in actions {
                     match action {
                         crate::rules::traits::RuleAction::Discount(amount) => {
-                            discount_total = discount_total + amount;
+                            totals.discount = totals.discount + amount;
                         },
                         crate::rules::traits::RuleAction::Tax(amount) => {
-                            tax_total = tax_total + amount;
+                            totals.tax = totals.tax + amount;
+                        },
+                        crate::rules::traits::RuleAction::PercentageTax(rate) => {
+                            totals.percentage_taxes.push(rate);
                         },
                         crate::rules::traits::RuleAction::Fee(amount) => {
-                            fees_total = fees_total + amount;
+                            totals.fees = totals.fees + amount;
+                        },
+                        crate::rules::traits::RuleAction::TaxableFee(amount) => {
+                            totals.taxable_fees = totals.taxable_fees + amount;
+                        },
+                        crate::rules::traits::RuleAction::TargetedDiscount { value, target } => {
+                            totals.targeted.push((value, target));
                         },
                         _ => {} // Handle others later
                     }
                 }
+                if !rule.stackable() {
+                    break;
+                }
             }
         }
+        Ok(totals)
+    }
+}
 
-        // 3. අවසාන එකතුව (Total Calculation)
-        // Total = Subtotal - Discounts + Taxes + Fees
-        let total = subtotal - discount_total + tax_total + fees_total;
+/// Action amounts from one pass over the rules (internal)
+#[derive(Default)]
+struct RuleTotals {
+    discount: Money,
+    tax: Money,
+    fees: Money,
+    taxable_fees: Money,
+    /// Rates charged on the taxable base (goods + taxable fees) once all fees are known
+    percentage_taxes: Vec<f64>,
+    /// Resolved once all fees are known
+    targeted: Vec<(DiscountValue, DiscountTarget)>,
+}
 
-        // Example error check
-        if total.is_negative() {
-             return Err(EngineError::Calculation {
-                 code: "NEGATIVE_TOTAL".to_string(),
-                 message: "Total cannot be negative".to_string(),
-             });
+impl RuleTotals {
+    /// Apply targeted discounts: goods parts join `discount`, shipping parts reduce the fees
+    /// (non-taxable first) and can never exceed them. Returns the shipping discount.
+    fn resolve_targeted(&mut self, goods_base: Money, mode: RoundingMode) -> Money {
+        let fees = self.fees + self.taxable_fees;
+        let mut shipping = Money::zero();
+        for (value, target) in std::mem::take(&mut self.targeted) {
+            let remaining_fees = fees - shipping;
+            let (goods_part, shipping_part) = match (value, target) {
+                (DiscountValue::Fixed(amount), DiscountTarget::Goods) => (amount, Money::zero()),
+                (DiscountValue::Percentage(pct), DiscountTarget::Goods) => (goods_base.percentage_of_rounded(pct, mode), Money::zero()),
+                (DiscountValue::Fixed(amount), DiscountTarget::Shipping) => (Money::zero(), amount),
+                (DiscountValue::Percentage(pct), DiscountTarget::Shipping) => {
+                    (Money::zero(), remaining_fees.percentage_of_rounded(pct, mode))
+                }
+                (DiscountValue::Fixed(amount), DiscountTarget::Both) => {
+                    let goods_part = amount.min(goods_base);
+                    (goods_part, amount - goods_part)
+                }
+                (DiscountValue::Percentage(pct), DiscountTarget::Both) => {
+                    (goods_base.percentage_of_rounded(pct, mode), remaining_fees.percentage_of_rounded(pct, mode))
+                }
+            };
+            self.discount = self.discount + goods_part;
+            shipping = shipping + shipping_part.min(remaining_fees);
         }
 
-        Ok(CalculationResult {
-            subtotal,
-            discount_total,
-            tax_total,
-            grand_total: total,
-        })
+        let from_fees = shipping.min(self.fees);
+        self.fees = self.fees - from_fees;
+        self.taxable_fees = self.taxable_fees - (shipping - from_fees);
+        shipping
     }
 }
 
@@ -86,4 +182,12 @@ pub struct CalculationResult {
     pub discount_total: Money,
     pub tax_total: Money,
     pub grand_total: Money,
+    /// Fees included in the taxable base
+    #[serde(default)]
+    pub taxable_fees: Money,
+    #[serde(default)]
+    pub non_taxable_fees: Money,
+    /// Already taken off the fees above (shipping coupons)
+    #[serde(default)]
+    pub shipping_discount: Money,
 }
@@ -0,0 +1,141 @@
+use crate::core::errors::{EngineError, EngineResult};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// ============================================================================
+/// 📆 Business Calendar (ව්‍යාපාරික දින දර්ශනය)
+/// ============================================================================
+/// සති අන්ත සහ නිවාඩු දින මඟ හරිමින් settlement / due date ගණනය කරයි.
+/// Ex: Friday + 1 business day => Monday
+///
+/// 📆 Business Calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusinessCalendar {
+    weekend: Vec<Weekday>,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl BusinessCalendar {
+    /// Saturday/Sunday weekend, no holidays
+    pub fn new() -> Self {
+        BusinessCalendar {
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// Custom weekend (Ex: Friday/Saturday). At least one working day is required.
+    pub fn with_weekend(mut self, weekend: Vec<Weekday>) -> EngineResult<Self> {
+        let unique: BTreeSet<u32> = weekend.iter().map(|d| d.num_days_from_monday()).collect();
+        if unique.len() >= 7 {
+            return Err(EngineError::Validation {
+                message: "Business calendar needs at least one working day".to_string(),
+            });
+        }
+        self.weekend = weekend;
+        Ok(self)
+    }
+
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.holidays.insert(date);
+        self
+    }
+
+    pub fn with_holidays(mut self, dates: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(dates);
+        self
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// ⏭️ First business day strictly after `date`
+    pub fn next_business_day(&self, date: NaiveDate) -> NaiveDate {
+        self.roll_forward(date + Duration::days(1))
+    }
+
+    /// 📌 `date` itself if it is a business day, else the next one
+    pub fn roll_forward(&self, date: NaiveDate) -> NaiveDate {
+        let mut current = date;
+        while !self.is_business_day(current) {
+            current += Duration::days(1);
+        }
+        current
+    }
+
+    /// 📌 `date` itself if it is a business day, else the previous one
+    pub fn roll_backward(&self, date: NaiveDate) -> NaiveDate {
+        let mut current = date;
+        while !self.is_business_day(current) {
+            current -= Duration::days(1);
+        }
+        current
+    }
+
+    /// ➕ Add business days (0 => roll forward only)
+    pub fn add_business_days(&self, date: NaiveDate, days: u32) -> NaiveDate {
+        let mut current = self.roll_forward(date);
+        for _ in 0..days {
+            current = self.next_business_day(current);
+        }
+        current
+    }
+
+    /// 🔢 Business days in `[from, to)`
+    pub fn business_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        from.iter_days()
+            .take_while(|d| *d < to)
+            .filter(|d| self.is_business_day(*d))
+            .count() as i64
+    }
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_skips_weekend() {
+        let calendar = BusinessCalendar::new();
+        // 2024-03-08 is a Friday
+        assert!(calendar.is_business_day(date(2024, 3, 8)));
+        assert!(!calendar.is_business_day(date(2024, 3, 9)));
+        assert_eq!(calendar.next_business_day(date(2024, 3, 8)), date(2024, 3, 11));
+        assert_eq!(calendar.add_business_days(date(2024, 3, 7), 3), date(2024, 3, 12));
+    }
+
+    #[test]
+    fn test_skips_configured_holiday() {
+        // Monday 2024-03-11 declared a holiday
+        let calendar = BusinessCalendar::new().with_holiday(date(2024, 3, 11));
+        assert_eq!(calendar.next_business_day(date(2024, 3, 8)), date(2024, 3, 12));
+        assert_eq!(calendar.roll_forward(date(2024, 3, 9)), date(2024, 3, 12));
+        assert_eq!(calendar.business_days_between(date(2024, 3, 4), date(2024, 3, 18)), 9);
+    }
+
+    #[test]
+    fn test_all_weekend_rejected() {
+        let all = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ];
+        assert!(BusinessCalendar::new().with_weekend(all).is_err());
+    }
+}
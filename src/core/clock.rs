@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+
+/// ============================================================================
+/// ⏰ Clock (ඔරලෝසුව)
+/// ============================================================================
+/// "දැන්" කියන වේලාව ලබා දෙන source එක. Production හිදී system clock එකයි;
+/// tests වලදී `FixedClock` මගින් වේලාව නවත්වා (freeze) flash sale windows වැනි
+/// date-dependent rules නිවැරදිව පරීක්ෂා කළ හැක.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// 🖥️ Wall-clock time (`Utc::now`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 🧊 Always the same instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
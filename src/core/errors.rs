@@ -48,6 +48,62 @@ pub enum EngineError {
     Database { message: String },
 }
 
+impl EngineError {
+    /// 🔑 Machine-stable error code (clients branch on this, never on the message text)
+    ///
+    /// | Variant | Code |
+    /// |---|---|
+    /// | Calculation | `CALCULATION_ERROR` (+ `sub_code`, Ex: `NEGATIVE_TOTAL`) |
+    /// | Validation | `VALIDATION_ERROR` |
+    /// | System | `SYSTEM_ERROR` |
+    /// | Security | `SECURITY_ERROR` (+ `sub_code`) |
+    /// | NotFound | `NOT_FOUND` |
+    /// | Storage | `STORAGE_ERROR` |
+    /// | Network | `NETWORK_ERROR` |
+    /// | Unauthorized | `UNAUTHORIZED` |
+    /// | RateLimited | `RATE_LIMITED` |
+    /// | Transaction | `TRANSACTION_ERROR` |
+    /// | LedgerImbalance | `LEDGER_IMBALANCE` |
+    /// | ExternalService | `EXTERNAL_SERVICE_ERROR` |
+    /// | Database | `DATABASE_ERROR` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            EngineError::Calculation { .. } => "CALCULATION_ERROR",
+            EngineError::Validation { .. } => "VALIDATION_ERROR",
+            EngineError::System { .. } => "SYSTEM_ERROR",
+            EngineError::Security { .. } => "SECURITY_ERROR",
+            EngineError::NotFound { .. } => "NOT_FOUND",
+            EngineError::Storage { .. } => "STORAGE_ERROR",
+            EngineError::Network { .. } => "NETWORK_ERROR",
+            EngineError::Unauthorized { .. } => "UNAUTHORIZED",
+            EngineError::RateLimited { .. } => "RATE_LIMITED",
+            EngineError::Transaction { .. } => "TRANSACTION_ERROR",
+            EngineError::LedgerImbalance { .. } => "LEDGER_IMBALANCE",
+            EngineError::ExternalService { .. } => "EXTERNAL_SERVICE_ERROR",
+            EngineError::Database { .. } => "DATABASE_ERROR",
+        }
+    }
+
+    /// 🔑 Finer-grained code for variants that carry one (Calculation, Security)
+    pub fn sub_code(&self) -> Option<&str> {
+        match self {
+            EngineError::Calculation { code, .. } | EngineError::Security { code, .. } => {
+                Some(code.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// 🧩 GraphQL `extensions` object: `{"code": ..., "subCode": ...}`
+    pub fn graphql_extensions(&self) -> serde_json::Value {
+        let mut extensions = serde_json::json!({ "code": self.code() });
+        if let Some(sub_code) = self.sub_code() {
+            extensions["subCode"] = serde_json::Value::String(sub_code.to_string());
+        }
+        extensions
+    }
+}
+
 pub type EngineResult<T> = Result<T, EngineError>;
 
 pub struct ErrorHandler;
@@ -61,3 +117,39 @@ impl ErrorHandler {
         err
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_variant_has_documented_code() {
+        let text = || "x".to_string();
+        let cases = vec![
+            (EngineError::Calculation { code: "NEGATIVE_TOTAL".to_string(), message: text() }, "CALCULATION_ERROR"),
+            (EngineError::Validation { message: text() }, "VALIDATION_ERROR"),
+            (EngineError::System { message: text() }, "SYSTEM_ERROR"),
+            (EngineError::Security { code: "SQLI".to_string(), message: text() }, "SECURITY_ERROR"),
+            (EngineError::NotFound { resource: text(), id: text() }, "NOT_FOUND"),
+            (EngineError::Storage { message: text() }, "STORAGE_ERROR"),
+            (EngineError::Network { message: text() }, "NETWORK_ERROR"),
+            (EngineError::Unauthorized { message: text() }, "UNAUTHORIZED"),
+            (EngineError::RateLimited { message: text() }, "RATE_LIMITED"),
+            (EngineError::Transaction { message: text() }, "TRANSACTION_ERROR"),
+            (EngineError::LedgerImbalance { debit: 1, credit: 2 }, "LEDGER_IMBALANCE"),
+            (EngineError::ExternalService { service: text(), message: text() }, "EXTERNAL_SERVICE_ERROR"),
+            (EngineError::Database { message: text() }, "DATABASE_ERROR"),
+        ];
+
+        for (error, code) in &cases {
+            assert_eq!(error.code(), *code);
+        }
+
+        assert_eq!(cases[0].0.sub_code(), Some("NEGATIVE_TOTAL"));
+        assert_eq!(cases[1].0.sub_code(), None);
+        assert_eq!(
+            cases[3].0.graphql_extensions(),
+            serde_json::json!({ "code": "SECURITY_ERROR", "subCode": "SQLI" })
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::{CurrencyAmount, Money};
+use crate::core::rounding::RoundingMode;
+use crate::types::currency::Currency;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// 💱 Exchange Rates (විනිමය අනුපාත)
+/// ============================================================================
+/// මුදල් වර්ග දෙකක් අතර පරිවර්තනය සැමවිටම පැහැදිලිව (explicit) සිදු කළ යුතුය:
+/// LKR සහ USD සත කිසිදා නිහඬව එකතු නොවේ. Rate එක major unit එකකට වේ
+/// (1 USD = 300 LKR => 300.0) සහ minor-unit වෙනස්කම් (JPY 0, BHD 3) මෙහිදී සැලකේ.
+///
+/// 📡 Source of rates (static table, bank feed, ...)
+pub trait ExchangeRateProvider: Send + Sync {
+    /// Units of `to` per one unit of `from` (major units)
+    fn rate(&self, from: Currency, to: Currency) -> EngineResult<f64>;
+}
+
+/// 📋 Fixed rate table; the inverse of a configured pair is derived
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StaticExchangeRates {
+    /// "FROM/TO" => rate
+    rates: BTreeMap<String, f64>,
+}
+
+impl StaticExchangeRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rate(mut self, from: Currency, to: Currency, rate: f64) -> EngineResult<Self> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(EngineError::Validation {
+                message: format!("Exchange rate {}/{} must be positive, got {}", from.code(), to.code(), rate),
+            });
+        }
+        self.rates.insert(Self::key(from, to), rate);
+        Ok(self)
+    }
+
+    fn key(from: Currency, to: Currency) -> String {
+        format!("{}/{}", from.code(), to.code())
+    }
+}
+
+impl ExchangeRateProvider for StaticExchangeRates {
+    fn rate(&self, from: Currency, to: Currency) -> EngineResult<f64> {
+        if from == to {
+            return Ok(1.0);
+        }
+        self.rates
+            .get(&Self::key(from, to))
+            .copied()
+            .or_else(|| self.rates.get(&Self::key(to, from)).map(|rate| 1.0 / rate))
+            .ok_or_else(|| EngineError::NotFound {
+                resource: "Exchange rate".to_string(),
+                id: Self::key(from, to),
+            })
+    }
+}
+
+/// 🔄 Convert minor units of `from` into minor units of `to`
+pub fn convert(
+    amount: Money,
+    from: Currency,
+    to: Currency,
+    rates: &dyn ExchangeRateProvider,
+    mode: RoundingMode,
+) -> EngineResult<Money> {
+    if from == to {
+        return Ok(amount);
+    }
+    let rate = rates.rate(from, to)?;
+    let minor_ratio = to.minor_unit_factor() as f64 / from.minor_unit_factor() as f64;
+    let converted = amount.amount as f64 * rate * minor_ratio;
+    if !converted.is_finite() || converted.abs() >= i64::MAX as f64 {
+        return Err(EngineError::Calculation {
+            code: "EXCHANGE_OVERFLOW".to_string(),
+            message: format!("Converting {} {} to {} overflows", amount.amount, from.code(), to.code()),
+        });
+    }
+    Ok(Money::from_cents(mode.round_cents(converted)))
+}
+
+impl CurrencyAmount {
+    /// 🔄 Same value in another currency
+    pub fn convert_to(
+        &self,
+        to: Currency,
+        rates: &dyn ExchangeRateProvider,
+        mode: RoundingMode,
+    ) -> EngineResult<CurrencyAmount> {
+        Ok(CurrencyAmount::new(convert(self.amount, self.currency, to, rates, mode)?, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_respects_minor_units_and_mixing_is_rejected() {
+        let jpy = Currency::from_code("JPY").unwrap();
+        let bhd = Currency::from_code("BHD").unwrap();
+        let rates = StaticExchangeRates::new()
+            .with_rate(Currency::USD, Currency::LKR, 300.0)
+            .unwrap()
+            .with_rate(Currency::USD, jpy, 150.0)
+            .unwrap()
+            .with_rate(bhd, Currency::USD, 2.65)
+            .unwrap();
+        assert!(StaticExchangeRates::new().with_rate(Currency::USD, jpy, 0.0).is_err());
+
+        let ten_dollars = CurrencyAmount::new(Money::new(10, 0), Currency::USD);
+        let mode = RoundingMode::Standard;
+        // $10.00 => 1500 yen (no minor unit)
+        assert_eq!(ten_dollars.convert_to(jpy, &rates, mode).unwrap().amount.amount, 1500);
+        // Inverse pair: Rs.3000 => $10.00
+        let rupees = CurrencyAmount::new(Money::new(3000, 0), Currency::LKR);
+        assert_eq!(rupees.convert_to(Currency::USD, &rates, mode).unwrap(), ten_dollars);
+        // 1.000 BHD (3 decimals) => $2.65
+        let dinar = CurrencyAmount::new(Money::from_cents(1000), bhd);
+        assert_eq!(dinar.convert_to(Currency::USD, &rates, mode).unwrap().amount, Money::new(2, 65));
+        assert_eq!(dinar.to_string(), "BHD 1.000");
+        assert!(ten_dollars.convert_to(Currency::EUR, &rates, mode).is_err());
+
+        // No silent LKR + USD
+        assert!(rupees.checked_add(&ten_dollars).is_err());
+        let converted = ten_dollars.convert_to(Currency::LKR, &rates, mode).unwrap();
+        assert_eq!(rupees.checked_add(&converted).unwrap().amount, Money::new(6000, 0));
+    }
+}
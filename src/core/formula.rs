@@ -0,0 +1,297 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// ➗ Grand Total Formula (අවසාන එකතුවේ සූත්‍රය)
+/// ============================================================================
+/// Subtotal, discount, tax, fee, levy සහ deposit එකතු වන අනුපිළිවෙළ
+/// deployment එක අනුව (region එක අනුව) වෙනස් කළ හැක.
+/// Ex: "subtotal - discount + deposit + tax" (bottle deposit before tax)
+///
+/// 🪣 Total Bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TotalBucket {
+    Subtotal,
+    Discount,
+    Tax,
+    Fee,
+    Levy,
+    Deposit,
+}
+
+impl TotalBucket {
+    pub const ALL: [TotalBucket; 6] = [
+        TotalBucket::Subtotal,
+        TotalBucket::Discount,
+        TotalBucket::Tax,
+        TotalBucket::Fee,
+        TotalBucket::Levy,
+        TotalBucket::Deposit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TotalBucket::Subtotal => "subtotal",
+            TotalBucket::Discount => "discount",
+            TotalBucket::Tax => "tax",
+            TotalBucket::Fee => "fee",
+            TotalBucket::Levy => "levy",
+            TotalBucket::Deposit => "deposit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> EngineResult<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|bucket| bucket.name() == name.trim().to_lowercase())
+            .ok_or_else(|| EngineError::Validation {
+                message: format!(
+                    "Unknown total bucket '{}' (expected one of subtotal, discount, tax, fee, levy, deposit)",
+                    name.trim()
+                ),
+            })
+    }
+}
+
+/// ➕➖ Formula Step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormulaStep {
+    Add(TotalBucket),
+    Subtract(TotalBucket),
+}
+
+impl FormulaStep {
+    pub fn bucket(&self) -> TotalBucket {
+        match self {
+            FormulaStep::Add(bucket) | FormulaStep::Subtract(bucket) => *bucket,
+        }
+    }
+}
+
+/// 💰 Bucket Amounts (formula එකට ආදාන)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketAmounts {
+    pub subtotal: Money,
+    pub discount: Money,
+    pub tax: Money,
+    pub fee: Money,
+    pub levy: Money,
+    pub deposit: Money,
+}
+
+impl BucketAmounts {
+    pub fn new(subtotal: Money, discount: Money, tax: Money) -> Self {
+        BucketAmounts {
+            subtotal,
+            discount,
+            tax,
+            fee: Money::zero(),
+            levy: Money::zero(),
+            deposit: Money::zero(),
+        }
+    }
+
+    pub fn with(mut self, bucket: TotalBucket, amount: Money) -> Self {
+        *self.slot(bucket) = amount;
+        self
+    }
+
+    pub fn get(&self, bucket: TotalBucket) -> Money {
+        match bucket {
+            TotalBucket::Subtotal => self.subtotal,
+            TotalBucket::Discount => self.discount,
+            TotalBucket::Tax => self.tax,
+            TotalBucket::Fee => self.fee,
+            TotalBucket::Levy => self.levy,
+            TotalBucket::Deposit => self.deposit,
+        }
+    }
+
+    fn slot(&mut self, bucket: TotalBucket) -> &mut Money {
+        match bucket {
+            TotalBucket::Subtotal => &mut self.subtotal,
+            TotalBucket::Discount => &mut self.discount,
+            TotalBucket::Tax => &mut self.tax,
+            TotalBucket::Fee => &mut self.fee,
+            TotalBucket::Levy => &mut self.levy,
+            TotalBucket::Deposit => &mut self.deposit,
+        }
+    }
+}
+
+/// 🧾 One evaluated step with the running total after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposedLine {
+    pub step: FormulaStep,
+    pub amount: Money,
+    pub running_total: Money,
+}
+
+/// 📊 Composition Result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposedTotal {
+    pub lines: Vec<ComposedLine>,
+    pub grand_total: Money,
+}
+
+impl ComposedTotal {
+    /// Running total just before `bucket` was applied (Ex: the pre-tax total)
+    pub fn total_before(&self, bucket: TotalBucket) -> Option<Money> {
+        let index = self.lines.iter().position(|l| l.step.bucket() == bucket)?;
+        Some(match index {
+            0 => Money::zero(),
+            _ => self.lines[index - 1].running_total,
+        })
+    }
+}
+
+/// ➗ Total Formula
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotalFormula {
+    steps: Vec<FormulaStep>,
+}
+
+impl TotalFormula {
+    /// Validated formula: must start with `+subtotal` and use each bucket at most once
+    pub fn new(steps: Vec<FormulaStep>) -> EngineResult<Self> {
+        if steps.first() != Some(&FormulaStep::Add(TotalBucket::Subtotal)) {
+            return Err(EngineError::Validation {
+                message: "Total formula must start with subtotal".to_string(),
+            });
+        }
+        for (index, step) in steps.iter().enumerate() {
+            if steps[..index].iter().any(|s| s.bucket() == step.bucket()) {
+                return Err(EngineError::Validation {
+                    message: format!("Bucket '{}' appears more than once", step.bucket().name()),
+                });
+            }
+        }
+        Ok(TotalFormula { steps })
+    }
+
+    /// Parse "subtotal - discount + deposit + tax"
+    pub fn parse(expression: &str) -> EngineResult<Self> {
+        let mut steps = Vec::new();
+        let mut sign = Some('+');
+        for token in expression.split_whitespace() {
+            match token {
+                "+" | "-" if sign.is_none() => sign = token.chars().next(),
+                "+" | "-" => {
+                    return Err(EngineError::Validation {
+                        message: format!("Unexpected operator '{}' in total formula", token),
+                    })
+                }
+                name => {
+                    let bucket = TotalBucket::from_name(name)?;
+                    steps.push(match sign.take() {
+                        Some('-') => FormulaStep::Subtract(bucket),
+                        Some(_) => FormulaStep::Add(bucket),
+                        None => {
+                            return Err(EngineError::Validation {
+                                message: format!("Missing operator before '{}'", name),
+                            })
+                        }
+                    });
+                }
+            }
+        }
+        if sign.is_some() && !steps.is_empty() {
+            return Err(EngineError::Validation {
+                message: "Total formula cannot end with an operator".to_string(),
+            });
+        }
+        Self::new(steps)
+    }
+
+    /// subtotal - discount + tax + fee + levy + deposit
+    pub fn standard() -> Self {
+        TotalFormula {
+            steps: vec![
+                FormulaStep::Add(TotalBucket::Subtotal),
+                FormulaStep::Subtract(TotalBucket::Discount),
+                FormulaStep::Add(TotalBucket::Tax),
+                FormulaStep::Add(TotalBucket::Fee),
+                FormulaStep::Add(TotalBucket::Levy),
+                FormulaStep::Add(TotalBucket::Deposit),
+            ],
+        }
+    }
+
+    pub fn steps(&self) -> &[FormulaStep] {
+        &self.steps
+    }
+
+    /// 🚀 Combine the buckets in order
+    /// A non-zero bucket the formula does not reference is an error (it would silently vanish).
+    pub fn evaluate(&self, amounts: &BucketAmounts) -> EngineResult<ComposedTotal> {
+        for bucket in TotalBucket::ALL {
+            if !amounts.get(bucket).is_zero() && !self.steps.iter().any(|s| s.bucket() == bucket) {
+                return Err(EngineError::Calculation {
+                    code: "UNMAPPED_TOTAL_BUCKET".to_string(),
+                    message: format!("Total formula does not include '{}'", bucket.name()),
+                });
+            }
+        }
+
+        let mut running_total = Money::zero();
+        let mut lines = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let amount = amounts.get(step.bucket());
+            running_total = match step {
+                FormulaStep::Add(_) => running_total + amount,
+                FormulaStep::Subtract(_) => running_total - amount,
+            };
+            lines.push(ComposedLine {
+                step: *step,
+                amount,
+                running_total,
+            });
+        }
+
+        Ok(ComposedTotal {
+            lines,
+            grand_total: running_total,
+        })
+    }
+}
+
+impl Default for TotalFormula {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bottle_deposit_before_tax() {
+        let formula = TotalFormula::parse("subtotal - discount + deposit + tax").unwrap();
+        let amounts = BucketAmounts::new(Money::new(100, 0), Money::new(10, 0), Money::new(17, 10))
+            .with(TotalBucket::Deposit, Money::new(5, 0));
+
+        let composed = formula.evaluate(&amounts).unwrap();
+
+        // Pre-tax total carries the deposit: 100 - 10 + 5 = 95
+        assert_eq!(composed.total_before(TotalBucket::Tax), Some(Money::new(95, 0)));
+        assert_eq!(composed.grand_total, Money::new(112, 10));
+        assert_eq!(composed.lines[2].step, FormulaStep::Add(TotalBucket::Deposit));
+    }
+
+    #[test]
+    fn test_formula_validation() {
+        assert!(TotalFormula::parse("subtotal - coupon").is_err());
+        assert!(TotalFormula::parse("discount + subtotal").is_err());
+        assert!(TotalFormula::parse("subtotal + tax + tax").is_err());
+        assert!(TotalFormula::parse("subtotal +").is_err());
+
+        // Levy present but not in the formula
+        let formula = TotalFormula::parse("subtotal - discount + tax").unwrap();
+        let amounts = BucketAmounts::new(Money::new(100, 0), Money::zero(), Money::zero())
+            .with(TotalBucket::Levy, Money::new(2, 0));
+        assert!(formula.evaluate(&amounts).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// ============================================================================
+/// 🏥 Subsystem Health Registry (උප පද්ධති සෞඛ්‍ය ලේඛනය)
+/// ============================================================================
+/// DB ping, dunning scheduler, webhook dispatcher වැනි background tasks
+/// තමන්ගේ තත්ත්වය මෙහි වාර්තා කරයි. `/health` endpoint එක query කරන විට
+/// කිසිදු I/O එකක් සිදු නොවේ - අවසන් වරට වාර්තා වූ තත්ත්වය පමණක් කියවයි.
+///
+/// 🚦 Health Status (worst = Down)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HealthStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+/// 📋 One subsystem's last report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    pub message: Option<String>,
+    pub last_checked: DateTime<Utc>,
+}
+
+/// 📊 Aggregated Health Report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Worst status across all subsystems (Up when none are registered)
+    pub status: HealthStatus,
+    pub subsystems: Vec<SubsystemHealth>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// 🗂️ Status Registry
+#[derive(Debug, Default)]
+pub struct StatusRegistry {
+    subsystems: RwLock<BTreeMap<String, SubsystemHealth>>,
+}
+
+impl StatusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a subsystem with its initial status
+    pub fn register(&self, name: &str, status: HealthStatus) {
+        self.report(name, status, None);
+    }
+
+    /// 📡 Report the latest status (registers the subsystem if unknown)
+    pub fn report(&self, name: &str, status: HealthStatus, message: Option<&str>) {
+        let health = SubsystemHealth {
+            name: name.to_string(),
+            status,
+            message: message.map(str::to_string),
+            last_checked: Utc::now(),
+        };
+        // A poisoned lock only means a reporter panicked mid-insert; the map is still usable
+        let mut subsystems = self.subsystems.write().unwrap_or_else(|e| e.into_inner());
+        subsystems.insert(name.to_string(), health);
+    }
+
+    pub fn get(&self, name: &str) -> Option<SubsystemHealth> {
+        let subsystems = self.subsystems.read().unwrap_or_else(|e| e.into_inner());
+        subsystems.get(name).cloned()
+    }
+
+    /// 🚦 Worst status across subsystems
+    pub fn aggregate(&self) -> HealthStatus {
+        let subsystems = self.subsystems.read().unwrap_or_else(|e| e.into_inner());
+        subsystems
+            .values()
+            .map(|s| s.status)
+            .max()
+            .unwrap_or(HealthStatus::Up)
+    }
+
+    /// 📊 Snapshot for the health endpoint
+    pub fn report_all(&self) -> HealthReport {
+        let subsystems: Vec<SubsystemHealth> = {
+            let map = self.subsystems.read().unwrap_or_else(|e| e.into_inner());
+            map.values().cloned().collect()
+        };
+        HealthReport {
+            status: subsystems
+                .iter()
+                .map(|s| s.status)
+                .max()
+                .unwrap_or(HealthStatus::Up),
+            subsystems,
+            generated_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_reflects_worst() {
+        let registry = StatusRegistry::new();
+        assert_eq!(registry.aggregate(), HealthStatus::Up);
+
+        registry.register("database", HealthStatus::Up);
+        registry.register("webhooks", HealthStatus::Up);
+        assert_eq!(registry.aggregate(), HealthStatus::Up);
+
+        registry.report("webhooks", HealthStatus::Degraded, Some("retry queue backing up"));
+        assert_eq!(registry.aggregate(), HealthStatus::Degraded);
+
+        registry.report("database", HealthStatus::Down, Some("connection refused"));
+        let report = registry.report_all();
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.subsystems.len(), 2);
+        assert_eq!(
+            registry.get("webhooks").unwrap().message.as_deref(),
+            Some("retry queue backing up")
+        );
+    }
+}
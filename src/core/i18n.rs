@@ -0,0 +1,129 @@
+use crate::core::errors::EngineError;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 🌐 Localized Error Messages (දෝෂ පණිවිඩ භාෂාව)
+/// ============================================================================
+/// `EngineError::code()` යතුර ලෙස භාවිතා කර English / සිංහල පණිවිඩ ලබා දෙයි.
+/// Locale එක `Accept-Language` header එකෙන් හෝ `ENGINE_LOCALE` config එකෙන් තෝරයි.
+///
+/// 🗣️ Locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    /// English (default for integrators)
+    #[default]
+    En,
+    /// සිංහල
+    Si,
+}
+
+impl Locale {
+    /// "en", "en-US", "si", "si-LK" ...
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Locale::En),
+            "si" => Some(Locale::Si),
+            _ => None,
+        }
+    }
+
+    /// Best supported locale from an `Accept-Language` header (q-values respected)
+    /// Ex: "fr-FR, si;q=0.9, en;q=0.8" => Si
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut best: Option<(f32, Locale)> = None;
+        for part in header.split(',') {
+            let mut pieces = part.split(';');
+            let tag = pieces.next().unwrap_or_default();
+            let quality = pieces
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if let Some(locale) = Self::from_tag(tag) {
+                if quality > 0.0 && best.is_none_or(|(q, _)| quality > q) {
+                    best = Some((quality, locale));
+                }
+            }
+        }
+        best.map(|(_, locale)| locale)
+    }
+
+    /// Deployment default from `ENGINE_LOCALE` (falls back to English)
+    pub fn configured() -> Self {
+        std::env::var("ENGINE_LOCALE")
+            .ok()
+            .and_then(|tag| Self::from_tag(&tag))
+            .unwrap_or_default()
+    }
+
+    /// Header first, then configured default
+    pub fn resolve(accept_language: Option<&str>) -> Self {
+        accept_language
+            .and_then(Self::from_accept_language)
+            .unwrap_or_else(Self::configured)
+    }
+}
+
+impl EngineError {
+    /// 🌐 Render this error in the requested language
+    pub fn localized_message(&self, locale: Locale) -> String {
+        match locale {
+            // Display impl is the Sinhala text
+            Locale::Si => self.to_string(),
+            Locale::En => match self {
+                EngineError::Calculation { code, message } => {
+                    format!("Calculation error: {} (Code: {})", message, code)
+                }
+                EngineError::Validation { message } => format!("Invalid data: {}", message),
+                EngineError::System { message } => format!("System error: {}", message),
+                EngineError::Security { code, message } => {
+                    format!("Security error: {} - {}", code, message)
+                }
+                EngineError::NotFound { resource, id } => {
+                    format!("Resource not found: {} (ID: {})", resource, id)
+                }
+                EngineError::Storage { message } => format!("Storage error: {}", message),
+                EngineError::Network { message } => format!("Network error: {}", message),
+                EngineError::Unauthorized { message } => format!("Unauthorized: {}", message),
+                EngineError::RateLimited { message } => format!("Rate limit exceeded: {}", message),
+                EngineError::Transaction { message } => format!("Transaction error: {}", message),
+                EngineError::LedgerImbalance { debit, credit } => {
+                    format!("Ledger is not balanced: Debit={}, Credit={}", debit, credit)
+                }
+                EngineError::ExternalService { service, message } => {
+                    format!("External service error: {} - {}", service, message)
+                }
+                EngineError::Database { message } => format!("Database error: {}", message),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_error_in_english_and_sinhala() {
+        let err = EngineError::Validation {
+            message: "quantity must be positive".to_string(),
+        };
+        assert_eq!(err.code(), "VALIDATION_ERROR");
+        assert_eq!(
+            err.localized_message(Locale::En),
+            "Invalid data: quantity must be positive"
+        );
+        assert_eq!(
+            err.localized_message(Locale::Si),
+            "වලංගු නොවන දත්ත: quantity must be positive"
+        );
+    }
+
+    #[test]
+    fn test_accept_language_negotiation() {
+        assert_eq!(Locale::from_accept_language("si-LK"), Some(Locale::Si));
+        assert_eq!(Locale::from_accept_language("fr-FR, si;q=0.9, en;q=0.8"), Some(Locale::Si));
+        assert_eq!(Locale::from_accept_language("si;q=0.5, en-GB"), Some(Locale::En));
+        assert_eq!(Locale::from_accept_language("fr, de"), None);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::core::errors::EngineError;
+#[cfg(feature = "observability")]
+use crate::core::errors::EngineResult;
+use crate::core::money::Money;
+#[cfg(feature = "observability")]
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+#[cfg(feature = "observability")]
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// ============================================================================
+/// 📈 Metrics (නිරීක්ෂණ මිනුම්)
+/// ============================================================================
+/// ගණනය කිරීම්, refunds, ledger postings, rate-limit ප්‍රතික්ෂේප කිරීම් සහ
+/// error code අනුව දෝෂ `metrics` facade එක හරහා වාර්තා කරයි.
+/// Recorder එකක් install කර නොමැති නම් මේවා no-op වේ (zero-cost);
+/// `install_prometheus()` කළ පසු `/metrics` endpoint එක Prometheus format එකෙන් ලබා දෙයි
+/// (`observability` feature; core build එකේ ඇත්තේ facade එක පමණි).
+///
+/// 🏷️ Metric names
+pub const CALCULATIONS_TOTAL: &str = "engine_calculations_total";
+pub const CALCULATION_SECONDS: &str = "engine_calculation_seconds";
+pub const REFUNDS_TOTAL: &str = "engine_refunds_total";
+/// Refunded amount in major units (rupees)
+pub const REFUND_AMOUNT: &str = "engine_refund_amount";
+pub const LEDGER_POSTINGS_TOTAL: &str = "engine_ledger_postings_total";
+pub const RATE_LIMITED_TOTAL: &str = "engine_rate_limited_total";
+/// Labelled by `code` (`EngineError::code()`)
+pub const ERRORS_TOTAL: &str = "engine_errors_total";
+
+#[cfg(feature = "observability")]
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// 🔌 Install the global Prometheus recorder (once, at startup)
+#[cfg(feature = "observability")]
+pub fn install_prometheus() -> EngineResult<&'static PrometheusHandle> {
+    if let Some(handle) = PROMETHEUS.get() {
+        return Ok(handle);
+    }
+    let handle = PrometheusBuilder::new()
+        .install_recorder()
+        .map_err(|e| EngineError::System {
+            message: format!("Failed to install metrics recorder: {}", e),
+        })?;
+    Ok(PROMETHEUS.get_or_init(|| handle))
+}
+
+/// Installed Prometheus handle, if any
+#[cfg(feature = "observability")]
+pub fn prometheus() -> Option<&'static PrometheusHandle> {
+    PROMETHEUS.get()
+}
+
+pub fn record_calculation(elapsed: Duration) {
+    metrics::counter!(CALCULATIONS_TOTAL).increment(1);
+    metrics::histogram!(CALCULATION_SECONDS).record(elapsed.as_secs_f64());
+}
+
+pub fn record_refund(amount: Money) {
+    metrics::counter!(REFUNDS_TOTAL).increment(1);
+    metrics::histogram!(REFUND_AMOUNT).record(amount.to_float());
+}
+
+pub fn record_ledger_posting() {
+    metrics::counter!(LEDGER_POSTINGS_TOTAL).increment(1);
+}
+
+pub fn record_rate_limited() {
+    metrics::counter!(RATE_LIMITED_TOTAL).increment(1);
+}
+
+pub fn record_error(error: &EngineError) {
+    metrics::counter!(ERRORS_TOTAL, "code" => error.code()).increment(1);
+}
+
+#[cfg(all(test, feature = "observability"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_calculation_increments_counter() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(500, 0), 1.0));
+        let engine = MixedScenarioEngine::new();
+        metrics::with_local_recorder(&recorder, || {
+            engine.calculate_cart(&cart, &[], None).unwrap();
+            engine.calculate_cart(&cart, &[], None).unwrap();
+            record_error(&EngineError::Validation {
+                message: "bad".to_string(),
+            });
+        });
+
+        let rendered = handle.render();
+        assert!(rendered.contains("engine_calculations_total 2"), "{}", rendered);
+        assert!(rendered.contains("engine_errors_total{code=\"VALIDATION_ERROR\"} 1"), "{}", rendered);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Sub};
+
+/// ============================================================================
+/// 🔬 MicroMoney - උප-සත මිල ගණන් (Sub-cent unit pricing)
+/// ============================================================================
+/// API call එකකට Rs.0.0035 වැනි ඒකක මිල `Money` (සත) මගින් නිරූපණය කළ නොහැක.
+/// MicroMoney රුපියලේ මිලියනයෙන් කොටස් (micros) ලෙස ගබඩා කරයි:
+/// Rs.1 = 1,000,000 micros, 1 සතය = 10,000 micros.
+/// එකතු කිරීම micros වලින් සිදු කර, invoice total එකේදී පමණක් `Money` වලට වට කරයි.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MicroMoney {
+    /// අගය micros වලින් (millionths of a rupee)
+    pub micros: i64,
+}
+
+/// Micros per cent
+const MICROS_PER_CENT: i64 = 10_000;
+
+impl MicroMoney {
+    pub fn zero() -> Self {
+        MicroMoney { micros: 0 }
+    }
+
+    pub fn from_micros(micros: i64) -> Self {
+        MicroMoney { micros }
+    }
+
+    /// "0.0035" => 3500 micros (up to 6 decimal places)
+    pub fn parse(value: &str) -> EngineResult<Self> {
+        let invalid = || EngineError::Validation {
+            message: format!("Invalid micro amount '{}'", value),
+        };
+        let trimmed = value.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() || fraction.len() > 6 {
+            return Err(invalid());
+        }
+        let whole: i64 = match whole {
+            "" => 0,
+            w => w.parse().map_err(|_| invalid())?,
+        };
+        let fraction: i64 = match fraction {
+            "" => 0,
+            f => format!("{:0<6}", f).parse().map_err(|_| invalid())?,
+        };
+        let micros = whole
+            .checked_mul(1_000_000)
+            .and_then(|w| w.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(MicroMoney {
+            micros: if negative { -micros } else { micros },
+        })
+    }
+
+    /// Money => MicroMoney (lossless)
+    pub fn from_money(money: Money) -> Self {
+        MicroMoney {
+            micros: money.amount * MICROS_PER_CENT,
+        }
+    }
+
+    /// ✖️ Unit price × units (exact)
+    pub fn times(&self, units: i64) -> EngineResult<Self> {
+        self.micros
+            .checked_mul(units)
+            .map(MicroMoney::from_micros)
+            .ok_or_else(|| EngineError::Calculation {
+                code: "MICRO_OVERFLOW".to_string(),
+                message: format!("{} × {} units overflows", self, units),
+            })
+    }
+
+    /// 🔄 Round to whole cents (invoice total එකේදී පමණක්)
+    pub fn to_money(&self, mode: RoundingMode) -> Money {
+        let quotient = self.micros.div_euclid(MICROS_PER_CENT);
+        let remainder = self.micros.rem_euclid(MICROS_PER_CENT);
+        let half = MICROS_PER_CENT / 2;
+        let round_up = match mode {
+            RoundingMode::Down => false,
+            RoundingMode::Up => remainder > 0,
+            // Half away from zero
+            RoundingMode::Standard => remainder > half || (remainder == half && self.micros > 0),
+            RoundingMode::Bankers => remainder > half || (remainder == half && quotient.rem_euclid(2) == 1),
+            // Half toward zero
+            RoundingMode::HalfDown => remainder > half || (remainder == half && self.micros < 0),
+        };
+        Money::from_cents(if round_up { quotient + 1 } else { quotient })
+    }
+}
+
+impl Add for MicroMoney {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        MicroMoney {
+            micros: self.micros + other.micros,
+        }
+    }
+}
+
+impl Sub for MicroMoney {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        MicroMoney {
+            micros: self.micros - other.micros,
+        }
+    }
+}
+
+impl fmt::Display for MicroMoney {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.micros < 0 { "-" } else { "" };
+        let abs = self.micros.unsigned_abs();
+        write!(f, "{}{}.{:06}", sign, abs / 1_000_000, abs % 1_000_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_round() {
+        let rate = MicroMoney::parse("0.0035").unwrap();
+        assert_eq!(rate.micros, 3500);
+        assert_eq!(rate.to_string(), "0.003500");
+        assert!(MicroMoney::parse("0.0000001").is_err());
+
+        // 30 × 0.0035 = 0.105 => 10.5 cents
+        let total = rate.times(30).unwrap();
+        assert_eq!(total.to_money(RoundingMode::Standard), Money::from_cents(11));
+        assert_eq!(total.to_money(RoundingMode::Down), Money::from_cents(10));
+        assert_eq!(total.to_money(RoundingMode::Bankers), Money::from_cents(10));
+        assert_eq!(MicroMoney::from_micros(-5000).to_money(RoundingMode::Standard), Money::from_cents(-1));
+    }
+}
@@ -1,5 +1,15 @@
 pub mod money;
+pub mod exchange;
+pub mod micro_money;
 pub mod rounding;
 pub mod calculation;
 pub mod errors;
 pub mod logger;
+pub mod words;
+pub mod calendar;
+pub mod formula;
+pub mod health;
+pub mod metrics;
+pub mod i18n;
+pub mod timezone;
+pub mod clock;
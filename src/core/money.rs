@@ -1,4 +1,6 @@
-use crate::core::errors::EngineError;
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::rounding::RoundingMode;
+use crate::types::currency::Currency;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
@@ -13,7 +15,7 @@ use std::ops::{Add, Div, Mul, Sub};
 /// උදාහරණයක් ලෙස:
 /// රු. 10.50 => 1050 (සත)
 /// මෙය ගණිතමය දෝෂ (floating point errors) සම්පූර්ණයෙන්ම ඉවත් කරයි.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Money {
     /// අගය සත වලින් (Value in cents)
     pub amount: i64,
@@ -28,12 +30,36 @@ impl Money {
     /// 💵 රුපියල් සහ සත වලින් මුදලක් සාදන්න
     /// (Create from major and minor units)
     /// Ex: Money::new(100, 50) => Rs. 100.50
+    /// Always assumes 100 minor units per major unit; use `from_major_minor` for JPY, BHD etc.
     pub fn new(rupees: i64, cents: i64) -> Self {
         Money {
             amount: rupees * 100 + cents,
         }
     }
 
+    /// 🌍 Currency එකේ සැබෑ minor-unit ගණනින් සාදන්න
+    /// Ex: (1500, 0, JPY) => 1500 yen, (2, 125, BHD) => 2125 fils
+    pub fn from_major_minor(major: i64, minor: i64, currency: Currency) -> Result<Self, EngineError> {
+        let factor = currency.minor_unit_factor();
+        if minor.abs() >= factor {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "{} has {} minor units per major unit; got minor part {}",
+                    currency.code(),
+                    factor,
+                    minor
+                ),
+            });
+        }
+        major
+            .checked_mul(factor)
+            .and_then(|amount| amount.checked_add(minor))
+            .map(|amount| Money { amount })
+            .ok_or_else(|| EngineError::Validation {
+                message: format!("Amount {}.{} {} overflows", major, minor, currency.code()),
+            })
+    }
+
     /// 🔢 සත වලින් කෙලින්ම සාදන්න (Create from cents)
     pub fn from_cents(cents: i64) -> Self {
         Money { amount: cents }
@@ -46,6 +72,36 @@ impl Money {
         Money { amount: cents }
     }
 
+    /// 🛡️ Float අගයක් වලංගු කර සාදන්න - වට කිරීමක් නැත
+    /// NaN/Infinity සහ currency එකේ minor units වලට වඩා දශම (Ex: LKR 19.999) ප්‍රතික්ෂේප කරයි.
+    pub fn try_from_float(val: f64, currency: Currency) -> Result<Self, EngineError> {
+        if !val.is_finite() {
+            return Err(EngineError::Validation {
+                message: format!("Amount must be a finite number, got {}", val),
+            });
+        }
+        let scaled = val * currency.minor_unit_factor() as f64;
+        if scaled.abs() >= i64::MAX as f64 {
+            return Err(EngineError::Validation {
+                message: format!("Amount {} {} overflows", val, currency.code()),
+            });
+        }
+        // Tolerance absorbs binary representation error (19.99 × 100 = 1998.9999999999998)
+        if (scaled - scaled.round()).abs() > 1e-6 {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Amount {} has more than {} decimal places for {}",
+                    val,
+                    currency.minor_units(),
+                    currency.code()
+                ),
+            });
+        }
+        Ok(Money {
+            amount: scaled.round() as i64,
+        })
+    }
+
     /// 🔄 Float එකක් ලෙස ලබාගන්න (දර්ශනය සඳහා පමණි)
     /// (Get as float - for display only)
     pub fn to_float(&self) -> f64 {
@@ -55,18 +111,26 @@ impl Money {
     /// ➕ ප්‍රතිශතයක් එකතු කරන්න (Add percentage)
     /// Ex: Rs. 100 + 10% = Rs. 110
     pub fn add_percentage(&self, percentage: f64) -> Self {
-        let increase = (self.amount as f64 * (percentage / 100.0)).round() as i64;
+        self.add_percentage_rounded(percentage, RoundingMode::Standard)
+    }
+
+    /// ➕ ප්‍රතිශතයක් එකතු කරන්න - given rounding mode
+    pub fn add_percentage_rounded(&self, percentage: f64, mode: RoundingMode) -> Self {
         Money {
-            amount: self.amount + increase,
+            amount: self.amount + self.percentage_of_rounded(percentage, mode).amount,
         }
     }
 
     /// ➖ ප්‍රතිශතයක් අඩු කරන්න (Subtract percentage)
     /// Ex: Rs. 100 - 10% = Rs. 90
     pub fn sub_percentage(&self, percentage: f64) -> Self {
-        let decrease = (self.amount as f64 * (percentage / 100.0)).round() as i64;
+        self.sub_percentage_rounded(percentage, RoundingMode::Standard)
+    }
+
+    /// ➖ ප්‍රතිශතයක් අඩු කරන්න - given rounding mode (the decrease is rounded)
+    pub fn sub_percentage_rounded(&self, percentage: f64, mode: RoundingMode) -> Self {
         Money {
-            amount: self.amount - decrease,
+            amount: self.amount - self.percentage_of_rounded(percentage, mode).amount,
         }
     }
 
@@ -96,6 +160,49 @@ impl Money {
         Ok(results)
     }
 
+    /// ⚖️ බර අනුව බෙදන්න (Allocate by weights)
+    /// Shares always sum exactly to the original; leftover cents go to the largest
+    /// fractional remainders (earliest wins ties).
+    /// Ex: Rs.100 by [1, 1, 1] => [33.34, 33.33, 33.33]
+    pub fn allocate(&self, weights: &[i64]) -> Result<Vec<Money>, EngineError> {
+        let total_weight: i128 = weights.iter().map(|w| *w as i128).sum();
+        if weights.iter().any(|w| *w < 0) || total_weight <= 0 {
+            return Err(EngineError::Calculation {
+                code: "INVALID_ALLOCATION".to_string(),
+                message: "බර (weights) ධන විය යුතු අතර එකතුව 0 ට වැඩි විය යුතුය".to_string(),
+            });
+        }
+
+        let sign: i128 = if self.amount < 0 { -1 } else { 1 };
+        let amount = (self.amount as i128).abs();
+
+        let mut shares = Vec::with_capacity(weights.len());
+        let mut remainders = Vec::with_capacity(weights.len());
+        for (index, weight) in weights.iter().enumerate() {
+            let product = amount * *weight as i128;
+            shares.push(product / total_weight);
+            remainders.push((product % total_weight, index));
+        }
+
+        let allocated: i128 = shares.iter().sum();
+        let mut leftover = amount - allocated;
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (_, index) in remainders {
+            if leftover == 0 {
+                break;
+            }
+            shares[index] += 1;
+            leftover -= 1;
+        }
+
+        Ok(shares
+            .into_iter()
+            .map(|share| Money {
+                amount: (share * sign) as i64,
+            })
+            .collect())
+    }
+
     /// ✅ ධන අගයක්ද? (Is positive?)
     pub fn is_positive(&self) -> bool {
         self.amount > 0
@@ -120,15 +227,26 @@ impl Money {
 
     /// 📊 ප්‍රතිශතයක් ගණනය කිරීම (Calculate percentage)
     pub fn percentage_of(&self, percentage: f64) -> Self {
-        let val = (self.amount as f64 * (percentage / 100.0)).round() as i64;
-        Money { amount: val }
+        self.percentage_of_rounded(percentage, RoundingMode::Standard)
+    }
+
+    /// 📊 ප්‍රතිශතය - engine එකේ rounding mode එකට අනුව වට කරයි
+    /// Ex: Rs.3 × 7.5% = 22.5 cents => Standard 23, Down 22, Bankers 22
+    pub fn percentage_of_rounded(&self, percentage: f64, mode: RoundingMode) -> Self {
+        Money {
+            amount: mode.round_cents(self.amount as f64 * percentage / 100.0),
+        }
     }
 
     /// ✖️ අනුපාතයකින් ගුණ කරන්න (Multiply by ratio)
     /// Ex: Total * (2.0 / 5.0)
     pub fn mul_ratio(&self, ratio: f64) -> Self {
-        let val = (self.amount as f64 * ratio).round() as i64;
-        Money { amount: val }
+        self.mul_ratio_rounded(ratio, RoundingMode::Standard)
+    }
+
+    /// ✖️ අනුපාතයෙන් ගුණ කර දී ඇති ක්‍රමයට වට කරන්න
+    pub fn mul_ratio_rounded(&self, ratio: f64, mode: RoundingMode) -> Self {
+        Money::from_cents(mode.round_cents(self.amount as f64 * ratio))
     }
 }
 
@@ -136,12 +254,74 @@ impl Money {
 /// ➕ ගණිතමය ක්‍රියාකාරකම් (Arithmetic Operations)
 /// ============================================================================
 
+impl Money {
+    fn overflow(op: &str, left: i64, right: i64) -> EngineError {
+        EngineError::Calculation {
+            code: "MONEY_OVERFLOW".to_string(),
+            message: format!("Money {} overflows: {} and {}", op, left, right),
+        }
+    }
+
+    /// ➕ Overflow-checked sum (Ex: totals of bulk B2B invoices)
+    pub fn checked_add(&self, other: Money) -> EngineResult<Money> {
+        self.amount
+            .checked_add(other.amount)
+            .map(Money::from_cents)
+            .ok_or_else(|| Self::overflow("addition", self.amount, other.amount))
+    }
+
+    /// ➖ Overflow-checked difference
+    pub fn checked_sub(&self, other: Money) -> EngineResult<Money> {
+        self.amount
+            .checked_sub(other.amount)
+            .map(Money::from_cents)
+            .ok_or_else(|| Self::overflow("subtraction", self.amount, other.amount))
+    }
+
+    /// ✖️ Overflow-checked scaling
+    pub fn checked_mul(&self, scalar: i64) -> EngineResult<Money> {
+        self.amount
+            .checked_mul(scalar)
+            .map(Money::from_cents)
+            .ok_or_else(|| Self::overflow("multiplication", self.amount, scalar))
+    }
+
+    /// ➗ Checked integer division (rounding toward zero); zero divisor is an error
+    pub fn checked_div(&self, scalar: i64) -> EngineResult<Money> {
+        if scalar == 0 {
+            return Err(EngineError::Calculation {
+                code: "DIVISION_BY_ZERO".to_string(),
+                message: format!("Cannot divide {} by zero", self),
+            });
+        }
+        self.amount
+            .checked_div(scalar)
+            .map(Money::from_cents)
+            .ok_or_else(|| Self::overflow("division", self.amount, scalar))
+    }
+}
+
+/// Operator overflow policy: by default an overflowing operator panics in every build
+/// profile (never wraps silently in release); with the `saturating-money` feature it
+/// clamps to `i64::MIN`/`i64::MAX` instead. Use the `checked_*` methods to get an error.
+fn on_overflow(checked: Option<i64>, saturated: i64, op: &str) -> i64 {
+    match checked {
+        Some(amount) => amount,
+        None if cfg!(feature = "saturating-money") => saturated,
+        None => panic!("Money {} overflow (use Money::checked_* for an error)", op),
+    }
+}
+
 impl Add for Money {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
         Money {
-            amount: self.amount + other.amount,
+            amount: on_overflow(
+                self.amount.checked_add(other.amount),
+                self.amount.saturating_add(other.amount),
+                "addition",
+            ),
         }
     }
 }
@@ -151,7 +331,11 @@ impl Sub for Money {
 
     fn sub(self, other: Self) -> Self {
         Money {
-            amount: self.amount - other.amount,
+            amount: on_overflow(
+                self.amount.checked_sub(other.amount),
+                self.amount.saturating_sub(other.amount),
+                "subtraction",
+            ),
         }
     }
 }
@@ -161,7 +345,11 @@ impl Mul<i64> for Money {
 
     fn mul(self, scalar: i64) -> Self {
         Money {
-            amount: self.amount * scalar,
+            amount: on_overflow(
+                self.amount.checked_mul(scalar),
+                self.amount.saturating_mul(scalar),
+                "multiplication",
+            ),
         }
     }
 }
@@ -170,9 +358,13 @@ impl Div<i64> for Money {
     type Output = Self;
 
     fn div(self, scalar: i64) -> Self {
-        // Integer division (rounding down)
+        // Integer division (rounding down); only i64::MIN / -1 overflows
         Money {
-            amount: self.amount / scalar,
+            amount: on_overflow(
+                self.amount.checked_div(scalar),
+                self.amount.saturating_div(scalar),
+                "division",
+            ),
         }
     }
 }
@@ -207,10 +399,141 @@ impl fmt::Display for Money {
     }
 }
 
+/// ============================================================================
+/// 🌍 Currency-tagged amount (මුදල් වර්ගය සහිත අගය)
+/// ============================================================================
+/// `Money` තනියම currency එකක් නොදනී; විවිධ මුදල් වර්ග හමුවන තැන්වල (multi-currency
+/// carts, settlements) මෙය භාවිතා කරන්න. එකතු කිරීම/අඩු කිරීම එකම currency එකක් අතර
+/// පමණක් සිදු වේ - අනෙක් විට `core::exchange` හරහා පැහැදිලිව පරිවර්තනය කරන්න.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CurrencyAmount {
+    /// Minor units of `currency` (yen, cents, fils...)
+    pub amount: Money,
+    pub currency: Currency,
+}
+
+impl CurrencyAmount {
+    pub fn new(amount: Money, currency: Currency) -> Self {
+        CurrencyAmount { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Money::zero(), currency)
+    }
+
+    fn same_currency(&self, other: &CurrencyAmount, op: &str) -> Result<(), EngineError> {
+        if self.currency != other.currency {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Cannot {} {} and {} amounts without an explicit conversion",
+                    op,
+                    self.currency.code(),
+                    other.currency.code()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// ➕ Sum; an error (not a silent sum) if the currencies differ
+    pub fn checked_add(&self, other: &CurrencyAmount) -> Result<Self, EngineError> {
+        self.same_currency(other, "add")?;
+        Ok(Self::new(self.amount + other.amount, self.currency))
+    }
+
+    /// ➖ Difference; an error if the currencies differ
+    pub fn checked_sub(&self, other: &CurrencyAmount) -> Result<Self, EngineError> {
+        self.same_currency(other, "subtract")?;
+        Ok(Self::new(self.amount - other.amount, self.currency))
+    }
+}
+
+/// Ex: "JPY 1500", "BHD 2.125", "USD -10.50"
+impl fmt::Display for CurrencyAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let factor = self.currency.minor_unit_factor();
+        let abs_val = self.amount.amount.abs();
+        let sign = if self.amount.amount < 0 { "-" } else { "" };
+        let code = self.currency.code();
+        match self.currency.minor_units() {
+            0 => write!(f, "{} {}{}", code, sign, abs_val),
+            digits => write!(
+                f,
+                "{} {}{}.{:0width$}",
+                code,
+                sign,
+                abs_val / factor,
+                abs_val % factor,
+                width = digits as usize
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_allocate_sums_exactly() {
+        let shares = Money::new(100, 0).allocate(&[1, 1, 1]).unwrap();
+        assert_eq!(shares, vec![Money::from_cents(3334), Money::from_cents(3333), Money::from_cents(3333)]);
+
+        let shares = Money::from_cents(-1001).allocate(&[70, 30]).unwrap();
+        assert_eq!(shares, vec![Money::from_cents(-701), Money::from_cents(-300)]);
+
+        assert!(Money::new(10, 0).allocate(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_from_major_minor_jpy_and_bhd() {
+        let jpy = Currency::from_code("JPY").unwrap();
+        assert_eq!(Money::from_major_minor(1500, 0, jpy).unwrap().amount, 1500);
+        // JPY has no minor unit
+        assert!(Money::from_major_minor(100, 50, jpy).is_err());
+
+        let bhd = Currency::from_code("BHD").unwrap();
+        assert_eq!(bhd.minor_units(), 3);
+        assert_eq!(Money::from_major_minor(2, 125, bhd).unwrap().amount, 2125);
+        assert!(Money::from_major_minor(2, 1000, bhd).is_err());
+
+        assert_eq!(
+            Money::from_major_minor(100, 50, Currency::LKR).unwrap(),
+            Money::new(100, 50)
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic_at_i64_boundaries() {
+        let max = Money::from_cents(i64::MAX);
+        let min = Money::from_cents(i64::MIN);
+        let cent = Money::from_cents(1);
+
+        assert_eq!(max.checked_sub(cent).unwrap().checked_add(cent).unwrap(), max);
+        assert!(matches!(max.checked_add(cent), Err(EngineError::Calculation { .. })));
+        assert!(min.checked_sub(cent).is_err());
+        assert!(Money::from_cents(i64::MAX / 2 + 1).checked_mul(2).is_err());
+        assert_eq!(Money::from_cents(i64::MAX / 2).checked_mul(2).unwrap().amount, i64::MAX - 1);
+        assert!(min.checked_div(-1).is_err());
+        assert!(cent.checked_div(0).is_err());
+        assert_eq!(Money::new(10, 0).checked_div(3).unwrap(), Money::from_cents(333));
+    }
+
+    #[cfg(not(feature = "saturating-money"))]
+    #[test]
+    #[should_panic(expected = "Money addition overflow")]
+    fn test_overflowing_operator_panics_instead_of_wrapping() {
+        let _ = Money::from_cents(i64::MAX) + Money::from_cents(1);
+    }
+
+    #[cfg(feature = "saturating-money")]
+    #[test]
+    fn test_overflowing_operator_saturates() {
+        assert_eq!((Money::from_cents(i64::MAX) + Money::from_cents(1)).amount, i64::MAX);
+        assert_eq!((Money::from_cents(i64::MIN) - Money::from_cents(1)).amount, i64::MIN);
+        assert_eq!((Money::from_cents(i64::MIN) * 2).amount, i64::MIN);
+    }
+
     #[test]
     fn test_addition() {
         let a = Money::new(10, 50); // Rs. 10.50
@@ -5,46 +5,53 @@
 /// අපි ප්‍රධාන ක්‍රම කිහිපයක් මෙහි ක්‍රියාවට නංවන්නෙමු.
 
 use crate::core::money::Money;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum RoundingMode {
     /// සාමාන්‍ය වට කිරීම (Standard Nearest Neighbor)
     /// 0.5 හෝ ඊට වැඩි නම් ඉහළට, නැත්නම් පහළට.
+    #[default]
+    #[serde(alias = "HalfUp")]
     Standard,
 
     /// සැමවිටම ඉහළට (Ceiling / Round Up)
     /// සතයක් හෝ තිබේ නම් ඊළඟ රුපියලට වට කරයි.
+    #[serde(alias = "Ceiling")]
     Up,
 
     /// සැමවිටම පහළට (Floor / Round Down)
     /// ඉතිරි සත ගණන ඉවත් කරයි.
+    #[serde(alias = "Floor")]
     Down,
 
     /// බැංකු ක්‍රමය (Banker's Rounding / Round Half to Even)
     /// මෙය සංඛ්‍යා ලේඛන දෝෂ අවම කිරීමට භාවිතා කරයි.
     /// හරියටම මැද (0.5) ඇති විට ළඟම ඇති ඉරට්ටේ සංඛ්‍යාවට වට කරයි.
+    #[serde(alias = "HalfEven")]
     Bankers,
+
+    /// හරියටම මැද (0.5) ඇති විට ශුන්‍යය දෙසට (Half Down / toward zero)
+    /// Ex: 22.5 => 22, -22.5 => -22; අනෙක් අගයන් ළඟම පූර්ණ සංඛ්‍යාවට.
+    HalfDown,
 }
 
 impl RoundingMode {
     /// 🛠️ මුදලක් වට කරන්න
     pub fn round(&self, amount: f64) -> Money {
+        Money::from_cents(self.round_cents(amount * 100.0))
+    }
+
+    /// 🛠️ භාගික සත ගණනක් පූර්ණ සත වලට වට කරන්න (Ex: 22.5 => 23 / 22)
+    pub fn round_cents(&self, cents: f64) -> i64 {
         match self {
-            RoundingMode::Standard => {
-                Money::from_cents((amount * 100.0).round() as i64)
-            },
-            RoundingMode::Up => {
-                Money::from_cents((amount * 100.0).ceil() as i64)
-            },
-            RoundingMode::Down => {
-                Money::from_cents((amount * 100.0).floor() as i64)
-            },
-            RoundingMode::Bankers => {
-                // බැංකු ක්‍රමය (Banker's rounding)
-                // Rust වල `round_tiest_even` මේ සඳහා භාවිතා කළ හැකියි
-                let cents = (amount * 100.0).round_ties_even() as i64;
-                Money::from_cents(cents)
-            }
+            RoundingMode::Standard => cents.round() as i64,
+            RoundingMode::Up => cents.ceil() as i64,
+            RoundingMode::Down => cents.floor() as i64,
+            // බැංකු ක්‍රමය (Banker's rounding)
+            RoundingMode::Bankers => cents.round_ties_even() as i64,
+            RoundingMode::HalfDown if (cents - cents.trunc()).abs() == 0.5 => cents.trunc() as i64,
+            RoundingMode::HalfDown => cents.round() as i64,
         }
     }
 }
@@ -66,4 +73,31 @@ mod tests {
         let r_up = RoundingMode::Up.round(val);
         assert_eq!(r_up.amount, 1056); //  10.56
     }
+
+    #[test]
+    fn test_percentage_on_half_cent_boundary() {
+        // Rs.3 × 7.5% = 22.5 cents
+        let amount = Money::new(3, 0);
+        let expected = [
+            (RoundingMode::Standard, 23),
+            (RoundingMode::Up, 23),
+            (RoundingMode::Down, 22),
+            (RoundingMode::Bankers, 22),
+            (RoundingMode::HalfDown, 22),
+        ];
+        for (mode, cents) in expected {
+            assert_eq!(amount.percentage_of_rounded(7.5, mode).amount, cents, "{:?}", mode);
+            assert_eq!(amount.add_percentage_rounded(7.5, mode).amount, 300 + cents);
+            assert_eq!(amount.sub_percentage_rounded(7.5, mode).amount, 300 - cents);
+        }
+        // Legacy helpers stay half-away-from-zero
+        assert_eq!(amount.percentage_of(7.5).amount, 23);
+
+        // Off the midpoint every mode but Up/Down rounds to nearest; -22.5 ties toward zero
+        assert_eq!(RoundingMode::HalfDown.round_cents(22.51), 23);
+        assert_eq!(RoundingMode::HalfDown.round_cents(-22.5), -22);
+        assert_eq!(RoundingMode::Bankers.round_cents(23.5), 24);
+        let configured: RoundingMode = serde_json::from_str("\"Floor\"").unwrap();
+        assert_eq!(configured, RoundingMode::Down);
+    }
 }
@@ -0,0 +1,129 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// 🕰️ Store Time Zone (වෙළඳසැලේ වේලා කලාපය)
+/// ============================================================================
+/// "Jan 22 දක්වා" flash sale එකක් හෝ දෛනික විකුණුම් වාර්තාවක් UTC අනුව නොව
+/// වෙළඳසැලේ දේශීය වේලාව අනුව ගණනය කළ යුතුය.
+/// Ex: Asia/Colombo වල 23:30 විකුණුමක් UTC අනුව 18:00 - එය එදිනම ව්‍යාපාරික දිනයට අයත් වේ.
+///
+/// 🕰️ Store Time Zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreTimeZone {
+    tz: Tz,
+}
+
+impl StoreTimeZone {
+    pub fn new(tz: Tz) -> Self {
+        StoreTimeZone { tz }
+    }
+
+    /// IANA name, Ex: "Asia/Colombo"
+    pub fn parse(name: &str) -> EngineResult<Self> {
+        name.trim()
+            .parse::<Tz>()
+            .map(Self::new)
+            .map_err(|_| EngineError::Validation {
+                message: format!("Unknown time zone '{}'", name),
+            })
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// 📅 Local business day of an instant
+    pub fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        instant.with_timezone(&self.tz).date_naive()
+    }
+
+    /// "2024-01-22" or "2024-01-22T18:00[:00]" in store-local time => UTC instant.
+    /// A bare date means the start of that local day, or its end when `end_of_day` is set.
+    /// An RFC 3339 value with its own offset ("2024-01-22T18:00:00+05:30") is taken as-is.
+    /// None for unparseable values or times skipped by a DST change.
+    pub fn parse_local(&self, value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+        let value = value.trim();
+        if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+            return Some(instant.with_timezone(&Utc));
+        }
+        let local = match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) if end_of_day => date.succ_opt()?.and_time(NaiveTime::MIN),
+            Ok(date) => date.and_time(NaiveTime::MIN),
+            Err(_) => NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+                .ok()?,
+        };
+        let instant = self.tz.from_local_datetime(&local).earliest()?.with_timezone(&Utc);
+        Some(instant)
+    }
+
+    /// 🗓️ `[start, end)` of a local window; a bare `to` date includes that whole day,
+    /// an explicit `to` time is exclusive. Unparseable or empty windows are config errors.
+    pub fn window(&self, from: &str, to: &str) -> EngineResult<(DateTime<Utc>, DateTime<Utc>)> {
+        let parse = |value: &str, end_of_day: bool| {
+            self.parse_local(value, end_of_day).ok_or_else(|| EngineError::Validation {
+                message: format!("Invalid date '{}' (expected YYYY-MM-DD, local date-time or RFC 3339)", value),
+            })
+        };
+        let (start, end) = (parse(from, false)?, parse(to, true)?);
+        if start >= end {
+            return Err(EngineError::Validation {
+                message: format!("Date range '{}'..'{}' ends before it starts", from, to),
+            });
+        }
+        Ok((start, end))
+    }
+
+    /// ⏱️ Is `instant` inside the local window? (see `window`)
+    pub fn window_contains(&self, from: &str, to: &str, instant: DateTime<Utc>) -> bool {
+        // Malformed window never matches
+        self.window(from, to).is_ok_and(|(start, end)| instant >= start && instant < end)
+    }
+
+    /// 📊 Bucket timestamped amounts by local business day
+    pub fn daily_totals(&self, entries: &[(DateTime<Utc>, Money)]) -> BTreeMap<NaiveDate, Money> {
+        let mut totals: BTreeMap<NaiveDate, Money> = BTreeMap::new();
+        for (instant, amount) in entries {
+            let day = totals.entry(self.local_date(*instant)).or_insert_with(Money::zero);
+            *day = *day + *amount;
+        }
+        totals
+    }
+}
+
+impl Default for StoreTimeZone {
+    fn default() -> Self {
+        Self::new(Tz::UTC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_late_night_sale_bucketed_to_local_day() {
+        let colombo = StoreTimeZone::parse("Asia/Colombo").unwrap();
+        // 2024-01-22 23:30 in Colombo (+05:30) is 18:00 UTC the same day;
+        // 2024-01-22 20:00 UTC is already 01:30 on the 23rd locally
+        let sales = [
+            (utc(2024, 1, 22, 18, 0), Money::new(100, 0)),
+            (utc(2024, 1, 22, 20, 0), Money::new(50, 0)),
+        ];
+        let totals = colombo.daily_totals(&sales);
+
+        let jan = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        assert_eq!(totals.get(&jan(22)), Some(&Money::new(100, 0)));
+        assert_eq!(totals.get(&jan(23)), Some(&Money::new(50, 0)));
+        assert!(StoreTimeZone::parse("Mars/Olympus").is_err());
+    }
+}
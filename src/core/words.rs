@@ -0,0 +1,210 @@
+use crate::core::money::Money;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 🔤 Amount in Words (අකුරින් මුදල)
+/// ============================================================================
+/// Cheque printing සහ formal invoices සඳහා මුදල අකුරින් ලියයි.
+/// Ex: Rs.1,234.50 => "One Thousand Two Hundred Thirty Four Rupees and Fifty Cents"
+///
+/// 🔢 Numbering System (ඉලක්කම් කාණ්ඩ කිරීම)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberingSystem {
+    /// Thousand, Million, Billion ... (1,000,000)
+    International,
+    /// Thousand, Lakh, Crore (10,00,000)
+    Indian,
+}
+
+/// 💱 Currency unit names used when spelling out an amount
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyWords {
+    pub major: String,
+    pub minor: String,
+    pub numbering: NumberingSystem,
+}
+
+impl CurrencyWords {
+    pub fn new(major: &str, minor: &str, numbering: NumberingSystem) -> Self {
+        CurrencyWords {
+            major: major.to_string(),
+            minor: minor.to_string(),
+            numbering,
+        }
+    }
+
+    /// ශ්‍රී ලංකා රුපියල් (Rupees / Cents, international grouping)
+    pub fn rupees() -> Self {
+        Self::new("Rupees", "Cents", NumberingSystem::International)
+    }
+
+    pub fn with_numbering(mut self, numbering: NumberingSystem) -> Self {
+        self.numbering = numbering;
+        self
+    }
+}
+
+impl Default for CurrencyWords {
+    fn default() -> Self {
+        Self::rupees()
+    }
+}
+
+impl Money {
+    /// 🔤 අකුරින් ලියන්න (Spell out the amount)
+    /// Negative amounts are prefixed with "Minus".
+    pub fn to_words(&self, currency_words: CurrencyWords) -> String {
+        let abs = self.amount.unsigned_abs();
+        let major = abs / 100;
+        let minor = abs % 100;
+
+        let mut words = format!(
+            "{} {}",
+            number_to_words(major, currency_words.numbering),
+            currency_words.major
+        );
+        if minor > 0 {
+            words = format!(
+                "{} and {} {}",
+                words,
+                number_to_words(minor, currency_words.numbering),
+                currency_words.minor
+            );
+        }
+        if self.is_negative() {
+            words = format!("Minus {}", words);
+        }
+        words
+    }
+}
+
+const ONES: [&str; 20] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten",
+    "Eleven", "Twelve", "Thirteen", "Fourteen", "Fifteen", "Sixteen", "Seventeen", "Eighteen",
+    "Nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+];
+
+const SCALES: [&str; 7] = [
+    "", "Thousand", "Million", "Billion", "Trillion", "Quadrillion", "Quintillion",
+];
+
+/// 🔢 Spell out a whole number
+pub fn number_to_words(n: u64, numbering: NumberingSystem) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    match numbering {
+        NumberingSystem::International => international(n),
+        NumberingSystem::Indian => indian(n),
+    }
+}
+
+fn below_hundred(n: u64) -> String {
+    if n < 20 {
+        return ONES[n as usize].to_string();
+    }
+    match n % 10 {
+        0 => TENS[(n / 10) as usize].to_string(),
+        unit => format!("{} {}", TENS[(n / 10) as usize], ONES[unit as usize]),
+    }
+}
+
+fn below_thousand(n: u64) -> String {
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(format!("{} Hundred", ONES[(n / 100) as usize]));
+    }
+    let rest = n % 100;
+    if rest > 0 {
+        parts.push(below_hundred(rest));
+    }
+    parts.join(" ")
+}
+
+fn international(n: u64) -> String {
+    let mut groups = Vec::new();
+    let mut remaining = n;
+    let mut scale = 0;
+    while remaining > 0 {
+        let chunk = remaining % 1000;
+        if chunk > 0 {
+            let words = below_thousand(chunk);
+            groups.push(if SCALES[scale].is_empty() {
+                words
+            } else {
+                format!("{} {}", words, SCALES[scale])
+            });
+        }
+        remaining /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+    groups.join(" ")
+}
+
+/// Indian grouping: 3 digits, then pairs (Thousand, Lakh), then Crore repeats
+fn indian(n: u64) -> String {
+    let mut parts = Vec::new();
+
+    let crores = n / 10_000_000;
+    if crores > 0 {
+        parts.push(format!("{} Crore", indian(crores)));
+    }
+
+    let rest = n % 10_000_000;
+    let lakhs = rest / 100_000;
+    let thousands = (rest / 1000) % 100;
+    let hundreds = rest % 1000;
+
+    if lakhs > 0 {
+        parts.push(format!("{} Lakh", below_hundred(lakhs)));
+    }
+    if thousands > 0 {
+        parts.push(format!("{} Thousand", below_hundred(thousands)));
+    }
+    if hundreds > 0 {
+        parts.push(below_thousand(hundreds));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero() {
+        assert_eq!(Money::zero().to_words(CurrencyWords::rupees()), "Zero Rupees");
+    }
+
+    #[test]
+    fn test_rupees_and_cents() {
+        assert_eq!(
+            Money::new(1234, 50).to_words(CurrencyWords::rupees()),
+            "One Thousand Two Hundred Thirty Four Rupees and Fifty Cents"
+        );
+        assert_eq!(
+            Money::new(-1234, -50).to_words(CurrencyWords::rupees()),
+            "Minus One Thousand Two Hundred Thirty Four Rupees and Fifty Cents"
+        );
+    }
+
+    #[test]
+    fn test_indian_grouping() {
+        let indian = CurrencyWords::rupees().with_numbering(NumberingSystem::Indian);
+        // Rs.1,00,00,000
+        assert_eq!(Money::new(10_000_000, 0).to_words(indian.clone()), "One Crore Rupees");
+        assert_eq!(
+            Money::new(1_234_567, 0).to_words(indian),
+            "Twelve Lakh Thirty Four Thousand Five Hundred Sixty Seven Rupees"
+        );
+        assert_eq!(
+            Money::new(10_000_000, 0).to_words(CurrencyWords::rupees()),
+            "Ten Million Rupees"
+        );
+    }
+}
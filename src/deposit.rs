@@ -0,0 +1,310 @@
+//! # 🍾 Container Deposits (බෝතල් තැන්පතු)
+//! Beverage sales can carry a refundable per-unit container deposit. The deposit is added
+//! to what the customer pays but is a liability, not revenue: it is credited to a
+//! deposit-liability account on sale and debited back when the container is returned and
+//! the deposit refunded. Whether a deposit is taxed is configured per product.
+//! Refunds come from the deposits charged on the sale (not today's schedule), so the
+//! liability can never be refunded below zero.
+
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use crate::rules::mixed_scenarios::CartCalculation;
+use crate::types::cart::Cart;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 1. Deposit configuration
+/// Deposit charged per unit of a product
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContainerDeposit {
+    pub per_unit: Money,
+    /// Tax percent charged on the deposit; None = deposit is not taxable
+    pub tax_percent: Option<f64>,
+}
+
+/// Product id => deposit
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DepositSchedule {
+    pub deposits: BTreeMap<String, ContainerDeposit>,
+    pub rounding: RoundingMode,
+}
+
+impl DepositSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deposit(mut self, product_id: &str, deposit: ContainerDeposit) -> EngineResult<Self> {
+        let valid_tax = deposit
+            .tax_percent
+            .is_none_or(|percent| percent.is_finite() && (0.0..=100.0).contains(&percent));
+        if deposit.per_unit.is_negative() || !valid_tax {
+            return Err(EngineError::Validation {
+                message: format!("Invalid container deposit {:?} for '{}'", deposit, product_id),
+            });
+        }
+        self.deposits.insert(product_id.to_string(), deposit);
+        Ok(self)
+    }
+
+    fn line(&self, product_id: &str, units: f64) -> Option<DepositLine> {
+        let deposit = self.deposits.get(product_id)?;
+        let amount = deposit.per_unit.mul_ratio_rounded(units, self.rounding);
+        let tax = deposit
+            .tax_percent
+            .map(|percent| amount.percentage_of_rounded(percent, self.rounding))
+            .unwrap_or_else(Money::zero);
+        Some(DepositLine {
+            product_id: product_id.to_string(),
+            units,
+            deposit: amount,
+            tax,
+            returned_units: 0.0,
+        })
+    }
+
+    // 2. Sale: deposit lines for a cart
+    /// Deposits on every cart item that has one (return lines are left to `refund`), added to
+    /// `calculation.grand_total`. Keep the movement with the sale - returns are refunded from it.
+    pub fn charge(&self, order_id: &str, cart: &Cart, calculation: &mut CartCalculation) -> DepositMovement {
+        let lines = cart
+            .items
+            .iter()
+            .filter(|item| item.quantity > 0.0)
+            .filter_map(|item| self.line(&item.id, item.quantity))
+            .collect();
+        let movement = DepositMovement::new(order_id, lines);
+        calculation.grand_total = calculation.grand_total - calculation.deposits + movement.total;
+        calculation.deposits = movement.total;
+        movement
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositLine {
+    pub product_id: String,
+    pub units: f64,
+    pub deposit: Money,
+    /// Tax on the deposit (zero if not taxable)
+    pub tax: Money,
+    /// Containers of a sale line already refunded
+    #[serde(default)]
+    pub returned_units: f64,
+}
+
+/// Slack for float unit counts (Ex: 0.1 + 0.2 containers)
+const UNIT_EPSILON: f64 = 1e-9;
+
+impl DepositLine {
+    /// Part of the charged deposit and tax covering the first `units` containers
+    fn charged_for(&self, units: f64) -> (Money, Money) {
+        let ratio = units / self.units;
+        (
+            self.deposit.mul_ratio_rounded(ratio, RoundingMode::Standard),
+            self.tax.mul_ratio_rounded(ratio, RoundingMode::Standard),
+        )
+    }
+
+    /// Refund `units` more containers of this sale line. Shares are taken from the cumulative
+    /// return, so returning every container refunds exactly what was charged.
+    fn refund(&mut self, units: f64) -> DepositLine {
+        let (deposit_before, tax_before) = self.charged_for(self.returned_units);
+        self.returned_units += units;
+        if self.units - self.returned_units < UNIT_EPSILON {
+            self.returned_units = self.units;
+        }
+        let (deposit_after, tax_after) = self.charged_for(self.returned_units);
+        DepositLine {
+            product_id: self.product_id.clone(),
+            units,
+            deposit: deposit_after - deposit_before,
+            tax: tax_after - tax_before,
+            returned_units: 0.0,
+        }
+    }
+}
+
+/// 🧾 Deposits charged on a sale, or refunded on a return (total == deposit + tax)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositMovement {
+    pub reference: String,
+    pub lines: Vec<DepositLine>,
+    pub deposit: Money,
+    pub tax: Money,
+    pub total: Money,
+}
+
+impl DepositMovement {
+    fn new(reference: &str, lines: Vec<DepositLine>) -> Self {
+        let deposit = lines.iter().fold(Money::zero(), |acc, l| acc + l.deposit);
+        let tax = lines.iter().fold(Money::zero(), |acc, l| acc + l.tax);
+        DepositMovement {
+            reference: reference.to_string(),
+            lines,
+            deposit,
+            tax,
+            total: deposit + tax,
+        }
+    }
+
+    // 3. Return: refund the deposit on returned containers
+    /// Refund returned containers of this sale at the deposit and tax charged on it.
+    /// `returned` is product id => containers brought back; more containers than were charged
+    /// and not yet refunded is an error, and then nothing is refunded.
+    pub fn refund(&mut self, return_id: &str, returned: &[(String, f64)]) -> EngineResult<DepositMovement> {
+        let mut sale_lines = self.lines.clone();
+        let mut lines = Vec::with_capacity(returned.len());
+        for (product_id, units) in returned {
+            if units.is_nan() || *units <= 0.0 {
+                return Err(EngineError::Validation {
+                    message: format!("Returned containers of '{}' must be positive, got {}", product_id, units),
+                });
+            }
+            if !sale_lines.iter().any(|line| &line.product_id == product_id) {
+                return Err(EngineError::NotFound {
+                    resource: "Container deposit".to_string(),
+                    id: product_id.clone(),
+                });
+            }
+            let mut left = *units;
+            for line in sale_lines.iter_mut().filter(|line| &line.product_id == product_id) {
+                let take = left.min(line.units - line.returned_units);
+                if take > UNIT_EPSILON {
+                    lines.push(line.refund(take));
+                    left -= take;
+                }
+            }
+            if left > UNIT_EPSILON {
+                return Err(EngineError::Validation {
+                    message: format!(
+                        "{} more containers of '{}' returned than {} charged deposits for",
+                        left, product_id, self.reference
+                    ),
+                });
+            }
+        }
+        self.lines = sale_lines;
+        Ok(DepositMovement::new(return_id, lines))
+    }
+}
+
+// 4. Ledger posting
+/// 🏦 Accounts deposits post to
+#[derive(Debug, Clone)]
+pub struct DepositAccounts {
+    /// Cash / receivable the deposit is paid into and refunded from
+    pub cash: String,
+    /// Deposits held until the container comes back
+    pub deposit_liability: String,
+    /// Tax charged on taxable deposits
+    pub tax_payable: String,
+}
+
+impl DepositMovement {
+    /// Sale: Dr cash, Cr deposit liability (+ Cr tax payable)
+    pub fn post_charge(&self, ledger: &mut GeneralLedger, accounts: &DepositAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new(&format!("Container deposit {}", self.reference))
+            .debit(&accounts.cash, self.total)
+            .credit(&accounts.deposit_liability, self.deposit);
+        if !self.tax.is_zero() {
+            transaction = transaction.credit(&accounts.tax_payable, self.tax);
+        }
+        self.post(ledger, transaction)
+    }
+
+    /// Return: Dr deposit liability (+ Dr tax payable), Cr cash
+    pub fn post_refund(&self, ledger: &mut GeneralLedger, accounts: &DepositAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new(&format!("Container deposit refund {}", self.reference))
+            .debit(&accounts.deposit_liability, self.deposit);
+        if !self.tax.is_zero() {
+            transaction = transaction.debit(&accounts.tax_payable, self.tax);
+        }
+        self.post(ledger, transaction.credit(&accounts.cash, self.total))
+    }
+
+    fn post(&self, ledger: &mut GeneralLedger, mut transaction: Transaction) -> EngineResult<Transaction> {
+        transaction.metadata.insert("reference".to_string(), self.reference.clone());
+        ledger.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_deposit_books_liability_and_return_clears_it() {
+        let schedule = DepositSchedule::new()
+            .with_deposit("COLA", ContainerDeposit { per_unit: Money::new(25, 0), tax_percent: None })
+            .unwrap()
+            .with_deposit("BEER", ContainerDeposit { per_unit: Money::new(40, 0), tax_percent: Some(10.0) })
+            .unwrap();
+        assert!(DepositSchedule::new()
+            .with_deposit("X", ContainerDeposit { per_unit: Money::zero() - Money::new(1, 0), tax_percent: None })
+            .is_err());
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cola = Item::new("Cola", Money::new(150, 0), 6.0);
+        cola.id = "COLA".to_string();
+        let mut beer = Item::new("Beer", Money::new(500, 0), 2.0);
+        beer.id = "BEER".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(cola);
+        cart.add_item(beer);
+        let mut calculation = engine.calculate_cart(&cart, &[], None).unwrap();
+        let goods = calculation.grand_total;
+
+        // 6 x 25 untaxed + 2 x 40 with 10% tax on the deposit
+        let mut charge = schedule.charge("ORD-1", &cart, &mut calculation);
+        assert_eq!(charge.deposit, Money::new(230, 0));
+        assert_eq!(charge.tax, Money::new(8, 0));
+        assert_eq!(calculation.grand_total, goods + Money::new(238, 0));
+        assert_eq!(calculation.sale_total(), goods);
+
+        let mut ledger = GeneralLedger::new();
+        for (id, kind) in [
+            ("1000", AccountType::Asset),
+            ("2200", AccountType::Liability),
+            ("2400", AccountType::Liability),
+            ("4000", AccountType::Income),
+        ] {
+            ledger.add_account(Account::new(id, id, kind));
+        }
+        let accounts = DepositAccounts {
+            cash: "1000".to_string(),
+            deposit_liability: "2400".to_string(),
+            tax_payable: "2200".to_string(),
+        };
+        assert!(charge.post_charge(&mut ledger, &accounts).unwrap().is_balanced());
+        assert_eq!(ledger.balance("2400"), Some(Money::zero() - Money::new(230, 0)));
+        // The sale itself posts the goods only: cash received == grand total
+        let tax_accounts = [("VAT".to_string(), "2200".to_string())].into_iter().collect();
+        ledger.post_sale_with_discounts(&calculation, "1000", "4000", None, &tax_accounts).unwrap();
+        assert_eq!(ledger.balance("1000"), Some(calculation.grand_total));
+        assert_eq!(ledger.balance("4000"), Some(Money::zero() - calculation.subtotal));
+
+        // Every container comes back over two returns: deposit and its tax refunded, liability cleared
+        let first = charge.refund("RET-1", &[("COLA".to_string(), 4.0), ("BEER".to_string(), 1.0)]).unwrap();
+        let rest = charge.refund("RET-2", &[("COLA".to_string(), 2.0), ("BEER".to_string(), 1.0)]).unwrap();
+        assert_eq!(first.total + rest.total, charge.total);
+        for refund in [first, rest] {
+            assert!(refund.post_refund(&mut ledger, &accounts).unwrap().is_balanced());
+        }
+        assert_eq!(ledger.balance("2400"), Some(Money::zero()));
+        assert_eq!(ledger.balance("1000"), Some(calculation.sale_total()));
+
+        // Nothing left to refund, and containers the sale never charged for are unknown
+        assert!(matches!(charge.refund("RET-3", &[("COLA".to_string(), 1.0)]), Err(EngineError::Validation { .. })));
+        assert!(matches!(
+            charge.refund("RET-4", &[("WATER".to_string(), 1.0)]),
+            Err(EngineError::NotFound { .. })
+        ));
+        assert_eq!(charge.lines[0].returned_units, 6.0);
+    }
+}
@@ -2,4 +2,5 @@ pub mod percentage;
 pub mod fixed;
 pub mod tiered;
 pub mod item_discount;
+pub mod promo;
 
@@ -2,6 +2,7 @@ use crate::rules::traits::{Rule, RuleAction};
 use crate::types::cart::Cart;
 use crate::core::errors::EngineResult;
 use crate::rules::conditions::Condition;
+use crate::core::rounding::RoundingMode;
 
 /// ============================================================================
 /// 📉 Percentage Discount (ප්‍රතිශත වට්ටම්)
@@ -12,6 +13,7 @@ pub struct PercentageDiscount {
     percentage: f64,
     condition: Condition,
     priority: i32,
+    rounding: RoundingMode,
 }
 
 impl PercentageDiscount {
@@ -21,8 +23,15 @@ impl PercentageDiscount {
             percentage,
             condition,
             priority: 10, // Default priority
+            rounding: RoundingMode::Standard,
         }
     }
+
+    /// Match the engine's rounding mode
+    pub fn with_rounding(mut self, mode: RoundingMode) -> Self {
+        self.rounding = mode;
+        self
+    }
 }
 
 impl Rule for PercentageDiscount {
@@ -35,11 +44,11 @@ impl Rule for PercentageDiscount {
     }
 
     fn apply(&self, cart: &Cart) -> EngineResult<Vec<RuleAction>> {
-        let subtotal = cart.subtotal();
+        let subtotal = cart.discountable_subtotal();
         // Calculate discount amount: subtotal * (percentage / 100)
         // We can use Money::sub_percentage logic but here we need the AMOUNT to subtract
         let original = subtotal;
-        let discounted = subtotal.sub_percentage(self.percentage);
+        let discounted = subtotal.sub_percentage_rounded(self.percentage, self.rounding);
         let discount_amount = original - discounted;
 
         Ok(vec![RuleAction::Discount(discount_amount)])
@@ -48,4 +57,8 @@ impl Rule for PercentageDiscount {
     fn priority(&self) -> i32 {
         self.priority
     }
+
+    fn condition(&self) -> Option<&Condition> {
+        Some(&self.condition)
+    }
 }
@@ -0,0 +1,497 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// ============================================================================
+/// 🎟️ Promo Velocity Limits (ප්‍රවර්ධන කේත සීමා)
+/// ============================================================================
+/// එකම පාරිභෝගිකයා එකම coupon එක කුඩා ඇණවුම් දුසිම් ගණනකට යොදා "farm" කිරීම
+/// වැළැක්වීමට `PromoCodeManager` redemptions ලියාපදිංචි කර velocity limit එකක් බලාත්මක කරයි:
+/// rolling window එකක් තුළ එක් පාරිභෝගිකයෙකුට උපරිම N වාරයක්.
+///
+/// 🚦 Velocity Limit (max redemptions per customer per rolling window)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VelocityLimit {
+    pub max_redemptions: u32,
+    pub window_seconds: i64,
+}
+
+impl VelocityLimit {
+    pub fn new(max_redemptions: u32, window: Duration) -> Self {
+        VelocityLimit {
+            max_redemptions,
+            window_seconds: window.num_seconds(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::seconds(self.window_seconds)
+    }
+}
+
+/// Error sub-code returned when a customer redeems a code too often
+pub const PROMO_VELOCITY_EXCEEDED: &str = "PROMO_VELOCITY_EXCEEDED";
+
+/// Same code as typed by the customer and as configured? (codes are case-insensitive)
+pub fn same_code(entered: &str, configured: &str) -> bool {
+    normalize(entered) == normalize(configured)
+}
+
+fn normalize(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// Error sub-codes for rejected promo codes
+pub const PROMO_INACTIVE: &str = "PROMO_INACTIVE";
+pub const PROMO_NOT_YET_VALID: &str = "PROMO_NOT_YET_VALID";
+pub const PROMO_EXPIRED: &str = "PROMO_EXPIRED";
+pub const PROMO_EXHAUSTED: &str = "PROMO_EXHAUSTED";
+pub const PROMO_CUSTOMER_LIMIT: &str = "PROMO_CUSTOMER_LIMIT";
+pub const PROMO_MIN_CART_VALUE: &str = "PROMO_MIN_CART_VALUE";
+
+/// ============================================================================
+/// 🏷️ Promo Code Manager (ප්‍රවර්ධන කේත කළමනාකරු)
+/// ============================================================================
+/// Discount conditions වල `PromoCode` යනු string match එකක් පමණි. මෙහි ලියාපදිංචි කළ
+/// codes වලට මුළු භාවිත සීමාවක්, පාරිභෝගිකයෙකුට සීමාවක්, වලංගු කාලයක් සහ අවම cart
+/// අගයක් ඇත. Redeem/release lock එකක් යටතේ check සහ count එකවර කරන බැවින් එකවර
+/// checkouts දෙකකට අවසාන භාවිතය දෙවරක් ලබා ගත නොහැක.
+///
+/// 🚦 Code status (`Exhausted` is derived from the use count, never set)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PromoStatus {
+    #[default]
+    Active,
+    Paused,
+    Exhausted,
+}
+
+/// 🏷️ A registered code and its limits (None => unlimited / open-ended)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromoCode {
+    pub code: String,
+    pub max_total_uses: Option<u32>,
+    pub max_uses_per_customer: Option<u32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    /// Exclusive end of the validity window
+    pub valid_until: Option<DateTime<Utc>>,
+    pub min_cart_value: Option<Money>,
+    /// Per-customer rolling-window limit (None => the manager's default, if any)
+    #[serde(default)]
+    pub velocity_limit: Option<VelocityLimit>,
+}
+
+impl PromoCode {
+    pub fn new(code: &str) -> Self {
+        PromoCode {
+            code: normalize(code),
+            max_total_uses: None,
+            max_uses_per_customer: None,
+            valid_from: None,
+            valid_until: None,
+            min_cart_value: None,
+            velocity_limit: None,
+        }
+    }
+
+    pub fn with_max_total_uses(mut self, uses: u32) -> Self {
+        self.max_total_uses = Some(uses);
+        self
+    }
+
+    pub fn with_max_uses_per_customer(mut self, uses: u32) -> Self {
+        self.max_uses_per_customer = Some(uses);
+        self
+    }
+
+    pub fn with_validity(mut self, from: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Self {
+        self.valid_from = from;
+        self.valid_until = until;
+        self
+    }
+
+    pub fn with_min_cart_value(mut self, value: Money) -> Self {
+        self.min_cart_value = Some(value);
+        self
+    }
+
+    pub fn with_velocity_limit(mut self, limit: VelocityLimit) -> Self {
+        self.velocity_limit = Some(limit);
+        self
+    }
+}
+
+/// 🎟️ A counted use, handed back to `release` if the order is abandoned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromoRedemption {
+    pub code: String,
+    pub customer_id: Option<String>,
+    pub redeemed_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct PromoEntry {
+    config: PromoCode,
+    paused: bool,
+    uses: u32,
+    customer_uses: HashMap<String, u32>,
+    /// customer => redemption times, oldest first (velocity limit)
+    redeemed_at: HashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+impl PromoEntry {
+    fn status(&self) -> PromoStatus {
+        if self.paused {
+            PromoStatus::Paused
+        } else if self.config.max_total_uses.is_some_and(|max| self.uses >= max) {
+            PromoStatus::Exhausted
+        } else {
+            PromoStatus::Active
+        }
+    }
+
+    /// Redemptions by this customer inside the window ending at `at`
+    fn recent_redemptions(&self, customer_id: &str, limit: VelocityLimit, at: DateTime<Utc>) -> u32 {
+        let since = at - limit.window();
+        self.redeemed_at
+            .get(customer_id)
+            .map(|times| times.iter().filter(|t| **t > since && **t <= at).count() as u32)
+            .unwrap_or(0)
+    }
+
+    fn check(
+        &self,
+        customer_id: Option<&str>,
+        cart_value: Money,
+        at: DateTime<Utc>,
+        velocity: Option<VelocityLimit>,
+    ) -> EngineResult<()> {
+        let code = &self.config.code;
+        let reject = |sub_code: &str, message: String| {
+            Err(EngineError::Calculation {
+                code: sub_code.to_string(),
+                message,
+            })
+        };
+        match self.status() {
+            PromoStatus::Paused => return reject(PROMO_INACTIVE, format!("Promo code '{}' is paused", code)),
+            PromoStatus::Exhausted => {
+                return reject(PROMO_EXHAUSTED, format!("Promo code '{}' has no uses left", code))
+            }
+            PromoStatus::Active => {}
+        }
+        if self.config.valid_from.is_some_and(|from| at < from) {
+            return reject(PROMO_NOT_YET_VALID, format!("Promo code '{}' is not valid yet", code));
+        }
+        if self.config.valid_until.is_some_and(|until| at >= until) {
+            return reject(PROMO_EXPIRED, format!("Promo code '{}' has expired", code));
+        }
+        if let Some(max) = self.config.max_uses_per_customer {
+            // Anonymous checkouts could sidestep a per-customer limit
+            let used = match customer_id {
+                Some(customer) => self.customer_uses.get(customer).copied().unwrap_or(0),
+                None => max,
+            };
+            if used >= max {
+                return reject(
+                    PROMO_CUSTOMER_LIMIT,
+                    format!("Promo code '{}' is limited to {} use(s) per identified customer", code, max),
+                );
+            }
+        }
+        if let Some(min) = self.config.min_cart_value.filter(|min| cart_value < *min) {
+            return reject(
+                PROMO_MIN_CART_VALUE,
+                format!("Promo code '{}' needs a cart of at least {}", code, min),
+            );
+        }
+        if let (Some(limit), Some(customer)) = (self.config.velocity_limit.or(velocity), customer_id) {
+            if self.recent_redemptions(customer, limit, at) >= limit.max_redemptions {
+                return reject(
+                    PROMO_VELOCITY_EXCEEDED,
+                    format!(
+                        "Promo code '{}' already redeemed {} times by this customer in the last {} seconds",
+                        code, limit.max_redemptions, limit.window_seconds
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct PromoBook {
+    codes: HashMap<String, PromoEntry>,
+    /// Velocity limit of codes without their own
+    default_velocity: Option<VelocityLimit>,
+}
+
+/// 🗂️ Registered codes with their use counts (shareable: every method takes `&self`)
+#[derive(Debug, Default)]
+pub struct PromoCodeManager {
+    book: Mutex<PromoBook>,
+}
+
+impl PromoCodeManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn book(&self) -> std::sync::MutexGuard<'_, PromoBook> {
+        self.book.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Velocity limit for codes registered without one
+    pub fn set_default_velocity_limit(&self, limit: Option<VelocityLimit>) {
+        self.book().default_velocity = limit;
+    }
+
+    fn not_found(code: &str) -> EngineError {
+        EngineError::NotFound {
+            resource: "Promo code".to_string(),
+            id: normalize(code),
+        }
+    }
+
+    /// ➕ Register a new code (codes are case-insensitive and unique)
+    pub fn register(&self, mut config: PromoCode) -> EngineResult<()> {
+        config.code = normalize(&config.code);
+        if config.code.is_empty() {
+            return Err(EngineError::Validation {
+                message: "Promo code must not be empty".to_string(),
+            });
+        }
+        if let (Some(from), Some(until)) = (config.valid_from, config.valid_until) {
+            if from >= until {
+                return Err(EngineError::Validation {
+                    message: format!("Promo code '{}' validity ends before it starts", config.code),
+                });
+            }
+        }
+        if config.min_cart_value.is_some_and(|min| min.is_negative()) {
+            return Err(EngineError::Validation {
+                message: format!("Promo code '{}' has a negative minimum cart value", config.code),
+            });
+        }
+        let mut book = self.book();
+        if book.codes.contains_key(&config.code) {
+            return Err(EngineError::Validation {
+                message: format!("Promo code '{}' is already registered", config.code),
+            });
+        }
+        book.codes.insert(
+            config.code.clone(),
+            PromoEntry {
+                config,
+                paused: false,
+                uses: 0,
+                customer_uses: HashMap::new(),
+                redeemed_at: HashMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn is_registered(&self, code: &str) -> bool {
+        self.book().codes.contains_key(&normalize(code))
+    }
+
+    pub fn status(&self, code: &str) -> Option<PromoStatus> {
+        self.book().codes.get(&normalize(code)).map(PromoEntry::status)
+    }
+
+    /// Uses counted so far (None for unknown codes)
+    pub fn uses(&self, code: &str) -> Option<u32> {
+        self.book().codes.get(&normalize(code)).map(|entry| entry.uses)
+    }
+
+    /// Redemptions by this customer still inside the code's velocity window ending at `at`
+    pub fn recent_redemptions(&self, code: &str, customer_id: &str, at: DateTime<Utc>) -> u32 {
+        let book = self.book();
+        let Some(entry) = book.codes.get(&normalize(code)) else {
+            return 0;
+        };
+        match entry.config.velocity_limit.or(book.default_velocity) {
+            Some(limit) => entry.recent_redemptions(customer_id, limit, at),
+            None => 0,
+        }
+    }
+
+    /// ⏸️ Pause or resume a code
+    pub fn set_paused(&self, code: &str, paused: bool) -> EngineResult<()> {
+        let mut book = self.book();
+        let entry = book.codes.get_mut(&normalize(code)).ok_or_else(|| Self::not_found(code))?;
+        entry.paused = paused;
+        Ok(())
+    }
+
+    /// ✅ Could this code be redeemed right now? (nothing is counted)
+    pub fn validate(
+        &self,
+        code: &str,
+        customer_id: Option<&str>,
+        cart_value: Money,
+        at: DateTime<Utc>,
+    ) -> EngineResult<()> {
+        let book = self.book();
+        let entry = book.codes.get(&normalize(code)).ok_or_else(|| Self::not_found(code))?;
+        entry.check(customer_id, cart_value, at, book.default_velocity)
+    }
+
+    /// 🎟️ Check and count one use atomically
+    pub fn redeem(
+        &self,
+        code: &str,
+        customer_id: Option<&str>,
+        cart_value: Money,
+        at: DateTime<Utc>,
+    ) -> EngineResult<PromoRedemption> {
+        let mut book = self.book();
+        let velocity = book.default_velocity;
+        let entry = book.codes.get_mut(&normalize(code)).ok_or_else(|| Self::not_found(code))?;
+        entry.check(customer_id, cart_value, at, velocity)?;
+        entry.uses += 1;
+        if let Some(customer) = customer_id {
+            *entry.customer_uses.entry(customer.to_string()).or_insert(0) += 1;
+            let times = entry.redeemed_at.entry(customer.to_string()).or_default();
+            // Entries outside the window can never count again
+            if let Some(limit) = entry.config.velocity_limit.or(velocity) {
+                while times.front().is_some_and(|t| *t <= at - limit.window()) {
+                    times.pop_front();
+                }
+            }
+            times.push_back(at);
+        }
+        Ok(PromoRedemption {
+            code: entry.config.code.clone(),
+            customer_id: customer_id.map(str::to_string),
+            redeemed_at: at,
+        })
+    }
+
+    /// ↩️ Give back a use (order cancelled or payment failed)
+    pub fn release(&self, redemption: &PromoRedemption) -> EngineResult<()> {
+        let mut book = self.book();
+        let entry = book.codes.get_mut(&redemption.code).ok_or_else(|| Self::not_found(&redemption.code))?;
+        let customer_uses = redemption
+            .customer_id
+            .as_ref()
+            .and_then(|customer| entry.customer_uses.get_mut(customer));
+        if entry.uses == 0 || (redemption.customer_id.is_some() && customer_uses.as_ref().is_none_or(|n| **n == 0)) {
+            return Err(EngineError::Validation {
+                message: format!("Promo code '{}' has no matching use to release", redemption.code),
+            });
+        }
+        if let Some(uses) = customer_uses {
+            *uses -= 1;
+        }
+        entry.uses -= 1;
+        if let Some(times) = redemption.customer_id.as_ref().and_then(|customer| entry.redeemed_at.get_mut(customer)) {
+            if let Some(index) = times.iter().rposition(|t| *t == redemption.redeemed_at) {
+                times.remove(index);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_velocity_limit_per_customer_in_rolling_window() {
+        let manager = PromoCodeManager::new();
+        manager
+            .register(PromoCode::new("SAVE10").with_velocity_limit(VelocityLimit::new(3, Duration::hours(24))))
+            .unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let cart = Money::new(1000, 0);
+
+        for hour in 0..3 {
+            manager.redeem("save10", Some("C001"), cart, start + Duration::hours(hour)).unwrap();
+        }
+        // 4th within 24h is rejected with the velocity code
+        let error = manager.redeem("SAVE10", Some("C001"), cart, start + Duration::hours(5)).unwrap_err();
+        assert_eq!(error.sub_code(), Some(PROMO_VELOCITY_EXCEEDED));
+        assert_eq!(manager.recent_redemptions("SAVE10", "C001", start + Duration::hours(5)), 3);
+        // Other customers are unaffected
+        manager.redeem("SAVE10", Some("C002"), cart, start + Duration::hours(5)).unwrap();
+
+        // Once the first redemption leaves the window, one more is allowed
+        manager.redeem("SAVE10", Some("C001"), cart, start + Duration::hours(24)).unwrap();
+        assert!(manager.redeem("SAVE10", Some("C001"), cart, start + Duration::hours(24)).is_err());
+    }
+
+    #[test]
+    fn test_codes_without_limit_are_unrestricted() {
+        let manager = PromoCodeManager::new();
+        manager.register(PromoCode::new("WELCOME")).unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        for _ in 0..10 {
+            manager.redeem("WELCOME", Some("C001"), Money::new(1000, 0), at).unwrap();
+        }
+
+        // The default limit covers codes registered without their own, and released uses free a slot
+        manager.set_default_velocity_limit(Some(VelocityLimit::new(1, Duration::hours(1))));
+        manager.register(PromoCode::new("FLASH")).unwrap();
+        let first = manager.redeem("FLASH", Some("C001"), Money::new(1000, 0), at).unwrap();
+        assert!(manager.redeem("FLASH", Some("C001"), Money::new(1000, 0), at).is_err());
+        manager.release(&first).unwrap();
+        manager.redeem("FLASH", Some("C001"), Money::new(1000, 0), at).unwrap();
+    }
+
+    #[test]
+    fn test_promo_code_limits_and_concurrent_redemption() {
+        use std::sync::Arc;
+
+        let manager = Arc::new(PromoCodeManager::new());
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        manager
+            .register(
+                PromoCode::new("launch")
+                    .with_max_total_uses(10)
+                    .with_max_uses_per_customer(1)
+                    .with_validity(Some(start), Some(start + Duration::days(7)))
+                    .with_min_cart_value(Money::new(1000, 0)),
+            )
+            .unwrap();
+        assert!(manager.register(PromoCode::new("LAUNCH")).is_err());
+
+        let at = start + Duration::days(1);
+        let big_cart = Money::new(1500, 0);
+        let sub_code = |result: EngineResult<PromoRedemption>| result.unwrap_err().sub_code().map(str::to_string);
+        assert_eq!(sub_code(manager.redeem("LAUNCH", Some("C0"), Money::new(999, 0), at)).as_deref(), Some(PROMO_MIN_CART_VALUE));
+        assert_eq!(sub_code(manager.redeem("LAUNCH", Some("C0"), big_cart, start + Duration::days(7))).as_deref(), Some(PROMO_EXPIRED));
+        assert_eq!(sub_code(manager.redeem("LAUNCH", None, big_cart, at)).as_deref(), Some(PROMO_CUSTOMER_LIMIT));
+
+        // 50 customers race for 10 uses: exactly 10 succeed
+        let handles: Vec<_> = (0..50)
+            .map(|n| {
+                let manager = manager.clone();
+                std::thread::spawn(move || manager.redeem("launch", Some(&format!("C{}", n)), big_cart, at).ok())
+            })
+            .collect();
+        let redeemed: Vec<PromoRedemption> = handles.into_iter().filter_map(|h| h.join().unwrap()).collect();
+        assert_eq!(redeemed.len(), 10);
+        assert_eq!(manager.status("LAUNCH"), Some(PromoStatus::Exhausted));
+
+        // Releasing an abandoned checkout frees the use again (but only once)
+        manager.release(&redeemed[0]).unwrap();
+        assert!(manager.release(&redeemed[0]).is_err());
+        assert_eq!(manager.status("LAUNCH"), Some(PromoStatus::Active));
+        // A customer who kept their use is still at the per-customer limit
+        let kept = redeemed[1].customer_id.as_deref();
+        assert_eq!(sub_code(manager.redeem("LAUNCH", kept, big_cart, at)).as_deref(), Some(PROMO_CUSTOMER_LIMIT));
+        manager.redeem("LAUNCH", redeemed[0].customer_id.as_deref(), big_cart, at).unwrap();
+        assert_eq!(manager.uses("LAUNCH"), Some(10));
+
+        manager.set_paused("LAUNCH", true).unwrap();
+        assert_eq!(manager.status("LAUNCH"), Some(PromoStatus::Paused));
+    }
+}
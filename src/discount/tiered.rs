@@ -45,7 +45,7 @@ impl Rule for TieredDiscount {
         // Assuming tiers are sorted descending
         for tier in &self.tiers {
             if total_qty >= tier.min_qty {
-                let subtotal = cart.subtotal();
+                let subtotal = cart.discountable_subtotal();
                 let original = subtotal;
                 let discounted = subtotal.sub_percentage(tier.percentage);
                 let discount_amount = original - discounted;
@@ -1,12 +1,15 @@
 use crate::core::errors::{EngineResult, EngineError};
 use serde::{Deserialize, Serialize};
+use crate::inventory::warehouse::Warehouse;
+use crate::security::audit_trail::{AuditAction, AuditEntry, AuditSeverity, AuditTrail};
 use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
 
 /// ============================================================================
 /// 📦 Stock Management (තොග පාලනය)
 /// ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MovementType {
     Inbound,  // Receiving (Purchasing)
     Outbound, // Shipping (Sales)
@@ -23,12 +26,71 @@ pub struct StockMovement {
     pub movement_type: MovementType,
     pub date: DateTime<Utc>,
     pub reference: String, // PO Number, Sales Order ID
+    /// Who made the change (audit)
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Why, Ex: "damaged", "stock take" (audit)
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl StockMovement {
+    pub fn new(
+        item_id: &str,
+        warehouse_id: &str,
+        quantity: f64,
+        movement_type: MovementType,
+        reference: &str,
+    ) -> Self {
+        StockMovement {
+            id: uuid::Uuid::new_v4().to_string(),
+            item_id: item_id.to_string(),
+            warehouse_id: warehouse_id.to_string(),
+            quantity,
+            movement_type,
+            date: Utc::now(),
+            reference: reference.to_string(),
+            user_id: None,
+            reason: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+}
+
+/// 🔁 What to do when a movement with an already-applied reference arrives again
+/// (Ex: a retried sync re-sending the same goods receipt)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    #[default]
+    Ignore,
+    Reject,
+}
+
+/// Result of `record_movement`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MovementOutcome {
+    Applied,
+    /// Applied, leaving the item negative in a warehouse that allows it
+    Backordered { quantity: f64 },
+    /// Inbound stock applied while the item is still negative - `backordered` is still owed
+    Received { backordered: f64 },
+    /// Same (reference, item, type) already applied - stock unchanged
+    Duplicate,
 }
 
 pub struct InventoryManager {
     // Key: WarehouseID -> Key: ItemID -> Quantity
     stock_levels: std::collections::HashMap<String, std::collections::HashMap<String, f64>>,
     movements: Vec<StockMovement>,
+    // (reference, item_id, movement_type) already applied
+    applied_references: std::collections::HashSet<(String, String, MovementType)>,
+    duplicate_policy: DuplicatePolicy,
+    audit_trail: Option<Arc<Mutex<AuditTrail>>>,
+    warehouses: std::collections::HashMap<String, Warehouse>,
 }
 
 impl InventoryManager {
@@ -36,15 +98,60 @@ impl InventoryManager {
         InventoryManager {
             stock_levels: std::collections::HashMap::new(),
             movements: Vec::new(),
+            applied_references: std::collections::HashSet::new(),
+            duplicate_policy: DuplicatePolicy::default(),
+            audit_trail: None,
+            warehouses: std::collections::HashMap::new(),
         }
     }
 
+    /// 🏭 Register a warehouse (unregistered warehouses never go negative)
+    pub fn register_warehouse(&mut self, warehouse: Warehouse) {
+        self.warehouses.insert(warehouse.id.clone(), warehouse);
+    }
+
+    /// 📜 Every applied movement is logged here (who, before/after, reason)
+    pub fn with_audit_trail(mut self, trail: Arc<Mutex<AuditTrail>>) -> Self {
+        self.audit_trail = Some(trail);
+        self
+    }
+
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
     /// Record a stock movement
-    pub fn record_movement(&mut self, movement: StockMovement) -> EngineResult<()> {
+    /// Idempotent by (reference, item_id, movement_type); movements without a reference are
+    /// always applied.
+    pub fn record_movement(&mut self, movement: StockMovement) -> EngineResult<MovementOutcome> {
+        let key = (
+            movement.reference.clone(),
+            movement.item_id.clone(),
+            movement.movement_type,
+        );
+        let has_reference = !movement.reference.trim().is_empty();
+        if has_reference && self.applied_references.contains(&key) {
+            return match self.duplicate_policy {
+                DuplicatePolicy::Ignore => Ok(MovementOutcome::Duplicate),
+                DuplicatePolicy::Reject => Err(EngineError::Validation {
+                    message: format!(
+                        "Movement {:?} for item {} with reference {} was already recorded",
+                        movement.movement_type, movement.item_id, movement.reference
+                    ),
+                }),
+            };
+        }
+
+        let allow_negative = self
+            .warehouses
+            .get(&movement.warehouse_id)
+            .is_some_and(|w| w.allow_negative);
         let warehouse_stock = self.stock_levels.entry(movement.warehouse_id.clone())
             .or_insert_with(std::collections::HashMap::new);
         
         let current_qty = warehouse_stock.entry(movement.item_id.clone()).or_insert(0.0);
+        let before_qty = *current_qty;
 
         match movement.movement_type {
             MovementType::Inbound | MovementType::Adjustment => {
@@ -53,7 +160,7 @@ impl InventoryManager {
                 *current_qty += movement.quantity;
             },
             MovementType::Outbound => {
-                if *current_qty < movement.quantity {
+                if *current_qty < movement.quantity && !allow_negative {
                      return Err(EngineError::Validation {
                         message: format!("Insufficient Stock for Item {}. Available: {}, Requested: {}", movement.item_id, current_qty, movement.quantity),
                     });
@@ -67,8 +174,67 @@ impl InventoryManager {
             }
         }
 
+        let after_qty = *current_qty;
+
+        if has_reference {
+            self.applied_references.insert(key);
+        }
+        self.audit(&movement, before_qty, after_qty);
         self.movements.push(movement);
-        Ok(())
+        // Only stock going out can create a backorder; receipts just reduce it
+        if after_qty < 0.0 && after_qty < before_qty {
+            return Ok(MovementOutcome::Backordered { quantity: -after_qty });
+        }
+        if after_qty < 0.0 {
+            return Ok(MovementOutcome::Received { backordered: -after_qty });
+        }
+        Ok(MovementOutcome::Applied)
+    }
+
+    /// 📋 Quantity owed to customers (stock below zero)
+    pub fn backordered(&self, warehouse_id: &str, item_id: &str) -> f64 {
+        (-self.get_stock(warehouse_id, item_id)).max(0.0)
+    }
+
+    fn audit(&self, movement: &StockMovement, before_qty: f64, after_qty: f64) {
+        let Some(trail) = &self.audit_trail else {
+            return;
+        };
+        // Manual corrections are the fraud-sensitive ones
+        let (action, severity) = match movement.movement_type {
+            MovementType::Adjustment => (AuditAction::StockAdjusted, AuditSeverity::Audit),
+            _ => (AuditAction::StockMoved, AuditSeverity::Info),
+        };
+        let reason = movement.reason.as_deref().unwrap_or("unspecified");
+        let description = format!(
+            "{:?} {} @ {}: {} -> {} ({})",
+            movement.movement_type, movement.item_id, movement.warehouse_id, before_qty, after_qty, reason
+        );
+
+        let mut entry = AuditEntry::new(action, severity, "Stock", &description)
+            .with_resource(&movement.item_id)
+            .with_changes(Some(&before_qty.to_string()), Some(&after_qty.to_string()))
+            .with_metadata("warehouse_id", &movement.warehouse_id)
+            .with_metadata("reference", &movement.reference)
+            .with_metadata("reason", reason);
+        if let Some(user_id) = &movement.user_id {
+            entry = entry.with_user(user_id, None, None);
+        }
+
+        // A poisoned trail is still appendable
+        trail.lock().unwrap_or_else(|e| e.into_inner()).log(entry);
+    }
+
+    /// Has this reference already been applied for the item / movement type?
+    pub fn is_applied(&self, reference: &str, item_id: &str, movement_type: MovementType) -> bool {
+        self.applied_references
+            .contains(&(reference.to_string(), item_id.to_string(), movement_type))
+    }
+
+    /// Forget an applied reference after its movement was rolled back, so a retry applies again
+    pub fn release_reference(&mut self, reference: &str, item_id: &str, movement_type: MovementType) -> bool {
+        self.applied_references
+            .remove(&(reference.to_string(), item_id.to_string(), movement_type))
     }
 
     pub fn get_stock(&self, warehouse_id: &str, item_id: &str) -> f64 {
@@ -80,3 +246,115 @@ impl InventoryManager {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(reference: &str, quantity: f64) -> StockMovement {
+        StockMovement {
+            id: uuid::Uuid::new_v4().to_string(),
+            item_id: "SKU-1".to_string(),
+            warehouse_id: "WH-1".to_string(),
+            quantity,
+            movement_type: MovementType::Inbound,
+            date: Utc::now(),
+            reference: reference.to_string(),
+            user_id: None,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_inbound_reference_ignored() {
+        let mut inventory = InventoryManager::new();
+        assert_eq!(inventory.record_movement(receipt("PO-100", 10.0)).unwrap(), MovementOutcome::Applied);
+        // Retried sync
+        assert_eq!(inventory.record_movement(receipt("PO-100", 10.0)).unwrap(), MovementOutcome::Duplicate);
+        assert_eq!(inventory.get_stock("WH-1", "SKU-1"), 10.0);
+        assert!(inventory.is_applied("PO-100", "SKU-1", MovementType::Inbound));
+
+        let mut strict = InventoryManager::new().with_duplicate_policy(DuplicatePolicy::Reject);
+        strict.record_movement(receipt("PO-100", 10.0)).unwrap();
+        assert!(strict.record_movement(receipt("PO-100", 10.0)).is_err());
+    }
+
+    #[test]
+    fn test_distinct_reference_applies() {
+        let mut inventory = InventoryManager::new();
+        inventory.record_movement(receipt("PO-100", 10.0)).unwrap();
+        assert_eq!(inventory.record_movement(receipt("PO-101", 5.0)).unwrap(), MovementOutcome::Applied);
+        assert_eq!(inventory.get_stock("WH-1", "SKU-1"), 15.0);
+    }
+
+    #[test]
+    fn test_adjustment_audited_with_before_and_after() {
+        let trail = Arc::new(Mutex::new(AuditTrail::new(100)));
+        let mut inventory = InventoryManager::new().with_audit_trail(trail.clone());
+        inventory.record_movement(receipt("PO-100", 10.0)).unwrap();
+
+        let mut shrinkage = receipt("ADJ-7", -3.0);
+        shrinkage.movement_type = MovementType::Adjustment;
+        shrinkage.user_id = Some("clerk-42".to_string());
+        shrinkage.reason = Some("damaged in storage".to_string());
+        inventory.record_movement(shrinkage).unwrap();
+
+        let trail = trail.lock().unwrap();
+        assert_eq!(trail.count(), 2);
+        let adjustments = trail.get_by_action(&AuditAction::StockAdjusted);
+        assert_eq!(adjustments.len(), 1);
+        let entry = adjustments[0];
+        assert_eq!(entry.old_value.as_deref(), Some("10"));
+        assert_eq!(entry.new_value.as_deref(), Some("7"));
+        assert_eq!(entry.user_id.as_deref(), Some("clerk-42"));
+        assert_eq!(entry.metadata.get("reason").map(String::as_str), Some("damaged in storage"));
+        assert!(entry.verify_integrity());
+    }
+
+    fn shipment(warehouse_id: &str, quantity: f64) -> StockMovement {
+        let mut movement = receipt("", quantity);
+        movement.warehouse_id = warehouse_id.to_string();
+        movement.movement_type = MovementType::Outbound;
+        movement
+    }
+
+    #[test]
+    fn test_backorder_allowed_in_dropship_warehouse() {
+        let mut inventory = InventoryManager::new();
+        let dropship = Warehouse::new("Dropship", "Supplier").allowing_negative();
+        let dropship_id = dropship.id.clone();
+        inventory.register_warehouse(dropship);
+
+        let mut stock = receipt("PO-1", 2.0);
+        stock.warehouse_id = dropship_id.clone();
+        inventory.record_movement(stock).unwrap();
+
+        let outcome = inventory.record_movement(shipment(&dropship_id, 5.0)).unwrap();
+        assert_eq!(outcome, MovementOutcome::Backordered { quantity: 3.0 });
+        assert_eq!(inventory.get_stock(&dropship_id, "SKU-1"), -3.0);
+        assert_eq!(inventory.backordered(&dropship_id, "SKU-1"), 3.0);
+
+        // A partial receipt is not a new backorder
+        let mut restock = receipt("PO-2", 1.0);
+        restock.warehouse_id = dropship_id.clone();
+        let outcome = inventory.record_movement(restock).unwrap();
+        assert_eq!(outcome, MovementOutcome::Received { backordered: 2.0 });
+        assert_eq!(inventory.backordered(&dropship_id, "SKU-1"), 2.0);
+    }
+
+    #[test]
+    fn test_oversell_rejected_on_retail_floor() {
+        let mut inventory = InventoryManager::new();
+        let floor = Warehouse::new("Retail Floor", "Colombo 03");
+        let floor_id = floor.id.clone();
+        inventory.register_warehouse(floor);
+
+        let mut stock = receipt("PO-1", 2.0);
+        stock.warehouse_id = floor_id.clone();
+        inventory.record_movement(stock).unwrap();
+
+        assert!(inventory.record_movement(shipment(&floor_id, 5.0)).is_err());
+        assert_eq!(inventory.get_stock(&floor_id, "SKU-1"), 2.0);
+        assert_eq!(inventory.backordered(&floor_id, "SKU-1"), 0.0);
+    }
+}
@@ -10,6 +10,9 @@ pub struct Warehouse {
     pub name: String,
     pub location: String, // Or Address Struct
     pub is_active: bool,
+    /// Dropship / backorder warehouses may ship more than they hold
+    #[serde(default)]
+    pub allow_negative: bool,
 }
 
 impl Warehouse {
@@ -19,6 +22,13 @@ impl Warehouse {
             name: name.to_string(),
             location: location.to_string(),
             is_active: true,
+            allow_negative: false,
         }
     }
+
+    /// Permit outbound movements to take stock below zero (backorders)
+    pub fn allowing_negative(mut self) -> Self {
+        self.allow_negative = true;
+        self
+    }
 }
@@ -0,0 +1,252 @@
+use crate::core::money::Money;
+use crate::core::words::CurrencyWords;
+use crate::rules::mixed_scenarios::CartCalculation;
+use crate::types::cart::Cart;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// 🧾 Invoice Document (ඉන්වොයිසිය)
+/// ============================================================================
+/// PDF generator එකකට කෙලින්ම දිය හැකි ව්‍යුහගත ඉන්වොයිසියක්.
+/// Seller, buyer, line items (HS code / tax class), tax summary by rate,
+/// සහ අකුරින් මුදල (amount in words) ඇතුළත් වේ.
+///
+/// 🏢 Invoice Party (Seller / Buyer)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvoiceParty {
+    pub name: String,
+    pub address: Option<String>,
+    pub tax_id: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+impl InvoiceParty {
+    pub fn new(name: &str) -> Self {
+        InvoiceParty {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_address(mut self, address: &str) -> Self {
+        self.address = Some(address.to_string());
+        self
+    }
+
+    pub fn with_tax_id(mut self, tax_id: &str) -> Self {
+        self.tax_id = Some(tax_id.to_string());
+        self
+    }
+}
+
+/// 📦 Invoice Line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub item_id: String,
+    pub description: String,
+    /// Harmonized System code (item metadata `hs_code`)
+    pub hs_code: Option<String>,
+    /// Tax class (item metadata `tax_class`)
+    pub tax_class: Option<String>,
+    pub quantity: f64,
+    pub unit_price: Money,
+    pub gross_amount: Money,
+    pub discount: Money,
+    pub net_amount: Money,
+    pub tax: Money,
+    pub total: Money,
+}
+
+/// 🏛️ Tax Summary (rate එක අනුව බදු සාරාංශය)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxSummaryLine {
+    pub name: String,
+    pub rate: f64,
+    pub taxable_amount: Money,
+    pub tax_amount: Money,
+}
+
+/// 🧾 Invoice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    pub invoice_number: String,
+    pub issue_date: DateTime<Utc>,
+    pub currency: String,
+    pub seller: InvoiceParty,
+    pub buyer: InvoiceParty,
+    pub lines: Vec<InvoiceLine>,
+    pub tax_summary: Vec<TaxSummaryLine>,
+    pub subtotal: Money,
+    pub total_discount: Money,
+    pub total_tax: Money,
+    pub grand_total: Money,
+    pub amount_in_words: String,
+}
+
+impl Invoice {
+    /// 🏗️ Build an invoice from a cart and its calculation
+    pub fn from_calculation(
+        cart: &Cart,
+        calculation: &CartCalculation,
+        seller: InvoiceParty,
+        buyer: InvoiceParty,
+    ) -> Self {
+        let mut lines = Vec::new();
+
+        for line in &calculation.items {
+            let item = cart.items.iter().find(|i| i.id == line.item_id);
+            let quantity = item.map(|i| i.quantity).unwrap_or(0.0);
+
+            lines.push(InvoiceLine {
+                item_id: line.item_id.clone(),
+                description: item.map(|i| i.name.clone()).unwrap_or_default(),
+                hs_code: item.and_then(|i| i.metadata.get("hs_code").cloned()),
+                tax_class: item.and_then(|i| i.metadata.get("tax_class").cloned()),
+                quantity,
+                unit_price: item.map(|i| i.price).unwrap_or_else(Money::zero),
+                gross_amount: line.base_amount,
+                discount: line.discount_amount,
+                net_amount: line.base_amount - line.discount_amount,
+                tax: line.tax_amount,
+                total: line.total,
+            });
+        }
+
+        Invoice {
+            invoice_number: uuid::Uuid::new_v4().to_string(),
+            issue_date: Utc::now(),
+            currency: cart.currency.code(),
+            seller,
+            buyer,
+            lines,
+            tax_summary: Self::summarize_taxes(calculation),
+            subtotal: calculation.subtotal,
+            total_discount: calculation.total_discount,
+            total_tax: calculation.total_tax,
+            grand_total: calculation.grand_total,
+            amount_in_words: amount_in_words(calculation.grand_total),
+        }
+    }
+
+    /// 📊 Group line taxes by (name, rate)
+    /// Lines without tax details fall back to their effective rate.
+    pub fn summarize_taxes(calculation: &CartCalculation) -> Vec<TaxSummaryLine> {
+        // Key: (name, rate in basis points) -> (rate, taxable, tax)
+        let mut groups: BTreeMap<(i64, String), (f64, Money, Money)> = BTreeMap::new();
+
+        for line in &calculation.items {
+            let net = line.base_amount - line.discount_amount;
+
+            if line.tax_details.is_empty() {
+                let rate = if net.is_zero() {
+                    0.0
+                } else {
+                    (line.tax_amount.amount as f64 * 10000.0 / net.amount as f64).round() / 100.0
+                };
+                let name = if line.tax_amount.is_zero() { "Exempt" } else { "Tax" };
+                let entry = groups
+                    .entry(((rate * 100.0).round() as i64, name.to_string()))
+                    .or_insert((rate, Money::zero(), Money::zero()));
+                entry.1 = entry.1 + net;
+                entry.2 = entry.2 + line.tax_amount;
+                continue;
+            }
+
+            for detail in &line.tax_details {
+                let entry = groups
+                    .entry(((detail.rate * 100.0).round() as i64, detail.name.clone()))
+                    .or_insert((detail.rate, Money::zero(), Money::zero()));
+                entry.1 = entry.1 + net;
+                entry.2 = entry.2 + detail.amount;
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|((_, name), (rate, taxable_amount, tax_amount))| TaxSummaryLine {
+                name,
+                rate,
+                taxable_amount,
+                tax_amount,
+            })
+            .collect()
+    }
+}
+
+/// 🔤 අකුරින් මුදල (Amount in words - English)
+/// Ex: Rs.1,234.50 => "One Thousand Two Hundred Thirty Four Rupees and Fifty Cents"
+pub fn amount_in_words(money: Money) -> String {
+    money.to_words(CurrencyWords::rupees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn line(item_id: &str, base: i64, tax_name: &str, rate: f64, tax: i64) -> ItemCalculation {
+        ItemCalculation {
+            item_id: item_id.to_string(),
+            base_amount: Money::from_cents(base),
+            discount_amount: Money::zero(),
+            tax_amount: Money::from_cents(tax),
+            total: Money::from_cents(base + tax),
+            discount_details: Vec::new(),
+            tax_details: vec![TaxDetail {
+                name: tax_name.to_string(),
+                rate,
+                amount: Money::from_cents(tax),
+                treatment: TaxTreatment::Standard,
+                reverse_charged: Money::zero(),
+                taxable_base: Money::from_cents(base),
+            }],
+            cap_adjustments: Vec::new(),
+            suppressed_discounts: Vec::new(),
+            tax_included: false,
+            cost: None,
+        }
+    }
+
+    #[test]
+    fn test_tax_summary_grouped_by_rate() {
+        let calculation = CartCalculation {
+            items: vec![
+                line("A", 10000, "VAT", 18.0, 1800),
+                line("B", 5000, "VAT", 18.0, 900),
+                line("C", 20000, "VAT", 8.0, 1600),
+            ],
+            subtotal: Money::from_cents(35000),
+            total_discount: Money::zero(),
+            total_tax: Money::from_cents(4300),
+            grand_total: Money::from_cents(39300),
+            rounding_adjustment: Money::zero(),
+            suppressed_discounts: Vec::new(),
+            included_tax: Money::zero(),
+            added_tax: Money::zero(),
+            deposits: Money::zero(),
+        };
+
+        let summary = Invoice::summarize_taxes(&calculation);
+        assert_eq!(summary.len(), 2);
+
+        assert_eq!(summary[0].rate, 8.0);
+        assert_eq!(summary[0].taxable_amount.amount, 20000);
+        assert_eq!(summary[0].tax_amount.amount, 1600);
+
+        assert_eq!(summary[1].rate, 18.0);
+        assert_eq!(summary[1].taxable_amount.amount, 15000);
+        assert_eq!(summary[1].tax_amount.amount, 2700);
+    }
+
+    #[test]
+    fn test_amount_in_words() {
+        assert_eq!(
+            amount_in_words(Money::new(1234, 50)),
+            "One Thousand Two Hundred Thirty Four Rupees and Fifty Cents"
+        );
+        assert_eq!(amount_in_words(Money::zero()), "Zero Rupees");
+    }
+}
@@ -0,0 +1,3 @@
+pub mod document;
+pub mod receivables;
+pub mod sequence;
@@ -0,0 +1,334 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 📒 Outstanding Invoice (ණය විකුණුම් හිඟ ශේෂය)
+/// ============================================================================
+/// `PaymentMethod::Credit` සම්පූර්ණ මුදල receivable ගිණුමට යවයි.
+/// පාරිභෝගිකයා වාරික වශයෙන් ගෙවන විට එක් එක් ගෙවීම මෙහි සටහන් කර,
+/// ledger එකට (Debit cash / Credit receivable) post කරයි.
+/// `aging_report` මගින් හිඟ ශේෂ දින ගණන අනුව (0-30, 31-60, 61-90, 90+) කාණ්ඩ කරයි.
+///
+/// 🧾 Payment Record (ලැබුණු ගෙවීම)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    pub id: String,
+    pub amount: Money,
+    /// Cash / bank account the payment was deposited to
+    pub deposit_account: String,
+    /// Ledger transaction that recorded this payment
+    pub transaction_id: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// 📊 Settlement Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SettlementStatus {
+    Open,
+    PartiallyPaid,
+    Paid,
+}
+
+/// 📒 Outstanding Invoice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutstandingInvoice {
+    pub invoice_number: String,
+    pub customer_id: String,
+    /// Accounts Receivable ledger account for this customer
+    pub receivable_account: String,
+    pub original_amount: Money,
+    pub payments: Vec<PaymentRecord>,
+    pub issued_at: DateTime<Utc>,
+    /// Aging is measured from here; falls back to the issue date when unset
+    pub due_date: Option<NaiveDate>,
+}
+
+impl OutstandingInvoice {
+    pub fn new(
+        invoice_number: &str,
+        customer_id: &str,
+        receivable_account: &str,
+        original_amount: Money,
+    ) -> Self {
+        OutstandingInvoice {
+            invoice_number: invoice_number.to_string(),
+            customer_id: customer_id.to_string(),
+            receivable_account: receivable_account.to_string(),
+            original_amount,
+            payments: Vec::new(),
+            issued_at: Utc::now(),
+            due_date: None,
+        }
+    }
+
+    pub fn with_due_date(mut self, due_date: NaiveDate) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+
+    /// 📅 Days overdue as of a date (0 when not yet due)
+    pub fn days_overdue(&self, as_of: NaiveDate) -> i64 {
+        let due = self.due_date.unwrap_or_else(|| self.issued_at.date_naive());
+        (as_of - due).num_days().max(0)
+    }
+
+    /// 💵 Total received so far
+    pub fn amount_paid(&self) -> Money {
+        self.payments
+            .iter()
+            .fold(Money::zero(), |acc, payment| acc + payment.amount)
+    }
+
+    /// ⏳ Remaining balance (ඉතිරි ශේෂය)
+    pub fn remaining_balance(&self) -> Money {
+        self.original_amount - self.amount_paid()
+    }
+
+    pub fn status(&self) -> SettlementStatus {
+        if !self.remaining_balance().is_positive() {
+            SettlementStatus::Paid
+        } else if self.payments.is_empty() {
+            SettlementStatus::Open
+        } else {
+            SettlementStatus::PartiallyPaid
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.status() == SettlementStatus::Paid
+    }
+
+    /// 💳 Record an installment against this invoice
+    /// Posts Debit `deposit_account` / Credit receivable; overpayments are rejected
+    /// before anything reaches the ledger.
+    pub fn record_payment(
+        &mut self,
+        ledger: &mut GeneralLedger,
+        deposit_account: &str,
+        amount: Money,
+    ) -> EngineResult<PaymentRecord> {
+        if !amount.is_positive() {
+            return Err(EngineError::Validation {
+                message: format!("Payment amount must be positive, got {}", amount),
+            });
+        }
+
+        let remaining = self.remaining_balance();
+        if amount > remaining {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Overpayment on invoice {}: paying {} but only {} is outstanding",
+                    self.invoice_number, amount, remaining
+                ),
+            });
+        }
+
+        let mut transaction = Transaction::new(&format!(
+            "Payment received for invoice {}",
+            self.invoice_number
+        ))
+        .debit(deposit_account, amount)
+        .credit(&self.receivable_account, amount);
+        transaction
+            .metadata
+            .insert("invoice_number".to_string(), self.invoice_number.clone());
+        transaction
+            .metadata
+            .insert("customer_id".to_string(), self.customer_id.clone());
+
+        let record = PaymentRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            amount,
+            deposit_account: deposit_account.to_string(),
+            transaction_id: transaction.id.clone(),
+            received_at: transaction.date,
+        };
+
+        ledger.post_transaction(transaction)?;
+        self.payments.push(record.clone());
+
+        Ok(record)
+    }
+}
+
+/// 🗂️ Aging Buckets (කාල පරාසය අනුව ශේෂ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgingBuckets {
+    pub days_0_30: Money,
+    pub days_31_60: Money,
+    pub days_61_90: Money,
+    pub over_90: Money,
+}
+
+impl Default for AgingBuckets {
+    fn default() -> Self {
+        AgingBuckets {
+            days_0_30: Money::zero(),
+            days_31_60: Money::zero(),
+            days_61_90: Money::zero(),
+            over_90: Money::zero(),
+        }
+    }
+}
+
+impl AgingBuckets {
+    fn add(&mut self, days_overdue: i64, amount: Money) {
+        let bucket = match days_overdue {
+            i64::MIN..=30 => &mut self.days_0_30,
+            31..=60 => &mut self.days_31_60,
+            61..=90 => &mut self.days_61_90,
+            _ => &mut self.over_90,
+        };
+        *bucket = *bucket + amount;
+    }
+
+    pub fn total(&self) -> Money {
+        self.days_0_30 + self.days_31_60 + self.days_61_90 + self.over_90
+    }
+}
+
+/// 📊 Aging Report (ණය වයස් වාර්තාව)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgingReport {
+    pub as_of: NaiveDate,
+    pub by_customer: BTreeMap<String, AgingBuckets>,
+    pub totals: AgingBuckets,
+}
+
+/// 🚀 Bucket each invoice's remaining balance by days past its due date
+/// Settled invoices are skipped. Invoices not yet due (future-dated) count as current (0-30).
+pub fn aging_report(invoices: &[OutstandingInvoice], as_of: NaiveDate) -> AgingReport {
+    let mut by_customer: BTreeMap<String, AgingBuckets> = BTreeMap::new();
+    let mut totals = AgingBuckets::default();
+
+    for invoice in invoices {
+        let remaining = invoice.remaining_balance();
+        if !remaining.is_positive() {
+            continue;
+        }
+        let days = invoice.days_overdue(as_of);
+        by_customer
+            .entry(invoice.customer_id.clone())
+            .or_default()
+            .add(days, remaining);
+        totals.add(days, remaining);
+    }
+
+    AgingReport {
+        as_of,
+        by_customer,
+        totals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn ledger() -> GeneralLedger {
+        let mut ledger = GeneralLedger::new();
+        ledger.add_account(Account::new("CASH", "Cash in Hand", AccountType::Asset));
+        ledger.add_account(Account::new("AR-C001", "Receivable - C001", AccountType::Asset));
+        ledger.add_account(Account::new("SALES", "Sales Revenue", AccountType::Income));
+        ledger
+    }
+
+    /// Credit sale: Debit receivable / Credit sales
+    fn credit_sale(ledger: &mut GeneralLedger, amount: Money) {
+        let sale = Transaction::new("Credit Sale")
+            .debit("AR-C001", amount)
+            .credit("SALES", amount);
+        ledger.post_transaction(sale).unwrap();
+    }
+
+    #[test]
+    fn test_two_partial_payments_clear_invoice() {
+        let mut ledger = ledger();
+        credit_sale(&mut ledger, Money::new(1000, 0));
+        let mut invoice = OutstandingInvoice::new("INV-001", "C001", "AR-C001", Money::new(1000, 0));
+        assert_eq!(invoice.status(), SettlementStatus::Open);
+
+        invoice.record_payment(&mut ledger, "CASH", Money::new(400, 0)).unwrap();
+        assert_eq!(invoice.status(), SettlementStatus::PartiallyPaid);
+        assert_eq!(invoice.remaining_balance(), Money::new(600, 0));
+
+        invoice.record_payment(&mut ledger, "CASH", Money::new(600, 0)).unwrap();
+        assert!(invoice.is_settled());
+        assert!(invoice.remaining_balance().is_zero());
+
+        // Debit cash / Credit receivable for each installment
+        assert_eq!(ledger.journal().len(), 3);
+        assert_eq!(ledger.balance("CASH"), Some(Money::new(1000, 0)));
+        assert_eq!(ledger.balance("AR-C001"), Some(Money::zero()));
+    }
+
+    #[test]
+    fn test_overpayment_rejected() {
+        let mut ledger = ledger();
+        credit_sale(&mut ledger, Money::new(500, 0));
+        let mut invoice = OutstandingInvoice::new("INV-002", "C001", "AR-C001", Money::new(500, 0));
+        invoice.record_payment(&mut ledger, "CASH", Money::new(300, 0)).unwrap();
+
+        let result = invoice.record_payment(&mut ledger, "CASH", Money::new(250, 0));
+        assert!(matches!(result, Err(EngineError::Validation { .. })));
+
+        // Nothing posted for the rejected payment
+        assert_eq!(invoice.remaining_balance(), Money::new(200, 0));
+        assert_eq!(ledger.journal().len(), 2);
+        assert_eq!(ledger.balance("AR-C001"), Some(Money::new(200, 0)));
+    }
+
+    #[test]
+    fn test_aging_report_buckets() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        let due = |days_ago: i64| as_of - chrono::Duration::days(days_ago);
+
+        let mut ledger = ledger();
+        credit_sale(&mut ledger, Money::new(1000, 0));
+        let mut partly_paid = OutstandingInvoice::new("INV-1", "C001", "AR-C001", Money::new(1000, 0))
+            .with_due_date(due(45));
+        partly_paid.record_payment(&mut ledger, "CASH", Money::new(400, 0)).unwrap();
+
+        let invoices = vec![
+            OutstandingInvoice::new("INV-0", "C001", "AR-C001", Money::new(100, 0)).with_due_date(due(10)),
+            partly_paid,
+            OutstandingInvoice::new("INV-2", "C002", "AR-C002", Money::new(300, 0)).with_due_date(due(75)),
+            OutstandingInvoice::new("INV-3", "C002", "AR-C002", Money::new(200, 0)).with_due_date(due(120)),
+            // Future-dated: not yet due
+            OutstandingInvoice::new("INV-4", "C003", "AR-C003", Money::new(50, 0)).with_due_date(due(-15)),
+        ];
+
+        let report = aging_report(&invoices, as_of);
+
+        let c001 = report.by_customer["C001"];
+        assert_eq!(c001.days_0_30, Money::new(100, 0));
+        assert_eq!(c001.days_31_60, Money::new(600, 0));
+
+        let c002 = report.by_customer["C002"];
+        assert_eq!(c002.days_61_90, Money::new(300, 0));
+        assert_eq!(c002.over_90, Money::new(200, 0));
+
+        assert_eq!(report.by_customer["C003"].days_0_30, Money::new(50, 0));
+
+        assert_eq!(report.totals.days_0_30, Money::new(150, 0));
+        assert_eq!(report.totals.days_31_60, Money::new(600, 0));
+        assert_eq!(report.totals.days_61_90, Money::new(300, 0));
+        assert_eq!(report.totals.over_90, Money::new(200, 0));
+        assert_eq!(report.totals.total(), Money::new(1250, 0));
+    }
+
+    #[test]
+    fn test_aging_without_due_date_uses_issue_date() {
+        let invoice = OutstandingInvoice::new("INV-5", "C001", "AR-C001", Money::new(100, 0));
+        let as_of = invoice.issued_at.date_naive() + chrono::Duration::days(40);
+
+        let report = aging_report(std::slice::from_ref(&invoice), as_of);
+        assert_eq!(report.totals.days_31_60, Money::new(100, 0));
+    }
+}
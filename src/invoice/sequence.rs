@@ -0,0 +1,236 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::invoice::document::Invoice;
+use crate::storage::database::StorageBackend;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// ============================================================================
+/// 🔢 Invoice Numbering (ඉන්වොයිස් අංක අනුපිළිවෙල)
+/// ============================================================================
+/// බදු අධිකාරීන්ට store එකකට, series එකකට සහ කාල පරිච්ඡේදයකට අනුපිළිවෙලින්,
+/// හිඩැස් නොමැති invoice අංක අවශ්‍ය වේ (Ex: INV-2024-000123).
+/// Counter එක `StorageBackend` එකේ තබා ඇති අතර සෑම නිකුත් කළ අංකයක්ම වෙනම
+/// record එකක් ලෙස සටහන් වේ - එබැවින් counter එකට ළඟා වූ නමුත් record නොවූ අංක
+/// `detect_gaps` මගින් සොයාගත හැක.
+///
+/// 🗓️ When a series restarts at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SequenceReset {
+    Never,
+    #[default]
+    Yearly,
+    Monthly,
+}
+
+impl SequenceReset {
+    /// Period label the counter is kept under (Ex: "2024", "2024-03")
+    pub fn period(&self, date: DateTime<Utc>) -> String {
+        match self {
+            SequenceReset::Never => "all".to_string(),
+            SequenceReset::Yearly => format!("{}", date.year()),
+            SequenceReset::Monthly => format!("{}-{:02}", date.year(), date.month()),
+        }
+    }
+}
+
+/// 🏷️ Number format
+/// Placeholders: `{store}`, `{series}`, `{period}` and `{seq}` (zero-padded to `seq_width`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceFormat {
+    pub template: String,
+    pub seq_width: usize,
+    pub reset: SequenceReset,
+}
+
+impl Default for SequenceFormat {
+    /// INV-2024-000123
+    fn default() -> Self {
+        SequenceFormat {
+            template: "{series}-{period}-{seq}".to_string(),
+            seq_width: 6,
+            reset: SequenceReset::Yearly,
+        }
+    }
+}
+
+impl SequenceFormat {
+    pub fn validate(&self) -> EngineResult<()> {
+        if !self.template.contains("{seq}") {
+            return Err(EngineError::Validation {
+                message: format!("Invoice number template '{}' has no {{seq}}", self.template),
+            });
+        }
+        if self.reset != SequenceReset::Never && !self.template.contains("{period}") {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Invoice number template '{}' resets {:?} but has no {{period}}; numbers would repeat",
+                    self.template, self.reset
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, store_id: &str, series: &str, period: &str, seq: u64) -> String {
+        self.template
+            .replace("{store}", store_id)
+            .replace("{series}", series)
+            .replace("{period}", period)
+            .replace("{seq}", &format!("{:0width$}", seq, width = self.seq_width))
+    }
+}
+
+/// 🧮 Invoice Sequencer
+/// Numbers are drawn with `StorageBackend::increment`, so sequencers in several
+/// processes sharing one backend never issue the same number.
+pub struct InvoiceSequencer {
+    storage: Arc<dyn StorageBackend>,
+    format: SequenceFormat,
+}
+
+impl InvoiceSequencer {
+    pub fn new(storage: Arc<dyn StorageBackend>, format: SequenceFormat) -> EngineResult<Self> {
+        format.validate()?;
+        Ok(InvoiceSequencer { storage, format })
+    }
+
+    pub fn format(&self) -> &SequenceFormat {
+        &self.format
+    }
+
+    /// Counters are split only by what the number shows: without `{store}` every store
+    /// draws from one counter (otherwise two stores would issue the same number); same for `{series}`
+    fn counter_key(&self, store_id: &str, series: &str, period: &str) -> String {
+        let shown = |placeholder: &str, value: &str| {
+            if self.format.template.contains(placeholder) {
+                value.to_string()
+            } else {
+                "all".to_string()
+            }
+        };
+        format!("invoice_seq:{}:{}:{}", shown("{store}", store_id), shown("{series}", series), period)
+    }
+
+    fn record_key(counter_key: &str, seq: u64) -> String {
+        format!("{}:#{:010}", counter_key, seq)
+    }
+
+    fn read_counter(&self, key: &str) -> EngineResult<u64> {
+        match self.storage.get(key)? {
+            Some(value) => value.parse().map_err(|_| EngineError::Storage {
+                message: format!("Corrupt invoice counter '{}': {}", key, value),
+            }),
+            None => Ok(0),
+        }
+    }
+
+    /// 🔢 Issue the next number for a store/series in the period containing `date`
+    pub fn next_number(&self, store_id: &str, series: &str, date: DateTime<Utc>) -> EngineResult<String> {
+        let period = self.format.reset.period(date);
+        let key = self.counter_key(store_id, series, &period);
+
+        let seq = self.storage.increment(&key, 1)?;
+        let seq = u64::try_from(seq).map_err(|_| EngineError::Storage {
+            message: format!("Corrupt invoice counter '{}': {}", key, seq),
+        })?;
+        let number = self.format.render(store_id, series, &period, seq);
+        // A failure after the counter moved leaves a gap `detect_gaps` reports
+        self.storage.set(&Self::record_key(&key, seq), &number)?;
+        Ok(number)
+    }
+
+    /// 🧾 Replace an invoice's placeholder number with the next sequential one
+    pub fn assign(&self, invoice: &mut Invoice, store_id: &str, series: &str) -> EngineResult<()> {
+        invoice.invoice_number = self.next_number(store_id, series, invoice.issue_date)?;
+        Ok(())
+    }
+
+    /// Last sequence issued in the period containing `date` (0 if none)
+    pub fn current(&self, store_id: &str, series: &str, date: DateTime<Utc>) -> EngineResult<u64> {
+        let period = self.format.reset.period(date);
+        self.read_counter(&self.counter_key(store_id, series, &period))
+    }
+
+    /// 🕳️ Sequences up to the counter that have no issued record
+    pub fn detect_gaps(&self, store_id: &str, series: &str, date: DateTime<Utc>) -> EngineResult<Vec<u64>> {
+        let period = self.format.reset.period(date);
+        let key = self.counter_key(store_id, series, &period);
+        let last = self.read_counter(&key)?;
+        let mut gaps = Vec::new();
+        for seq in 1..=last {
+            if !self.storage.exists(&Self::record_key(&key, seq))? {
+                gaps.push(seq);
+            }
+        }
+        Ok(gaps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::database::InMemoryStorage;
+    use chrono::TimeZone;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_concurrent_issue_has_no_duplicates_or_gaps() {
+        let storage: Arc<dyn StorageBackend> = Arc::new(InMemoryStorage::new());
+        let sequencer = Arc::new(InvoiceSequencer::new(storage.clone(), SequenceFormat::default()).unwrap());
+        let date = Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let sequencer = sequencer.clone();
+                std::thread::spawn(move || {
+                    (0..25)
+                        .map(|_| sequencer.next_number("S1", "INV", date).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let issued: Vec<String> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+
+        let unique: HashSet<_> = issued.iter().cloned().collect();
+        assert_eq!(unique.len(), 200);
+        let expected: HashSet<_> = (1..=200).map(|n| format!("INV-2024-{:06}", n)).collect();
+        assert_eq!(unique, expected);
+        assert_eq!(sequencer.current("S1", "INV", date).unwrap(), 200);
+        assert!(sequencer.detect_gaps("S1", "INV", date).unwrap().is_empty());
+
+        // The number has no {store}: another store continues the shared sequence
+        assert_eq!(sequencer.next_number("S2", "INV", date).unwrap(), "INV-2024-000201");
+        // The next year starts over
+        let next_year = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(sequencer.next_number("S1", "INV", next_year).unwrap(), "INV-2025-000001");
+
+        // A second sequencer on the same backend (another process) continues the counter
+        let other = InvoiceSequencer::new(storage.clone(), SequenceFormat::default()).unwrap();
+        assert_eq!(other.next_number("S1", "INV", date).unwrap(), "INV-2024-000202");
+
+        // A lost record shows up as a gap
+        storage.delete("invoice_seq:all:INV:2024:#0000000042").unwrap();
+        assert_eq!(sequencer.detect_gaps("S1", "INV", date).unwrap(), vec![42]);
+    }
+
+    #[test]
+    fn test_format_requires_seq_and_period() {
+        let storage: Arc<dyn StorageBackend> = Arc::new(InMemoryStorage::new());
+        let no_seq = SequenceFormat { template: "{series}-{period}".to_string(), ..Default::default() };
+        assert!(InvoiceSequencer::new(storage.clone(), no_seq).is_err());
+        let no_period = SequenceFormat { template: "{store}/{seq}".to_string(), ..Default::default() };
+        assert!(InvoiceSequencer::new(storage.clone(), no_period).is_err());
+
+        let monthly = SequenceFormat {
+            template: "{store}/{period}/{seq}".to_string(),
+            seq_width: 4,
+            reset: SequenceReset::Monthly,
+        };
+        let sequencer = InvoiceSequencer::new(storage, monthly).unwrap();
+        let date = Utc.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap();
+        assert_eq!(sequencer.next_number("COL", "INV", date).unwrap(), "COL/2024-03/0001");
+        // {store} in the number: each store has its own sequence
+        assert_eq!(sequencer.next_number("KDY", "INV", date).unwrap(), "KDY/2024-03/0001");
+    }
+}
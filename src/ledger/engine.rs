@@ -1,7 +1,9 @@
+#[cfg(feature = "persistence")]
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "persistence")]
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -16,8 +18,10 @@ pub struct JournalEntry {
     pub created_at: DateTime<Utc>,
 }
 
+#[cfg(feature = "persistence")]
 pub struct LedgerEngine;
 
+#[cfg(feature = "persistence")]
 impl LedgerEngine {
     pub fn new() -> Self {
         LedgerEngine
@@ -1,7 +1,11 @@
 use crate::ledger::transaction::Transaction;
 use crate::ledger::account::Account;
 use crate::core::errors::{EngineResult, EngineError};
-use std::collections::HashMap;
+use crate::core::metrics;
+use crate::core::money::Money;
+use crate::refund::types::{RefundDestination, RefundResult};
+use crate::rules::mixed_scenarios::CartCalculation;
+use std::collections::{BTreeMap, HashMap};
 
 /// ============================================================================
 /// 📚 General Ledger (ප්‍රධාන ලෙජරය)
@@ -12,6 +16,29 @@ pub struct GeneralLedger {
     journal: Vec<Transaction>,
 }
 
+/// 🏦 Accounts a refund posts to, per destination
+#[derive(Debug, Clone)]
+pub struct RefundAccounts {
+    /// Debited with the refunded amount
+    pub revenue: String,
+    /// Cash / card clearing (OriginalTender)
+    pub original_tender: String,
+    /// Store-credit liability: credit owed to the customer until redeemed
+    pub store_credit: String,
+    /// Bank payouts clearing
+    pub bank_transfer: String,
+}
+
+impl RefundAccounts {
+    pub fn payout_account(&self, destination: RefundDestination) -> &str {
+        match destination {
+            RefundDestination::OriginalTender => &self.original_tender,
+            RefundDestination::StoreCredit => &self.store_credit,
+            RefundDestination::BankTransfer => &self.bank_transfer,
+        }
+    }
+}
+
 impl GeneralLedger {
     pub fn new() -> Self {
         GeneralLedger {
@@ -24,6 +51,16 @@ impl GeneralLedger {
         self.accounts.insert(account.id.clone(), account);
     }
 
+    /// 💰 Current balance of an account (debits - credits)
+    pub fn balance(&self, account_id: &str) -> Option<Money> {
+        self.accounts.get(account_id).map(|account| account.balance)
+    }
+
+    /// 📜 Posted transactions in posting order
+    pub fn journal(&self) -> &[Transaction] {
+        &self.journal
+    }
+
     /// Post a transaction to the ledger
     /// This updates account balances strictly following Double Entry rules.
     pub fn post_transaction(&mut self, transaction: Transaction) -> EngineResult<()> {
@@ -61,6 +98,240 @@ impl GeneralLedger {
             }
         }
 
+        metrics::record_ledger_posting();
         Ok(())
     }
+
+    /// 🧾 Post a calculated sale
+    /// Dr cash/receivable (grand total), Cr revenue (net of tax), and each tax in
+    /// `tax_details` is credited to its own payable account from `tax_accounts`
+    /// (tax name => account id) so VAT, luxury tax, NBT... can be remitted separately.
+    pub fn post_sale(
+        &mut self,
+        sale: &CartCalculation,
+        receivable_account: &str,
+        revenue_account: &str,
+        tax_accounts: &HashMap<String, String>,
+    ) -> EngineResult<Transaction> {
+        self.post_sale_with_discounts(sale, receivable_account, revenue_account, None, tax_accounts)
+    }
+
+    /// 🧾 Post a sale, optionally booking discounts on their own contra-revenue account
+    /// With `discount_account`, revenue is credited gross and the discount debited there.
+    /// A free order (zero grand total, Ex: 100% off) must use one: nothing is receivable,
+    /// so no zero-amount receivable line is written - the entry is Dr discounts, Cr revenue
+    /// (and tax) - and the transaction is tagged `free = true`.
+    pub fn post_sale_with_discounts(
+        &mut self,
+        sale: &CartCalculation,
+        receivable_account: &str,
+        revenue_account: &str,
+        discount_account: Option<&str>,
+        tax_accounts: &HashMap<String, String>,
+    ) -> EngineResult<Transaction> {
+        let free = sale.is_free();
+        if free && discount_account.is_none() && !sale.total_discount.is_zero() {
+            return Err(EngineError::Validation {
+                message: "A free (zero-total) sale needs a discount account to record its discount".to_string(),
+            });
+        }
+
+        let mut tax_by_name: BTreeMap<&str, Money> = BTreeMap::new();
+        // Reverse-charged taxes are reported, not collected: nothing to credit
+        for detail in sale.items.iter().flat_map(|item| &item.tax_details).filter(|d| !d.amount.is_zero()) {
+            let total = tax_by_name.entry(detail.name.as_str()).or_insert_with(Money::zero);
+            *total = *total + detail.amount;
+        }
+
+        let itemized = tax_by_name.values().fold(Money::zero(), |acc, amount| acc + *amount);
+        if itemized != sale.total_tax {
+            return Err(EngineError::Calculation {
+                code: "TAX_BREAKDOWN_MISMATCH".to_string(),
+                message: format!(
+                    "Tax details sum to {} but the sale's total tax is {}",
+                    itemized, sale.total_tax
+                ),
+            });
+        }
+
+        // Container deposits are posted by `DepositMovement::post_charge`, not as revenue
+        let net_revenue = sale.sale_total() - sale.total_tax;
+        let mut transaction = Transaction::new("Sale");
+        if !free {
+            transaction = transaction.debit(receivable_account, sale.sale_total());
+        }
+        transaction = match discount_account {
+            Some(account) if !sale.total_discount.is_zero() => transaction
+                .debit(account, sale.total_discount)
+                .credit(revenue_account, net_revenue + sale.total_discount),
+            _ => transaction.credit(revenue_account, net_revenue),
+        };
+        if free {
+            transaction.metadata.insert("free".to_string(), "true".to_string());
+        }
+        for (name, amount) in tax_by_name {
+            let account = tax_accounts.get(name).ok_or_else(|| EngineError::Validation {
+                message: format!("No tax payable account mapped for '{}'", name),
+            })?;
+            transaction = transaction.credit(account, amount);
+        }
+
+        self.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+
+    /// 🔄 Post a refund: Dr revenue, Cr the destination's account
+    /// A store-credit refund credits the store-credit liability (no cash leaves the business).
+    pub fn post_refund(&mut self, refund: &RefundResult, accounts: &RefundAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new("Refund")
+            .debit(&accounts.revenue, refund.refund_amount)
+            .credit(accounts.payout_account(refund.destination), refund.refund_amount);
+        transaction.metadata.insert("refund_id".to_string(), refund.id.clone());
+        transaction
+            .metadata
+            .insert("destination".to_string(), format!("{:?}", refund.destination));
+        self.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn line(item_id: &str, base: Money, taxes: &[(&str, f64, Money)]) -> ItemCalculation {
+        let tax_details: Vec<TaxDetail> = taxes
+            .iter()
+            .map(|(name, rate, amount)| TaxDetail {
+                name: name.to_string(),
+                rate: *rate,
+                amount: *amount,
+                treatment: TaxTreatment::Standard,
+                reverse_charged: Money::zero(),
+                taxable_base: base,
+            })
+            .collect();
+        let tax = tax_details.iter().fold(Money::zero(), |acc, t| acc + t.amount);
+        ItemCalculation {
+            item_id: item_id.to_string(),
+            base_amount: base,
+            discount_amount: Money::zero(),
+            tax_amount: tax,
+            total: base + tax,
+            discount_details: Vec::new(),
+            tax_details,
+            cap_adjustments: Vec::new(),
+            suppressed_discounts: Vec::new(),
+            tax_included: false,
+            cost: None,
+        }
+    }
+
+    fn sale_ledger() -> GeneralLedger {
+        let mut ledger = GeneralLedger::new();
+        ledger.add_account(Account::new("cash", "Cash", AccountType::Asset));
+        ledger.add_account(Account::new("sales", "Sales Revenue", AccountType::Income));
+        ledger.add_account(Account::new("vat_payable", "VAT Payable", AccountType::Liability));
+        ledger.add_account(Account::new("luxury_payable", "Luxury Tax Payable", AccountType::Liability));
+        ledger
+    }
+
+    #[test]
+    fn test_post_sale_splits_vat_and_luxury_tax() {
+        // Watch Rs.10,000: VAT 18% + luxury 10%; Book Rs.1,000: VAT 18%
+        let items = vec![
+            line(
+                "watch",
+                Money::new(10_000, 0),
+                &[("VAT", 18.0, Money::new(1800, 0)), ("Luxury", 10.0, Money::new(1000, 0))],
+            ),
+            line("book", Money::new(1000, 0), &[("VAT", 18.0, Money::new(180, 0))]),
+        ];
+        let sale = CartCalculation {
+            items,
+            subtotal: Money::new(11_000, 0),
+            total_discount: Money::zero(),
+            total_tax: Money::new(2980, 0),
+            grand_total: Money::new(13_980, 0),
+            rounding_adjustment: Money::zero(),
+            suppressed_discounts: Vec::new(),
+            included_tax: Money::zero(),
+            added_tax: Money::zero(),
+            deposits: Money::zero(),
+        };
+        let tax_accounts: HashMap<String, String> = [
+            ("VAT".to_string(), "vat_payable".to_string()),
+            ("Luxury".to_string(), "luxury_payable".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut ledger = sale_ledger();
+        let posted = ledger.post_sale(&sale, "cash", "sales", &tax_accounts).unwrap();
+
+        assert!(posted.is_balanced());
+        assert_eq!(ledger.balance("cash"), Some(Money::new(13_980, 0)));
+        assert_eq!(ledger.balance("sales"), Some(Money::new(-11_000, 0)));
+        assert_eq!(ledger.balance("vat_payable"), Some(Money::new(-1980, 0)));
+        assert_eq!(ledger.balance("luxury_payable"), Some(Money::new(-1000, 0)));
+
+        // Unmapped tax name => nothing posted
+        let vat_only: HashMap<String, String> =
+            [("VAT".to_string(), "vat_payable".to_string())].into_iter().collect();
+        let mut ledger = sale_ledger();
+        assert!(ledger.post_sale(&sale, "cash", "sales", &vat_only).is_err());
+        assert!(ledger.journal().is_empty());
+    }
+
+    #[test]
+    fn test_post_engine_calculated_taxed_sale() {
+        use crate::rules::mixed_scenarios::{
+            DiscountRule, DiscountType, MixedScenarioEngine, ProductDiscountConfig, TaxAppliesTo, TaxRate,
+        };
+        use crate::types::cart::Cart;
+        use crate::types::item::Item;
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine
+            .add_global_tax(TaxRate::new("Luxury", 10.0, "LK", TaxAppliesTo::Product("watch".to_string())))
+            .unwrap();
+        engine
+            .add_product_discount(ProductDiscountConfig {
+                product_id: "watch".to_string(),
+                discounts: vec![DiscountRule {
+                    id: "SALE".to_string(),
+                    name: "10% off".to_string(),
+                    discount_type: DiscountType::Percentage(10.0),
+                    priority: 1,
+                    conditions: Vec::new(),
+                    stackable: true,
+                }],
+                stackable: true,
+                max_discount_percent: None,
+            })
+            .unwrap();
+        let mut cart = Cart::new();
+        let mut watch = Item::new("Watch", Money::new(10_000, 0), 1.0);
+        watch.id = "watch".to_string();
+        cart.add_item(watch);
+        cart.add_item(Item::new("Book", Money::new(999, 99), 3.0));
+        let sale = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        assert!(!sale.total_tax.is_zero());
+
+        let tax_accounts: HashMap<String, String> = [
+            ("VAT".to_string(), "vat_payable".to_string()),
+            ("Luxury".to_string(), "luxury_payable".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let mut ledger = sale_ledger();
+        let posted = ledger.post_sale(&sale, "cash", "sales", &tax_accounts).unwrap();
+        assert!(posted.is_balanced());
+        assert_eq!(ledger.balance("cash"), Some(sale.grand_total));
+        let payable = ledger.balance("vat_payable").unwrap() + ledger.balance("luxury_payable").unwrap();
+        assert_eq!(payable, Money::zero() - sale.total_tax);
+        assert_eq!(ledger.balance("luxury_payable"), Some(Money::new(-900, 0)));
+    }
 }
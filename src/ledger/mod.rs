@@ -3,4 +3,5 @@ pub mod account;
 pub mod transaction;
 pub mod engine;
 
+#[cfg(feature = "persistence")]
 pub use engine::LedgerEngine;
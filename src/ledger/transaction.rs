@@ -54,6 +54,22 @@ impl Transaction {
         self
     }
 
+    /// ↩️ Mirror-image transaction cancelling this one (debits and credits swapped)
+    pub fn reversal(&self) -> Self {
+        let mut reversal = Transaction::new(&format!("Reversal: {}", self.description));
+        reversal.entries = self
+            .entries
+            .iter()
+            .map(|entry| Entry {
+                account_id: entry.account_id.clone(),
+                debit: entry.credit,
+                credit: entry.debit,
+            })
+            .collect();
+        reversal.metadata.insert("reverses".to_string(), self.id.clone());
+        reversal
+    }
+
     /// Validate if Debit == Credit
     pub fn is_balanced(&self) -> bool {
         let mut total_debit = Money::zero();
@@ -27,10 +27,17 @@ pub mod plugins;
 pub mod storage;
 pub mod api;
 pub mod ledger;
+#[cfg(feature = "persistence")]
 pub mod accounts; // Centralized Creditor/Debtor Management
 pub mod advanced_payments; // POS Split Payments & Cheques
+pub mod loyalty; // Loyalty points accrual & redemption
+pub mod commission; // Marketplace commission split & seller payouts
+pub mod deposit; // Refundable container deposits (liability, not revenue)
+pub mod order; // Orders: priced, persisted, pending → paid → fulfilled
 pub mod inventory;
 pub mod subscription;
+pub mod invoice;
+pub mod prelude; // Stable public API: `use financial_engine::prelude::*`
 
 // Re-exports for convenience
 pub use core::money::Money;
@@ -39,5 +46,24 @@ pub use core::calculation::{CalculationEngine, CalculationResult};
 pub use api::facade::FinancialEngine;
 pub use security::guard::IronGuard;
 pub use rules::traits::{Rule, RuleAction};
+
+/// 🧪 Standalone core check (CI: `cargo test --no-default-features`)
+/// Server/persistence/observability නොමැතිව පමණක් compile වේ - core එකට ඒවා අවශ්‍ය නොවන බව තහවුරු කරයි.
+#[cfg(all(test, not(any(feature = "server", feature = "persistence", feature = "observability"))))]
+mod core_standalone_tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_core_calculates_without_server_features() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(500, 0), 2.0));
+
+        let result = engine.calculate_cart(&cart, &[], None).unwrap();
+        assert_eq!(result.grand_total, Money::new(1180, 0));
+    }
+}
+
 // Ready for Financial Transactions
 // pub type MudalGananaEngine = FinancialEngine; (Removed)
@@ -0,0 +1,386 @@
+//! # 🎁 Loyalty Points (පාරිභෝගික ලකුණු)
+//! Accrual of points on purchases and their conversion back to money at redemption.
+//! Rs.2550 at 1 point per Rs.100 is 25.5 points - whether that becomes 25, 26 or
+//! stays 25.5 is an explicit per-program policy, and a redemption is always rounded
+//! to whole cents with a declared `RoundingMode` so points and money reconcile.
+//! Redeemed points are either a discount (tax on the reduced price) or a tender
+//! (tax on the full price), per `RedemptionTreatment`.
+
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use crate::rules::mixed_scenarios::{CartCalculation, MixedScenarioEngine};
+use crate::types::cart::Cart;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+/// Decimal places kept for fractional point balances (`KeepFractional`)
+pub const FRACTIONAL_POINT_SCALE: u32 = 2;
+
+/// Discount line id of points redeemed as a discount
+pub const LOYALTY_RULE_ID: &str = "LOYALTY";
+
+// 1. Accrual rounding policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AccrualRounding {
+    /// 25.5 → 25 (fractions are forfeited)
+    #[default]
+    FloorToWhole,
+    /// 25.5 → 26 (half rounds up)
+    RoundToWhole,
+    /// 25.5 → 25.5 (banked to `FRACTIONAL_POINT_SCALE` places, remainder truncated)
+    KeepFractional,
+}
+
+impl AccrualRounding {
+    /// Decimal places a point balance is held at under this policy
+    pub fn scale(&self) -> u32 {
+        match self {
+            AccrualRounding::KeepFractional => FRACTIONAL_POINT_SCALE,
+            _ => 0,
+        }
+    }
+
+    fn apply(&self, points: Decimal) -> Decimal {
+        match self {
+            AccrualRounding::FloorToWhole => points.floor(),
+            AccrualRounding::RoundToWhole => {
+                points.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            }
+            AccrualRounding::KeepFractional => {
+                points.round_dp_with_strategy(FRACTIONAL_POINT_SCALE, RoundingStrategy::ToZero)
+            }
+        }
+    }
+}
+
+// 2. How redeemed points meet tax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RedemptionTreatment {
+    /// Points reduce the price: tax is charged on the amount after redemption
+    #[default]
+    Discount,
+    /// Points pay part of the bill: tax is charged on the full amount
+    Tender,
+}
+
+// 3. Program configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoyaltyProgram {
+    /// Spend that earns one point (Rs.100 => 1 point)
+    pub spend_per_point: Money,
+    pub accrual_rounding: AccrualRounding,
+    /// Money value of one point at redemption, in major units (same as `PaymentMethod::FidelityPoints`)
+    pub conversion_rate: Decimal,
+    /// How a redemption value is rounded to cents (Down never pays out more than the points are worth)
+    pub redemption_rounding: RoundingMode,
+    #[serde(default)]
+    pub redemption_treatment: RedemptionTreatment,
+}
+
+impl LoyaltyProgram {
+    pub fn new(
+        spend_per_point: Money,
+        accrual_rounding: AccrualRounding,
+        conversion_rate: Decimal,
+    ) -> EngineResult<Self> {
+        if !spend_per_point.is_positive() {
+            return Err(EngineError::Validation {
+                message: "Loyalty spend per point must be positive".to_string(),
+            });
+        }
+        if conversion_rate <= Decimal::ZERO {
+            return Err(EngineError::Validation {
+                message: "Loyalty conversion rate must be positive".to_string(),
+            });
+        }
+        Ok(LoyaltyProgram {
+            spend_per_point,
+            accrual_rounding,
+            conversion_rate,
+            redemption_rounding: RoundingMode::Down,
+            redemption_treatment: RedemptionTreatment::default(),
+        })
+    }
+
+    pub fn with_redemption_rounding(mut self, mode: RoundingMode) -> Self {
+        self.redemption_rounding = mode;
+        self
+    }
+
+    pub fn with_redemption_treatment(mut self, treatment: RedemptionTreatment) -> Self {
+        self.redemption_treatment = treatment;
+        self
+    }
+
+    // 4. Accrual
+    /// 🪙 Points earned on a purchase (refunds and zero spend earn nothing)
+    pub fn accrue(&self, spend: Money) -> Decimal {
+        if !spend.is_positive() {
+            return Decimal::ZERO;
+        }
+        let raw = Decimal::from(spend.amount) / Decimal::from(self.spend_per_point.amount);
+        self.accrual_rounding.apply(raw)
+    }
+
+    // 5. Redemption
+    /// 💱 Money value of `points`, rounded to cents with `redemption_rounding`
+    pub fn redemption_value(&self, points: Decimal) -> EngineResult<Money> {
+        if points < Decimal::ZERO {
+            return Err(EngineError::Validation {
+                message: format!("Cannot redeem negative points: {}", points),
+            });
+        }
+        let cents = (points * self.conversion_rate * Decimal::ONE_HUNDRED)
+            .round_dp_with_strategy(0, strategy(self.redemption_rounding));
+        cents.to_i64().map(Money::from_cents).ok_or_else(|| EngineError::Calculation {
+            code: "LOYALTY_OVERFLOW".to_string(),
+            message: format!("Redemption value of {} points overflows", points),
+        })
+    }
+
+    /// 🎯 Fewest points (at the program's point scale) whose redemption value covers `amount`
+    pub fn points_for(&self, amount: Money) -> EngineResult<Decimal> {
+        if amount.is_negative() {
+            return Err(EngineError::Validation {
+                message: format!("Cannot convert a negative amount to points: {}", amount),
+            });
+        }
+        let scale = self.accrual_rounding.scale();
+        let step = Decimal::new(1, scale);
+        let mut points = (Decimal::new(amount.amount, 2) / self.conversion_rate)
+            .round_dp_with_strategy(scale, RoundingStrategy::AwayFromZero);
+        // Redemption rounding may still land a cent short; step up until the value covers it
+        while self.redemption_value(points)? < amount {
+            points += step;
+        }
+        Ok(points)
+    }
+
+    // 6. Redemption on a cart
+    /// 🛒 Price a cart with `points` redeemed under the program's treatment
+    /// Only the points needed are consumed when the cart is worth less than they are.
+    pub fn redeem_on_cart(
+        &self,
+        engine: &MixedScenarioEngine,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        points: Decimal,
+    ) -> EngineResult<LoyaltyRedemption> {
+        let mut calculation = engine.calculate_cart(cart, promo_codes, target_jurisdiction)?;
+        let offered = self.redemption_value(points)?;
+        let value = match self.redemption_treatment {
+            RedemptionTreatment::Discount => engine.apply_cart_discount(
+                cart,
+                target_jurisdiction,
+                &mut calculation,
+                offered,
+                (LOYALTY_RULE_ID, "Loyalty points"),
+            )?,
+            RedemptionTreatment::Tender => offered.min(calculation.grand_total.max(Money::zero())),
+        };
+        let points = if value == offered {
+            points
+        } else {
+            self.points_for(value)?.min(points)
+        };
+        let amount_due = match self.redemption_treatment {
+            RedemptionTreatment::Discount => calculation.grand_total,
+            RedemptionTreatment::Tender => calculation.grand_total - value,
+        };
+        Ok(LoyaltyRedemption {
+            treatment: self.redemption_treatment,
+            points,
+            value,
+            calculation,
+            amount_due,
+        })
+    }
+}
+
+// 7. Redemption outcome and its ledger treatment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoyaltyRedemption {
+    pub treatment: RedemptionTreatment,
+    /// Points consumed
+    pub points: Decimal,
+    /// Money value of the consumed points
+    pub value: Money,
+    /// Cart calculation (carrying the `LOYALTY` discount line under `Discount`)
+    pub calculation: CartCalculation,
+    /// Left for other tenders
+    pub amount_due: Money,
+}
+
+/// 🏦 Accounts a sale with redeemed points posts to
+#[derive(Debug, Clone)]
+pub struct LoyaltyAccounts {
+    pub receivable: String,
+    pub revenue: String,
+    /// Contra-revenue account for discounts (points redeemed as a discount land here)
+    pub discounts: String,
+    /// Points liability settled when points are redeemed (either treatment)
+    pub points_liability: String,
+    /// Tax name => tax payable account
+    pub tax_accounts: std::collections::HashMap<String, String>,
+}
+
+impl LoyaltyRedemption {
+    /// 📒 Post the sale and the redemption
+    /// Discount: the points value is booked in `discounts` with the sale, and only the
+    /// reduced total is receivable; a second entry moves it from `discounts` onto
+    /// `points_liability`, since the points were expensed when issued. Tender: the sale posts
+    /// at full price and a second entry settles part of the receivable from `points_liability`.
+    pub fn post(&self, ledger: &mut GeneralLedger, accounts: &LoyaltyAccounts) -> EngineResult<Vec<Transaction>> {
+        let sale = ledger.post_sale_with_discounts(
+            &self.calculation,
+            &accounts.receivable,
+            &accounts.revenue,
+            Some(&accounts.discounts),
+            &accounts.tax_accounts,
+        )?;
+        let mut posted = vec![sale];
+        if self.value.is_positive() {
+            let settled = match self.treatment {
+                RedemptionTreatment::Discount => &accounts.discounts,
+                RedemptionTreatment::Tender => &accounts.receivable,
+            };
+            let mut redemption = Transaction::new("Loyalty redemption")
+                .debit(&accounts.points_liability, self.value)
+                .credit(settled, self.value);
+            redemption.metadata.insert("points".to_string(), self.points.to_string());
+            ledger.post_transaction(redemption.clone())?;
+            posted.push(redemption);
+        }
+        Ok(posted)
+    }
+}
+
+fn strategy(mode: RoundingMode) -> RoundingStrategy {
+    match mode {
+        RoundingMode::Standard => RoundingStrategy::MidpointAwayFromZero,
+        RoundingMode::Up => RoundingStrategy::AwayFromZero,
+        RoundingMode::Down => RoundingStrategy::ToZero,
+        RoundingMode::Bankers => RoundingStrategy::MidpointNearestEven,
+        RoundingMode::HalfDown => RoundingStrategy::MidpointTowardZero,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn program(rounding: AccrualRounding) -> LoyaltyProgram {
+        // 1 point per Rs.100, each point worth Rs.0.75
+        LoyaltyProgram::new(Money::new(100, 0), rounding, Decimal::new(75, 2)).unwrap()
+    }
+
+    #[test]
+    fn test_accrual_policies_on_rs_2550() {
+        let spend = Money::new(2550, 0);
+        assert_eq!(program(AccrualRounding::FloorToWhole).accrue(spend), Decimal::new(25, 0));
+        assert_eq!(program(AccrualRounding::RoundToWhole).accrue(spend), Decimal::new(26, 0));
+        assert_eq!(program(AccrualRounding::KeepFractional).accrue(spend), Decimal::new(255, 1));
+        assert_eq!(program(AccrualRounding::RoundToWhole).accrue(Money::from_cents(-255_000)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_redemption_reconciles_with_points() {
+        let fractional = program(AccrualRounding::KeepFractional);
+        let points = fractional.accrue(Money::new(2550, 0));
+        // 25.5 × 0.75 = Rs.19.125 → Down: 19.12, Standard: 19.13
+        assert_eq!(fractional.redemption_value(points).unwrap(), Money::new(19, 12));
+        let standard = fractional.clone().with_redemption_rounding(RoundingMode::Standard);
+        assert_eq!(standard.redemption_value(points).unwrap(), Money::new(19, 13));
+
+        // Points needed for an amount always redeem for at least that amount
+        for program in [fractional, program(AccrualRounding::FloorToWhole)] {
+            for amount in [Money::new(19, 12), Money::new(19, 13), Money::new(0, 1)] {
+                let needed = program.points_for(amount).unwrap();
+                assert!(program.redemption_value(needed).unwrap() >= amount);
+                let fewer = needed - Decimal::new(1, program.accrual_rounding.scale());
+                assert!(program.redemption_value(fewer).unwrap() < amount);
+            }
+        }
+        assert_eq!(program(AccrualRounding::FloorToWhole).points_for(Money::new(19, 13)).unwrap(), Decimal::new(26, 0));
+        assert!(program(AccrualRounding::FloorToWhole).redemption_value(Decimal::new(-1, 0)).is_err());
+    }
+
+    fn redeem(engine: &MixedScenarioEngine, treatment: RedemptionTreatment, points: i64) -> LoyaltyRedemption {
+        // 1 point = Rs.1
+        let program = LoyaltyProgram::new(Money::new(100, 0), AccrualRounding::FloorToWhole, Decimal::ONE)
+            .unwrap()
+            .with_redemption_treatment(treatment);
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Kettle", Money::new(1000, 0), 1.0));
+        program
+            .redeem_on_cart(engine, &cart, &[], None, Decimal::new(points, 0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_points_as_discount_vs_tender_tax() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+
+        // Discount: VAT on 1000 - 100 = 900
+        let discount = redeem(&engine, RedemptionTreatment::Discount, 100);
+        assert_eq!(discount.value, Money::new(100, 0));
+        assert_eq!(discount.calculation.total_tax, Money::new(162, 0));
+        assert_eq!(discount.amount_due, Money::new(1062, 0));
+
+        // Tender: VAT on the full 1000, points pay Rs.100 of the 1180
+        let tender = redeem(&engine, RedemptionTreatment::Tender, 100);
+        assert_eq!(tender.calculation.total_tax, Money::new(180, 0));
+        assert_eq!(tender.amount_due, Money::new(1080, 0));
+
+        // More points than the cart is worth: only what is needed is consumed
+        let capped = redeem(&engine, RedemptionTreatment::Discount, 5000);
+        assert_eq!(capped.value, Money::new(1000, 0));
+        assert_eq!(capped.points, Decimal::new(1000, 0));
+        assert_eq!(capped.amount_due, Money::zero());
+    }
+
+    #[test]
+    fn test_ledger_treatment_of_redeemed_points() {
+        let engine = MixedScenarioEngine::new();
+        let accounts = LoyaltyAccounts {
+            receivable: "cash".to_string(),
+            revenue: "sales".to_string(),
+            discounts: "discounts".to_string(),
+            points_liability: "points".to_string(),
+            tax_accounts: Default::default(),
+        };
+        let ledger = || {
+            let mut ledger = GeneralLedger::new();
+            ledger.add_account(Account::new("cash", "Cash", AccountType::Asset));
+            ledger.add_account(Account::new("sales", "Sales", AccountType::Income));
+            ledger.add_account(Account::new("discounts", "Discounts", AccountType::Expense));
+            ledger.add_account(Account::new("points", "Points Liability", AccountType::Liability));
+            ledger
+        };
+
+        let mut as_discount = ledger();
+        let posted = redeem(&engine, RedemptionTreatment::Discount, 100).post(&mut as_discount, &accounts).unwrap();
+        assert_eq!(posted.len(), 2);
+        assert_eq!(as_discount.balance("cash"), Some(Money::new(900, 0)));
+        // Booked as a discount on the sale, then carried by the points liability
+        assert_eq!(posted[0].entries.iter().filter(|e| e.account_id == "discounts").count(), 1);
+        assert_eq!(as_discount.balance("discounts"), Some(Money::zero()));
+        assert_eq!(as_discount.balance("points"), Some(Money::new(100, 0)));
+        assert_eq!(as_discount.balance("sales"), Some(Money::new(-1000, 0)));
+
+        let mut as_tender = ledger();
+        let posted = redeem(&engine, RedemptionTreatment::Tender, 100).post(&mut as_tender, &accounts).unwrap();
+        assert_eq!(posted.len(), 2);
+        assert_eq!(as_tender.balance("cash"), Some(Money::new(900, 0)));
+        assert_eq!(as_tender.balance("points"), Some(Money::new(100, 0)));
+        assert_eq!(as_tender.balance("discounts"), Some(Money::zero()));
+        assert_eq!(as_tender.balance("sales"), Some(Money::new(-1000, 0)));
+    }
+}
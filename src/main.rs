@@ -1,7 +1,10 @@
 use axum::middleware;
-use financial_engine::api::routes::create_router;
+use financial_engine::api::routes::create_configured_router;
 use financial_engine::security::gateway::secure_guard;
+use financial_engine::storage::database::{InMemoryStorage, JsonFileStorage, StorageBackend};
+use financial_engine::types::currency::CurrencyRegistry;
 
+use std::sync::Arc;
 use std::time::Duration;
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer;
@@ -34,6 +37,17 @@ async fn main() {
 
     println!("🚀 Starting Ultimate Financial Engine Microservice...");
 
+    // Supported currencies (refuse to start on a broken CURRENCY_REGISTRY)
+    if let Err(e) = CurrencyRegistry::from_env().and_then(|r| r.validate()) {
+        println!("❌ CRITICAL ERROR: Invalid currency registry -> {}", e);
+        std::process::exit(1);
+    }
+
+    // 4. Metrics (served at /metrics)
+    if let Err(e) = financial_engine::core::metrics::install_prometheus() {
+        println!("⚠️ Metrics disabled -> {}", e);
+    }
+
     // 2. Initialize Database (Universal Connector)
     match financial_engine::storage::connector::init_db().await {
         Ok(_) => println!("💾 Database System Initialized."),
@@ -47,8 +61,22 @@ async fn main() {
         }
     }
 
+    // Engine config: stored (admin edits) > ENGINE_CONFIG_PATH rules file > empty
+    let storage: Arc<dyn StorageBackend> = match std::env::var("ENGINE_DATA_DIR") {
+        Ok(dir) => Arc::new(JsonFileStorage::new(&dir)),
+        Err(_) => Arc::new(InMemoryStorage::new()),
+    };
+    let config_path = std::env::var("ENGINE_CONFIG_PATH").ok();
+    let router = match create_configured_router(storage, config_path.as_deref(), std::env::var("ADMIN_API_TOKEN").ok()) {
+        Ok(router) => router,
+        Err(e) => {
+            println!("❌ CRITICAL ERROR: Invalid engine config -> {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // 3. Build our Application with Middleware Stack
-    let app = create_router()
+    let app = router
         // Add Logging Middleware
         .layer(TraceLayer::new_for_http())
         // Add Timeout (Slowloris protection) - 30 seconds max per request
@@ -0,0 +1,524 @@
+use crate::api::rest::{CustomerInput, OrderRequest};
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::discount::promo::PromoRedemption;
+use crate::rules::mixed_scenarios::{CalculationContext, CartCalculation, MixedScenarioEngine};
+use crate::storage::database::{EntitySerializer, StorageBackend};
+use crate::storage::models::{TransactionItemRow, TransactionRow};
+use crate::tax::reporting::TaxTransaction;
+use crate::tax::sourcing::TaxSourcing;
+use crate::types::cart::Cart;
+use crate::types::currency::CurrencyRegistry;
+use crate::types::item::Item;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// ============================================================================
+/// 🛒 Orders (ඇණවුම්)
+/// ============================================================================
+/// `OrderRequest` එකක් එන්ජිමෙන් ගණනය කර, `transactions` සහ `transaction_items`
+/// tables වල ආකෘතියෙන් ගබඩා කරයි. Status lifecycle:
+/// pending → paid → fulfilled; pending හෝ paid ඇණවුමක් cancel කළ හැක.
+/// Fulfilled සහ cancelled අවසාන තත්ත්වයන් වේ (fulfilled ඇණවුමක් ආපසු දීමට refund).
+/// Paid වූ විට විකුණුම tax reports සඳහා `TaxTransaction` එකක් ලෙස සටහන් වේ; cancel කළ විට
+/// එය ඉවත් කර promo code භාවිතයන් ආපසු ලබා දේ.
+///
+/// 🏷️ `EntitySerializer` kind of a stored order
+pub const ORDER_KIND: &str = "Order";
+const KEY_PREFIX: &str = "order:";
+/// `order-index:{created_at millis}:{id}` => "" (lists page through keys, not orders)
+const INDEX_PREFIX: &str = "order-index:";
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// 🚦 Order lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Fulfilled,
+    Cancelled,
+}
+
+impl OrderStatus {
+    /// `transactions.status` value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Paid => "paid",
+            OrderStatus::Fulfilled => "fulfilled",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> EngineResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "pending" => Ok(OrderStatus::Pending),
+            "paid" => Ok(OrderStatus::Paid),
+            "fulfilled" => Ok(OrderStatus::Fulfilled),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            other => Err(EngineError::Validation {
+                message: format!("Unknown order status '{}' (pending, paid, fulfilled, cancelled)", other),
+            }),
+        }
+    }
+
+    pub fn can_transition_to(self, next: OrderStatus) -> bool {
+        matches!(
+            (self, next),
+            (OrderStatus::Pending, OrderStatus::Paid)
+                | (OrderStatus::Paid, OrderStatus::Fulfilled)
+                | (OrderStatus::Pending | OrderStatus::Paid, OrderStatus::Cancelled)
+        )
+    }
+}
+
+/// 🛒 A placed order: the priced cart and where it is in its lifecycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    pub status: OrderStatus,
+    pub customer: CustomerInput,
+    pub payment_method: String,
+    /// Jurisdiction the order was taxed in (`OrderRequest::tax_jurisdiction`)
+    pub jurisdiction: Option<String>,
+    pub cart: Cart,
+    pub calculation: CartCalculation,
+    /// Promo code uses counted when the order was placed (returned on cancel)
+    #[serde(default)]
+    pub promo_redemptions: Vec<PromoRedemption>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Order {
+    /// 🧮 Price `request` and open a pending order
+    pub fn place(
+        engine: &MixedScenarioEngine,
+        request: &OrderRequest,
+        sourcing: TaxSourcing,
+        now: DateTime<Utc>,
+    ) -> EngineResult<Self> {
+        let input = &request.calculation;
+        if input.items.is_empty() {
+            return Err(EngineError::Validation {
+                message: "Order has no items".to_string(),
+            });
+        }
+        let currency = CurrencyRegistry::configured().resolve(&input.currency)?;
+        let mut cart = Cart::new();
+        cart.customer_id = request.customer.id.clone().or_else(|| input.customer_id.clone());
+        cart.currency = currency;
+        cart.created_at = Some(now);
+        for line in &input.items {
+            let mut item = Item::new(&line.name, Money::try_from_float(line.price, currency)?, line.quantity);
+            item.id = line.id.clone();
+            item.currency = currency;
+            if let Some(category) = &line.category {
+                item = item.with_metadata("category", category);
+            }
+            if !line.discount_eligible {
+                item = item.with_metadata("discountable", "false");
+            }
+            cart.add_item(item);
+        }
+
+        let jurisdiction = request.tax_jurisdiction(sourcing);
+        let mut context = CalculationContext::new().at(now).in_currency(currency);
+        if let Some(code) = &jurisdiction {
+            context = context.in_jurisdiction(code);
+        }
+        engine.check_promo_codes(&cart, &input.discount_codes)?;
+        let calculation = engine.calculate_cart_with_context(&cart, &input.discount_codes, &context)?;
+        let promo_redemptions = engine.redeem_promo_codes(&cart, &input.discount_codes, now)?;
+
+        Ok(Order {
+            id: Uuid::new_v4().to_string(),
+            status: OrderStatus::Pending,
+            customer: request.customer.clone(),
+            payment_method: request.payment.method.clone(),
+            jurisdiction,
+            cart,
+            calculation,
+            promo_redemptions,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// 🚦 Move to `next`; disallowed transitions leave the order unchanged
+    pub fn transition(&mut self, next: OrderStatus, now: DateTime<Utc>) -> EngineResult<()> {
+        if !self.status.can_transition_to(next) {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Order '{}' cannot go from {} to {}",
+                    self.id,
+                    self.status.as_str(),
+                    next.as_str()
+                ),
+            });
+        }
+        self.status = next;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// `transactions` row of this order
+    pub fn transaction_row(&self) -> TransactionRow {
+        TransactionRow {
+            id: self.id.clone(),
+            transaction_type: "order".to_string(),
+            subtotal: self.calculation.subtotal.amount,
+            discount_total: self.calculation.total_discount.amount,
+            tax_total: self.calculation.total_tax.amount,
+            grand_total: self.calculation.grand_total.amount,
+            currency: self.cart.currency.code(),
+            customer_id: self.cart.customer_id.clone(),
+            status: self.status.as_str().to_string(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            // Card tokens and addresses stay out of the row
+            metadata: serde_json::json!({
+                "customer_email": self.customer.email,
+                "customer_name": self.customer.name,
+                "payment_method": self.payment_method,
+                "jurisdiction": self.jurisdiction,
+            }),
+        }
+    }
+
+    /// `transaction_items` rows, one per calculated line (lines follow the cart's item order)
+    pub fn item_rows(&self) -> Vec<TransactionItemRow> {
+        self.cart
+            .items
+            .iter()
+            .zip(&self.calculation.items)
+            .map(|(item, line)| TransactionItemRow {
+                id: Uuid::new_v4().to_string(),
+                transaction_id: self.id.clone(),
+                item_id: line.item_id.clone(),
+                item_name: item.name.clone(),
+                unit_price: item.price.amount,
+                quantity: item.quantity,
+                discount: line.discount_amount.amount,
+                tax: line.tax_amount.amount,
+                total: line.total.amount,
+                metadata: serde_json::json!({ "category": item.category() }),
+            })
+            .collect()
+    }
+
+    fn index_key(&self) -> String {
+        format!("{}{:015}:{}", INDEX_PREFIX, self.created_at.timestamp_millis(), self.id)
+    }
+
+    pub fn store(&self, backend: &dyn StorageBackend) -> EngineResult<()> {
+        let json = EntitySerializer::to_versioned_json(ORDER_KIND, self)?;
+        backend.set(&format!("{}{}", KEY_PREFIX, self.id), &json)?;
+        backend.set(&self.index_key(), "")
+    }
+
+    /// The sale as the tax reports see it (recorded when paid)
+    pub fn tax_transaction(&self, recorded_at: DateTime<Utc>) -> TaxTransaction {
+        TaxTransaction::new(
+            &self.id,
+            self.jurisdiction.as_deref().unwrap_or_default(),
+            self.calculation.clone(),
+            recorded_at,
+        )
+    }
+
+    /// 💾 Store the order after `transition` from `previous`, with its status side effects:
+    /// paid records the sale for the tax reports, cancelled withdraws that record and returns
+    /// the promo code uses. If storing fails the tax record is put back as it was.
+    pub fn store_transition(
+        &self,
+        previous: &Order,
+        backend: &dyn StorageBackend,
+        engine: &MixedScenarioEngine,
+    ) -> EngineResult<()> {
+        let recorded = TaxTransaction::load(backend, &self.id)?;
+        match self.status {
+            OrderStatus::Paid => self.tax_transaction(self.updated_at).store(backend)?,
+            OrderStatus::Cancelled => {
+                TaxTransaction::delete(backend, &self.id)?;
+            }
+            OrderStatus::Pending | OrderStatus::Fulfilled => {}
+        }
+        if let Err(e) = self.store(backend) {
+            let _ = match &recorded {
+                Some(record) => record.store(backend),
+                None => TaxTransaction::delete(backend, &self.id).map(|_| ()),
+            };
+            let _ = previous.store(backend);
+            return Err(e);
+        }
+        if self.status == OrderStatus::Cancelled {
+            engine.release_promo_codes(&self.promo_redemptions);
+        }
+        Ok(())
+    }
+
+    pub fn load(backend: &dyn StorageBackend, id: &str) -> EngineResult<Option<Order>> {
+        match backend.get(&format!("{}{}", KEY_PREFIX, id))? {
+            Some(json) => EntitySerializer::from_json(&json).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 📄 Newest orders first; `page` starts at 1
+    pub fn list(backend: &dyn StorageBackend, page: usize, per_page: usize) -> EngineResult<OrderPage> {
+        if page == 0 || per_page == 0 || per_page > MAX_PAGE_SIZE {
+            return Err(EngineError::Validation {
+                message: format!("Page must be >= 1 and page size 1..={}", MAX_PAGE_SIZE),
+            });
+        }
+        // (created_at millis, id) from the index keys; only the requested page is read
+        let mut index: Vec<(i64, String)> = backend
+            .keys(INDEX_PREFIX)?
+            .iter()
+            .filter_map(|key| {
+                let (millis, id) = key.strip_prefix(INDEX_PREFIX)?.split_once(':')?;
+                Some((millis.parse().ok()?, id.to_string()))
+            })
+            .collect();
+        index.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let total = index.len();
+        let mut orders = Vec::new();
+        for (_, id) in index.iter().skip((page - 1) * per_page).take(per_page) {
+            if let Some(order) = Self::load(backend, id)? {
+                orders.push(order);
+            }
+        }
+        Ok(OrderPage {
+            orders,
+            page,
+            per_page,
+            total,
+        })
+    }
+}
+
+/// 📄 One page of `Order::list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderPage {
+    pub orders: Vec<Order>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Orders across all pages
+    pub total: usize,
+}
+
+/// 🐘 Mirror of the stored orders in the SQL `transactions` / `transaction_items` tables
+#[cfg(feature = "persistence")]
+pub mod sql {
+    use super::Order;
+    use crate::core::errors::{EngineError, EngineResult};
+    use rust_decimal::Decimal;
+    use sqlx::{PgPool, Postgres, Transaction};
+    use uuid::Uuid;
+
+    fn database_error(e: sqlx::Error) -> EngineError {
+        EngineError::Database {
+            message: format!("Order SQL write failed: {}", e),
+        }
+    }
+
+    fn uuid(value: &str) -> EngineResult<Uuid> {
+        Uuid::parse_str(value).map_err(|e| EngineError::Validation {
+            message: format!("'{}' is not a UUID: {}", value, e),
+        })
+    }
+
+    /// Insert the order and its items in one SQL transaction
+    pub async fn insert(pool: &PgPool, order: &Order) -> EngineResult<()> {
+        let row = order.transaction_row();
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await.map_err(database_error)?;
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (id, transaction_type, subtotal, discount_total, tax_total,
+                grand_total, currency, customer_id, status, created_at, updated_at, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::jsonb)
+            "#,
+        )
+        .bind(uuid(&row.id)?)
+        .bind(&row.transaction_type)
+        .bind(row.subtotal)
+        .bind(row.discount_total)
+        .bind(row.tax_total)
+        .bind(row.grand_total)
+        .bind(&row.currency)
+        // `customer_id` is a UUID column; other customer ids live only in the stored order
+        .bind(row.customer_id.as_deref().and_then(|id| Uuid::parse_str(id).ok()))
+        .bind(&row.status)
+        .bind(row.created_at)
+        .bind(row.updated_at)
+        .bind(row.metadata.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        for item in order.item_rows() {
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_items (id, transaction_id, item_id, item_name, unit_price,
+                    quantity, discount, tax, total, metadata)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::jsonb)
+                "#,
+            )
+            .bind(uuid(&item.id)?)
+            .bind(uuid(&item.transaction_id)?)
+            .bind(&item.item_id)
+            .bind(&item.item_name)
+            .bind(item.unit_price)
+            .bind(Decimal::from_f64_retain(item.quantity).unwrap_or_default())
+            .bind(item.discount)
+            .bind(item.tax)
+            .bind(item.total)
+            .bind(item.metadata.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+        }
+        tx.commit().await.map_err(database_error)
+    }
+
+    /// Remove an order whose placement could not be completed
+    pub async fn delete(pool: &PgPool, order: &Order) -> EngineResult<()> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await.map_err(database_error)?;
+        for table in ["transaction_items WHERE transaction_id", "transactions WHERE id"] {
+            sqlx::query(&format!("DELETE FROM {} = $1", table))
+                .bind(uuid(&order.id)?)
+                .execute(&mut *tx)
+                .await
+                .map_err(database_error)?;
+        }
+        tx.commit().await.map_err(database_error)
+    }
+
+    pub async fn update_status(pool: &PgPool, order: &Order) -> EngineResult<()> {
+        sqlx::query("UPDATE transactions SET status = $1, updated_at = $2 WHERE id = $3")
+            .bind(order.status.as_str())
+            .bind(order.updated_at)
+            .bind(uuid(&order.id)?)
+            .execute(pool)
+            .await
+            .map_err(database_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::api::rest::{ItemInput, PaymentInput};
+    use crate::storage::database::InMemoryStorage;
+    use chrono::{Duration, TimeZone};
+
+    fn request(price: f64) -> OrderRequest {
+        OrderRequest {
+            calculation: CalculationRequest {
+                items: vec![ItemInput {
+                    id: "TEA".to_string(),
+                    name: "Tea".to_string(),
+                    price,
+                    quantity: 2.0,
+                    category: None,
+                    tax_class: None,
+                    discount_eligible: true,
+                }],
+                customer_id: None,
+                discount_codes: vec![],
+                tax_region: Some("LK".to_string()),
+                currency: "LKR".to_string(),
+            },
+            customer: CustomerInput {
+                id: None,
+                email: "a@example.com".to_string(),
+                name: "A".to_string(),
+                phone: None,
+            },
+            payment: PaymentInput {
+                method: "card".to_string(),
+                card_token: Some("tok_secret".to_string()),
+                billing_address: None,
+            },
+            shipping: None,
+        }
+    }
+
+    #[test]
+    fn test_order_lifecycle_rows_and_pagination() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap();
+
+        let mut order = Order::place(&engine, &request(500.0), TaxSourcing::Origin, start).unwrap();
+        assert_eq!(order.status, OrderStatus::Pending);
+        let row = order.transaction_row();
+        assert_eq!((row.subtotal, row.tax_total, row.grand_total), (100_000, 18_000, 118_000));
+        assert!(!row.metadata.to_string().contains("tok_secret"));
+        let items = order.item_rows();
+        assert_eq!((items[0].item_id.as_str(), items[0].unit_price, items[0].tax), ("TEA", 50_000, 18_000));
+        assert!(Order::place(&engine, &request(1.234), TaxSourcing::Origin, start).is_err());
+
+        // pending → paid → fulfilled; nothing after fulfilled
+        assert!(order.transition(OrderStatus::Fulfilled, start).is_err());
+        order.transition(OrderStatus::Paid, start).unwrap();
+        order.transition(OrderStatus::Fulfilled, start).unwrap();
+        assert!(order.transition(OrderStatus::Cancelled, start).is_err());
+        assert_eq!(order.transaction_row().status, "fulfilled");
+
+        let storage = InMemoryStorage::new();
+        order.store(&storage).unwrap();
+        for minutes in 1..=4 {
+            Order::place(&engine, &request(100.0), TaxSourcing::Origin, start + Duration::minutes(minutes))
+                .unwrap()
+                .store(&storage)
+                .unwrap();
+        }
+        assert_eq!(Order::load(&storage, &order.id).unwrap().unwrap().status, OrderStatus::Fulfilled);
+        assert!(Order::load(&storage, "missing").unwrap().is_none());
+        let page = Order::list(&storage, 3, 2).unwrap();
+        assert_eq!((page.total, page.orders.len()), (5, 1));
+        assert_eq!(page.orders[0].id, order.id);
+        assert!(Order::list(&storage, 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_order_redeems_promo_codes_and_cancel_gives_them_back() {
+        use crate::discount::promo::{PromoCode, PromoCodeManager};
+        use std::sync::Arc;
+
+        let promos = Arc::new(PromoCodeManager::new());
+        promos.register(PromoCode::new("ONCE").with_max_total_uses(1)).unwrap();
+        let mut engine = MixedScenarioEngine::new();
+        engine.set_promo_manager(Some(promos.clone()));
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap();
+        let mut promo_request = request(500.0);
+        promo_request.calculation.discount_codes = vec!["once".to_string()];
+        // Same SKU twice at different prices: each row keeps its own line
+        let mut second = promo_request.calculation.items[0].clone();
+        second.name = "Tea (gift tin)".to_string();
+        second.price = 800.0;
+        promo_request.calculation.items.push(second);
+
+        let order = Order::place(&engine, &promo_request, TaxSourcing::Origin, now).unwrap();
+        let rows = order.item_rows();
+        assert_eq!((rows[0].item_name.as_str(), rows[0].unit_price), ("Tea", 50_000));
+        assert_eq!((rows[1].item_name.as_str(), rows[1].unit_price), ("Tea (gift tin)", 80_000));
+        assert_eq!(promos.uses("ONCE"), Some(1));
+        assert!(Order::place(&engine, &promo_request, TaxSourcing::Origin, now).is_err());
+
+        let storage = InMemoryStorage::new();
+        order.store(&storage).unwrap();
+        let mut cancelled = order.clone();
+        cancelled.transition(OrderStatus::Cancelled, now).unwrap();
+        cancelled.store_transition(&order, &storage, &engine).unwrap();
+        assert_eq!(promos.uses("ONCE"), Some(0));
+        assert_eq!(Order::load(&storage, &order.id).unwrap().unwrap().status, OrderStatus::Cancelled);
+    }
+}
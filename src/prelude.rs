@@ -0,0 +1,94 @@
+//! ============================================================================
+//! 📦 Prelude (ස්ථාවර පොදු API)
+//! ============================================================================
+//! `use financial_engine::prelude::*;` යනු සහාය දක්වන එකම entry point එකයි.
+//! Module ව්‍යුහය versions අතර වෙනස් විය හැකි බැවින් `rules::mixed_scenarios::*`
+//! වැනි ගැඹුරු paths වෙනුවට මෙහි re-exports භාවිතා කරන්න.
+//!
+//! ```
+//! use financial_engine::prelude::*;
+//!
+//! let mut engine = MixedScenarioEngine::new();
+//! engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+//!
+//! let mut cart = Cart::new();
+//! cart.add_item(Item::new("Tea", Money::new(500, 0), 2.0));
+//!
+//! let result: CartCalculation = engine.calculate_cart(&cart, &[], None).unwrap();
+//! assert_eq!(result.subtotal, Money::new(1000, 0));
+//! assert_eq!(result.total_tax, Money::new(180, 0));
+//! assert_eq!(result.grand_total, Money::new(1180, 0));
+//! ```
+
+// Core
+pub use crate::core::calculation::{CalculationEngine, CalculationResult};
+pub use crate::core::clock::{Clock, FixedClock, SystemClock};
+pub use crate::core::errors::{EngineError, EngineResult};
+pub use crate::core::exchange::{ExchangeRateProvider, StaticExchangeRates};
+pub use crate::core::i18n::Locale;
+pub use crate::core::micro_money::MicroMoney;
+pub use crate::core::money::{CurrencyAmount, Money};
+pub use crate::core::rounding::RoundingMode;
+pub use crate::core::timezone::StoreTimeZone;
+pub use crate::types::cart::{Cart, CartLimits};
+pub use crate::types::customer::CustomerContext;
+pub use crate::types::currency::{Currency, CurrencyInfo, CurrencyRegistry};
+pub use crate::types::item::Item;
+
+// Engines
+pub use crate::api::facade::FinancialEngine;
+pub use crate::rules::mixed_scenarios::{
+    BogoMode, CalculationOrder, CapStrategy, CartBundle, CartCalculation, CartDiscountConfig,
+    CalculationContext, CartDiscountType, CartTier, DiscountCondition,
+    DiscountDetail, DiscountRule, DiscountType, EngineSnapshot, ItemCalculation, MarginSummary,
+    Kit, KitComponent, MixedScenarioEngine, PriceList, PriceListScope, ProductDiscountConfig, ProductTaxConfig,
+    ReplayInput, ResidueTarget, SmallOrderTax, SmallOrderTreatment, StackingPolicy, SuppressedDiscount,
+    SuppressionReason, TaxAppliesTo, TaxBase, TaxBasis, TaxDetail, TaxRate, TaxTreatment,
+};
+pub use crate::refund::processor::RefundProcessor;
+pub use crate::refund::store_credit::{StoreCredit, StoreCreditBook};
+pub use crate::refund::types::{
+    ManagerApproval, RefundDestination, RefundReason, RefundRequest, RefundResult, RefundType,
+};
+pub use crate::subscription::proration::{
+    ProratedUsageRequest, ProratedUsageResult, ProrationEngine, ProrationMethod, ProrationRequest,
+    ProrationResult, UsageCaps,
+};
+pub use crate::ledger::account::{Account, AccountType};
+pub use crate::ledger::journal::{GeneralLedger, RefundAccounts};
+pub use crate::ledger::transaction::Transaction;
+pub use crate::tax::remittance::{RemittanceLine, RemittanceSummary, TaxRecord, TaxRemittance};
+pub use crate::tax::jurisdiction::{Jurisdiction, JurisdictionResolver, TaxAddress};
+pub use crate::tax::reporting::{ReportGranularity, TaxReport, TaxReportRow, TaxTransaction};
+pub use crate::tax::sourcing::TaxSourcing;
+pub use crate::tax::withholding::{SupplierProfile, WithholdingAccounts, WithholdingSchedule, WithholdingSplit};
+pub use crate::invoice::sequence::{InvoiceSequencer, SequenceFormat, SequenceReset};
+pub use crate::commission::{
+    CommissionAccounts, CommissionRate, CommissionSchedule, CommissionSplit, PayoutBatch, SellerPayout,
+};
+pub use crate::deposit::{ContainerDeposit, DepositAccounts, DepositMovement, DepositSchedule};
+pub use crate::order::{Order, OrderPage, OrderStatus};
+pub use crate::discount::promo::{PromoCode, PromoCodeManager, PromoRedemption, PromoStatus, VelocityLimit};
+pub use crate::loyalty::{
+    AccrualRounding, LoyaltyAccounts, LoyaltyProgram, LoyaltyRedemption, RedemptionTreatment,
+};
+
+// Rules
+pub use crate::rules::conditions::{Condition, Operator};
+pub use crate::rules::definition::{load_rules, ActionSpec, RuleDefinition};
+pub use crate::rules::multi_store::MultiStoreEngine;
+pub use crate::rules::processor::RuleProcessor;
+pub use crate::rules::promotions::{
+    BuyNGetFree, FreeShippingThreshold, PriceThresholdFixed, QtyThresholdPercentage,
+};
+pub use crate::rules::traits::{DiscountTarget, DiscountValue, Rule, RuleAction};
+
+// API DTOs
+pub use crate::api::config::EngineConfig;
+pub use crate::api::rest::{
+    ApiError, ApiResponse, CalculationRequest, CalculationResponse, MoneyDto,
+    SubscriptionPreviewRequest, SubscriptionPreviewResponse,
+};
+pub use crate::api::webhook::{
+    DeadLetter, DeliveryOutcome, RetryPolicy, WebhookDispatcher, WebhookEvent, WebhookTransport,
+};
@@ -1,2 +1,3 @@
 pub mod processor;
 pub mod types;
+pub mod store_credit;
@@ -1,9 +1,13 @@
 use crate::audit::logger::{LogLevel, Logger};
 use crate::core::errors::{EngineError, EngineResult};
+use crate::core::metrics;
 use crate::core::money::Money;
-use crate::refund::types::{RefundRequest, RefundResult, RefundType};
+use crate::refund::types::{RefundLine, RefundRequest, RefundResult, RefundType};
 use crate::rules::mixed_scenarios::CartCalculation;
+use crate::security::audit_trail::{AuditAction, AuditEntry, AuditSeverity, AuditTrail};
 use crate::types::cart::Cart;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
 
 /// ============================================================================
 /// 🔄 Refund Processor (ආපසු ගෙවීම් යන්ත්‍රය)
@@ -11,15 +15,83 @@ use crate::types::cart::Cart;
 /// Refund logic පාලනය කරයි.
 /// State history සහ Audit සමඟ සම්බන්ධ වේ.
 
+/// Quantities closer than this are treated as equal (fractional weights)
+const QTY_EPSILON: f64 = 1e-9;
+
 pub struct RefundProcessor {
     logger: Logger,
+    refund_window: Option<Duration>,
+    audit_trail: Option<Arc<Mutex<AuditTrail>>>,
 }
 
 impl RefundProcessor {
     pub fn new() -> Self {
         RefundProcessor {
             logger: Logger::new(),
+            refund_window: None,
+            audit_trail: None,
+        }
+    }
+
+    /// ⏳ Return window (Ex: 30 days) counted from the original cart's `created_at`
+    /// Later refunds need a `manager_approval`, which is written to the audit trail.
+    pub fn with_refund_window(mut self, window: Duration) -> Self {
+        self.refund_window = Some(window);
+        self
+    }
+
+    /// 📜 Manager-approved late refunds are logged here
+    pub fn with_audit_trail(mut self, trail: Arc<Mutex<AuditTrail>>) -> Self {
+        self.audit_trail = Some(trail);
+        self
+    }
+
+    /// Reject a refund past the window unless a manager approved it (then audit the override)
+    fn check_refund_window(
+        &self,
+        original_cart: &Cart,
+        request: &RefundRequest,
+        now: DateTime<Utc>,
+    ) -> EngineResult<()> {
+        let Some(window) = self.refund_window else {
+            return Ok(());
+        };
+        let sold_at = original_cart.created_at.ok_or_else(|| EngineError::Validation {
+            message: format!("Transaction {} has no sale time; refund window cannot be checked", original_cart.id),
+        })?;
+        let age = now - sold_at;
+        if age <= window {
+            return Ok(());
+        }
+
+        let Some(approval) = &request.manager_approval else {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Refund window of {} days expired for transaction {} (sold {} days ago)",
+                    window.num_days(),
+                    original_cart.id,
+                    age.num_days()
+                ),
+            });
+        };
+        let description = format!(
+            "Late refund approved by {}: {} days after sale, window {} days ({})",
+            approval.manager_id,
+            age.num_days(),
+            window.num_days(),
+            approval.reason
+        );
+        self.logger.log(LogLevel::Audit, "REFUND", "WINDOW_OVERRIDE", &description)?;
+        if let Some(trail) = &self.audit_trail {
+            let entry = AuditEntry::new(AuditAction::TransactionRefunded, AuditSeverity::Audit, "Refund", &description)
+                .with_user(&approval.manager_id, None, None)
+                .with_resource(&original_cart.id)
+                .with_metadata("override", "refund_window")
+                .with_metadata("reason", &approval.reason);
+            // A poisoned trail is still appendable
+            trail.lock().unwrap_or_else(|e| e.into_inner()).log(entry);
         }
+        Ok(())
     }
 
     /// 🚀 Process Refund ( නිවැරදි ක්‍රමය )
@@ -30,8 +102,23 @@ impl RefundProcessor {
         original_cart: &Cart,
         original_calculation: &CartCalculation,
         request: &RefundRequest,
+    ) -> EngineResult<RefundResult> {
+        self.process_partial(original_cart, original_calculation, request, &[])
+    }
+
+    /// 🧮 Process a refund after earlier partial refunds of the same transaction
+    /// Each line is refunded as (cumulative share after this refund) - (already refunded),
+    /// so rounding never drifts: the final refund of a line is exactly its remaining balance
+    /// and a full-return sequence nets to zero. Ex: Rs.100 / 3 => 33.33 + 33.34 + 33.33
+    pub fn process_partial(
+        &self,
+        original_cart: &Cart,
+        original_calculation: &CartCalculation,
+        request: &RefundRequest,
+        previous_refunds: &[RefundResult],
     ) -> EngineResult<RefundResult> {
         let mut total_refund = Money::zero();
+        let mut lines = Vec::new();
 
         // Audit Log Start
         self.logger.log(
@@ -52,11 +139,22 @@ impl RefundProcessor {
                     id: item_id.clone(),
                 })?;
 
-            if *return_qty > original_item.quantity {
+            let (refunded_qty, refunded_amount) = previous_refunds
+                .iter()
+                .filter(|r| r.transaction_id == original_cart.id)
+                .flat_map(|r| &r.lines)
+                .filter(|line| line.item_id == original_item.id)
+                .fold((0.0, Money::zero()), |(qty, amount), line| {
+                    (qty + line.quantity, amount + line.amount)
+                });
+
+            if *return_qty + refunded_qty > original_item.quantity + QTY_EPSILON {
                 return Err(EngineError::Validation {
                     message: format!(
-                        "Refund qty {} exceeds original {}",
-                        return_qty, original_item.quantity
+                        "Refund qty {} exceeds remaining {} (original {})",
+                        return_qty,
+                        original_item.quantity - refunded_qty,
+                        original_item.quantity
                     ),
                 });
             }
@@ -71,13 +169,27 @@ impl RefundProcessor {
                 })?;
 
             // 3. Pro-rata Logic (Proportional Refund)
-            // Refund = Total Paid For Line * (Return Qty / Original Qty)
-            let ratio = return_qty / original_item.quantity;
-            let refund_amount = calc_result.total.mul_ratio(ratio);
+            // Refund = Total Paid For Line * (Cumulative Qty / Original Qty) - Already Refunded
+            let cumulative_qty = refunded_qty + return_qty;
+            let cumulative_share = if original_item.quantity - cumulative_qty <= QTY_EPSILON {
+                calc_result.total
+            } else {
+                calc_result.total.mul_ratio(cumulative_qty / original_item.quantity)
+            };
+            let refund_amount = cumulative_share - refunded_amount;
 
             total_refund = total_refund + refund_amount;
+            lines.push(RefundLine {
+                item_id: original_item.id.clone(),
+                quantity: *return_qty,
+                amount: refund_amount,
+            });
         }
 
+        // Only a refund that is otherwise valid can use (and audit) a late-refund approval
+        self.check_refund_window(original_cart, request, Utc::now())?;
+        metrics::record_refund(total_refund);
+
         // Audit Log Success
         self.logger.log(
             LogLevel::Info,
@@ -89,10 +201,119 @@ impl RefundProcessor {
         Ok(RefundResult {
             id: uuid::Uuid::new_v4().to_string(),
             transaction_id: original_cart.id.clone(),
-            timestamp: chrono::Utc::now(),
+            timestamp: Utc::now(),
             refund_amount: total_refund,
             refund_type: RefundType::Partial,
             new_cart_state: None,
+            lines,
+            reason: request.reason,
+            note: request.note.clone(),
+            destination: request.destination,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_reason_carried_to_result() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(2000, 0), 2.0));
+        let calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        let request = RefundRequest {
+            original_transaction_id: cart.id.clone(),
+            items_to_refund: vec![("Shirt".to_string(), 1.0)],
+            reason: RefundReason::WrongSize,
+            note: Some("needs L".to_string()),
+            destination: RefundDestination::OriginalTender,
+            manager_approval: None,
+        };
+
+        let result = RefundProcessor::new().process(&cart, &calculation, &request).unwrap();
+        assert_eq!(result.refund_amount, Money::new(2000, 0));
+        assert_eq!(result.reason, RefundReason::WrongSize);
+        assert_eq!(result.note.as_deref(), Some("needs L"));
+    }
+
+    #[test]
+    fn test_three_partial_refunds_sum_to_line_total() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Pack", Money::from_cents(3334), 3.0));
+        let mut calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        // Rs.100 paid for the 3-unit line (after a line discount)
+        calculation.items[0].total = Money::new(100, 0);
+
+        let processor = RefundProcessor::new();
+        let one = RefundRequest {
+            original_transaction_id: cart.id.clone(),
+            items_to_refund: vec![("Pack".to_string(), 1.0)],
+            reason: RefundReason::ChangedMind,
+            note: None,
+            destination: RefundDestination::OriginalTender,
+            manager_approval: None,
+        };
+        let mut history = Vec::new();
+        for _ in 0..3 {
+            let result = processor.process_partial(&cart, &calculation, &one, &history).unwrap();
+            history.push(result);
+        }
+
+        let amounts: Vec<i64> = history.iter().map(|r| r.refund_amount.amount).collect();
+        assert_eq!(amounts, vec![3333, 3334, 3333]);
+        let total = history.iter().fold(Money::zero(), |acc, r| acc + r.refund_amount);
+        assert_eq!(total, Money::new(100, 0));
+
+        // Nothing left to refund
+        assert!(processor.process_partial(&cart, &calculation, &one, &history).is_err());
+    }
+
+    #[test]
+    fn test_refund_window_rejects_late_refund_unless_approved() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(2000, 0), 1.0));
+        let calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        let mut request = RefundRequest {
+            original_transaction_id: cart.id.clone(),
+            items_to_refund: vec![("Shirt".to_string(), 1.0)],
+            reason: RefundReason::Defective,
+            note: None,
+            destination: RefundDestination::OriginalTender,
+            manager_approval: None,
+        };
+        let trail = Arc::new(Mutex::new(AuditTrail::new(100)));
+        let processor = RefundProcessor::new()
+            .with_refund_window(Duration::days(30))
+            .with_audit_trail(trail.clone());
+
+        // Bought 10 days ago: in window
+        cart.created_at = Some(Utc::now() - Duration::days(10));
+        assert!(processor.process(&cart, &calculation, &request).is_ok());
+
+        // Bought 45 days ago: expired
+        cart.created_at = Some(Utc::now() - Duration::days(45));
+        let err = processor.process(&cart, &calculation, &request).unwrap_err();
+        assert!(err.to_string().contains("30 days"));
+        assert_eq!(trail.lock().unwrap().count(), 0);
+
+        // Manager override lets it through and is audited
+        request.manager_approval = Some(ManagerApproval {
+            manager_id: "MGR-7".to_string(),
+            reason: "Regular customer, unopened".to_string(),
+        });
+        // ...but not for a refund rejected for another reason (no approval left on record)
+        let mut too_many = request.clone();
+        too_many.items_to_refund = vec![("Shirt".to_string(), 2.0)];
+        assert!(processor.process(&cart, &calculation, &too_many).is_err());
+        assert_eq!(trail.lock().unwrap().count(), 0);
+        let result = processor.process(&cart, &calculation, &request).unwrap();
+        assert_eq!(result.refund_amount, Money::new(2000, 0));
+        let trail = trail.lock().unwrap();
+        let entries = trail.get_by_user("MGR-7");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].resource_id.as_deref(), Some(cart.id.as_str()));
+        assert!(entries[0].description.contains("45 days after sale"));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::refund::types::{RefundDestination, RefundResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// ============================================================================
+/// 🎫 Store Credit (වෙළඳසැල් ණය)
+/// ============================================================================
+/// Store credit වෙත කරන refund එකක් gift card එකක් මෙන් code එකක් සහිත balance
+/// එකක් නිකුත් කරයි. පාරිභෝගිකයා එය පසුව මිලදී ගැනීම් සඳහා භාවිතා කරයි.
+/// Ledger එකේ එය store-credit liability account එකට credit වේ (`GeneralLedger::post_refund`).
+///
+/// 🎫 One issued credit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreCredit {
+    pub code: String,
+    pub customer_id: String,
+    /// Refund that issued it
+    pub refund_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub original_amount: Money,
+    pub balance: Money,
+}
+
+/// 📒 Issued store credits by code
+#[derive(Debug, Clone, Default)]
+pub struct StoreCreditBook {
+    credits: HashMap<String, StoreCredit>,
+}
+
+impl StoreCreditBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ➕ Issue credit for a store-credit refund (once per refund)
+    pub fn issue(&mut self, refund: &RefundResult, customer_id: &str) -> EngineResult<StoreCredit> {
+        if refund.destination != RefundDestination::StoreCredit {
+            return Err(EngineError::Validation {
+                message: format!("Refund {} is not a store-credit refund", refund.id),
+            });
+        }
+        if !refund.refund_amount.is_positive() {
+            return Err(EngineError::Validation {
+                message: format!("Refund {} has no amount to credit", refund.id),
+            });
+        }
+        if self.credits.values().any(|c| c.refund_id == refund.id) {
+            return Err(EngineError::Validation {
+                message: format!("Store credit already issued for refund {}", refund.id),
+            });
+        }
+
+        let credit = StoreCredit {
+            code: format!("SC-{}", &uuid::Uuid::new_v4().simple().to_string()[..12].to_uppercase()),
+            customer_id: customer_id.to_string(),
+            refund_id: refund.id.clone(),
+            issued_at: refund.timestamp,
+            original_amount: refund.refund_amount,
+            balance: refund.refund_amount,
+        };
+        self.credits.insert(credit.code.clone(), credit.clone());
+        Ok(credit)
+    }
+
+    pub fn get(&self, code: &str) -> Option<&StoreCredit> {
+        self.credits.get(code)
+    }
+
+    pub fn balance(&self, code: &str) -> Option<Money> {
+        self.credits.get(code).map(|c| c.balance)
+    }
+
+    /// 💳 Spend credit; returns the remaining balance
+    pub fn redeem(&mut self, code: &str, amount: Money) -> EngineResult<Money> {
+        let credit = self.credits.get_mut(code).ok_or_else(|| EngineError::NotFound {
+            resource: "StoreCredit".to_string(),
+            id: code.to_string(),
+        })?;
+        if !amount.is_positive() || amount > credit.balance {
+            return Err(EngineError::Validation {
+                message: format!("Cannot redeem {} from store credit {} (balance {})", amount, code, credit.balance),
+            });
+        }
+        credit.balance = credit.balance - amount;
+        Ok(credit.balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_store_credit_refund_issues_credit_and_posts_liability() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(2000, 0), 2.0));
+        let calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        let request = RefundRequest {
+            original_transaction_id: cart.id.clone(),
+            items_to_refund: vec![("Shirt".to_string(), 1.0)],
+            reason: RefundReason::ChangedMind,
+            note: None,
+            destination: RefundDestination::StoreCredit,
+            manager_approval: None,
+        };
+        let refund = RefundProcessor::new().process(&cart, &calculation, &request).unwrap();
+        assert_eq!(refund.destination, RefundDestination::StoreCredit);
+
+        let mut ledger = GeneralLedger::new();
+        ledger.add_account(Account::new("cash", "Cash", AccountType::Asset));
+        ledger.add_account(Account::new("bank", "Bank Payouts", AccountType::Asset));
+        ledger.add_account(Account::new("sales", "Sales Revenue", AccountType::Income));
+        ledger.add_account(Account::new("store_credit", "Store Credit Liability", AccountType::Liability));
+        let accounts = RefundAccounts {
+            revenue: "sales".to_string(),
+            original_tender: "cash".to_string(),
+            store_credit: "store_credit".to_string(),
+            bank_transfer: "bank".to_string(),
+        };
+        let posted = ledger.post_refund(&refund, &accounts).unwrap();
+
+        // Dr revenue, Cr store-credit liability; no cash paid out
+        assert!(posted.is_balanced());
+        assert_eq!(ledger.balance("sales"), Some(Money::new(2000, 0)));
+        assert_eq!(ledger.balance("store_credit"), Some(Money::new(-2000, 0)));
+        assert_eq!(ledger.balance("cash"), Some(Money::zero()));
+
+        // Redeemable later, once per refund
+        let mut book = StoreCreditBook::new();
+        let credit = book.issue(&refund, "C001").unwrap();
+        assert_eq!(credit.balance, Money::new(2000, 0));
+        assert!(book.issue(&refund, "C001").is_err());
+        assert_eq!(book.redeem(&credit.code, Money::new(1500, 0)).unwrap(), Money::new(500, 0));
+        assert!(book.redeem(&credit.code, Money::new(600, 0)).is_err());
+        assert_eq!(book.balance(&credit.code), Some(Money::new(500, 0)));
+    }
+
+    #[test]
+    fn test_cash_refund_issues_no_credit() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shirt", Money::new(2000, 0), 1.0));
+        let calculation = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        let request = RefundRequest {
+            original_transaction_id: cart.id.clone(),
+            items_to_refund: vec![("Shirt".to_string(), 1.0)],
+            reason: RefundReason::Defective,
+            note: None,
+            destination: RefundDestination::OriginalTender,
+            manager_approval: None,
+        };
+        let refund = RefundProcessor::new().process(&cart, &calculation, &request).unwrap();
+        assert!(StoreCreditBook::new().issue(&refund, "C001").is_err());
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use crate::types::cart::Cart;
 use crate::core::money::Money;
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 
 /// ============================================================================
 /// 🔄 Refund Types (ආපසු ගෙවීම් වර්ග)
@@ -15,13 +16,111 @@ pub enum RefundType {
     Partial,
 }
 
+/// 🏷️ Refund Reason (ආපසු දීමට හේතුව)
+/// නිෂ්පාදන ආපසු එන්නේ ඇයිදැයි විශ්ලේෂණය කිරීමට (merchandising).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum RefundReason {
+    /// දෝෂ සහිත භාණ්ඩය
+    Defective,
+    /// ප්‍රමාණය නොගැළපේ
+    WrongSize,
+    /// වැරදි භාණ්ඩය ලැබුණි
+    WrongItem,
+    /// විස්තරයට නොගැළපේ
+    NotAsDescribed,
+    /// ප්‍රවාහනයේදී හානි වී ඇත
+    DamagedInTransit,
+    /// අදහස වෙනස් විය
+    ChangedMind,
+    #[default]
+    Other,
+}
+
+/// 💸 Refund Destination (ආපසු ගෙවීම යන තැන)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum RefundDestination {
+    /// ගෙවූ ක්‍රමයටම (cash / card)
+    #[default]
+    OriginalTender,
+    /// Store credit - පසුව භාවිතා කළ හැකි වගකීමක් (liability), මුදල් පිටතට යාමක් නොවේ
+    StoreCredit,
+    /// බැංකු මාරුවක්
+    BankTransfer,
+}
+
+impl RefundReason {
+    /// "Defective", "wrong_size", "changed-mind" ... (None for free text)
+    pub fn parse(value: &str) -> Option<Self> {
+        let key: String = value
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match key.as_str() {
+            "defective" => Some(RefundReason::Defective),
+            "wrongsize" => Some(RefundReason::WrongSize),
+            "wrongitem" => Some(RefundReason::WrongItem),
+            "notasdescribed" => Some(RefundReason::NotAsDescribed),
+            "damagedintransit" => Some(RefundReason::DamagedInTransit),
+            "changedmind" => Some(RefundReason::ChangedMind),
+            "other" => Some(RefundReason::Other),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RefundRequestInput")]
 pub struct RefundRequest {
     pub original_transaction_id: String,
     pub items_to_refund: Vec<(String, f64)>, // Item ID, Quantity
+    pub reason: RefundReason,
+    /// Optional free text (Ex: "screen cracked on arrival")
+    pub note: Option<String>,
+    pub destination: RefundDestination,
+    /// Manager sign-off for a refund past the refund window
+    pub manager_approval: Option<ManagerApproval>,
+}
+
+/// ✍️ Manager approval (කළමනාකරු අනුමැතිය) - allows a late refund, always audited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagerApproval {
+    pub manager_id: String,
     pub reason: String,
 }
 
+/// Wire format: `reason` may still be legacy free text
+#[derive(Deserialize)]
+struct RefundRequestInput {
+    original_transaction_id: String,
+    items_to_refund: Vec<(String, f64)>,
+    reason: String,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    destination: RefundDestination,
+    #[serde(default)]
+    manager_approval: Option<ManagerApproval>,
+}
+
+impl From<RefundRequestInput> for RefundRequest {
+    fn from(input: RefundRequestInput) -> Self {
+        // Unknown text => Other, keeping the original text as the note
+        let (reason, note) = match RefundReason::parse(&input.reason) {
+            Some(reason) => (reason, input.note),
+            None => (RefundReason::Other, input.note.or(Some(input.reason))),
+        };
+        RefundRequest {
+            original_transaction_id: input.original_transaction_id,
+            items_to_refund: input.items_to_refund,
+            reason,
+            note,
+            destination: input.destination,
+            manager_approval: input.manager_approval,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefundResult {
     pub id: String,
@@ -30,4 +129,108 @@ pub struct RefundResult {
     pub refund_amount: Money,
     pub refund_type: RefundType,
     pub new_cart_state: Option<Cart>, // State after partial refund
+    /// Per-line breakdown (used to reconcile later partial refunds)
+    #[serde(default)]
+    pub lines: Vec<RefundLine>,
+    #[serde(default)]
+    pub reason: RefundReason,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub destination: RefundDestination,
+}
+
+/// 🧾 One refunded line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefundLine {
+    pub item_id: String,
+    pub quantity: f64,
+    pub amount: Money,
+}
+
+/// 📊 Refunds for one reason
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RefundReasonStats {
+    pub count: usize,
+    pub amount: Money,
+}
+
+/// 📊 Count refunds by reason within `[from, to)`
+pub fn refunds_by_reason(
+    results: &[RefundResult],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> BTreeMap<RefundReason, RefundReasonStats> {
+    let mut stats: BTreeMap<RefundReason, RefundReasonStats> = BTreeMap::new();
+    for result in results.iter().filter(|r| r.timestamp >= from && r.timestamp < to) {
+        let entry = stats.entry(result.reason).or_insert(RefundReasonStats {
+            count: 0,
+            amount: Money::zero(),
+        });
+        entry.count += 1;
+        entry.amount = entry.amount + result.refund_amount;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn refund(reason: RefundReason, day: u32, amount: Money) -> RefundResult {
+        RefundResult {
+            id: format!("R{}", day),
+            transaction_id: "TX".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap(),
+            refund_amount: amount,
+            refund_type: RefundType::Partial,
+            new_cart_state: None,
+            lines: Vec::new(),
+            reason,
+            note: None,
+            destination: RefundDestination::OriginalTender,
+        }
+    }
+
+    #[test]
+    fn test_legacy_reason_text_maps_to_other() {
+        let legacy: RefundRequest = serde_json::from_str(
+            r#"{ "original_transaction_id": "TX", "items_to_refund": [["A", 1.0]], "reason": "screen cracked" }"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.reason, RefundReason::Other);
+        assert_eq!(legacy.note.as_deref(), Some("screen cracked"));
+
+        let structured: RefundRequest = serde_json::from_str(
+            r#"{ "original_transaction_id": "TX", "items_to_refund": [], "reason": "WrongSize", "note": "too small" }"#,
+        )
+        .unwrap();
+        assert_eq!(structured.reason, RefundReason::WrongSize);
+        assert_eq!(structured.note.as_deref(), Some("too small"));
+
+        // Round trip keeps the category
+        let json = serde_json::to_string(&structured).unwrap();
+        assert_eq!(serde_json::from_str::<RefundRequest>(&json).unwrap().reason, RefundReason::WrongSize);
+    }
+
+    #[test]
+    fn test_refunds_counted_by_reason_in_period() {
+        let results = [
+            refund(RefundReason::Defective, 1, Money::new(100, 0)),
+            refund(RefundReason::Defective, 10, Money::new(50, 0)),
+            refund(RefundReason::WrongSize, 15, Money::new(30, 0)),
+            // Outside the period
+            refund(RefundReason::Defective, 31, Money::new(999, 0)),
+        ];
+        let from = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 3, 31, 0, 0, 0).unwrap();
+        let stats = refunds_by_reason(&results, from, to);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[&RefundReason::Defective].count, 2);
+        assert_eq!(stats[&RefundReason::Defective].amount, Money::new(150, 0));
+        assert_eq!(stats[&RefundReason::WrongSize].count, 1);
+        assert!(!stats.contains_key(&RefundReason::ChangedMind));
+    }
 }
@@ -31,6 +31,10 @@ impl Rule for SimpleRule {
     fn priority(&self) -> i32 {
         self.priority
     }
+
+    fn condition(&self) -> Option<&Condition> {
+        Some(&self.condition)
+    }
 }
 
 pub struct RuleBuilder {
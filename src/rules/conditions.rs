@@ -32,6 +32,18 @@ pub enum Condition {
         value: Money,
     },
 
+    /// වට්ටම් සඳහා සුදුසු අයිතම වල එකතුව (Ex: "spend Rs.5000 on eligible items")
+    DiscountableSubtotal {
+        op: Operator,
+        value: Money,
+    },
+
+    /// බදු අය කළ හැකි අයිතම වල එකතුව
+    TaxableSubtotal {
+        op: Operator,
+        value: Money,
+    },
+
     /// මුළු භාණ්ඩ ප්‍රමාණය (Total Item Quantity)
     TotalQuantity {
         op: Operator,
@@ -57,16 +69,12 @@ impl Condition {
     /// 🕵️ කොන්දේසිය පරීක්ෂා කරන්න (Evaluate)
     pub fn evaluate(&self, cart: &Cart) -> bool {
         match self {
-            Condition::Subtotal { op, value } => {
-                let subtotal = cart.subtotal();
-                match op {
-                    Operator::Gt => subtotal > *value,
-                    Operator::Lt => subtotal < *value,
-                    Operator::Eq => subtotal == *value,
-                    Operator::Gte => subtotal >= *value,
-                    Operator::Lte => subtotal <= *value,
-                    _ => false, // TODO: Implement other ops logic for Money
-                }
+            Condition::Subtotal { op, value } => compare_money(op, cart.subtotal(), *value),
+            Condition::DiscountableSubtotal { op, value } => {
+                compare_money(op, cart.discountable_subtotal(), *value)
+            }
+            Condition::TaxableSubtotal { op, value } => {
+                compare_money(op, cart.taxable_subtotal(), *value)
             }
             Condition::TotalQuantity { op, value } => {
                 let total_qty: f64 = cart.items.iter().map(|i| i.quantity).sum();
@@ -86,3 +94,33 @@ impl Condition {
         }
     }
 }
+
+fn compare_money(op: &Operator, subtotal: Money, value: Money) -> bool {
+    match op {
+        Operator::Gt => subtotal > value,
+        Operator::Lt => subtotal < value,
+        Operator::Eq => subtotal == value,
+        Operator::Gte => subtotal >= value,
+        Operator::Lte => subtotal <= value,
+        _ => false, // TODO: Implement other ops logic for Money
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_gift_card_does_not_unlock_eligible_threshold() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shoes", Money::new(3000, 0), 1.0));
+        cart.add_item(Item::new("Gift Card", Money::new(2500, 0), 1.0).with_metadata("discountable", "false"));
+
+        let threshold = Money::new(5000, 0);
+        // Gross Rs.5500 passes, eligible Rs.3000 does not
+        assert!(Condition::Subtotal { op: Operator::Gte, value: threshold }.evaluate(&cart));
+        assert!(!Condition::DiscountableSubtotal { op: Operator::Gte, value: threshold }.evaluate(&cart));
+        assert!(Condition::TaxableSubtotal { op: Operator::Gte, value: threshold }.evaluate(&cart));
+    }
+}
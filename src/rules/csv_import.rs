@@ -0,0 +1,354 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::rules::mixed_scenarios::{
+    BogoMode, DiscountRule, DiscountType, MixedScenarioEngine, ProductDiscountConfig, ProductTaxConfig,
+    TaxAppliesTo, TaxRate, TierLevel,
+};
+use serde::{Deserialize, Serialize};
+
+/// 📄 Raw CSV Row
+#[derive(Debug, Clone, Deserialize)]
+struct ProductCsvRow {
+    product_id: String,
+    #[serde(default)]
+    tax_rates: String,
+    #[serde(default)]
+    tax_exempt: String,
+    #[serde(default)]
+    tax_included: String,
+    #[serde(default)]
+    discount_type: String,
+    #[serde(default)]
+    discount_params: String,
+    #[serde(default)]
+    stackable: String,
+    #[serde(default)]
+    max_discount_percent: String,
+}
+
+/// ❌ Rejected Row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedRow {
+    pub line: usize,
+    pub product_id: Option<String>,
+    pub reason: String,
+}
+
+/// 📊 Import Report (ආයාත වාර්තාව)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: Vec<String>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl ImportReport {
+    pub fn imported_count(&self) -> usize {
+        self.imported.len()
+    }
+
+    pub fn rejected_count(&self) -> usize {
+        self.rejected.len()
+    }
+}
+
+/// ============================================================================
+/// 📥 CSV Product Import (CSV ආයාත කිරීම)
+/// ============================================================================
+/// Merchant onboarding සඳහා product tax/discount configs CSV එකකින් පූරණය කරයි.
+///
+/// Expected header:
+/// `product_id,tax_class,tax_rates,tax_exempt,tax_included,discount_type,discount_params,stackable,max_discount_percent`
+///
+/// - `tax_class`: informational only - a row's rates always apply to its own product
+/// - `tax_rates`: `;` වලින් වෙන් කළ `NAME:RATE[@JURISDICTION]` (Ex: `VAT:18;NBT:2@LK`)
+/// - `discount_type`: `fixed` (cents), `percentage`, `bogo` (`buy:get:free_percent`),
+///   `tiered` (`min-max:pct|min-:pct`) or empty for no discount
+///
+/// 📥 Product CSV Importer
+#[derive(Debug, Clone, Default)]
+pub struct ProductCsvImporter {
+    dry_run: bool,
+}
+
+impl ProductCsvImporter {
+    pub fn new() -> Self {
+        ProductCsvImporter { dry_run: false }
+    }
+
+    /// Validate only - do not touch the engine
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// 🚀 Import CSV content into the engine
+    /// Each row is validated on its own; a bad row is rejected without stopping the import.
+    pub fn import(&self, csv_data: &str, engine: &mut MixedScenarioEngine) -> EngineResult<ImportReport> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(csv_data.as_bytes());
+
+        let headers = reader.headers().map_err(|e| EngineError::Validation {
+            message: format!("Invalid CSV header: {}", e),
+        })?;
+        if !headers.iter().any(|h| h == "product_id") {
+            return Err(EngineError::Validation {
+                message: "CSV header must contain a product_id column".to_string(),
+            });
+        }
+
+        let mut report = ImportReport {
+            dry_run: self.dry_run,
+            ..Default::default()
+        };
+
+        for (index, record) in reader.deserialize::<ProductCsvRow>().enumerate() {
+            // Line 1 is the header
+            let line = index + 2;
+            let row = match record {
+                Ok(row) => row,
+                Err(e) => {
+                    report.rejected.push(RejectedRow {
+                        line,
+                        product_id: None,
+                        reason: format!("Malformed row: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            let parsed = Self::parse_row(&row, line).and_then(|(tax_config, discount_config)| {
+                // Out-of-range rates are row errors, caught in dry runs too
+                engine
+                    .validate_product_tax(&tax_config)
+                    .and_then(|_| match &discount_config {
+                        Some(config) => engine.validate_product_discount(config),
+                        None => Ok(()),
+                    })
+                    .map_err(|e| e.to_string())?;
+                Ok((tax_config, discount_config))
+            });
+            match parsed {
+                Ok((tax_config, discount_config)) => {
+                    if !self.dry_run {
+                        engine.add_product_tax(tax_config)?;
+                        if let Some(discount_config) = discount_config {
+                            engine.add_product_discount(discount_config)?;
+                        }
+                    }
+                    report.imported.push(row.product_id.clone());
+                }
+                Err(reason) => report.rejected.push(RejectedRow {
+                    line,
+                    product_id: Some(row.product_id.clone()).filter(|id| !id.is_empty()),
+                    reason,
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Convert a raw row into engine configs
+    fn parse_row(
+        row: &ProductCsvRow,
+        line: usize,
+    ) -> Result<(ProductTaxConfig, Option<ProductDiscountConfig>), String> {
+        if row.product_id.is_empty() {
+            return Err("product_id is required".to_string());
+        }
+
+        // Product configs only reach their own product, whatever the row's tax class
+        let applies_to = TaxAppliesTo::Product(row.product_id.clone());
+
+        let mut tax_rates = Vec::new();
+        for spec in row.tax_rates.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, rest) = spec
+                .split_once(':')
+                .ok_or_else(|| format!("Tax rate '{}' must be NAME:RATE", spec))?;
+            let (rate, jurisdiction) = match rest.split_once('@') {
+                Some((rate, jurisdiction)) => (rate, jurisdiction.to_string()),
+                None => (rest, "ALL".to_string()),
+            };
+            tax_rates.push(TaxRate::new(
+                name.trim(),
+                parse_percent(rate, "tax rate")?,
+                &jurisdiction,
+                applies_to.clone(),
+            ));
+        }
+
+        let tax_config = ProductTaxConfig {
+            product_id: row.product_id.clone(),
+            tax_rates,
+            tax_exempt: parse_bool(&row.tax_exempt, "tax_exempt")?,
+            tax_included_in_price: parse_bool(&row.tax_included, "tax_included")?,
+        };
+
+        let discount_type = match row.discount_type.to_lowercase().as_str() {
+            "" => None,
+            "fixed" => Some(DiscountType::FixedAmount(
+                row.discount_params
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|cents| *cents >= 0)
+                    .ok_or_else(|| format!("Invalid fixed discount '{}'", row.discount_params))?,
+            )),
+            "percentage" => Some(DiscountType::Percentage(parse_percent(
+                &row.discount_params,
+                "discount percentage",
+            )?)),
+            "bogo" => {
+                let parts: Vec<&str> = row.discount_params.split(':').collect();
+                if parts.len() != 3 && parts.len() != 4 {
+                    return Err(format!(
+                        "BOGO params '{}' must be buy:get:free_percent[:discrete|weighted]",
+                        row.discount_params
+                    ));
+                }
+                let mode = match parts.get(3).map(|m| m.trim().to_lowercase()).as_deref() {
+                    None | Some("discrete") => BogoMode::Discrete,
+                    Some("weighted") => BogoMode::Weighted,
+                    Some(other) => return Err(format!("Unknown BOGO mode '{}'", other)),
+                };
+                Some(DiscountType::BuyXGetY {
+                    buy: parse_quantity(parts[0], "buy")?,
+                    get: parse_quantity(parts[1], "get")?,
+                    free_percent: parse_percent(parts[2], "free_percent")?,
+                    mode,
+                })
+            }
+            "tiered" => {
+                let mut tiers = Vec::new();
+                for tier in row.discount_params.split('|').map(str::trim) {
+                    let (range, pct) = tier
+                        .split_once(':')
+                        .ok_or_else(|| format!("Tier '{}' must be min-max:pct", tier))?;
+                    let (min, max) = range
+                        .split_once('-')
+                        .ok_or_else(|| format!("Tier range '{}' must be min-max", range))?;
+                    tiers.push(TierLevel {
+                        min_qty: parse_quantity(min, "tier min")?,
+                        max_qty: if max.trim().is_empty() {
+                            None
+                        } else {
+                            Some(parse_quantity(max, "tier max")?)
+                        },
+                        discount_percent: parse_percent(pct, "tier percentage")?,
+                    });
+                }
+                Some(DiscountType::Tiered(tiers))
+            }
+            other => return Err(format!("Unknown discount_type '{}'", other)),
+        };
+
+        let stackable = parse_bool(&row.stackable, "stackable")?;
+        let max_discount_percent = if row.max_discount_percent.is_empty() {
+            None
+        } else {
+            Some(parse_percent(&row.max_discount_percent, "max_discount_percent")?)
+        };
+
+        let discount_config = discount_type.map(|discount_type| ProductDiscountConfig {
+            product_id: row.product_id.clone(),
+            discounts: vec![DiscountRule {
+                id: format!("CSV-{}-{}", row.product_id, line),
+                name: format!("{} ({})", row.discount_type, row.product_id),
+                discount_type,
+                priority: 0,
+                conditions: Vec::new(),
+                stackable,
+            }],
+            stackable,
+            max_discount_percent,
+        });
+
+        Ok((tax_config, discount_config))
+    }
+}
+
+fn parse_bool(value: &str, field: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "" | "false" | "no" | "0" => Ok(false),
+        "true" | "yes" | "1" => Ok(true),
+        other => Err(format!("Invalid {} value '{}'", field, other)),
+    }
+}
+
+fn parse_percent(value: &str, field: &str) -> Result<f64, String> {
+    let pct: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid {} '{}'", field, value))?;
+    if !pct.is_finite() || !(0.0..=100.0).contains(&pct) {
+        return Err(format!("{} {} must be between 0 and 100", field, pct));
+    }
+    Ok(pct)
+}
+
+fn parse_quantity(value: &str, field: &str) -> Result<f64, String> {
+    let qty: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid {} '{}'", field, value))?;
+    if !qty.is_finite() || qty < 0.0 {
+        return Err(format!("{} {} must be a non-negative number", field, qty));
+    }
+    Ok(qty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const CSV: &str = "\
+product_id,tax_class,tax_rates,tax_exempt,tax_included,discount_type,discount_params,stackable,max_discount_percent
+SKU-1,STANDARD,VAT:18,false,false,percentage,10,false,
+SKU-2,STANDARD,VAT:abc,false,false,percentage,10,false,
+";
+
+    fn item(id: &str) -> Item {
+        let mut item = Item::new(id, Money::new(100, 0), 1.0);
+        item.id = id.to_string();
+        item
+    }
+
+    #[test]
+    fn test_import_valid_and_malformed_rows() {
+        let mut engine = MixedScenarioEngine::new();
+        let report = ProductCsvImporter::new().import(CSV, &mut engine).unwrap();
+
+        assert_eq!(report.imported, vec!["SKU-1".to_string()]);
+        assert_eq!(report.rejected_count(), 1);
+        assert_eq!(report.rejected[0].line, 3);
+        assert_eq!(report.rejected[0].product_id.as_deref(), Some("SKU-2"));
+        assert!(report.rejected[0].reason.contains("tax rate"));
+
+        // Rs.100 - 10% = Rs.90, VAT 18% = Rs.16.20
+        let sku = item("SKU-1");
+        let result = engine.calculate_item(&sku, std::slice::from_ref(&sku), &[], None).unwrap();
+        assert_eq!(result.discount_amount.amount, 1000);
+        assert_eq!(result.tax_amount.amount, 1620);
+        let config = engine.snapshot().product_taxes.remove("SKU-1").unwrap();
+        assert_eq!(config.tax_rates[0].applies_to, TaxAppliesTo::Product("SKU-1".to_string()));
+    }
+
+    #[test]
+    fn test_dry_run_leaves_engine_untouched() {
+        let mut engine = MixedScenarioEngine::new();
+        let report = ProductCsvImporter::new()
+            .dry_run(true)
+            .import(CSV, &mut engine)
+            .unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.imported_count(), 1);
+
+        let sku = item("SKU-1");
+        let result = engine.calculate_item(&sku, std::slice::from_ref(&sku), &[], None).unwrap();
+        assert!(result.discount_amount.is_zero());
+        assert!(result.tax_amount.is_zero());
+    }
+}
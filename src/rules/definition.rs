@@ -0,0 +1,299 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::rules::conditions::Condition;
+use crate::rules::traits::{DiscountTarget, DiscountValue, Rule, RuleAction};
+use crate::types::cart::Cart;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 📜 Rule Definitions (JSON රීති නිර්වචන)
+/// ============================================================================
+/// Merchants ලාට redeploy නොකර JSON මගින් promotions නිර්වචනය කළ හැක.
+/// Condition tree + action specs => load වන විට `Rule` එකක් බවට compile වේ.
+/// Ex: { "name": "Big Spender", "condition": { "Subtotal": { "op": "Gt", "value": { "amount": 500000 } } },
+///       "actions": [ { "PercentageDiscount": { "percent": 10.0 } } ] }
+///
+/// 🎬 Action Spec
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ActionSpec {
+    /// % off the discountable subtotal (or the shipping fee, per `target`)
+    PercentageDiscount {
+        percent: f64,
+        #[serde(default)]
+        target: DiscountTarget,
+    },
+    FixedDiscount {
+        amount: Money,
+        #[serde(default)]
+        target: DiscountTarget,
+    },
+    /// % of the taxable subtotal
+    PercentageTax { rate: f64 },
+    FixedTax { amount: Money },
+    /// `taxable: true` => included in the base of percentage taxes
+    Fee {
+        amount: Money,
+        #[serde(default)]
+        taxable: bool,
+    },
+    FreeItem { item_id: String, qty: f64 },
+    PriceOverride { item_id: String, price: Money },
+}
+
+impl ActionSpec {
+    fn validate(&self) -> EngineResult<()> {
+        let invalid = match self {
+            ActionSpec::PercentageDiscount { percent, .. } => !(0.0..=100.0).contains(percent),
+            ActionSpec::PercentageTax { rate } => !(0.0..=100.0).contains(rate),
+            ActionSpec::FixedDiscount { amount, .. }
+            | ActionSpec::FixedTax { amount }
+            | ActionSpec::Fee { amount, .. } => amount.is_negative(),
+            ActionSpec::FreeItem { qty, .. } => *qty <= 0.0,
+            ActionSpec::PriceOverride { price, .. } => price.is_negative(),
+        };
+        if invalid {
+            return Err(EngineError::Validation {
+                message: format!("Invalid rule action: {:?}", self),
+            });
+        }
+        Ok(())
+    }
+
+    fn to_action(&self) -> RuleAction {
+        match self {
+            // Percentages are resolved (and rounded) by the engine
+            ActionSpec::PercentageDiscount { percent, target } => RuleAction::TargetedDiscount {
+                value: DiscountValue::Percentage(*percent),
+                target: *target,
+            },
+            ActionSpec::FixedDiscount {
+                amount,
+                target: DiscountTarget::Goods,
+            } => RuleAction::Discount(*amount),
+            ActionSpec::FixedDiscount { amount, target } => RuleAction::TargetedDiscount {
+                value: DiscountValue::Fixed(*amount),
+                target: *target,
+            },
+            ActionSpec::PercentageTax { rate } => RuleAction::PercentageTax(*rate),
+            ActionSpec::FixedTax { amount } => RuleAction::Tax(*amount),
+            ActionSpec::Fee { amount, taxable: false } => RuleAction::Fee(*amount),
+            ActionSpec::Fee { amount, taxable: true } => RuleAction::TaxableFee(*amount),
+            ActionSpec::FreeItem { item_id, qty } => RuleAction::FreeItem {
+                item_id: item_id.clone(),
+                qty: *qty,
+            },
+            ActionSpec::PriceOverride { item_id, price } => RuleAction::PriceOverride {
+                item_id: item_id.clone(),
+                price: *price,
+            },
+        }
+    }
+}
+
+/// 📜 Rule Definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    /// false => lower-priority rules are skipped once this one applies
+    #[serde(default = "stackable")]
+    pub stackable: bool,
+    /// Omitted => always applies
+    #[serde(default = "always")]
+    pub condition: Condition,
+    pub actions: Vec<ActionSpec>,
+}
+
+fn always() -> Condition {
+    Condition::Always
+}
+
+fn stackable() -> bool {
+    true
+}
+
+impl RuleDefinition {
+    pub fn from_json(json: &str) -> EngineResult<Self> {
+        serde_json::from_str(json).map_err(|e| EngineError::Validation {
+            message: format!("Invalid rule definition: {}", e),
+        })
+    }
+
+    /// 🔨 Validate and compile into an executable rule
+    pub fn compile(self) -> EngineResult<Box<dyn Rule + Send + Sync>> {
+        if self.name.trim().is_empty() {
+            return Err(EngineError::Validation {
+                message: "Rule name is required".to_string(),
+            });
+        }
+        if self.actions.is_empty() {
+            return Err(EngineError::Validation {
+                message: format!("Rule '{}' has no actions", self.name),
+            });
+        }
+        for action in &self.actions {
+            action.validate()?;
+        }
+        Ok(Box::new(DefinedRule { definition: self }))
+    }
+}
+
+/// 📥 Load a JSON array of rule definitions
+pub fn load_rules(json: &str) -> EngineResult<Vec<Box<dyn Rule + Send + Sync>>> {
+    let definitions: Vec<RuleDefinition> =
+        serde_json::from_str(json).map_err(|e| EngineError::Validation {
+            message: format!("Invalid rule definitions: {}", e),
+        })?;
+    definitions.into_iter().map(RuleDefinition::compile).collect()
+}
+
+/// ⚙️ Compiled rule
+struct DefinedRule {
+    definition: RuleDefinition,
+}
+
+impl Rule for DefinedRule {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn can_apply(&self, cart: &Cart) -> bool {
+        self.definition.condition.evaluate(cart)
+    }
+
+    fn apply(&self, _cart: &Cart) -> EngineResult<Vec<RuleAction>> {
+        Ok(self.definition.actions.iter().map(ActionSpec::to_action).collect())
+    }
+
+    fn priority(&self) -> i32 {
+        self.definition.priority
+    }
+
+    fn stackable(&self) -> bool {
+        self.definition.stackable
+    }
+
+    fn condition(&self) -> Option<&Condition> {
+        Some(&self.definition.condition)
+    }
+
+    fn price_override_targets(&self) -> Vec<String> {
+        self.definition
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                ActionSpec::PriceOverride { item_id, .. } => Some(item_id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const BIG_SPENDER: &str = r#"{
+        "name": "Big Spender",
+        "priority": 10,
+        "condition": { "Subtotal": { "op": "Gt", "value": { "amount": 500000 } } },
+        "actions": [ { "PercentageDiscount": { "percent": 10.0 } } ]
+    }"#;
+
+    #[test]
+    fn test_json_rule_subtotal_over_5000_gets_10_percent() {
+        let rule = RuleDefinition::from_json(BIG_SPENDER).unwrap().compile().unwrap();
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Jacket", Money::new(6000, 0), 1.0));
+        let result = CalculationEngine::new().calculate(&cart, &[rule]).unwrap();
+
+        assert_eq!(result.discount_total, Money::new(600, 0));
+        assert_eq!(result.grand_total, Money::new(5400, 0));
+
+        let rules = load_rules(&format!("[{}]", BIG_SPENDER)).unwrap();
+        let mut small = Cart::new();
+        small.add_item(Item::new("Socks", Money::new(400, 0), 1.0));
+        assert!(!rules[0].can_apply(&small));
+    }
+
+    #[test]
+    fn test_taxable_delivery_fee_increases_vat() {
+        let rules_with_fee = |taxable: bool| {
+            load_rules(&format!(
+                r#"[
+                    {{ "name": "VAT", "actions": [ {{ "PercentageTax": {{ "rate": 18.0 }} }} ] }},
+                    {{ "name": "Delivery", "actions": [ {{ "Fee": {{ "amount": {{ "amount": 50000 }}, "taxable": {} }} }} ] }}
+                ]"#,
+                taxable
+            ))
+            .unwrap()
+        };
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Lamp", Money::new(1000, 0), 1.0));
+
+        // VAT 18% of (1,000 + 500 delivery)
+        let taxable = CalculationEngine::new().calculate(&cart, &rules_with_fee(true)).unwrap();
+        assert_eq!(taxable.tax_total, Money::new(270, 0));
+        assert_eq!(taxable.taxable_fees, Money::new(500, 0));
+        assert!(taxable.non_taxable_fees.is_zero());
+        assert_eq!(taxable.grand_total, Money::new(1770, 0));
+
+        // VAT on the goods only
+        let exempt = CalculationEngine::new().calculate(&cart, &rules_with_fee(false)).unwrap();
+        assert_eq!(exempt.tax_total, Money::new(180, 0));
+        assert!(exempt.taxable_fees.is_zero());
+        assert_eq!(exempt.non_taxable_fees, Money::new(500, 0));
+        assert_eq!(exempt.grand_total, Money::new(1680, 0));
+
+        // The fee only widens the tax base: rules still see the goods-only cart (Rs.1,000)
+        let with_levy = load_rules(
+            r#"[
+                { "name": "VAT", "actions": [ { "PercentageTax": { "rate": 18.0 } } ] },
+                { "name": "Delivery", "actions": [ { "Fee": { "amount": { "amount": 50000 }, "taxable": true } } ] },
+                { "name": "Big basket levy",
+                  "condition": { "Subtotal": { "op": "Gt", "value": { "amount": 120000 } } },
+                  "actions": [ { "FixedTax": { "amount": { "amount": 10000 } } } ] }
+            ]"#,
+        )
+        .unwrap();
+        let result = CalculationEngine::new().calculate(&cart, &with_levy).unwrap();
+        assert_eq!(result.tax_total, Money::new(270, 0));
+        assert_eq!(result.grand_total, Money::new(1770, 0));
+    }
+
+    #[test]
+    fn test_engine_rounding_applies_to_rule_percentages() {
+        use crate::core::rounding::RoundingMode;
+
+        let rules = load_rules(
+            r#"[
+                { "name": "Levy", "actions": [ { "PercentageTax": { "rate": 7.5 } } ] },
+                { "name": "Promo", "actions": [ { "PercentageDiscount": { "percent": 7.5 } } ] }
+            ]"#,
+        )
+        .unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Stamp", Money::new(3, 0), 1.0));
+
+        // 7.5% of Rs.3 = 22.5 cents
+        let half_up = CalculationEngine::new().calculate(&cart, &rules).unwrap();
+        assert_eq!(half_up.tax_total, Money::from_cents(23));
+        assert_eq!(half_up.discount_total, Money::from_cents(23));
+        let half_even = CalculationEngine::new()
+            .with_rounding(RoundingMode::Bankers)
+            .calculate(&cart, &rules)
+            .unwrap();
+        assert_eq!(half_even.tax_total, Money::from_cents(22));
+        assert_eq!(half_even.discount_total, Money::from_cents(22));
+    }
+
+    #[test]
+    fn test_invalid_definition_rejected() {
+        let json = r#"{ "name": "Bad", "actions": [ { "PercentageDiscount": { "percent": 150.0 } } ] }"#;
+        assert!(RuleDefinition::from_json(json).unwrap().compile().is_err());
+        assert!(RuleDefinition::from_json(r#"{ "name": "x" }"#).is_err());
+    }
+}
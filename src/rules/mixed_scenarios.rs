@@ -1,9 +1,21 @@
-use crate::core::errors::EngineResult;
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::formula::{BucketAmounts, TotalFormula};
+use crate::core::metrics;
 use crate::core::money::Money;
-use crate::types::cart::Cart;
+use crate::core::rounding::RoundingMode;
+use crate::core::timezone::StoreTimeZone;
+use crate::core::clock::{Clock, SystemClock};
+use crate::discount::promo::{self, PromoCodeManager, PromoRedemption};
+use crate::tax::jurisdiction::{self, JurisdictionResolver, TaxAddress};
+use crate::types::cart::{Cart, CartLimits};
+use crate::types::currency::{Currency, CurrencyRegistry};
+use crate::types::customer::CustomerContext;
 use crate::types::item::Item;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::ops::{Div, Mul};
+use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::sync::Arc;
 
 /// ============================================================================
 /// 🎯 Advanced Mixed Discount/Tax Engine (උසස් මිශ්‍ර වට්ටම්/බදු එන්ජිම)
@@ -27,9 +39,172 @@ pub struct ProductTaxConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaxRate {
     pub name: String,
-    pub rate: f64,            // Percentage
+    pub rate: f64,            // Percentage (ignored for PerUnit basis)
     pub jurisdiction: String, // Country/State
     pub applies_to: TaxAppliesTo,
+    #[serde(default)]
+    pub basis: TaxBasis,
+    /// Percentage tax charged on (amount + per-unit excise), Ex: VAT on excise
+    #[serde(default)]
+    pub include_excise_in_base: bool,
+    /// Floor for a charged tax
+    #[serde(default)]
+    pub min_tax: Option<Money>,
+    /// Ceiling for a charged tax
+    #[serde(default)]
+    pub max_tax: Option<Money>,
+    /// No tax unless the taxable amount is above this value
+    #[serde(default)]
+    pub applies_above: Option<Money>,
+    /// Gross or net-of-discount base for this tax only; None => engine `CalculationOrder`
+    #[serde(default)]
+    pub discount_base: Option<TaxBase>,
+    /// Tax on tax: charged on the base plus every percentage tax of a lower `sequence`
+    /// (Ex: Quebec QST on price + GST before 2013)
+    #[serde(default)]
+    pub compound: bool,
+    /// Application order of percentage taxes (lowest first; equal keeps configured order)
+    #[serde(default)]
+    pub sequence: u32,
+    /// Reverse charge: a business buyer with a valid VAT ID self-accounts for this tax
+    #[serde(default)]
+    pub treatment: TaxTreatment,
+}
+
+impl TaxRate {
+    /// Percentage tax
+    pub fn new(name: &str, rate: f64, jurisdiction: &str, applies_to: TaxAppliesTo) -> Self {
+        TaxRate {
+            name: name.to_string(),
+            rate,
+            jurisdiction: jurisdiction.to_string(),
+            applies_to,
+            basis: TaxBasis::Percentage,
+            include_excise_in_base: false,
+            min_tax: None,
+            max_tax: None,
+            applies_above: None,
+            discount_base: None,
+            compound: false,
+            sequence: 0,
+            treatment: TaxTreatment::Standard,
+        }
+    }
+
+    /// Fixed amount per unit (excise per liter/stick)
+    pub fn per_unit(name: &str, amount: Money, jurisdiction: &str, applies_to: TaxAppliesTo) -> Self {
+        TaxRate {
+            basis: TaxBasis::PerUnit(amount),
+            ..Self::new(name, 0.0, jurisdiction, applies_to)
+        }
+    }
+
+    pub fn with_excise_in_base(mut self) -> Self {
+        self.include_excise_in_base = true;
+        self
+    }
+
+    /// Clamp the charged tax between `min` and `max`
+    pub fn with_limits(mut self, min: Option<Money>, max: Option<Money>) -> Self {
+        self.min_tax = min;
+        self.max_tax = max;
+        self
+    }
+
+    pub fn applies_above(mut self, threshold: Money) -> Self {
+        self.applies_above = Some(threshold);
+        self
+    }
+
+    /// Override the engine-wide calculation order for this tax
+    /// Ex: VAT on the discounted amount, a gross receipts levy on the pre-discount amount
+    pub fn on_base(mut self, base: TaxBase) -> Self {
+        self.discount_base = Some(base);
+        self
+    }
+
+    /// Charge on the base plus the taxes applied before it (`sequence`)
+    pub fn compounded(mut self, sequence: u32) -> Self {
+        self.compound = true;
+        self.sequence = sequence;
+        self
+    }
+
+    pub fn with_sequence(mut self, sequence: u32) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Cross-border B2B (EU/UK): nothing charged to a VAT-registered buyer
+    pub fn reverse_charged(mut self) -> Self {
+        self.treatment = TaxTreatment::ReverseCharge;
+        self
+    }
+
+    /// Percent of the net amount each rate charges once compounding is applied
+    /// (`rates` in sequence order). Ex: 5% then 9.5% compound => [5.0, 9.975]
+    fn effective_rates(rates: &[&TaxRate]) -> Vec<f64> {
+        let mut effective: Vec<f64> = Vec::with_capacity(rates.len());
+        for (i, tax) in rates.iter().enumerate() {
+            let prior: f64 = if tax.compound {
+                rates[..i]
+                    .iter()
+                    .zip(&effective)
+                    .filter(|(earlier, _)| earlier.sequence < tax.sequence)
+                    .map(|(_, rate)| rate)
+                    .sum()
+            } else {
+                0.0
+            };
+            effective.push(tax.rate * (1.0 + prior / 100.0));
+        }
+        effective
+    }
+
+    /// Apply threshold and min/max to a raw tax computed on `base`
+    /// The floor only applies to a positive base (a free or fully discounted line owes nothing).
+    fn bounded(&self, base: Money, raw_tax: Money) -> Money {
+        if matches!(self.applies_above, Some(threshold) if base <= threshold) {
+            return Money::zero();
+        }
+        let mut tax = raw_tax;
+        if let Some(min) = self.min_tax.filter(|_| base.is_positive()) {
+            tax = tax.max(min);
+        }
+        if let Some(max) = self.max_tax {
+            tax = tax.min(max);
+        }
+        tax
+    }
+}
+
+/// 📐 Tax Basis (බදු පදනම)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TaxBasis {
+    #[default]
+    Percentage,
+    /// Amount × quantity
+    PerUnit(Money),
+}
+
+/// 🧾 Which amount a tax is charged on, relative to discounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaxBase {
+    /// Gross amount before discounts
+    PreDiscount,
+    /// Net amount after discounts
+    PostDiscount,
+}
+
+/// 🔁 How a tax is accounted for (ගිණුම්කරණ ක්‍රමය)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum TaxTreatment {
+    /// Charged to the buyer
+    #[default]
+    Standard,
+    /// Charged at 0 and reported on both sides; the buyer accounts for the tax
+    /// (falls back to `Standard` when the buyer has no valid VAT ID)
+    ReverseCharge,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -59,6 +234,25 @@ pub struct DiscountRule {
     pub stackable: bool,
 }
 
+impl DiscountRule {
+    /// ⚖️ Order rules are tried in: higher priority first, then rule id ascending
+    /// (byte-wise). The id tie-break makes equal-priority rules - and equally good auto-best
+    /// picks - resolve the same way whatever order they were configured in, so the same
+    /// inputs always consume the same coupon.
+    pub fn precedence(&self, other: &DiscountRule) -> std::cmp::Ordering {
+        other.priority.cmp(&self.priority).then_with(|| self.id.cmp(&other.id))
+    }
+
+    /// Quantity-threshold rules (BOGO, tiers, `MinQuantity`) - not applied to return lines
+    fn depends_on_quantity(&self) -> bool {
+        matches!(self.discount_type, DiscountType::BuyXGetY { .. } | DiscountType::Tiered(_))
+            || self
+                .conditions
+                .iter()
+                .any(|c| matches!(c, DiscountCondition::MinQuantity(_)))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DiscountType {
     FixedAmount(i64), // Cents
@@ -67,14 +261,28 @@ pub enum DiscountType {
         buy: f64,
         get: f64,
         free_percent: f64,
+        #[serde(default)]
+        mode: BogoMode,
     },
     Tiered(Vec<TierLevel>),
+    /// Percentage off every complete set of `items` in the cart (plus the product carrying
+    /// the rule, if not listed), applied by `calculate_cart` like a `CartBundle`
     Bundle {
         items: Vec<String>,
         discount_percent: f64,
     },
 }
 
+/// 🎁 How `BuyXGetY` counts free quantity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BogoMode {
+    /// Whole sets only: floor(qty / (buy + get)) * get (Ex: buy 2 get 1 shirts)
+    #[default]
+    Discrete,
+    /// Proportional for weighted goods: qty * get / (buy + get) (Ex: buy 2kg get 0.5kg)
+    Weighted,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TierLevel {
     pub min_qty: f64,
@@ -82,34 +290,387 @@ pub struct TierLevel {
     pub discount_percent: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DiscountCondition {
     MinQuantity(f64),
     MinAmount(i64),
+    /// Exact match on the cart's `customer_group` or one of its `CustomerContext` groups (Ex: "staff")
     CustomerGroup(String),
+    /// Any of these customer groups
+    AnyOf(Vec<String>),
+    /// Store-local window: "2024-01-22" (whole day), "2024-01-22T18:00", or RFC 3339 with
+    /// an offset ("2024-01-22T18:00:00+05:30"); validated when the rule is added
     DateRange { from: String, to: String },
+    /// Customer with no earlier purchases (needs a `CustomerContext`)
     FirstPurchase,
     PromoCode(String),
     CartContains(String),
 }
 
+/// Tolerance for fractional quantity comparisons (weighted goods)
+const QTY_EPSILON: f64 = 1e-9;
+
 /// 🧮 Mixed Scenario Calculator (මිශ්‍ර ගණනය කරන්නා)
+#[derive(Clone)]
 pub struct MixedScenarioEngine {
     product_taxes: std::collections::HashMap<String, ProductTaxConfig>,
     product_discounts: std::collections::HashMap<String, ProductDiscountConfig>,
     global_tax_rates: Vec<TaxRate>,
     calculation_order: CalculationOrder,
+    total_formula: TotalFormula,
+    cap_strategy: CapStrategy,
+    rounding_mode: RoundingMode,
+    tax_rounding: Option<RoundingMode>,
+    cart_limits: CartLimits,
+    currencies: CurrencyRegistry,
+    auto_best: bool,
+    stacking_policy: StackingPolicy,
+    jurisdictions: JurisdictionResolver,
+    store_timezone: StoreTimeZone,
+    pricing_time: Option<DateTime<Utc>>,
+    clock: Arc<dyn Clock>,
+    promo_manager: Option<Arc<PromoCodeManager>>,
+    cart_bundles: Vec<CartBundle>,
+    small_order_tax: Option<SmallOrderTax>,
+    residue_target: ResidueTarget,
+    price_lists: Vec<PriceList>,
+    kits: std::collections::HashMap<String, Kit>,
+    cart_discounts: Vec<CartDiscountConfig>,
+    max_rate_percent: f64,
+}
+
+/// Highest tax or discount percentage accepted at config time unless raised
+/// (`set_max_rate_percent`, Ex: for excise duties above 100%)
+pub const DEFAULT_MAX_RATE_PERCENT: f64 = 100.0;
+
+/// 🎯 Where a cart rounding residue goes (වට කිරීමේ ඉතිරිය)
+/// A line whose total was rounded on its own (Ex: priced gross per unit) can differ from
+/// its `base - discount + tax` by a cent; summed over many lines that residue would break
+/// `grand_total == subtotal - total_discount + total_tax`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ResidueTarget {
+    /// Reported as `CartCalculation::rounding_adjustment`, a bucket of its own
+    #[default]
+    Adjustment,
+    /// Absorbed by the base amount of the largest line (tax stays as charged)
+    LargestLine,
+}
+
+/// Move the lines' rounding residue to `target`; None if the lines already reconcile,
+/// else the amount left for the cart's rounding adjustment
+fn absorb_rounding_residue(lines: &mut [ItemCalculation], target: ResidueTarget) -> Option<Money> {
+    let residue = lines.iter().fold(Money::zero(), |acc, l| {
+        acc + l.total - (l.base_amount - l.discount_amount + l.tax_amount)
+    });
+    if residue.is_zero() {
+        return None;
+    }
+    let largest = lines
+        .iter()
+        .enumerate()
+        .max_by_key(|(index, line)| (line.total.abs(), std::cmp::Reverse(*index)))
+        .map(|(index, _)| index);
+    match (target, largest) {
+        (ResidueTarget::LargestLine, Some(index)) => {
+            lines[index].base_amount = lines[index].base_amount + residue;
+            Some(Money::zero())
+        }
+        _ => Some(residue),
+    }
+}
+
+/// 📒 Customer price list (B2B ගිවිසුම් මිල)
+/// Negotiated unit prices that replace the list price (`Item.price`) before any discount
+/// or tax is worked out, so the contract price is the base for all later math.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceList {
+    pub id: String,
+    pub name: String,
+    pub scope: PriceListScope,
+    /// Item id => contract unit price
+    pub prices: BTreeMap<String, Money>,
+    /// Contract-priced lines get no product (promotional) discounts
+    #[serde(default)]
+    pub skip_promotions: bool,
+}
+
+/// Who a price list is for; a customer's own list wins over their group's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceListScope {
+    Customer(String),
+    Group(String),
+}
+
+/// 🧰 Composite product (kit / bill of materials)
+/// Sold as one line but priced and taxed per component: calculating the kit expands it into
+/// its components (each with its own price, product taxes and discounts) and sums them back
+/// into the kit line. The kit item's own price is not used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kit {
+    /// Item id of the kit SKU
+    pub id: String,
+    pub name: String,
+    pub components: Vec<KitComponent>,
+}
+
+/// One component SKU of a kit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KitComponent {
+    /// Component item id (its `ProductTaxConfig` / `ProductDiscountConfig` key)
+    pub product_id: String,
+    pub name: String,
+    pub unit_price: Money,
+    /// Units per kit
+    pub quantity: f64,
+    /// Unit cost (gross margin); the kit's cost is known only if every component's is
+    #[serde(default)]
+    pub unit_cost: Option<Money>,
+}
+
+/// 📦 Cart-level bundle: a percentage off the combined price of all members
+/// The discount is rounded once on the bundle and allocated back to member lines
+/// (with the tax it removes) so per-line figures reconcile to the bundle totals.
+/// A member listed twice needs two units per set; a cart holding several complete sets
+/// gets the discount on each, and units beyond the last complete set pay full price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CartBundle {
+    pub id: String,
+    pub name: String,
+    /// Member item ids (or names), one entry per unit in a set
+    pub items: Vec<String>,
+    pub discount_percent: f64,
+}
+
+/// 🧾 Cart-level (full-bill) discount (සම්පූර්ණ බිල්පතට වට්ටම්)
+/// Evaluated on the cart's net value (after line discounts and bundles, before tax) and
+/// allocated back to the sale lines in proportion to their net value, so every line -
+/// and a later partial refund of it - carries its share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CartDiscountConfig {
+    pub id: String,
+    pub name: String,
+    pub discount_type: CartDiscountType,
+    /// Checked against the cart's total quantity and net value (Ex: `PromoCode`)
+    #[serde(default)]
+    pub conditions: Vec<DiscountCondition>,
+    pub priority: i32,
+    /// false => no lower-priority cart discount applies after this one
+    pub stackable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CartDiscountType {
+    Percentage(f64),
+    FixedAmount(Money),
+    /// Highest tier whose `min` (cents) the cart net reaches
+    TieredByTotal(Vec<CartTier>),
+    /// Highest tier whose `min` the cart's total quantity reaches
+    TieredByQuantity(Vec<CartTier>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CartTier {
+    pub min: f64,
+    pub discount_percent: f64,
+}
+
+/// 🪶 Small-order tax (කුඩා ඇණවුම් බදු)
+/// Orders whose value (subtotal - discounts, before tax) is below `threshold` are
+/// exempt, or pay one simplified flat rate instead of the configured taxes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmallOrderTax {
+    pub threshold: Money,
+    pub treatment: SmallOrderTreatment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SmallOrderTreatment {
+    /// No tax at all (Ex: no VAT below Rs.100)
+    Exempt,
+    /// Flat percentage replacing every configured tax
+    FlatRate(f64),
+}
+
+/// 🧢 How the per-product max-discount cap shrinks stacked discounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CapStrategy {
+    /// Scale every discount line down so the lines sum to the cap
+    #[default]
+    ProRate,
+    /// Remove lowest-priority discounts first (the last one is trimmed if needed)
+    DropLowestPriority,
+}
+
+/// 🧱 Stacking Policy (වට්ටම් එකට යෙදීමේ ප්‍රතිපත්තිය)
+/// Rules-level `stackable` flag එකට අමතරව: exclusive groups, stacking limits සහ
+/// category exclusions. Groups and limits apply within a level - item rules on a line
+/// among themselves, cart discounts on the cart among themselves.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StackingPolicy {
+    /// Group name => rule ids of which at most one applies
+    #[serde(default)]
+    pub exclusive_groups: BTreeMap<String, Vec<String>>,
+    /// Most item rules applied to one line
+    #[serde(default)]
+    pub max_per_line: Option<usize>,
+    /// Most cart-level discounts applied to one cart (Ex: one cart coupon)
+    #[serde(default)]
+    pub max_cart_discounts: Option<usize>,
+    /// Rules that always stack (Ex: free shipping): not limited, grouped or blocked by
+    /// non-stackable rules, and not counted against the limits
+    #[serde(default)]
+    pub always_stack: Vec<String>,
+    /// Item category (`metadata["category"]`) => rule ids that never apply to it
+    #[serde(default)]
+    pub category_exclusions: BTreeMap<String, Vec<String>>,
+}
+
+impl StackingPolicy {
+    fn always_stacks(&self, rule_id: &str) -> bool {
+        self.always_stack.iter().any(|id| id == rule_id)
+    }
+
+    fn group_of(&self, rule_id: &str) -> Option<&str> {
+        self.exclusive_groups
+            .iter()
+            .find(|(_, rules)| rules.iter().any(|id| id == rule_id))
+            .map(|(group, _)| group.as_str())
+    }
+
+    /// Category of `item` that excludes the rule, if any
+    fn excluded_category(&self, rule_id: &str, item: &Item) -> Option<String> {
+        let category = item.category()?;
+        self.category_exclusions
+            .get(category)
+            .filter(|rules| rules.iter().any(|id| id == rule_id))
+            .map(|_| category.to_string())
+    }
+}
+
+/// 🚫 A qualifying discount left out by stacking rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuppressedDiscount {
+    pub rule_id: String,
+    pub reason: SuppressionReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SuppressionReason {
+    /// Non-stackable and another non-stackable rule already applied
+    NotStackable { applied: String },
+    /// Auto-best chose a larger exclusive discount
+    NotBest { chosen: String },
+    /// Another rule of the same exclusive group already applied
+    ExclusiveGroup { group: String, applied: String },
+    /// The stacking limit was reached
+    MaxStacked { limit: usize },
+    /// The item's category excludes this rule
+    CategoryExcluded { category: String },
+}
+
+impl std::fmt::Display for SuppressionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuppressionReason::NotStackable { applied } => write!(f, "does not stack with {}", applied),
+            SuppressionReason::NotBest { chosen } => write!(f, "{} gives a larger discount", chosen),
+            SuppressionReason::ExclusiveGroup { group, applied } => {
+                write!(f, "{} already applied from exclusive group {}", applied, group)
+            }
+            SuppressionReason::MaxStacked { limit } => write!(f, "at most {} discount(s) stack", limit),
+            SuppressionReason::CategoryExcluded { category } => write!(f, "not valid on {} items", category),
+        }
+    }
+}
+
+/// Which rules have applied at one level, for stacking decisions (internal)
+#[derive(Default)]
+struct StackState<'a> {
+    non_stackable: Option<&'a str>,
+    groups: Vec<(&'a str, &'a str)>,
+    count: usize,
+}
+
+impl<'a> StackState<'a> {
+    /// Why `rule_id` may not join what has applied so far (category and auto-best aside)
+    fn blocked(&self, policy: &StackingPolicy, rule_id: &str, stackable: bool, limit: Option<usize>) -> Option<SuppressionReason> {
+        if policy.always_stacks(rule_id) {
+            return None;
+        }
+        if let (false, Some(applied)) = (stackable, self.non_stackable) {
+            return Some(SuppressionReason::NotStackable { applied: applied.to_string() });
+        }
+        if let Some(group) = policy.group_of(rule_id) {
+            if let Some((_, applied)) = self.groups.iter().find(|(g, _)| *g == group) {
+                return Some(SuppressionReason::ExclusiveGroup {
+                    group: group.to_string(),
+                    applied: applied.to_string(),
+                });
+            }
+        }
+        limit.filter(|max| self.count >= *max).map(|limit| SuppressionReason::MaxStacked { limit })
+    }
+
+    fn record(&mut self, policy: &'a StackingPolicy, rule_id: &'a str, stackable: bool) {
+        if policy.always_stacks(rule_id) {
+            return;
+        }
+        if !stackable && self.non_stackable.is_none() {
+            self.non_stackable = Some(rule_id);
+        }
+        if let Some(group) = policy.group_of(rule_id) {
+            self.groups.push((group, rule_id));
+        }
+        self.count += 1;
+    }
+}
+
+/// ✂️ A discount line changed by the cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapAdjustment {
+    pub rule_id: String,
+    pub name: String,
+    pub original_amount: Money,
+    pub applied_amount: Money,
+    pub removed: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CalculationOrder {
     /// Discount first, then tax on discounted amount
     DiscountFirst,
-    /// Tax first, then discount on taxed amount  
+    /// Tax first, then discount on taxed amount (percentage discounts include the tax;
+    /// a per-tax `PostDiscount` base has no discount to subtract yet). The line reports the tax
+    /// left after the discount's tax share and only the net part as its discount
     TaxFirst,
     /// Tax on original, discount on original (independent)
     Parallel,
 }
+// Invariants (see `order_invariant_tests`):
+// - zero tax or zero discount => all orders give the same total
+// - DiscountFirst <= Parallel and TaxFirst <= Parallel
+// - percentage discount & tax => DiscountFirst == TaxFirst (± 2 cents of rounding); fixed discount => DiscountFirst <= TaxFirst
+
+impl CalculationOrder {
+    /// "DiscountFirst", "tax_first", "parallel" ...
+    pub fn parse(value: &str) -> EngineResult<Self> {
+        let key: String = value
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match key.as_str() {
+            "discountfirst" => Ok(CalculationOrder::DiscountFirst),
+            "taxfirst" => Ok(CalculationOrder::TaxFirst),
+            "parallel" => Ok(CalculationOrder::Parallel),
+            _ => Err(EngineError::Validation {
+                message: format!(
+                    "Unknown calculation order '{}' (expected DiscountFirst, TaxFirst or Parallel)",
+                    value
+                ),
+            }),
+        }
+    }
+}
 
 impl MixedScenarioEngine {
     pub fn new() -> Self {
@@ -118,344 +679,4352 @@ impl MixedScenarioEngine {
             product_discounts: std::collections::HashMap::new(),
             global_tax_rates: Vec::new(),
             calculation_order: CalculationOrder::DiscountFirst,
+            total_formula: TotalFormula::standard(),
+            cap_strategy: CapStrategy::default(),
+            rounding_mode: RoundingMode::default(),
+            tax_rounding: None,
+            cart_limits: CartLimits::default(),
+            currencies: CurrencyRegistry::configured().clone(),
+            auto_best: false,
+            stacking_policy: StackingPolicy::default(),
+            jurisdictions: JurisdictionResolver::default(),
+            store_timezone: StoreTimeZone::default(),
+            pricing_time: None,
+            clock: Arc::new(SystemClock),
+            promo_manager: None,
+            cart_bundles: Vec::new(),
+            small_order_tax: None,
+            residue_target: ResidueTarget::default(),
+            price_lists: Vec::new(),
+            kits: std::collections::HashMap::new(),
+            cart_discounts: Vec::new(),
+            max_rate_percent: DEFAULT_MAX_RATE_PERCENT,
         }
     }
 
+    /// 🆕 Engine rounding every percentage discount and tax with `mode`
+    /// (Ex: `Bankers` where a jurisdiction requires half-even)
+    pub fn with_rounding_mode(mode: RoundingMode) -> Self {
+        let mut engine = Self::new();
+        engine.set_rounding_mode(mode);
+        engine
+    }
+
     /// Set calculation order
     pub fn set_calculation_order(&mut self, order: CalculationOrder) {
         self.calculation_order = order;
     }
 
-    /// Add global tax rate
-    pub fn add_global_tax(&mut self, tax: TaxRate) {
-        self.global_tax_rates.push(tax);
+    pub fn calculation_order(&self) -> CalculationOrder {
+        self.calculation_order
     }
 
-    /// Add product-specific tax config
-    pub fn add_product_tax(&mut self, config: ProductTaxConfig) {
-        self.product_taxes.insert(config.product_id.clone(), config);
+    /// Set how `max_discount_percent` caps stacked discounts
+    pub fn set_cap_strategy(&mut self, strategy: CapStrategy) {
+        self.cap_strategy = strategy;
     }
 
-    /// Add product-specific discount config
-    pub fn add_product_discount(&mut self, config: ProductDiscountConfig) {
-        self.product_discounts
-            .insert(config.product_id.clone(), config);
+    /// Rounding for percentage discounts and taxes (half-cents)
+    pub fn set_rounding_mode(&mut self, mode: RoundingMode) {
+        self.rounding_mode = mode;
     }
 
-    /// 💰 Calculate for a single item
-    pub fn calculate_item(
-        &self,
-        item: &Item,
-        cart_items: &[Item],
-        promo_codes: &[String],
-        target_jurisdiction: Option<&str>,
-    ) -> EngineResult<ItemCalculation> {
-        let base_amount = item.price * (item.quantity as i64);
-
-        // Get applicable discounts
-        let discount_amount = self.calculate_item_discount(
-            &item.id,
-            &base_amount,
-            item.quantity,
-            cart_items,
-            promo_codes,
-        )?;
+    /// Rounding direction for taxes only (HalfUp, HalfEven, Down, Up).
+    /// Ex: consumer-protection rules require Down; some revenue authorities require Up.
+    /// Unset => taxes follow `set_rounding_mode`
+    pub fn set_tax_rounding(&mut self, mode: RoundingMode) {
+        self.tax_rounding = Some(mode);
+    }
 
-        // Calculate taxable amount based on order
-        let taxable_amount = match self.calculation_order {
-            CalculationOrder::DiscountFirst => base_amount - discount_amount,
-            CalculationOrder::TaxFirst | CalculationOrder::Parallel => base_amount,
-        };
+    fn tax_rounding(&self) -> RoundingMode {
+        self.tax_rounding.unwrap_or(self.rounding_mode)
+    }
 
-        // Get applicable taxes
-        let tax_amount = self.calculate_item_tax(&item.id, &taxable_amount, target_jurisdiction)?;
+    /// Max lines / quantities accepted by `calculate_cart`
+    pub fn set_cart_limits(&mut self, limits: CartLimits) {
+        self.cart_limits = limits;
+    }
 
-        // Final total
-        let total = match self.calculation_order {
-            CalculationOrder::DiscountFirst => taxable_amount + tax_amount,
-            CalculationOrder::TaxFirst => base_amount + tax_amount - discount_amount,
-            CalculationOrder::Parallel => base_amount - discount_amount + tax_amount,
-        };
+    /// Currencies `calculate_cart` accepts
+    pub fn set_currency_registry(&mut self, currencies: CurrencyRegistry) {
+        self.currencies = currencies;
+    }
 
-        Ok(ItemCalculation {
-            item_id: item.id.clone(),
-            base_amount,
-            discount_amount,
-            tax_amount,
-            total,
-            discount_details: Vec::new(),
-            tax_details: Vec::new(),
-        })
+    /// Best-for-customer: among qualifying non-stackable discounts apply the largest,
+    /// not the highest priority (consumer-protection rules in some markets)
+    pub fn set_auto_best(&mut self, auto_best: bool) {
+        self.auto_best = auto_best;
     }
 
-    /// Calculate discount for item
-    fn calculate_item_discount(
-        &self,
-        item_id: &str,
-        base_amount: &Money,
-        quantity: f64,
-        cart_items: &[Item],
-        promo_codes: &[String],
-    ) -> EngineResult<Money> {
-        let mut total_discount = Money::zero();
+    /// Exclusive groups, stacking limits and category exclusions on top of `stackable`
+    pub fn set_stacking_policy(&mut self, policy: StackingPolicy) {
+        self.stacking_policy = policy;
+    }
 
-        if let Some(config) = self.product_discounts.get(item_id) {
-            let mut applied_non_stackable = false;
+    /// Postal overrides and nexus used by `calculate_cart_for_address`; its home jurisdiction
+    /// is used when a calculation names none, and no tax is charged outside its nexus
+    pub fn set_jurisdiction_resolver(&mut self, resolver: JurisdictionResolver) {
+        self.jurisdictions = resolver;
+    }
 
-            // Sort by priority (higher first)
-            let mut rules = config.discounts.clone();
-            rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+    pub fn jurisdiction_resolver(&self) -> &JurisdictionResolver {
+        &self.jurisdictions
+    }
 
-            for rule in rules {
-                // Check if we can still apply
-                if applied_non_stackable && !rule.stackable {
-                    continue;
-                }
+    /// Time zone used for `DateRange` conditions (UTC by default)
+    pub fn set_store_timezone(&mut self, timezone: StoreTimeZone) {
+        self.store_timezone = timezone;
+    }
 
-                // Check conditions
-                let conditions_met = self.check_conditions(
-                    &rule.conditions,
-                    quantity,
-                    base_amount,
-                    cart_items,
-                    promo_codes,
-                );
-                if !conditions_met {
-                    continue;
-                }
+    /// Price as of a fixed instant (previews, replays); None => now
+    pub fn set_pricing_time(&mut self, at: Option<DateTime<Utc>>) {
+        self.pricing_time = at;
+    }
 
-                // Calculate discount
-                let discount = match &rule.discount_type {
-                    DiscountType::FixedAmount(cents) => Money::from_cents(*cents),
-                    DiscountType::Percentage(pct) => {
-                        base_amount.sub_percentage(*pct) - *base_amount
-                    }
-                    DiscountType::BuyXGetY {
-                        buy,
-                        get,
-                        free_percent,
-                    } => {
-                        let sets = (quantity / (*buy + *get)).floor();
-                        let free_items = sets * get;
-                        let unit_price = (*base_amount).div(quantity as i64);
-                        let discount_per_free = unit_price
-                            .mul((*free_percent / 100.0 * 100.0) as i64)
-                            .div(100);
-                        discount_per_free * (free_items as i64)
-                    }
-                    DiscountType::Tiered(tiers) => {
-                        let mut tier_discount = Money::zero();
-                        for tier in tiers {
-                            let max = tier.max_qty.unwrap_or(f64::MAX);
-                            if quantity >= tier.min_qty && quantity <= max {
-                                tier_discount = base_amount.sub_percentage(tier.discount_percent);
-                                tier_discount = *base_amount - tier_discount;
-                                break;
-                            }
-                        }
-                        tier_discount
-                    }
-                    DiscountType::Bundle {
-                        items,
-                        discount_percent,
-                    } => {
-                        // Check if all required items exist in cart (excluding current item)
-                        let mut all_found = true;
-                        for bundle_item_id in items {
-                            if !cart_items
-                                .iter()
-                                .any(|i| i.id == *bundle_item_id || i.name == *bundle_item_id)
-                            {
-                                all_found = false;
-                                break;
-                            }
-                        }
-
-                        if all_found {
-                            base_amount.sub_percentage(*discount_percent).abs()
-                            // Note: usually bundle discount is calculated on sum, here we apply % to this item if bundle exists
-                        } else {
-                            Money::zero()
-                        }
-                    }
-                };
+    /// Source of "now" for `DateRange` conditions when no pricing time is set
+    /// (`FixedClock` freezes time in tests)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
 
-                total_discount = total_discount + discount.abs();
+    fn now(&self) -> DateTime<Utc> {
+        self.pricing_time.unwrap_or_else(|| self.clock.now())
+    }
 
-                if !rule.stackable {
-                    applied_non_stackable = true;
-                }
-            }
+    /// Registered promo codes (limits, validity, minimum cart value) checked before pricing;
+    /// codes the manager doesn't know stay plain `PromoCode` condition matches
+    pub fn set_promo_manager(&mut self, manager: Option<Arc<PromoCodeManager>>) {
+        self.promo_manager = manager;
+    }
 
-            // Apply max discount cap
-            if let Some(max_pct) = config.max_discount_percent {
-                let max_discount = (*base_amount).mul((max_pct * 100.0) as i64).div(10000);
-                if total_discount > max_discount {
-                    total_discount = max_discount;
-                }
-            }
-        }
+    pub fn promo_manager(&self) -> Option<&Arc<PromoCodeManager>> {
+        self.promo_manager.as_ref()
+    }
 
-        Ok(total_discount)
+    /// ✅ First reason a registered code can't be used on this cart right now
+    pub fn check_promo_codes(&self, cart: &Cart, promo_codes: &[String]) -> EngineResult<()> {
+        self.check_promo_codes_at(cart, promo_codes, self.now())
     }
 
-    /// Calculate tax for item
-    fn calculate_item_tax(
+    /// ✅ Same check at the context's time and for its customer, as `calculate_cart_with_context` prices
+    pub fn check_promo_codes_with_context(
         &self,
-        item_id: &str,
-        taxable_amount: &Money,
-        target_jurisdiction: Option<&str>,
-    ) -> EngineResult<Money> {
-        let mut total_tax = Money::zero();
+        cart: &Cart,
+        promo_codes: &[String],
+        context: &CalculationContext,
+    ) -> EngineResult<()> {
+        let cart = context.apply_to(cart);
+        self.check_promo_codes_at(&cart, promo_codes, context.timestamp.unwrap_or_else(|| self.now()))
+    }
 
-        // Check product-specific taxes
-        if let Some(config) = self.product_taxes.get(item_id) {
-            if config.tax_exempt {
-                return Ok(Money::zero());
-            }
+    fn check_promo_codes_at(&self, cart: &Cart, promo_codes: &[String], now: DateTime<Utc>) -> EngineResult<()> {
+        let Some(manager) = &self.promo_manager else {
+            return Ok(());
+        };
+        let (customer_id, cart_value) = (ConditionContext::for_cart(cart, promo_codes, now).customer_id, cart.subtotal());
+        promo_codes
+            .iter()
+            .filter(|code| manager.is_registered(code))
+            .try_for_each(|code| manager.validate(code, customer_id, cart_value, now))
+    }
 
-            for tax_rate in &config.tax_rates {
-                // Check jurisdiction
-                if let Some(target) = target_jurisdiction {
-                    if tax_rate.jurisdiction != target && tax_rate.jurisdiction != "ALL" {
-                        continue;
-                    }
+    /// 🎟️ Count one use of every registered code on this cart (all or none)
+    /// Orders keep the redemptions and hand them to `release_promo_codes` if abandoned.
+    pub fn redeem_promo_codes(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        at: DateTime<Utc>,
+    ) -> EngineResult<Vec<PromoRedemption>> {
+        let Some(manager) = &self.promo_manager else {
+            return Ok(Vec::new());
+        };
+        let (customer_id, cart_value) = (ConditionContext::for_cart(cart, promo_codes, at).customer_id, cart.subtotal());
+        let mut redeemed = Vec::new();
+        for code in promo_codes.iter().filter(|code| manager.is_registered(code)) {
+            match manager.redeem(code, customer_id, cart_value, at) {
+                Ok(redemption) => redeemed.push(redemption),
+                Err(e) => {
+                    self.release_promo_codes(&redeemed);
+                    return Err(e);
                 }
-
-                let tax = (*taxable_amount)
-                    .mul((tax_rate.rate * 100.0) as i64)
-                    .div(10000);
-                total_tax = total_tax + tax;
             }
-        } else {
-            // Apply global taxes
-            // Apply global taxes
-            for tax_rate in &self.global_tax_rates {
-                // Check jurisdiction
-                if let Some(target) = target_jurisdiction {
-                    if tax_rate.jurisdiction != target && tax_rate.jurisdiction != "ALL" {
-                        continue;
-                    }
-                }
+        }
+        Ok(redeemed)
+    }
 
-                match &tax_rate.applies_to {
-                    TaxAppliesTo::All => {
-                        let tax = (*taxable_amount)
-                            .mul((tax_rate.rate * 100.0) as i64)
-                            .div(10000);
-                        total_tax = total_tax + tax;
-                    }
-                    TaxAppliesTo::Product(pid) if pid == item_id => {
-                        let tax = (*taxable_amount)
-                            .mul((tax_rate.rate * 100.0) as i64)
-                            .div(10000);
-                        total_tax = total_tax + tax;
-                    }
-                    _ => {}
-                }
+    /// ↩️ Give back uses counted by `redeem_promo_codes` (already released ones are skipped)
+    pub fn release_promo_codes(&self, redemptions: &[PromoRedemption]) {
+        if let Some(manager) = &self.promo_manager {
+            for redemption in redemptions {
+                let _ = manager.release(redemption);
             }
         }
-
-        Ok(total_tax)
     }
 
-    /// Check discount conditions
-    fn check_conditions(
+    /// Promo codes that may take part in pricing (invalid registered codes dropped)
+    fn usable_promo_codes<'a>(
         &self,
-        conditions: &[DiscountCondition],
-        quantity: f64,
-        amount: &Money,
-        cart_items: &[Item],
-        promo_codes: &[String],
-    ) -> bool {
-        if conditions.is_empty() {
-            return true;
+        customer_id: Option<&str>,
+        cart_value: Money,
+        promo_codes: &'a [String],
+        now: DateTime<Utc>,
+    ) -> Cow<'a, [String]> {
+        let Some(manager) = &self.promo_manager else {
+            return Cow::Borrowed(promo_codes);
+        };
+        let usable = |code: &String| {
+            !manager.is_registered(code) || manager.validate(code, customer_id, cart_value, now).is_ok()
+        };
+        if promo_codes.iter().all(usable) {
+            return Cow::Borrowed(promo_codes);
         }
+        Cow::Owned(promo_codes.iter().filter(|code| usable(code)).cloned().collect())
+    }
 
-        for condition in conditions {
-            let met = match condition {
-                DiscountCondition::MinQuantity(min) => quantity >= *min,
-                DiscountCondition::MinAmount(cents) => amount.amount >= *cents,
-                DiscountCondition::PromoCode(code) => promo_codes.contains(code),
-                DiscountCondition::CartContains(item_id) => cart_items
-                    .iter()
-                    .any(|i| i.id == *item_id || i.name == *item_id),
-                // Other conditions need external data
-                _ => true,
-            };
-            if !met {
-                return false;
+    /// Set the grand total composition (Ex: region with a levy or deposit)
+    pub fn set_total_formula(&mut self, formula: TotalFormula) {
+        self.total_formula = formula;
+    }
+
+    pub fn total_formula(&self) -> &TotalFormula {
+        &self.total_formula
+    }
+
+    /// Add global tax rate
+    pub fn add_global_tax(&mut self, tax: TaxRate) -> EngineResult<()> {
+        self.validate_tax_rate(&tax)?;
+        self.global_tax_rates.push(tax);
+        Ok(())
+    }
+
+    /// Add product-specific tax config
+    pub fn add_product_tax(&mut self, config: ProductTaxConfig) -> EngineResult<()> {
+        self.validate_product_tax(&config)?;
+        self.product_taxes.insert(config.product_id.clone(), config);
+        Ok(())
+    }
+
+    /// Add product-specific discount config
+    /// Rules are sorted by `DiscountRule::precedence` here, once, instead of on every calculation
+    pub fn add_product_discount(&mut self, mut config: ProductDiscountConfig) -> EngineResult<()> {
+        self.validate_product_discount(&config)?;
+        config.discounts.sort_by(DiscountRule::precedence);
+        self.product_discounts
+            .insert(config.product_id.clone(), config);
+        Ok(())
+    }
+
+    /// 🛡️ Highest percentage rate `add_*` accepts (default `DEFAULT_MAX_RATE_PERCENT`)
+    pub fn set_max_rate_percent(&mut self, max: f64) -> EngineResult<()> {
+        if !max.is_finite() || max < 0.0 {
+            return Err(EngineError::Validation {
+                message: format!("Maximum rate must be a finite, non-negative percentage, got {}", max),
+            });
+        }
+        self.max_rate_percent = max;
+        Ok(())
+    }
+
+    /// Rates must be finite and within 0..=max_rate_percent (NaN, -5% and 1000% are config errors)
+    fn check_rate(&self, what: &str, rate: f64) -> EngineResult<()> {
+        if !rate.is_finite() || rate < 0.0 || rate > self.max_rate_percent {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "{} rate {}% is outside the allowed range 0..={}%",
+                    what, rate, self.max_rate_percent
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Date ranges must parse and end after they start (a typo would otherwise never match)
+    fn check_conditions_config(&self, conditions: &[DiscountCondition]) -> EngineResult<()> {
+        for condition in conditions {
+            if let DiscountCondition::DateRange { from, to } = condition {
+                self.store_timezone.window(from, to)?;
             }
         }
-        true
+        Ok(())
     }
 
-    /// 📊 Calculate full cart
-    pub fn calculate_cart(
+    fn validate_tax_rate(&self, tax: &TaxRate) -> EngineResult<()> {
+        if tax.basis == TaxBasis::Percentage {
+            self.check_rate(&format!("Tax '{}'", tax.name), tax.rate)?;
+        }
+        Ok(())
+    }
+
+    /// ✅ Check a product tax config's rates without adding it
+    pub fn validate_product_tax(&self, config: &ProductTaxConfig) -> EngineResult<()> {
+        config.tax_rates.iter().try_for_each(|tax| self.validate_tax_rate(tax))
+    }
+
+    /// ✅ Check a product discount config's rates without adding it
+    pub fn validate_product_discount(&self, config: &ProductDiscountConfig) -> EngineResult<()> {
+        if let Some(max) = config.max_discount_percent {
+            self.check_rate(&format!("Max discount of '{}'", config.product_id), max)?;
+        }
+        for rule in &config.discounts {
+            let what = format!("Discount '{}'", rule.id);
+            self.check_conditions_config(&rule.conditions)?;
+            match &rule.discount_type {
+                DiscountType::Percentage(percent) => self.check_rate(&what, *percent)?,
+                DiscountType::BuyXGetY { free_percent, .. } => self.check_rate(&what, *free_percent)?,
+                DiscountType::Tiered(tiers) => {
+                    for tier in tiers {
+                        self.check_rate(&what, tier.discount_percent)?;
+                    }
+                }
+                DiscountType::Bundle { discount_percent, .. } => self.check_rate(&what, *discount_percent)?,
+                DiscountType::FixedAmount(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Where a rounding residue between line totals and the cart buckets goes
+    pub fn set_residue_target(&mut self, target: ResidueTarget) {
+        self.residue_target = target;
+    }
+
+    /// Add a customer or group price list (contract prices)
+    pub fn add_price_list(&mut self, list: PriceList) -> EngineResult<()> {
+        if let Some((item_id, price)) = list.prices.iter().find(|(_, price)| price.is_negative()) {
+            return Err(EngineError::Validation {
+                message: format!("Price list '{}' has a negative price {} for '{}'", list.id, price, item_id),
+            });
+        }
+        self.price_lists.push(list);
+        Ok(())
+    }
+
+    /// Contract unit price (and its skip-promotions flag) for a customer, else their group
+    fn contract_price(
         &self,
-        cart: &Cart,
+        item_id: &str,
+        customer_id: Option<&str>,
+        customer_group: Option<&str>,
+    ) -> Option<(Money, bool)> {
+        let in_scope = |scope: &PriceListScope| match scope {
+            PriceListScope::Customer(id) => customer_id == Some(id.as_str()),
+            PriceListScope::Group(group) => customer_group == Some(group.as_str()),
+        };
+        let lookup = |customer_scope: bool| {
+            self.price_lists
+                .iter()
+                .filter(|list| matches!(list.scope, PriceListScope::Customer(_)) == customer_scope)
+                .filter(|list| in_scope(&list.scope))
+                .find_map(|list| list.prices.get(item_id).map(|price| (*price, list.skip_promotions)))
+        };
+        lookup(true).or_else(|| lookup(false))
+    }
+
+    /// Add a cart-level discount (applied after line discounts and bundles)
+    pub fn add_cart_discount(&mut self, config: CartDiscountConfig) -> EngineResult<()> {
+        let what = format!("Cart discount '{}'", config.id);
+        self.check_conditions_config(&config.conditions)?;
+        match &config.discount_type {
+            CartDiscountType::Percentage(percent) => self.check_rate(&what, *percent)?,
+            CartDiscountType::FixedAmount(amount) if amount.is_negative() => {
+                return Err(EngineError::Validation {
+                    message: format!("{} has a negative amount {}", what, amount),
+                })
+            }
+            CartDiscountType::FixedAmount(_) => {}
+            CartDiscountType::TieredByTotal(tiers) | CartDiscountType::TieredByQuantity(tiers) => {
+                if tiers.is_empty() {
+                    return Err(EngineError::Validation {
+                        message: format!("{} has no tiers", what),
+                    });
+                }
+                for tier in tiers {
+                    self.check_rate(&what, tier.discount_percent)?;
+                }
+            }
+        }
+        self.cart_discounts.push(config);
+        self.cart_discounts.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+        Ok(())
+    }
+
+    /// 🔁 Replace every global and product tax; on error the engine is left unchanged
+    pub fn replace_taxes(&mut self, global: Vec<TaxRate>, product: Vec<ProductTaxConfig>) -> EngineResult<()> {
+        let mut staged = self.clone();
+        staged.global_tax_rates.clear();
+        staged.product_taxes.clear();
+        for tax in global {
+            staged.add_global_tax(tax)?;
+        }
+        for config in product {
+            staged.add_product_tax(config)?;
+        }
+        *self = staged;
+        Ok(())
+    }
+
+    /// 🔁 Replace every product and cart discount; on error the engine is left unchanged
+    pub fn replace_discounts(
+        &mut self,
+        product: Vec<ProductDiscountConfig>,
+        cart: Vec<CartDiscountConfig>,
+    ) -> EngineResult<()> {
+        let mut staged = self.clone();
+        staged.product_discounts.clear();
+        staged.cart_discounts.clear();
+        for config in product {
+            staged.add_product_discount(config)?;
+        }
+        for config in cart {
+            staged.add_cart_discount(config)?;
+        }
+        *self = staged;
+        Ok(())
+    }
+
+    /// Add a kit (composite product); components may not be kits themselves
+    pub fn add_kit(&mut self, kit: Kit) -> EngineResult<()> {
+        if kit.components.is_empty() {
+            return Err(EngineError::Validation {
+                message: format!("Kit '{}' has no components", kit.id),
+            });
+        }
+        for component in &kit.components {
+            if !(component.quantity.is_finite() && component.quantity > 0.0) || component.unit_price.is_negative() {
+                return Err(EngineError::Validation {
+                    message: format!(
+                        "Kit '{}' component '{}' needs a positive quantity and a non-negative price",
+                        kit.id, component.product_id
+                    ),
+                });
+            }
+            if component.product_id == kit.id || self.kits.contains_key(&component.product_id) {
+                return Err(EngineError::Validation {
+                    message: format!("Kit '{}' cannot contain the kit '{}'", kit.id, component.product_id),
+                });
+            }
+        }
+        if self.kits.values().any(|k| k.components.iter().any(|c| c.product_id == kit.id)) {
+            return Err(EngineError::Validation {
+                message: format!("Kit '{}' is already a component of another kit", kit.id),
+            });
+        }
+        self.kits.insert(kit.id.clone(), kit);
+        Ok(())
+    }
+
+    /// 🧰 Kit line: each component calculated as its own item, summed into one line
+    fn calculate_kit(
+        &self,
+        kit: &Kit,
+        item: &Item,
+        target_jurisdiction: Option<&str>,
+        context: &ConditionContext,
+    ) -> EngineResult<ItemCalculation> {
+        let mut line = ItemCalculation {
+            item_id: item.id.clone(),
+            base_amount: Money::zero(),
+            discount_amount: Money::zero(),
+            tax_amount: Money::zero(),
+            total: Money::zero(),
+            discount_details: Vec::new(),
+            tax_details: Vec::new(),
+            cap_adjustments: Vec::new(),
+            suppressed_discounts: Vec::new(),
+            // Inclusive only if every component is
+            tax_included: true,
+            cost: Some(Money::zero()),
+        };
+        for component in &kit.components {
+            let mut part = Item::new(&component.name, component.unit_price, component.quantity * item.quantity);
+            part.id = component.product_id.clone();
+            part.currency = item.currency;
+            part.cost = component.unit_cost;
+            let calc = self.price_item(&part, target_jurisdiction, *context)?;
+            line.base_amount = line.base_amount + calc.base_amount;
+            line.discount_amount = line.discount_amount + calc.discount_amount;
+            line.tax_amount = line.tax_amount + calc.tax_amount;
+            line.total = line.total + calc.total;
+            line.discount_details.extend(calc.discount_details);
+            line.tax_details.extend(calc.tax_details);
+            line.cap_adjustments.extend(calc.cap_adjustments);
+            line.suppressed_discounts.extend(calc.suppressed_discounts);
+            line.tax_included &= calc.tax_included;
+            line.cost = line.cost.zip(calc.cost).map(|(kit_cost, part_cost)| kit_cost + part_cost);
+        }
+        // A cost on the kit SKU itself wins over the component sum
+        line.cost = self.line_cost(item).or(line.cost);
+        Ok(line)
+    }
+
+    /// Exempt (or flat-tax) orders below a value threshold
+    pub fn set_small_order_tax(&mut self, rule: Option<SmallOrderTax>) {
+        self.small_order_tax = rule;
+    }
+
+    /// Add a cart-level bundle (applied after line discounts)
+    pub fn add_cart_bundle(&mut self, bundle: CartBundle) -> EngineResult<()> {
+        self.check_rate(&format!("Bundle '{}'", bundle.id), bundle.discount_percent)?;
+        self.cart_bundles.push(bundle);
+        Ok(())
+    }
+
+    /// Replace every cart bundle (all validated before any is swapped in)
+    pub fn replace_cart_bundles(&mut self, bundles: Vec<CartBundle>) -> EngineResult<()> {
+        let mut staged = self.clone();
+        staged.cart_bundles.clear();
+        for bundle in bundles {
+            staged.add_cart_bundle(bundle)?;
+        }
+        *self = staged;
+        Ok(())
+    }
+
+    /// 💰 Calculate for a single item
+    pub fn calculate_item(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
         promo_codes: &[String],
         target_jurisdiction: Option<&str>,
-    ) -> EngineResult<CartCalculation> {
-        let mut item_results = Vec::new();
-        let mut subtotal = Money::zero();
-        let mut total_discount = Money::zero();
-        let mut total_tax = Money::zero();
+    ) -> EngineResult<ItemCalculation> {
+        self.calculate_item_for_customer(item, cart_items, promo_codes, target_jurisdiction, None, None)
+    }
 
-        for item in &cart.items {
-            let result =
-                self.calculate_item(item, &cart.items, promo_codes, target_jurisdiction)?;
+    /// 💰 Calculate for a single item bought by a customer group (`CustomerGroup` conditions)
+    pub fn calculate_item_for_group(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        customer_group: Option<&str>,
+    ) -> EngineResult<ItemCalculation> {
+        self.calculate_item_for_customer(item, cart_items, promo_codes, target_jurisdiction, None, customer_group)
+    }
+
+    /// 💰 Calculate for a single item bought by a known customer (price lists, group conditions)
+    /// The customer's history is unknown, so it is never a first purchase
+    pub fn calculate_item_for_customer(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        customer_id: Option<&str>,
+        customer_group: Option<&str>,
+    ) -> EngineResult<ItemCalculation> {
+        let context = CalculationContext {
+            jurisdiction: target_jurisdiction.map(str::to_string),
+            ..CalculationContext::default()
+        };
+        self.price_item_in_context(item, cart_items, promo_codes, &context, (customer_id, customer_group))
+    }
+
+    /// 💰 Calculate for a single item bought by a customer with a known history
+    /// (`FirstPurchase`, multi-group conditions; the first group selects price lists)
+    #[deprecated(note = "use `calculate_item_in_context` with `CalculationContext::for_customer`")]
+    pub fn calculate_item_with_context(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        customer: &CustomerContext,
+    ) -> EngineResult<ItemCalculation> {
+        let context = CalculationContext {
+            jurisdiction: target_jurisdiction.map(str::to_string),
+            customer: Some(customer.clone()),
+            ..CalculationContext::default()
+        };
+        self.calculate_item_in_context(item, cart_items, promo_codes, &context)
+    }
+
+    /// 💰 Calculate for a single item under a `CalculationContext`
+    /// (jurisdiction, customer, pricing time and currency), as `calculate_cart_with_context` prices its lines.
+    /// Every other `calculate_item*` entry point is a shorthand for this one.
+    pub fn calculate_item_in_context(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        context: &CalculationContext,
+    ) -> EngineResult<ItemCalculation> {
+        self.price_item_in_context(item, cart_items, promo_codes, context, (None, None))
+    }
+
+    /// Price one item outside a cart. `(customer_id, customer_group)` stand in for a customer
+    /// known without history, as `cart.customer_id` / `cart.customer_group` do for a cart.
+    /// Registered promo codes are validated against the customer and the cart lines' value
+    /// first, as `calculate_cart` does.
+    fn price_item_in_context(
+        &self,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        context: &CalculationContext,
+        (customer_id, customer_group): (Option<&str>, Option<&str>),
+    ) -> EngineResult<ItemCalculation> {
+        context.check_currency(&format!("Item '{}'", item.name), item.currency)?;
+        let customer = context.customer.as_ref();
+        let now = context.timestamp.unwrap_or_else(|| self.now());
+        let customer_id = customer_id.or_else(|| customer.and_then(|c| c.customer_id.as_deref()));
+        let promo_codes = self.usable_promo_codes(customer_id, single_item_cart_value(item, cart_items), promo_codes, now);
+        let conditions = ConditionContext {
+            cart_items,
+            promo_codes: &promo_codes,
+            customer_id,
+            customer_group: customer_group.or_else(|| customer.and_then(|c| c.groups.first().map(String::as_str))),
+            customer,
+            is_return: false,
+            skip_promotions: false,
+            now,
+        };
+        self.price_item(item, context.jurisdiction.as_deref(), conditions)
+    }
+
+    fn price_item(
+        &self,
+        item: &Item,
+        target_jurisdiction: Option<&str>,
+        context: ConditionContext,
+    ) -> EngineResult<ItemCalculation> {
+        if let Some(kit) = self.kits.get(&item.id) {
+            let context = ConditionContext {
+                is_return: item.quantity < 0.0,
+                ..context
+            };
+            return self.calculate_kit(kit, item, target_jurisdiction, &context);
+        }
+        let contract = self.contract_price(&item.id, context.customer_id, context.customer_group);
+        let contract_priced;
+        let item = match contract {
+            Some((price, _)) => {
+                contract_priced = Item { price, ..item.clone() };
+                &contract_priced
+            }
+            None => item,
+        };
+        let context = ConditionContext {
+            is_return: item.quantity < 0.0,
+            skip_promotions: contract.is_some_and(|(_, skip)| skip),
+            ..context
+        };
+        if context.is_return {
+            return self.calculate_return_item(item, target_jurisdiction, &context);
+        }
+        self.calculate_line(item, target_jurisdiction, &context)
+    }
 
-            subtotal = subtotal + result.base_amount;
-            total_discount = total_discount + result.discount_amount;
-            total_tax = total_tax + result.tax_amount;
-            item_results.push(result);
+    /// ↩️ Return line (negative quantity, ආපසු භාණ්ඩ)
+    /// Priced as the mirror sale of the returned quantity - same price, taxes and
+    /// non-quantity discounts - with every amount negated, so a mixed cart nets to
+    /// purchase minus refund. Quantity-threshold discounts are skipped.
+    fn calculate_return_item(
+        &self,
+        item: &Item,
+        target_jurisdiction: Option<&str>,
+        context: &ConditionContext,
+    ) -> EngineResult<ItemCalculation> {
+        let mut returned = item.clone();
+        returned.quantity = -item.quantity;
+        let neg = |m: Money| Money::zero() - m;
+        let mut line = self.calculate_line(&returned, target_jurisdiction, context)?;
+        line.base_amount = neg(line.base_amount);
+        line.discount_amount = neg(line.discount_amount);
+        line.tax_amount = neg(line.tax_amount);
+        line.total = neg(line.total);
+        line.cost = line.cost.map(neg);
+        for detail in &mut line.discount_details {
+            detail.amount = neg(detail.amount);
         }
+        for detail in &mut line.tax_details {
+            detail.amount = neg(detail.amount);
+            detail.reverse_charged = neg(detail.reverse_charged);
+            detail.taxable_base = neg(detail.taxable_base);
+        }
+        for adjustment in &mut line.cap_adjustments {
+            adjustment.original_amount = neg(adjustment.original_amount);
+            adjustment.applied_amount = neg(adjustment.applied_amount);
+        }
+        Ok(line)
+    }
 
-        let grand_total = subtotal - total_discount + total_tax;
+    fn calculate_line(
+        &self,
+        item: &Item,
+        target_jurisdiction: Option<&str>,
+        context: &ConditionContext,
+    ) -> EngineResult<ItemCalculation> {
+        let reverse_charge = self.reverse_charge(context.customer, target_jurisdiction);
+        if let Some(rates) = self.inclusive_tax_rates(&item.id, target_jurisdiction) {
+            return self.calculate_inclusive_item(item, &rates, target_jurisdiction, reverse_charge, context);
+        }
+        // Rounded once on the line, so weighed quantities (1.5 kg) are not truncated
+        let base_amount = item.price.mul_ratio_rounded(item.quantity, self.rounding_mode);
 
-        Ok(CartCalculation {
-            items: item_results,
-            subtotal,
-            total_discount,
-            total_tax,
-            grand_total,
+        let (discounts, (tax_amount, tax_details)) = if self.calculation_order == CalculationOrder::TaxFirst {
+            // Tax on the undiscounted amount, then discounts on the taxed amount
+            let taxes =
+                self.calculate_item_tax(&item.id, &base_amount, &Money::zero(), item.quantity, target_jurisdiction, reverse_charge)?;
+            let taxed_amount = base_amount + taxes.0;
+            let discounts = self.calculate_item_discount(item, &taxed_amount, context)?;
+            self.split_taxed_discount(discounts, taxes, taxed_amount)?
+        } else {
+            // Get applicable discounts
+            let discounts = self.calculate_item_discount(item, &base_amount, context)?;
+
+            // Get applicable taxes (taxable amount per tax: its own base, else the engine order)
+            let taxes = self.calculate_item_tax(
+                &item.id,
+                &base_amount,
+                &discounts.total,
+                item.quantity,
+                target_jurisdiction,
+                reverse_charge,
+            )?;
+            (discounts, taxes)
+        };
+        let discount_amount = discounts.total;
+
+        // Final total
+        let total = match self.calculation_order {
+            CalculationOrder::DiscountFirst => base_amount - discount_amount + tax_amount,
+            CalculationOrder::TaxFirst => base_amount + tax_amount - discount_amount,
+            CalculationOrder::Parallel => base_amount - discount_amount + tax_amount,
+        };
+
+        Ok(ItemCalculation {
+            item_id: item.id.clone(),
+            base_amount,
+            discount_amount,
+            tax_amount,
+            total,
+            discount_details: discounts.details,
+            tax_details,
+            cap_adjustments: discounts.cap_adjustments,
+            suppressed_discounts: discounts.suppressed,
+            tax_included: false,
+            cost: self.line_cost(item),
         })
     }
-}
 
-/// 📋 Item Calculation Result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ItemCalculation {
-    pub item_id: String,
-    pub base_amount: Money,
-    pub discount_amount: Money,
-    pub tax_amount: Money,
-    pub total: Money,
-    pub discount_details: Vec<DiscountDetail>,
-    pub tax_details: Vec<TaxDetail>,
-}
+    /// TaxFirst: a discount off the taxed amount reduces the tax in proportion, so the line
+    /// reports the tax actually collected and only the net part as its discount
+    /// (base + tax - discount still equals what the customer pays).
+    fn split_taxed_discount(
+        &self,
+        mut discounts: ItemDiscounts,
+        (tax, mut details): (Money, Vec<TaxDetail>),
+        taxed_amount: Money,
+    ) -> EngineResult<(ItemDiscounts, (Money, Vec<TaxDetail>))> {
+        if !discounts.total.is_positive() || !tax.is_positive() || !taxed_amount.is_positive() {
+            return Ok((discounts, (tax, details)));
+        }
+        let share = discounts.total.amount as f64 / taxed_amount.amount as f64;
+        let tax_part = tax.mul_ratio_rounded(share, self.tax_rounding()).min(tax);
+        let weights: Vec<i64> = details.iter().map(|d| d.amount.amount.max(0)).collect();
+        for (detail, part) in details.iter_mut().zip(tax_part.allocate(&weights)?) {
+            detail.amount = detail.amount - part;
+            detail.taxable_base = detail.taxable_base - detail.taxable_base.mul_ratio_rounded(share, self.rounding_mode);
+        }
+        let net_part = discounts.total - tax_part;
+        let weights: Vec<i64> = discounts.details.iter().map(|d| d.amount.amount.max(0)).collect();
+        if weights.iter().sum::<i64>() > 0 {
+            for (detail, amount) in discounts.details.iter_mut().zip(net_part.allocate(&weights)?) {
+                detail.amount = amount;
+            }
+        }
+        discounts.total = net_part;
+        Ok((discounts, (tax - tax_part, details)))
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DiscountDetail {
-    pub rule_id: String,
-    pub name: String,
-    pub amount: Money,
-}
+    fn line_cost(&self, item: &Item) -> Option<Money> {
+        item.cost
+            .map(|cost| cost.mul_ratio_rounded(item.quantity, self.rounding_mode))
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TaxDetail {
-    pub name: String,
-    pub rate: f64,
-    pub amount: Money,
-}
+    /// Does the product's price already include tax (in this jurisdiction)?
+    fn is_tax_inclusive(&self, item_id: &str, target_jurisdiction: Option<&str>) -> bool {
+        self.inclusive_tax_rates(item_id, target_jurisdiction).is_some()
+    }
 
-/// 📊 Cart Calculation Result  
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CartCalculation {
-    pub items: Vec<ItemCalculation>,
-    pub subtotal: Money,
-    pub total_discount: Money,
-    pub total_tax: Money,
-    pub grand_total: Money,
+    /// Rates included in a product's price (percentage and per-unit), in sequence order
+    fn inclusive_tax_rates(&self, item_id: &str, target_jurisdiction: Option<&str>) -> Option<Vec<&TaxRate>> {
+        let config = self.product_taxes.get(item_id)?;
+        if !config.tax_included_in_price || config.tax_exempt {
+            return None;
+        }
+        let target = target_jurisdiction.or(self.jurisdictions.home());
+        let mut rates: Vec<&TaxRate> = config
+            .tax_rates
+            .iter()
+            .filter(|t| target.is_none_or(|target| jurisdiction::applies(&t.jurisdiction, target)))
+            .collect();
+        rates.sort_by_key(|t| t.sequence);
+        Some(rates)
+    }
+
+    /// Net amount that, taxed at `rates`, comes to `inclusive`
+    /// Per-unit excise is a fixed amount, so only the percentage part is backed out proportionally.
+    fn back_out_net(&self, inclusive: Money, rates: &[&TaxRate], quantity: f64) -> Money {
+        let rounding = self.tax_rounding();
+        let excise = rates
+            .iter()
+            .filter_map(|t| match t.basis {
+                TaxBasis::PerUnit(per_unit) => Some(per_unit.mul_ratio_rounded(quantity, rounding)),
+                TaxBasis::Percentage => None,
+            })
+            .fold(Money::zero(), |acc, amount| acc + amount);
+        let percentage: Vec<&TaxRate> = rates.iter().copied().filter(|t| t.basis == TaxBasis::Percentage).collect();
+        let effective = TaxRate::effective_rates(&percentage);
+        let rate: f64 = effective.iter().sum();
+        let rate_on_excise: f64 = percentage
+            .iter()
+            .zip(&effective)
+            .filter(|(t, _)| t.include_excise_in_base)
+            .map(|(_, rate)| rate)
+            .sum();
+        let excise_inclusive = excise.mul_ratio_rounded(1.0 + rate_on_excise / 100.0, rounding);
+        (inclusive - excise_inclusive)
+            .max(Money::zero())
+            .mul_ratio_rounded(100.0 / (100.0 + rate), self.rounding_mode)
+    }
+
+    /// 🧾 Tax-inclusive line (බදු ඇතුළත් මිල)
+    /// Price × quantity is rounded once at the line level and the net is backed out of that
+    /// line total (not per unit). The taxes are then charged on the net rate by rate, exactly as
+    /// on an exclusive line (per-unit excise, min/max bounds, `discount_base`, reverse charge),
+    /// and the net absorbs the rounding so net + tax always equals the inclusive line.
+    /// Discounts reduce the inclusive amount. A reverse-charged buyer pays the price net of
+    /// the taxes they self-account for.
+    fn calculate_inclusive_item(
+        &self,
+        item: &Item,
+        rates: &[&TaxRate],
+        target_jurisdiction: Option<&str>,
+        reverse_charge: bool,
+        context: &ConditionContext,
+    ) -> EngineResult<ItemCalculation> {
+        let line_amount = item.price.mul_ratio_rounded(item.quantity, self.rounding_mode);
+        let discounts = self.calculate_item_discount(item, &line_amount, context)?;
+        let (tax_amount, tax_details, total) = self.inclusive_taxes(
+            item,
+            rates,
+            (line_amount, discounts.total),
+            target_jurisdiction,
+            reverse_charge,
+        )?;
+
+        Ok(ItemCalculation {
+            item_id: item.id.clone(),
+            // Net of tax, before discount: base - discount + tax == total
+            base_amount: total - tax_amount + discounts.total,
+            discount_amount: discounts.total,
+            tax_amount,
+            total,
+            discount_details: discounts.details,
+            tax_details,
+            cap_adjustments: discounts.cap_adjustments,
+            suppressed_discounts: discounts.suppressed,
+            tax_included: true,
+            cost: self.line_cost(item),
+        })
+    }
+
+    /// (tax, details, total) of an inclusive `line_amount` less `discount`
+    fn inclusive_taxes(
+        &self,
+        item: &Item,
+        rates: &[&TaxRate],
+        (line_amount, discount): (Money, Money),
+        target_jurisdiction: Option<&str>,
+        reverse_charge: bool,
+    ) -> EngineResult<(Money, Vec<TaxDetail>, Money)> {
+        let payable = line_amount - discount;
+        let gross_net = self.back_out_net(line_amount, rates, item.quantity);
+        let discount_net = gross_net - self.back_out_net(payable, rates, item.quantity);
+        // Discounts come off the inclusive amount, so taxes follow them unless a rate says otherwise
+        let (tax_amount, tax_details) = self.charge_taxes(
+            &item.id,
+            (gross_net, discount_net),
+            TaxBase::PostDiscount,
+            item.quantity,
+            target_jurisdiction,
+            reverse_charge,
+        )?;
+        let self_accounted = tax_details
+            .iter()
+            .fold(Money::zero(), |acc, detail| acc + detail.reverse_charged);
+        Ok((tax_amount, tax_details, payable - self_accounted))
+    }
+
+    /// Calculate discount for item
+    fn calculate_item_discount(
+        &self,
+        item: &Item,
+        base_amount: &Money,
+        context: &ConditionContext,
+    ) -> EngineResult<ItemDiscounts> {
+        let quantity = item.quantity;
+        let policy = &self.stacking_policy;
+        let mut total_discount = Money::zero();
+        // (detail, priority) in application order (highest priority first)
+        let mut lines: Vec<(DiscountDetail, i32)> = Vec::new();
+        let mut cap_adjustments = Vec::new();
+        let mut suppressed = Vec::new();
+
+        let config = self.product_discounts.get(&item.id).filter(|_| !context.skip_promotions);
+        if let Some(config) = config {
+            let mut stack = StackState::default();
+
+            // Already in priority order (sorted once in `add_product_discount`)
+            let rules = &config.discounts;
+            let qualifies = |rule: &DiscountRule| {
+                !(context.is_return && rule.depends_on_quantity())
+                    && self.check_conditions(&rule.conditions, quantity, base_amount, context)
+            };
+
+            // Auto-best: the mutually exclusive (non-stackable) rule giving the largest
+            // discount wins instead of the highest-priority one
+            let best_exclusive = if self.auto_best {
+                rules
+                    .iter()
+                    .filter(|rule| !rule.stackable && !policy.always_stacks(&rule.id))
+                    .filter(|rule| policy.excluded_category(&rule.id, item).is_none())
+                    .filter(|rule| qualifies(rule))
+                    .map(|rule| (rule.id.clone(), self.rule_discount(rule, base_amount, quantity).abs()))
+                    // max_by_key keeps the last max; rules are in precedence order, so reverse to
+                    // prefer priority, then the lowest rule id, on ties
+                    .rev()
+                    .max_by_key(|(_, amount)| *amount)
+                    .map(|(id, _)| id)
+            } else {
+                None
+            };
+
+            for rule in rules {
+                if !qualifies(rule) {
+                    continue;
+                }
+                // Qualifying but left out by stacking rules => explained in the result
+                let reason = match policy.excluded_category(&rule.id, item) {
+                    Some(category) => Some(SuppressionReason::CategoryExcluded { category }),
+                    None => stack.blocked(policy, &rule.id, rule.stackable, policy.max_per_line).or_else(|| {
+                        best_exclusive
+                            .as_ref()
+                            .filter(|id| !rule.stackable && !policy.always_stacks(&rule.id) && **id != rule.id)
+                            .map(|id| SuppressionReason::NotBest { chosen: id.clone() })
+                    }),
+                };
+                if let Some(reason) = reason {
+                    suppressed.push(SuppressedDiscount {
+                        rule_id: rule.id.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+
+                // Calculate discount
+                let discount = self.rule_discount(rule, base_amount, quantity);
+
+                total_discount = total_discount + discount.abs();
+                if !discount.is_zero() {
+                    lines.push((
+                        DiscountDetail {
+                            rule_id: rule.id.clone(),
+                            name: rule.name.clone(),
+                            amount: discount.abs(),
+                        },
+                        rule.priority,
+                    ));
+                }
+                stack.record(policy, &rule.id, rule.stackable);
+            }
+
+            // Apply max discount cap
+            if let Some(max_pct) = config.max_discount_percent {
+                let max_discount = base_amount.percentage_of_rounded(max_pct, self.rounding_mode);
+                if total_discount > max_discount {
+                    cap_adjustments = self.apply_discount_cap(&mut lines, total_discount, max_discount)?;
+                    total_discount = max_discount;
+                }
+            }
+        }
+
+        Ok(ItemDiscounts {
+            total: total_discount,
+            details: lines.into_iter().map(|(detail, _)| detail).collect(),
+            cap_adjustments,
+            suppressed,
+        })
+    }
+
+    /// Discount a single rule gives on this line (conditions not checked)
+    fn rule_discount(&self, rule: &DiscountRule, base_amount: &Money, quantity: f64) -> Money {
+        match &rule.discount_type {
+            DiscountType::FixedAmount(cents) => Money::from_cents(*cents),
+            DiscountType::Percentage(pct) => {
+                base_amount.sub_percentage_rounded(*pct, self.rounding_mode) - *base_amount
+            }
+            DiscountType::BuyXGetY {
+                buy,
+                get,
+                free_percent,
+                mode,
+            } => {
+                let set_size = *buy + *get;
+                if quantity <= 0.0 || set_size <= 0.0 {
+                    return Money::zero();
+                }
+                let free_quantity = match mode {
+                    // Epsilon: 6.0 / 3.0 may land just below 2 sets
+                    BogoMode::Discrete => ((quantity + QTY_EPSILON) / set_size).floor() * get,
+                    BogoMode::Weighted => quantity * get / set_size,
+                };
+                // Share of the line that is free, rounded once on the line amount
+                base_amount.mul_ratio_rounded(free_quantity / quantity * free_percent / 100.0, self.rounding_mode)
+            }
+            DiscountType::Tiered(tiers) => {
+                let mut tier_discount = Money::zero();
+                for tier in tiers {
+                    let max = tier.max_qty.unwrap_or(f64::MAX);
+                    if quantity >= tier.min_qty && quantity <= max {
+                        tier_discount =
+                            base_amount.sub_percentage_rounded(tier.discount_percent, self.rounding_mode);
+                        tier_discount = *base_amount - tier_discount;
+                        break;
+                    }
+                }
+                tier_discount
+            }
+            // Priced across all members at cart level (`apply_cart_bundles`)
+            DiscountType::Bundle { .. } => Money::zero(),
+        }
+    }
+
+    /// 🧢 Shrink discount lines so they sum to `cap`, reporting every line touched
+    fn apply_discount_cap(
+        &self,
+        lines: &mut Vec<(DiscountDetail, i32)>,
+        uncapped: Money,
+        cap: Money,
+    ) -> EngineResult<Vec<CapAdjustment>> {
+        let mut adjustments = Vec::new();
+
+        match self.cap_strategy {
+            CapStrategy::ProRate => {
+                let weights: Vec<i64> = lines.iter().map(|(d, _)| d.amount.amount).collect();
+                let shares = cap.allocate(&weights)?;
+                for ((detail, _), share) in lines.iter_mut().zip(shares) {
+                    if share != detail.amount {
+                        adjustments.push(CapAdjustment {
+                            rule_id: detail.rule_id.clone(),
+                            name: detail.name.clone(),
+                            original_amount: detail.amount,
+                            applied_amount: share,
+                            removed: share.is_zero(),
+                        });
+                        detail.amount = share;
+                    }
+                }
+                lines.retain(|(detail, _)| !detail.amount.is_zero());
+            }
+            CapStrategy::DropLowestPriority => {
+                // Lowest priority last; stable so equal priorities drop in reverse application order
+                let mut order: Vec<usize> = (0..lines.len()).collect();
+                order.sort_by(|a, b| lines[*b].1.cmp(&lines[*a].1));
+
+                let mut excess = uncapped - cap;
+                let mut removed = Vec::new();
+                for index in order.into_iter().rev() {
+                    if !excess.is_positive() {
+                        break;
+                    }
+                    let detail = &mut lines[index].0;
+                    let original = detail.amount;
+                    let applied = if original <= excess { Money::zero() } else { original - excess };
+                    excess = excess - (original - applied);
+                    adjustments.push(CapAdjustment {
+                        rule_id: detail.rule_id.clone(),
+                        name: detail.name.clone(),
+                        original_amount: original,
+                        applied_amount: applied,
+                        removed: applied.is_zero(),
+                    });
+                    detail.amount = applied;
+                    if applied.is_zero() {
+                        removed.push(index);
+                    }
+                }
+                removed.sort_unstable();
+                for index in removed.into_iter().rev() {
+                    lines.remove(index);
+                }
+            }
+        }
+
+        Ok(adjustments)
+    }
+
+    /// Calculate tax for item
+    /// Per-unit taxes (excise) are computed first so percentage taxes flagged with
+    /// `include_excise_in_base` can apply on the excise-inclusive amount.
+    fn calculate_item_tax(
+        &self,
+        item_id: &str,
+        gross_amount: &Money,
+        discount_amount: &Money,
+        quantity: f64,
+        target_jurisdiction: Option<&str>,
+        reverse_charge: bool,
+    ) -> EngineResult<(Money, Vec<TaxDetail>)> {
+        let engine_base = match self.calculation_order {
+            CalculationOrder::DiscountFirst => TaxBase::PostDiscount,
+            CalculationOrder::TaxFirst | CalculationOrder::Parallel => TaxBase::PreDiscount,
+        };
+        self.charge_taxes(
+            item_id,
+            (*gross_amount, *discount_amount),
+            engine_base,
+            quantity,
+            target_jurisdiction,
+            reverse_charge,
+        )
+    }
+
+    /// Per-rate tax of one line; `engine_base` is the taxable amount of rates without their own `discount_base`
+    fn charge_taxes(
+        &self,
+        item_id: &str,
+        (gross_amount, discount_amount): (Money, Money),
+        engine_base: TaxBase,
+        quantity: f64,
+        target_jurisdiction: Option<&str>,
+        reverse_charge: bool,
+    ) -> EngineResult<(Money, Vec<TaxDetail>)> {
+        // Check product-specific taxes, else apply global taxes
+        let candidates: Vec<&TaxRate> = match self.product_taxes.get(item_id) {
+            Some(config) if config.tax_exempt => return Ok((Money::zero(), Vec::new())),
+            Some(config) => config.tax_rates.iter().collect(),
+            None => self
+                .global_tax_rates
+                .iter()
+                .filter(|t| match &t.applies_to {
+                    TaxAppliesTo::All | TaxAppliesTo::Region(_) => true,
+                    TaxAppliesTo::Product(pid) => pid == item_id,
+                    _ => false,
+                })
+                .collect(),
+        };
+        let Some(target) = self.tax_jurisdiction(target_jurisdiction) else {
+            return Ok((Money::zero(), Vec::new()));
+        };
+        let mut applicable: Vec<&TaxRate> = candidates
+            .into_iter()
+            .filter(|t| target.is_none_or(|target| jurisdiction::applies(&t.jurisdiction, target)))
+            .filter(|t| match &t.applies_to {
+                TaxAppliesTo::Region(region) => target.is_none_or(|target| jurisdiction::applies(region, target)),
+                _ => true,
+            })
+            .collect();
+        // Stable: equal sequences keep the configured order
+        applicable.sort_by_key(|t| t.sequence);
+
+        let net_amount = gross_amount - discount_amount;
+        let taxable = |tax_rate: &TaxRate| match tax_rate.discount_base.unwrap_or(engine_base) {
+            TaxBase::PreDiscount => gross_amount,
+            TaxBase::PostDiscount => net_amount,
+        };
+
+        let rounding = self.tax_rounding();
+        let reverse_charged = |tax_rate: &TaxRate| reverse_charge && tax_rate.treatment == TaxTreatment::ReverseCharge;
+        let mut details = Vec::new();
+        let mut excise_total = Money::zero();
+        for tax_rate in &applicable {
+            if let TaxBasis::PerUnit(per_unit) = tax_rate.basis {
+                let excise = tax_rate.bounded(taxable(tax_rate), per_unit.mul_ratio_rounded(quantity, rounding));
+                if reverse_charged(tax_rate) {
+                    details.push(TaxDetail::reverse_charge(tax_rate, taxable(tax_rate), excise));
+                    continue;
+                }
+                excise_total = excise_total + excise;
+                details.push(TaxDetail::new(tax_rate, taxable(tax_rate), excise));
+            }
+        }
+
+        let mut total_tax = excise_total;
+        // (sequence, amount) of the percentage taxes charged so far
+        let mut charged: Vec<(u32, Money)> = Vec::new();
+        for tax_rate in &applicable {
+            if tax_rate.basis != TaxBasis::Percentage {
+                continue;
+            }
+            let mut base = if tax_rate.include_excise_in_base {
+                taxable(tax_rate) + excise_total
+            } else {
+                taxable(tax_rate)
+            };
+            if tax_rate.compound {
+                base = charged
+                    .iter()
+                    .filter(|(sequence, _)| *sequence < tax_rate.sequence)
+                    .fold(base, |acc, (_, tax)| acc + *tax);
+            }
+            let tax = tax_rate.bounded(base, base.percentage_of_rounded(tax_rate.rate, rounding));
+            if reverse_charged(tax_rate) {
+                details.push(TaxDetail::reverse_charge(tax_rate, base, tax));
+                continue;
+            }
+            total_tax = total_tax + tax;
+            charged.push((tax_rate.sequence, tax));
+            details.push(TaxDetail::new(tax_rate, base, tax));
+        }
+        details.retain(|detail| !detail.amount.is_zero() || !detail.reverse_charged.is_zero());
+
+        Ok((total_tax, details))
+    }
+
+    /// 🗺️ Jurisdiction a line is taxed in: the caller's, else the resolver's home jurisdiction.
+    /// Some(None) when neither is set - an untargeted calculation, where every configured rate
+    /// applies. None outside the seller's nexus (no tax).
+    fn tax_jurisdiction<'a>(&'a self, target_jurisdiction: Option<&'a str>) -> Option<Option<&'a str>> {
+        match target_jurisdiction.or(self.jurisdictions.home()) {
+            Some(target) if !self.jurisdictions.has_nexus(target) => None,
+            target => Some(target),
+        }
+    }
+
+    /// Reverse charge applies to this customer's purchase in the (defaulted) jurisdiction
+    fn reverse_charge(&self, customer: Option<&CustomerContext>, target_jurisdiction: Option<&str>) -> bool {
+        let target = target_jurisdiction.or(self.jurisdictions.home());
+        customer.is_some_and(|customer| customer.reverse_charge_applies(target))
+    }
+
+    /// Check discount conditions
+    fn check_conditions(
+        &self,
+        conditions: &[DiscountCondition],
+        quantity: f64,
+        amount: &Money,
+        context: &ConditionContext,
+    ) -> bool {
+        if conditions.is_empty() {
+            return true;
+        }
+
+        for condition in conditions {
+            let met = match condition {
+                DiscountCondition::MinQuantity(min) => quantity >= *min,
+                DiscountCondition::MinAmount(cents) => amount.amount >= *cents,
+                DiscountCondition::PromoCode(code) => {
+                    context.promo_codes.iter().any(|entered| promo::same_code(entered, code))
+                }
+                DiscountCondition::CustomerGroup(group) => context.in_group(group),
+                DiscountCondition::AnyOf(groups) => groups.iter().any(|group| context.in_group(group)),
+                DiscountCondition::FirstPurchase => context.customer.is_some_and(CustomerContext::is_first_purchase),
+                DiscountCondition::CartContains(item_id) => context
+                    .cart_items
+                    .iter()
+                    .any(|i| i.id == *item_id || i.name == *item_id),
+                DiscountCondition::DateRange { from, to } => {
+                    self.store_timezone.window_contains(from, to, context.now)
+                }
+            };
+            if !met {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 📊 Calculate full cart
+    pub fn calculate_cart(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+    ) -> EngineResult<CartCalculation> {
+        self.calculate_cart_at(cart, promo_codes, target_jurisdiction, self.now())
+    }
+
+    /// 📊 Calculate full cart for a `CalculationContext`
+    /// The context's customer replaces `cart.customer`, its timestamp replaces the engine
+    /// clock for date conditions and promo validity, and its currency must be the cart's.
+    pub fn calculate_cart_with_context(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        context: &CalculationContext,
+    ) -> EngineResult<CartCalculation> {
+        context.check_currency("Cart", cart.currency)?;
+        let cart = context.apply_to(cart);
+        let now = context.timestamp.unwrap_or_else(|| self.now());
+        self.calculate_cart_at(&cart, promo_codes, context.jurisdiction.as_deref(), now)
+    }
+
+    fn calculate_cart_at(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> EngineResult<CartCalculation> {
+        let started = std::time::Instant::now();
+        self.check_cart(cart)?;
+        let customer_id = ConditionContext::for_cart(cart, promo_codes, now).customer_id;
+        let promo_codes = &*self.usable_promo_codes(customer_id, cart.subtotal(), promo_codes, now);
+        let mut stream = self.line_stream(cart, Cow::Borrowed(promo_codes), target_jurisdiction, now);
+        let mut items = stream.by_ref().collect::<EngineResult<Vec<_>>>()?;
+        let mut totals = stream.totals()?;
+        if self.apply_cart_bundles(cart, promo_codes, target_jurisdiction, now, &mut items)? {
+            totals = self.cart_totals(&items)?;
+        }
+        let mut suppressed_discounts = Vec::new();
+        if self.apply_cart_discounts(cart, promo_codes, target_jurisdiction, now, &mut items, &mut suppressed_discounts)? {
+            totals = self.cart_totals(&items)?;
+        }
+        if self.apply_small_order_tax(cart, target_jurisdiction, &totals, &mut items) {
+            totals = self.cart_totals(&items)?;
+        }
+        // Engine lines reconcile by construction; this keeps the cart identity exact if one doesn't
+        let mut rounding_adjustment = Money::zero();
+        if let Some(adjustment) = absorb_rounding_residue(&mut items, self.residue_target) {
+            totals = self.cart_totals(&items)?;
+            totals.grand_total = totals.grand_total + adjustment;
+            rounding_adjustment = adjustment;
+        }
+        metrics::record_calculation(started.elapsed());
+        let (included_tax, added_tax) = CartCalculation::split_tax(&items);
+
+        Ok(CartCalculation {
+            items,
+            subtotal: totals.subtotal,
+            total_discount: totals.total_discount,
+            total_tax: totals.total_tax,
+            grand_total: totals.grand_total,
+            rounding_adjustment,
+            suppressed_discounts,
+            included_tax,
+            added_tax,
+            deposits: Money::zero(),
+        })
+    }
+
+    /// 🗺️ Calculate for a ship-to / bill-to address
+    /// Rates apply along the resolved country → state → city chain ("US-CA" rates reach a
+    /// Los Angeles address); outside the seller's nexus no tax is collected.
+    pub fn calculate_cart_for_address(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        address: &TaxAddress,
+    ) -> EngineResult<CartCalculation> {
+        let resolved = self.jurisdictions.resolve(address)?;
+        self.calculate_cart(cart, promo_codes, resolved.code())
+    }
+
+    /// 📦 Apply cart bundles to finished lines; true if any bundle matched
+    /// The bundle discount is rounded once on the covered sets' combined net and the tax it
+    /// removes (when tax follows the discount) once on the members' combined tax; both are
+    /// split back with `Money::allocate` so member lines sum exactly to the bundle figures.
+    fn apply_cart_bundles(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        now: DateTime<Utc>,
+        lines: &mut [ItemCalculation],
+    ) -> EngineResult<bool> {
+        let mut applied = false;
+        for offer in self.bundle_offers(cart, promo_codes, now) {
+            let Some((members, nets, line_net)) = Self::bundle_sets(cart, lines, &offer.members) else {
+                continue;
+            };
+            let covered = Money::from_cents(nets.iter().sum());
+            let discount = covered.percentage_of_rounded(offer.discount_percent, self.rounding_mode);
+            if !discount.is_positive() || !line_net.is_positive() {
+                continue;
+            }
+            let spread = DiscountSpread {
+                members: &members,
+                nets: &nets,
+                discount,
+                // Whole lines covered => exactly the bundle percent of their tax
+                tax_percent: offer.discount_percent * covered.amount as f64 / line_net.amount as f64,
+                rule_id: offer.id,
+                name: offer.name,
+            };
+            self.spread_discount(cart, target_jurisdiction, lines, spread)?;
+            applied = true;
+        }
+        Ok(applied)
+    }
+
+    /// Configured `CartBundle`s, then product `DiscountType::Bundle` rules (once per rule id)
+    /// carried by a sale line whose conditions hold for the cart
+    fn bundle_offers<'a>(&'a self, cart: &'a Cart, promo_codes: &'a [String], now: DateTime<Utc>) -> Vec<BundleOffer<'a>> {
+        let mut offers: Vec<BundleOffer> = self
+            .cart_bundles
+            .iter()
+            .map(|bundle| BundleOffer {
+                id: &bundle.id,
+                name: &bundle.name,
+                members: bundle.items.iter().map(String::as_str).collect(),
+                discount_percent: bundle.discount_percent,
+            })
+            .collect();
+        let context = ConditionContext::for_cart(cart, promo_codes, now);
+        for item in cart.items.iter().filter(|item| item.quantity > 0.0) {
+            let Some(config) = self.product_discounts.get(&item.id) else { continue };
+            for rule in &config.discounts {
+                let DiscountType::Bundle { items, discount_percent } = &rule.discount_type else { continue };
+                if offers.iter().any(|offer| offer.id == rule.id)
+                    || !self.check_conditions(&rule.conditions, item.quantity, &item.total(), &context)
+                {
+                    continue;
+                }
+                let mut members: Vec<&str> = items.iter().map(String::as_str).collect();
+                if !items.iter().any(|member| *member == item.id || *member == item.name) {
+                    members.push(&item.id);
+                }
+                offers.push(BundleOffer {
+                    id: &rule.id,
+                    name: &rule.name,
+                    members,
+                    discount_percent: *discount_percent,
+                });
+            }
+        }
+        offers
+    }
+
+    /// Complete sets of `members` in the cart: (member lines, net of the units in complete
+    /// sets per line, full net of those lines); None without a complete set
+    fn bundle_sets(cart: &Cart, lines: &[ItemCalculation], members: &[&str]) -> Option<(Vec<usize>, Vec<i64>, Money)> {
+        // Units per set of each distinct member
+        let mut per_set: Vec<(&str, f64)> = Vec::new();
+        for member in members {
+            match per_set.iter_mut().find(|(id, _)| id == member) {
+                Some((_, units)) => *units += 1.0,
+                None => per_set.push((member, 1.0)),
+            }
+        }
+        let matching = |member: &str| -> Vec<usize> {
+            (0..lines.len())
+                .filter(|&i| {
+                    cart.items.get(i).is_some_and(|item| item.quantity > 0.0 && (item.id == member || item.name == member))
+                })
+                .collect()
+        };
+        let sets = per_set
+            .iter()
+            .map(|(member, units)| {
+                let available: f64 = matching(member).into_iter().map(|i| cart.items[i].quantity).sum();
+                ((available + QTY_EPSILON) / units).floor()
+            })
+            .fold(f64::INFINITY, f64::min);
+        if per_set.is_empty() || sets < 1.0 {
+            return None;
+        }
+
+        let mut indexes = Vec::new();
+        let mut nets = Vec::new();
+        let mut line_net = Money::zero();
+        for (member, units) in &per_set {
+            let mut needed = sets * units;
+            for i in matching(member) {
+                if needed <= 0.0 || indexes.contains(&i) {
+                    continue;
+                }
+                let quantity = cart.items[i].quantity;
+                let used = needed.min(quantity);
+                needed -= used;
+                let net = (lines[i].total - lines[i].tax_amount).max(Money::zero());
+                indexes.push(i);
+                nets.push(net.mul_ratio(used / quantity).amount);
+                line_net = line_net + net;
+            }
+        }
+        Some((indexes, nets, line_net))
+    }
+
+    /// Split a cart-level discount over its member lines by net value. Lines whose tax is
+    /// charged on the discounted amount are re-taxed rate by rate on their new net (per-unit
+    /// excise and min/max bounds do not shrink with the net); a kit line, whose taxes belong
+    /// to its components, loses `tax_percent` of its tax - rounded once, then allocated by
+    /// line tax.
+    fn spread_discount(
+        &self,
+        cart: &Cart,
+        target_jurisdiction: Option<&str>,
+        lines: &mut [ItemCalculation],
+        spread: DiscountSpread,
+    ) -> EngineResult<()> {
+        let DiscountSpread {
+            members,
+            nets,
+            discount,
+            tax_percent,
+            rule_id,
+            name,
+        } = spread;
+        let shares = discount.allocate(nets)?;
+
+        // Tax moves with the discount only where it is charged on the discounted amount
+        let follows_discount = |i: usize| {
+            self.calculation_order == CalculationOrder::DiscountFirst
+                || self.is_tax_inclusive(&cart.items[i].id, target_jurisdiction)
+        };
+        let is_kit = |i: usize| self.kits.contains_key(&cart.items[i].id);
+        let tax_weights: Vec<i64> = members
+            .iter()
+            .map(|&i| {
+                if follows_discount(i) && is_kit(i) {
+                    lines[i].tax_amount.amount.max(0)
+                } else {
+                    0
+                }
+            })
+            .collect();
+        let kit_tax = Money::from_cents(tax_weights.iter().sum());
+        let tax_reduction = kit_tax.percentage_of_rounded(tax_percent, self.tax_rounding());
+        let tax_shares = if tax_reduction.is_positive() {
+            tax_reduction.allocate(&tax_weights)?
+        } else {
+            vec![Money::zero(); members.len()]
+        };
+        let reverse_charge = self.reverse_charge(cart.customer.as_ref(), target_jurisdiction);
+
+        for ((&index, share), tax_share) in members.iter().zip(shares).zip(tax_shares) {
+            let item = &cart.items[index];
+            let line = &mut lines[index];
+            line.discount_details.push(DiscountDetail {
+                rule_id: rule_id.to_string(),
+                name: name.to_string(),
+                amount: share,
+            });
+            if follows_discount(index) && !is_kit(index) {
+                self.retax_discounted_line(item, line, share, target_jurisdiction, reverse_charge)?;
+                continue;
+            }
+            line.discount_amount = line.discount_amount + share;
+            line.tax_amount = line.tax_amount - tax_share;
+            line.total = line.total - share - tax_share;
+            let detail_weights: Vec<i64> = line.tax_details.iter().map(|d| d.amount.amount.max(0)).collect();
+            if tax_share.is_positive() && detail_weights.iter().sum::<i64>() > 0 {
+                for (detail, part) in line.tax_details.iter_mut().zip(tax_share.allocate(&detail_weights)?) {
+                    detail.amount = detail.amount - part;
+                }
+            }
+            // Reported reverse-charge amounts and the taxed bases shrink with the discount like charged tax
+            if follows_discount(index) {
+                for detail in line.tax_details.iter_mut() {
+                    let reduction = detail.reverse_charged.percentage_of_rounded(tax_percent, self.tax_rounding());
+                    detail.reverse_charged = detail.reverse_charged - reduction;
+                    let reduction = detail.taxable_base.percentage_of_rounded(tax_percent, self.rounding_mode);
+                    detail.taxable_base = detail.taxable_base - reduction;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a cart-level `discount` to a line and charge its taxes again on the discounted amount
+    fn retax_discounted_line(
+        &self,
+        item: &Item,
+        line: &mut ItemCalculation,
+        discount: Money,
+        target_jurisdiction: Option<&str>,
+        reverse_charge: bool,
+    ) -> EngineResult<()> {
+        if line.tax_included {
+            let rates = self.inclusive_tax_rates(&item.id, target_jurisdiction).unwrap_or_default();
+            let self_accounted = line
+                .tax_details
+                .iter()
+                .fold(Money::zero(), |acc, detail| acc + detail.reverse_charged);
+            // The inclusive line amount (price × quantity, possibly from a price list)
+            let line_amount = line.total + line.discount_amount + self_accounted;
+            line.discount_amount = line.discount_amount + discount;
+            let (tax_amount, tax_details, total) = self.inclusive_taxes(
+                item,
+                &rates,
+                (line_amount, line.discount_amount),
+                target_jurisdiction,
+                reverse_charge,
+            )?;
+            line.base_amount = total - tax_amount + line.discount_amount;
+            line.tax_amount = tax_amount;
+            line.tax_details = tax_details;
+            line.total = total;
+        } else {
+            line.discount_amount = line.discount_amount + discount;
+            let (tax_amount, tax_details) = self.calculate_item_tax(
+                &item.id,
+                &line.base_amount,
+                &line.discount_amount,
+                item.quantity,
+                target_jurisdiction,
+                reverse_charge,
+            )?;
+            line.tax_amount = tax_amount;
+            line.tax_details = tax_details;
+            line.total = line.base_amount - line.discount_amount + tax_amount;
+        }
+        Ok(())
+    }
+
+    /// 🧾 Apply the configured cart discounts to finished lines; true if any applied
+    /// A non-stackable cart discount ends the run (later ones are suppressed); lines whose
+    /// category excludes a discount neither count towards it nor share in it.
+    fn apply_cart_discounts(
+        &self,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+        now: DateTime<Utc>,
+        lines: &mut [ItemCalculation],
+        suppressed: &mut Vec<SuppressedDiscount>,
+    ) -> EngineResult<bool> {
+        let policy = &self.stacking_policy;
+        let context = ConditionContext::for_cart(cart, promo_codes, now);
+        let mut stack = StackState::default();
+        let mut applied = false;
+        for config in &self.cart_discounts {
+            let (members, excluded): (Vec<usize>, Vec<usize>) = Self::sale_lines(cart, lines)
+                .partition(|&i| policy.excluded_category(&config.id, &cart.items[i]).is_none());
+            let quantity: f64 = members.iter().map(|&i| cart.items[i].quantity).sum();
+            let net = members
+                .iter()
+                .fold(Money::zero(), |acc, &i| acc + lines[i].net_revenue().max(Money::zero()));
+            if !self.check_conditions(&config.conditions, quantity, &net, &context) {
+                continue;
+            }
+            let blocked = match stack.non_stackable {
+                Some(applied) if !policy.always_stacks(&config.id) => {
+                    Some(SuppressionReason::NotStackable { applied: applied.to_string() })
+                }
+                _ => stack.blocked(policy, &config.id, true, policy.max_cart_discounts),
+            };
+            if let Some(reason) = blocked {
+                suppressed.push(SuppressedDiscount {
+                    rule_id: config.id.clone(),
+                    reason,
+                });
+                continue;
+            }
+            let tier_percent = |tiers: &[CartTier], reached: f64| {
+                tiers
+                    .iter()
+                    .filter(|tier| reached + QTY_EPSILON >= tier.min)
+                    .max_by(|a, b| a.min.total_cmp(&b.min))
+                    .map(|tier| tier.discount_percent)
+            };
+            let amount = match &config.discount_type {
+                CartDiscountType::FixedAmount(amount) => Some(*amount),
+                CartDiscountType::Percentage(percent) => Some(net.percentage_of_rounded(*percent, self.rounding_mode)),
+                CartDiscountType::TieredByTotal(tiers) => tier_percent(tiers, net.amount as f64)
+                    .map(|percent| net.percentage_of_rounded(percent, self.rounding_mode)),
+                CartDiscountType::TieredByQuantity(tiers) => tier_percent(tiers, quantity)
+                    .map(|percent| net.percentage_of_rounded(percent, self.rounding_mode)),
+            };
+            let Some(amount) = amount else { continue };
+            let spread = self.spread_cart_discount(cart, target_jurisdiction, lines, &members, amount, (&config.id, &config.name))?;
+            if spread.is_positive() {
+                applied = true;
+                stack.record(policy, &config.id, config.stackable);
+                for i in excluded {
+                    let category = cart.items[i].category().unwrap_or_default().to_string();
+                    lines[i].suppressed_discounts.push(SuppressedDiscount {
+                        rule_id: config.id.clone(),
+                        reason: SuppressionReason::CategoryExcluded { category },
+                    });
+                }
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Indexes of the sale (positive quantity) lines
+    fn sale_lines<'a>(cart: &'a Cart, lines: &'a [ItemCalculation]) -> impl Iterator<Item = usize> + 'a {
+        (0..lines.len()).filter(|&i| cart.items.get(i).is_some_and(|item| item.quantity > 0.0))
+    }
+
+    /// Spread `amount` (capped at the members' net value) over the member lines by net value
+    fn spread_cart_discount(
+        &self,
+        cart: &Cart,
+        target_jurisdiction: Option<&str>,
+        lines: &mut [ItemCalculation],
+        members: &[usize],
+        amount: Money,
+        (rule_id, name): (&str, &str),
+    ) -> EngineResult<Money> {
+        let nets: Vec<i64> = members.iter().map(|&i| lines[i].net_revenue().amount.max(0)).collect();
+        let combined = Money::from_cents(nets.iter().sum());
+        let discount = amount.min(combined);
+        if !discount.is_positive() {
+            return Ok(Money::zero());
+        }
+        let spread = DiscountSpread {
+            members,
+            nets: &nets,
+            discount,
+            tax_percent: discount.amount as f64 / combined.amount as f64 * 100.0,
+            rule_id,
+            name,
+        };
+        self.spread_discount(cart, target_jurisdiction, lines, spread)?;
+        Ok(discount)
+    }
+
+    /// 🎟️ Apply a fixed cart-level discount to a finished calculation
+    /// (Ex: loyalty points redeemed as a discount). Capped at the cart's net value before
+    /// tax and spread over the sale lines like a bundle, so tax charged on the discounted
+    /// amount falls with it. Returns the amount actually applied.
+    pub fn apply_cart_discount(
+        &self,
+        cart: &Cart,
+        target_jurisdiction: Option<&str>,
+        calculation: &mut CartCalculation,
+        amount: Money,
+        (rule_id, name): (&str, &str),
+    ) -> EngineResult<Money> {
+        let members: Vec<usize> = Self::sale_lines(cart, &calculation.items).collect();
+        let discount =
+            self.spread_cart_discount(cart, target_jurisdiction, &mut calculation.items, &members, amount, (rule_id, name))?;
+        if discount.is_zero() {
+            return Ok(discount);
+        }
+        let totals = self.cart_totals(&calculation.items)?;
+        calculation.total_discount = totals.total_discount;
+        calculation.total_tax = totals.total_tax;
+        calculation.grand_total = totals.grand_total;
+        (calculation.included_tax, calculation.added_tax) = CartCalculation::split_tax(&calculation.items);
+        Ok(discount)
+    }
+
+    /// 🪶 Re-tax every line when the order is below the small-order threshold; true if applied
+    fn apply_small_order_tax(
+        &self,
+        cart: &Cart,
+        target_jurisdiction: Option<&str>,
+        totals: &CartTotals,
+        lines: &mut [ItemCalculation],
+    ) -> bool {
+        let Some(rule) = &self.small_order_tax else {
+            return false;
+        };
+        if totals.subtotal - totals.total_discount >= rule.threshold {
+            return false;
+        }
+        for (item, line) in cart.items.iter().zip(lines.iter_mut()) {
+            // Exempt products and lines with no rate in this jurisdiction stay untaxed
+            if line.tax_details.is_empty() {
+                continue;
+            }
+            let inclusive = self.is_tax_inclusive(&item.id, target_jurisdiction);
+            // The buyer self-accounts for reverse-charged taxes; those stay reported as they are
+            let reverse_charged: Vec<TaxDetail> = line
+                .tax_details
+                .iter()
+                .filter(|detail| detail.treatment == TaxTreatment::ReverseCharge)
+                .cloned()
+                .collect();
+            let (tax, detail) = match rule.treatment {
+                SmallOrderTreatment::Exempt => (Money::zero(), None),
+                SmallOrderTreatment::FlatRate(_) if !reverse_charged.is_empty() => (Money::zero(), None),
+                SmallOrderTreatment::FlatRate(rate) => {
+                    let tax = if inclusive {
+                        line.total.mul_ratio_rounded(rate / (100.0 + rate), self.tax_rounding())
+                    } else {
+                        line.net_revenue().percentage_of_rounded(rate, self.tax_rounding())
+                    };
+                    let detail = TaxDetail {
+                        name: "Small-order flat tax".to_string(),
+                        rate,
+                        amount: tax,
+                        treatment: TaxTreatment::Standard,
+                        reverse_charged: Money::zero(),
+                        taxable_base: if inclusive { line.total - tax } else { line.net_revenue() },
+                    };
+                    (tax, Some(detail))
+                }
+            };
+            if inclusive {
+                // The shelf price stays; only its tax share changes
+                line.base_amount = line.base_amount + line.tax_amount - tax;
+            } else {
+                line.total = line.net_revenue() + tax;
+            }
+            line.tax_amount = tax;
+            line.tax_details = reverse_charged.into_iter().chain(detail).collect();
+        }
+        true
+    }
+
+    fn cart_totals(&self, lines: &[ItemCalculation]) -> EngineResult<CartTotals> {
+        let sum = |f: fn(&ItemCalculation) -> Money| lines.iter().fold(Money::zero(), |acc, l| acc + f(l));
+        let (subtotal, total_discount, total_tax) =
+            (sum(|l| l.base_amount), sum(|l| l.discount_amount), sum(|l| l.tax_amount));
+        let grand_total = self
+            .total_formula
+            .evaluate(&BucketAmounts::new(subtotal, total_discount, total_tax))?
+            .grand_total;
+        Ok(CartTotals {
+            subtotal,
+            total_discount,
+            total_tax,
+            grand_total,
+        })
+    }
+
+    /// Cart limits, supported currencies, one currency per cart
+    fn check_cart(&self, cart: &Cart) -> EngineResult<()> {
+        self.cart_limits.check(cart)?;
+        self.currencies.ensure_supported(cart.currency)?;
+        for item in &cart.items {
+            self.currencies.ensure_supported(item.currency)?;
+            if item.currency != cart.currency {
+                return Err(EngineError::Validation {
+                    message: format!(
+                        "Item '{}' is priced in {} but the cart is in {}; convert it first (Cart::convert_items)",
+                        item.name,
+                        item.currency.code(),
+                        cart.currency.code()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Configured pass that needs every line before it can run (None => lines are final as priced)
+    fn cart_level_pass(&self) -> Option<&'static str> {
+        let bundle_rules = self
+            .product_discounts
+            .values()
+            .flat_map(|config| &config.discounts)
+            .any(|rule| matches!(rule.discount_type, DiscountType::Bundle { .. }));
+        if !self.cart_bundles.is_empty() || bundle_rules {
+            Some("bundles")
+        } else if !self.cart_discounts.is_empty() {
+            Some("cart discounts")
+        } else if self.small_order_tax.is_some() {
+            Some("a small-order tax")
+        } else {
+            None
+        }
+    }
+
+    /// 🌊 Line-by-line calculation for very large carts (B2B quotes)
+    /// Lines are yielded one at a time so they can be written out without holding them all;
+    /// call `totals()` once the iterator is exhausted. The cart and its promo codes are checked
+    /// as in `calculate_cart`. Bundles, cart discounts and a small-order tax need every line
+    /// first, so an engine configured with them is rejected rather than streamed to other totals.
+    pub fn calculate_cart_stream<'a>(
+        &'a self,
+        cart: &'a Cart,
+        promo_codes: &'a [String],
+        target_jurisdiction: Option<&'a str>,
+    ) -> EngineResult<CartCalculationStream<'a>> {
+        if let Some(pass) = self.cart_level_pass() {
+            return Err(EngineError::Validation {
+                message: format!("Cannot stream a cart through {}; use calculate_cart", pass),
+            });
+        }
+        self.check_cart(cart)?;
+        let now = self.now();
+        let customer_id = ConditionContext::for_cart(cart, promo_codes, now).customer_id;
+        let promo_codes = self.usable_promo_codes(customer_id, cart.subtotal(), promo_codes, now);
+        Ok(self.line_stream(cart, promo_codes, target_jurisdiction, now))
+    }
+
+    fn line_stream<'a>(
+        &'a self,
+        cart: &'a Cart,
+        promo_codes: Cow<'a, [String]>,
+        target_jurisdiction: Option<&'a str>,
+        now: DateTime<Utc>,
+    ) -> CartCalculationStream<'a> {
+        CartCalculationStream {
+            engine: self,
+            cart,
+            promo_codes,
+            target_jurisdiction,
+            now,
+            next_index: 0,
+            subtotal: Money::zero(),
+            total_discount: Money::zero(),
+            total_tax: Money::zero(),
+        }
+    }
+
+    /// 🔬 Preview a proposed config against the current one on the same cart
+    /// Ex: `let mut alt = engine.clone(); alt.add_product_discount(..);`
+    /// Neither engine nor the cart is modified.
+    pub fn compare(
+        &self,
+        cart: &Cart,
+        alternative: &MixedScenarioEngine,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+    ) -> EngineResult<PricingComparison> {
+        let baseline = self.calculate_cart(cart, promo_codes, target_jurisdiction)?;
+        let proposed = alternative.calculate_cart(cart, promo_codes, target_jurisdiction)?;
+
+        let lines = baseline
+            .items
+            .iter()
+            .zip(&proposed.items)
+            .map(|(before, after)| LineDelta {
+                item_id: before.item_id.clone(),
+                baseline_total: before.total,
+                alternative_total: after.total,
+                discount_delta: after.discount_amount - before.discount_amount,
+                tax_delta: after.tax_amount - before.tax_amount,
+                total_delta: after.total - before.total,
+            })
+            .collect();
+
+        Ok(PricingComparison {
+            lines,
+            discount_delta: proposed.total_discount - baseline.total_discount,
+            tax_delta: proposed.total_tax - baseline.total_tax,
+            grand_total_delta: proposed.grand_total - baseline.grand_total,
+            baseline,
+            alternative: proposed,
+        })
+    }
+}
+
+/// 🌊 Streaming cart calculation (see `MixedScenarioEngine::calculate_cart_stream`)
+pub struct CartCalculationStream<'a> {
+    engine: &'a MixedScenarioEngine,
+    cart: &'a Cart,
+    promo_codes: Cow<'a, [String]>,
+    target_jurisdiction: Option<&'a str>,
+    now: DateTime<Utc>,
+    next_index: usize,
+    subtotal: Money,
+    total_discount: Money,
+    total_tax: Money,
+}
+
+impl CartCalculationStream<'_> {
+    /// Totals of the lines yielded so far
+    pub fn totals(&self) -> EngineResult<CartTotals> {
+        let grand_total = self
+            .engine
+            .total_formula
+            .evaluate(&BucketAmounts::new(self.subtotal, self.total_discount, self.total_tax))?
+            .grand_total;
+        Ok(CartTotals {
+            subtotal: self.subtotal,
+            total_discount: self.total_discount,
+            total_tax: self.total_tax,
+            grand_total,
+        })
+    }
+}
+
+impl Iterator for CartCalculationStream<'_> {
+    type Item = EngineResult<ItemCalculation>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.cart.items.get(self.next_index)?;
+        self.next_index += 1;
+
+        let context = ConditionContext::for_cart(self.cart, &self.promo_codes, self.now);
+        let result = self.engine.price_item(item, self.target_jurisdiction, context);
+        if let Ok(line) = &result {
+            self.subtotal = self.subtotal + line.base_amount;
+            self.total_discount = self.total_discount + line.discount_amount;
+            self.total_tax = self.total_tax + line.tax_amount;
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.cart.items.len() - self.next_index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// ============================================================================
+/// 📸 Engine Snapshot (ගණනය කිරීමේ සැකසුම් ඡායාරූපය)
+/// ============================================================================
+/// Dispute එකකදී අතීත ගණනයක් නැවත ලබා ගැනීමට සම්පූර්ණ pricing config එක
+/// (rules, taxes, order, rounding, caps, formula, time zone) සහ pricing instant එක
+/// serialize කළ හැකි ලෙස ගබඩා කරයි. Live engine එක පසුව වෙනස් වුවත්
+/// `replay` එකම ප්‍රතිඵලය ලබා දෙයි.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub product_taxes: BTreeMap<String, ProductTaxConfig>,
+    pub product_discounts: BTreeMap<String, ProductDiscountConfig>,
+    pub global_tax_rates: Vec<TaxRate>,
+    pub calculation_order: CalculationOrder,
+    pub total_formula: TotalFormula,
+    pub cap_strategy: CapStrategy,
+    pub rounding_mode: RoundingMode,
+    pub tax_rounding: Option<RoundingMode>,
+    pub cart_limits: CartLimits,
+    pub currencies: CurrencyRegistry,
+    pub auto_best: bool,
+    pub store_timezone: StoreTimeZone,
+    #[serde(default)]
+    pub cart_bundles: Vec<CartBundle>,
+    #[serde(default)]
+    pub small_order_tax: Option<SmallOrderTax>,
+    #[serde(default)]
+    pub residue_target: ResidueTarget,
+    #[serde(default)]
+    pub price_lists: Vec<PriceList>,
+    #[serde(default)]
+    pub kits: BTreeMap<String, Kit>,
+    #[serde(default)]
+    pub cart_discounts: Vec<CartDiscountConfig>,
+    #[serde(default)]
+    pub stacking_policy: StackingPolicy,
+    #[serde(default)]
+    pub jurisdictions: JurisdictionResolver,
+    #[serde(default = "default_max_rate_percent")]
+    pub max_rate_percent: f64,
+    /// Codes the promo manager accepted for the calculation the snapshot was taken for
+    /// (`snapshot_for`); None => no promo manager or a plain `snapshot()`: every code applies
+    #[serde(default)]
+    pub accepted_promo_codes: Option<Vec<String>>,
+    /// Fixed pricing time, or the snapshot time when the engine used the live clock
+    pub pricing_time: DateTime<Utc>,
+}
+
+fn default_max_rate_percent() -> f64 {
+    DEFAULT_MAX_RATE_PERCENT
+}
+
+/// 🧭 Per-call calculation inputs beyond the cart (`calculate_cart_with_context`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalculationContext {
+    /// Tax jurisdiction; None => the resolver's home jurisdiction (none set => every rate applies)
+    #[serde(default)]
+    pub jurisdiction: Option<String>,
+    /// Overrides `cart.customer`
+    #[serde(default)]
+    pub customer: Option<CustomerContext>,
+    /// Price as of this instant; None => engine pricing time / clock
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Expected cart currency (a mismatch is rejected)
+    #[serde(default)]
+    pub currency: Option<Currency>,
+}
+
+impl CalculationContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn in_jurisdiction(mut self, jurisdiction: &str) -> Self {
+        self.jurisdiction = Some(jurisdiction.to_string());
+        self
+    }
+
+    pub fn for_customer(mut self, customer: CustomerContext) -> Self {
+        self.customer = Some(customer);
+        self
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn in_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// `what` ("Cart", "Item 'Tea'") must be priced in the expected currency
+    fn check_currency(&self, what: &str, currency: Currency) -> EngineResult<()> {
+        match self.currency.filter(|expected| *expected != currency) {
+            Some(expected) => Err(EngineError::Validation {
+                message: format!("{} is in {} but the calculation expects {}", what, currency.code(), expected.code()),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The cart as priced under this context (its customer replaces the cart's)
+    fn apply_to<'a>(&self, cart: &'a Cart) -> Cow<'a, Cart> {
+        match &self.customer {
+            Some(customer) => Cow::Owned(Cart {
+                customer: Some(customer.clone()),
+                ..cart.clone()
+            }),
+            None => Cow::Borrowed(cart),
+        }
+    }
+}
+
+/// 🔁 Input of a replayed item calculation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub item: Item,
+    pub cart_items: Vec<Item>,
+    #[serde(default)]
+    pub promo_codes: Vec<String>,
+    #[serde(default)]
+    pub target_jurisdiction: Option<String>,
+    #[serde(default)]
+    pub customer_group: Option<String>,
+    #[serde(default)]
+    pub customer_id: Option<String>,
+}
+
+impl MixedScenarioEngine {
+    /// 📸 Capture the full pricing configuration
+    pub fn snapshot(&self) -> EngineSnapshot {
+        let taken_at = self.clock.now();
+        EngineSnapshot {
+            taken_at,
+            product_taxes: self.product_taxes.clone().into_iter().collect(),
+            product_discounts: self.product_discounts.clone().into_iter().collect(),
+            global_tax_rates: self.global_tax_rates.clone(),
+            calculation_order: self.calculation_order,
+            total_formula: self.total_formula.clone(),
+            cap_strategy: self.cap_strategy,
+            rounding_mode: self.rounding_mode,
+            tax_rounding: self.tax_rounding,
+            cart_limits: self.cart_limits,
+            currencies: self.currencies.clone(),
+            auto_best: self.auto_best,
+            stacking_policy: self.stacking_policy.clone(),
+            jurisdictions: self.jurisdictions.clone(),
+            store_timezone: self.store_timezone,
+            cart_bundles: self.cart_bundles.clone(),
+            small_order_tax: self.small_order_tax.clone(),
+            residue_target: self.residue_target,
+            price_lists: self.price_lists.clone(),
+            kits: self.kits.clone().into_iter().collect(),
+            cart_discounts: self.cart_discounts.clone(),
+            max_rate_percent: self.max_rate_percent,
+            accepted_promo_codes: None,
+            pricing_time: self.pricing_time.unwrap_or(taken_at),
+        }
+    }
+
+    /// 📸 Snapshot for replaying `input` later: also records which of its promo codes the
+    /// promo manager accepts now, as the live calculation of `input` uses them
+    pub fn snapshot_for(&self, input: &ReplayInput) -> EngineSnapshot {
+        let mut snapshot = self.snapshot();
+        if self.promo_manager.is_some() {
+            let cart_value = single_item_cart_value(&input.item, &input.cart_items);
+            let accepted = self.usable_promo_codes(
+                input.customer_id.as_deref(),
+                cart_value,
+                &input.promo_codes,
+                snapshot.pricing_time,
+            );
+            snapshot.accepted_promo_codes = Some(accepted.into_owned());
+        }
+        snapshot
+    }
+
+    /// ♻️ Engine configured exactly as when the snapshot was taken (pricing time pinned)
+    pub fn from_snapshot(snapshot: &EngineSnapshot) -> Self {
+        MixedScenarioEngine {
+            product_taxes: snapshot.product_taxes.clone().into_iter().collect(),
+            // Snapshots may be edited or deserialized, so sort like `add_product_discount`
+            product_discounts: snapshot
+                .product_discounts
+                .clone()
+                .into_iter()
+                .map(|(id, mut config)| {
+                    config.discounts.sort_by(DiscountRule::precedence);
+                    (id, config)
+                })
+                .collect(),
+            global_tax_rates: snapshot.global_tax_rates.clone(),
+            calculation_order: snapshot.calculation_order,
+            total_formula: snapshot.total_formula.clone(),
+            cap_strategy: snapshot.cap_strategy,
+            rounding_mode: snapshot.rounding_mode,
+            tax_rounding: snapshot.tax_rounding,
+            cart_limits: snapshot.cart_limits,
+            currencies: snapshot.currencies.clone(),
+            auto_best: snapshot.auto_best,
+            stacking_policy: snapshot.stacking_policy.clone(),
+            jurisdictions: snapshot.jurisdictions.clone(),
+            store_timezone: snapshot.store_timezone,
+            pricing_time: Some(snapshot.pricing_time),
+            clock: Arc::new(SystemClock),
+            promo_manager: None,
+            cart_bundles: snapshot.cart_bundles.clone(),
+            small_order_tax: snapshot.small_order_tax.clone(),
+            residue_target: snapshot.residue_target,
+            price_lists: snapshot.price_lists.clone(),
+            kits: snapshot.kits.clone().into_iter().collect(),
+            cart_discounts: snapshot.cart_discounts.clone(),
+            max_rate_percent: snapshot.max_rate_percent,
+        }
+    }
+
+    /// 🔁 Recompute an item calculation under a past configuration
+    /// Codes the promo manager rejected at the time (`snapshot_for`) are left out
+    pub fn replay(snapshot: &EngineSnapshot, input: &ReplayInput) -> EngineResult<ItemCalculation> {
+        let promo_codes: Vec<String> = match &snapshot.accepted_promo_codes {
+            Some(accepted) => input
+                .promo_codes
+                .iter()
+                .filter(|code| accepted.iter().any(|a| promo::same_code(code, a)))
+                .cloned()
+                .collect(),
+            None => input.promo_codes.clone(),
+        };
+        Self::from_snapshot(snapshot).calculate_item_for_customer(
+            &input.item,
+            &input.cart_items,
+            &promo_codes,
+            input.target_jurisdiction.as_deref(),
+            input.customer_id.as_deref(),
+            input.customer_group.as_deref(),
+        )
+    }
+}
+
+/// Value promo codes are checked against when one item is priced outside a cart
+/// (the lines it is priced with, or the item alone)
+fn single_item_cart_value(item: &Item, cart_items: &[Item]) -> Money {
+    if cart_items.is_empty() {
+        return item.total();
+    }
+    cart_items
+        .iter()
+        .filter(|line| line.currency == item.currency)
+        .fold(Money::zero(), |total, line| total + line.total())
+}
+
+/// 📊 Cart totals without the per-line results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CartTotals {
+    pub subtotal: Money,
+    pub total_discount: Money,
+    pub total_tax: Money,
+    pub grand_total: Money,
+}
+
+/// 📐 Per-line difference (alternative - baseline)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineDelta {
+    pub item_id: String,
+    pub baseline_total: Money,
+    pub alternative_total: Money,
+    pub discount_delta: Money,
+    pub tax_delta: Money,
+    pub total_delta: Money,
+}
+
+/// 🔬 Pricing Comparison (alternative - baseline)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingComparison {
+    pub baseline: CartCalculation,
+    pub alternative: CartCalculation,
+    pub lines: Vec<LineDelta>,
+    pub discount_delta: Money,
+    pub tax_delta: Money,
+    pub grand_total_delta: Money,
+}
+
+/// 📋 Item Calculation Result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemCalculation {
+    pub item_id: String,
+    pub base_amount: Money,
+    pub discount_amount: Money,
+    pub tax_amount: Money,
+    pub total: Money,
+    pub discount_details: Vec<DiscountDetail>,
+    pub tax_details: Vec<TaxDetail>,
+    /// Discounts reduced or removed by `max_discount_percent`
+    #[serde(default)]
+    pub cap_adjustments: Vec<CapAdjustment>,
+    /// Qualifying discounts the stacking rules left out, with the reason
+    #[serde(default)]
+    pub suppressed_discounts: Vec<SuppressedDiscount>,
+    /// Price already contained the tax (backed out of the gross price, not added on top)
+    #[serde(default)]
+    pub tax_included: bool,
+    /// Unit cost × quantity (negative on return lines); None when the item has no cost
+    #[serde(default)]
+    pub cost: Option<Money>,
+}
+
+impl ItemCalculation {
+    /// Net-of-discount revenue, excluding tax
+    pub fn net_revenue(&self) -> Money {
+        self.base_amount - self.discount_amount
+    }
+
+    /// 📈 Revenue (after discounts, before tax) minus cost; None if the cost is unknown
+    pub fn gross_margin(&self) -> Option<Money> {
+        self.cost.map(|cost| self.net_revenue() - cost)
+    }
+
+    /// Gross margin as a percentage of net revenue; None if the cost is unknown or revenue is zero
+    pub fn margin_pct(&self) -> Option<f64> {
+        let revenue = self.net_revenue();
+        if revenue.is_zero() {
+            return None;
+        }
+        self.gross_margin()
+            .map(|margin| margin.amount as f64 / revenue.amount as f64 * 100.0)
+    }
+}
+
+/// 📊 Cart margin summary (only lines with a known cost are counted)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginSummary {
+    pub revenue: Money,
+    pub cost: Money,
+    pub gross_margin: Money,
+    /// None when no line has a known cost (or their revenue is zero)
+    pub margin_pct: Option<f64>,
+    /// Lines left out because their cost is unknown
+    pub unknown_cost_lines: usize,
+}
+
+/// Per-calculation inputs discount conditions are checked against (internal)
+#[derive(Clone, Copy)]
+struct ConditionContext<'a> {
+    cart_items: &'a [Item],
+    promo_codes: &'a [String],
+    customer_id: Option<&'a str>,
+    customer_group: Option<&'a str>,
+    /// Unknown customer when None (never a first purchase)
+    customer: Option<&'a CustomerContext>,
+    /// Negative-quantity line being priced as its mirror sale
+    is_return: bool,
+    /// Contract-priced line whose price list excludes promotions
+    skip_promotions: bool,
+    /// Instant `DateRange` conditions are checked at (one per calculation)
+    now: DateTime<Utc>,
+}
+
+impl<'a> ConditionContext<'a> {
+    /// Sale-side context of a whole cart (`customer` fills in an unset id or group)
+    fn for_cart(cart: &'a Cart, promo_codes: &'a [String], now: DateTime<Utc>) -> Self {
+        let customer = cart.customer.as_ref();
+        ConditionContext {
+            cart_items: &cart.items,
+            promo_codes,
+            customer_id: cart
+                .customer_id
+                .as_deref()
+                .or_else(|| customer.and_then(|c| c.customer_id.as_deref())),
+            customer_group: cart
+                .customer_group
+                .as_deref()
+                .or_else(|| customer.and_then(|c| c.groups.first().map(String::as_str))),
+            customer,
+            is_return: false,
+            skip_promotions: false,
+            now,
+        }
+    }
+
+    fn in_group(&self, group: &str) -> bool {
+        self.customer_group == Some(group) || self.customer.is_some_and(|c| c.in_group(group))
+    }
+}
+
+/// Cart-level discount being split over member lines (internal)
+struct DiscountSpread<'a> {
+    /// Line indexes
+    members: &'a [usize],
+    /// Allocation weights (net value per member, cents)
+    nets: &'a [i64],
+    discount: Money,
+    /// Share of a kit line's tax removed with the discount
+    tax_percent: f64,
+    rule_id: &'a str,
+    name: &'a str,
+}
+
+/// Bundle being looked for in a cart (internal)
+struct BundleOffer<'a> {
+    id: &'a str,
+    name: &'a str,
+    /// One entry per unit in a set
+    members: Vec<&'a str>,
+    discount_percent: f64,
+}
+
+/// Per-item discount outcome (internal)
+struct ItemDiscounts {
+    total: Money,
+    details: Vec<DiscountDetail>,
+    cap_adjustments: Vec<CapAdjustment>,
+    suppressed: Vec<SuppressedDiscount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscountDetail {
+    pub rule_id: String,
+    pub name: String,
+    pub amount: Money,
+}
+
+/// One applied tax rate (per-unit taxes report rate 0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxDetail {
+    pub name: String,
+    pub rate: f64,
+    /// Charged to the buyer (0 when reverse-charged)
+    pub amount: Money,
+    #[serde(default)]
+    pub treatment: TaxTreatment,
+    /// Tax the buyer self-accounts for under reverse charge (reported, not charged)
+    #[serde(default)]
+    pub reverse_charged: Money,
+    /// Amount this tax was charged on (its own discount base, plus excise or earlier
+    /// taxes where it compounds; the line's taxable amount for per-unit taxes)
+    #[serde(default)]
+    pub taxable_base: Money,
+}
+
+impl TaxDetail {
+    fn new(tax_rate: &TaxRate, taxable_base: Money, amount: Money) -> Self {
+        TaxDetail {
+            name: tax_rate.name.clone(),
+            rate: tax_rate.rate,
+            amount,
+            treatment: TaxTreatment::Standard,
+            reverse_charged: Money::zero(),
+            taxable_base,
+        }
+    }
+
+    fn reverse_charge(tax_rate: &TaxRate, taxable_base: Money, tax: Money) -> Self {
+        TaxDetail {
+            treatment: TaxTreatment::ReverseCharge,
+            reverse_charged: tax,
+            ..Self::new(tax_rate, taxable_base, Money::zero())
+        }
+    }
+}
+
+/// 🧾 Receipt line, Ex: "VAT 18%: Rs.81.00" (per-unit taxes: "Excise: Rs.12.00")
+impl std::fmt::Display for TaxDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.treatment == TaxTreatment::ReverseCharge {
+            write!(f, "{} {}%: reverse charge ({})", self.name, self.rate, self.reverse_charged)
+        } else if self.rate == 0.0 {
+            write!(f, "{}: {}", self.name, self.amount)
+        } else {
+            write!(f, "{} {}%: {}", self.name, self.rate, self.amount)
+        }
+    }
+}
+
+/// 📊 Cart Calculation Result  
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartCalculation {
+    pub items: Vec<ItemCalculation>,
+    pub subtotal: Money,
+    pub total_discount: Money,
+    pub total_tax: Money,
+    pub grand_total: Money,
+    /// Rounding residue of the lines (`ResidueTarget::Adjustment`), included in `grand_total`
+    #[serde(default)]
+    pub rounding_adjustment: Money,
+    /// Cart-level discounts the stacking rules left out (line-level ones are on the lines)
+    #[serde(default)]
+    pub suppressed_discounts: Vec<SuppressedDiscount>,
+    /// Part of `total_tax` contained in tax-inclusive prices (EU/UK gross pricing)
+    #[serde(default)]
+    pub included_tax: Money,
+    /// Part of `total_tax` added on top of net prices
+    #[serde(default)]
+    pub added_tax: Money,
+    /// Container deposits and their tax (`DepositSchedule::charge`), included in `grand_total`
+    /// but a liability, not a sale - see `sale_total`
+    #[serde(default)]
+    pub deposits: Money,
+}
+
+impl CartCalculation {
+    /// 🧮 Rounding reconciliation for lines assembled outside the engine (Ex: imported orders)
+    /// Buckets are re-summed from the lines and the residue goes to `target`; the grand total
+    /// is composed by `formula` (the engine's `total_formula()`) as `calculate_cart` composes it,
+    /// plus the rounding adjustment and deposits. Nothing changes when the formula rejects the buckets.
+    pub fn reconcile(&mut self, target: ResidueTarget, formula: &TotalFormula) -> EngineResult<()> {
+        let mut staged = self.clone();
+        staged.rounding_adjustment = absorb_rounding_residue(&mut staged.items, target).unwrap_or_default();
+        let sum = |f: fn(&ItemCalculation) -> Money| staged.items.iter().fold(Money::zero(), |acc, l| acc + f(l));
+        let (subtotal, total_discount, total_tax) =
+            (sum(|l| l.base_amount), sum(|l| l.discount_amount), sum(|l| l.tax_amount));
+        let composed = formula.evaluate(&BucketAmounts::new(subtotal, total_discount, total_tax))?;
+        staged.subtotal = subtotal;
+        staged.total_discount = total_discount;
+        staged.total_tax = total_tax;
+        staged.grand_total = composed.grand_total + staged.rounding_adjustment + staged.deposits;
+        (staged.included_tax, staged.added_tax) = Self::split_tax(&staged.items);
+        *self = staged;
+        Ok(())
+    }
+
+    /// (tax inside inclusive prices, tax added on top) of the lines
+    fn split_tax(items: &[ItemCalculation]) -> (Money, Money) {
+        items.iter().fold((Money::zero(), Money::zero()), |(included, added), line| {
+            if line.tax_included {
+                (included + line.tax_amount, added)
+            } else {
+                (included, added + line.tax_amount)
+            }
+        })
+    }
+
+    /// 🧾 Cart-wide tax lines (same name & rate merged, in first-seen order)
+    pub fn tax_breakdown(&self) -> Vec<TaxDetail> {
+        let mut merged: Vec<TaxDetail> = Vec::new();
+        for detail in self.items.iter().flat_map(|line| &line.tax_details) {
+            match merged
+                .iter_mut()
+                .find(|d| d.name == detail.name && d.rate == detail.rate && d.treatment == detail.treatment)
+            {
+                Some(existing) => {
+                    existing.amount = existing.amount + detail.amount;
+                    existing.reverse_charged = existing.reverse_charged + detail.reverse_charged;
+                    existing.taxable_base = existing.taxable_base + detail.taxable_base;
+                }
+                None => merged.push(detail.clone()),
+            }
+        }
+        merged
+    }
+
+    /// 🎁 Non-empty order with nothing to pay (Ex: 100% off) - no payment is captured
+    pub fn is_free(&self) -> bool {
+        !self.items.is_empty() && self.sale_total().is_zero()
+    }
+
+    /// What the goods sell for: `grand_total` without container deposits
+    pub fn sale_total(&self) -> Money {
+        self.grand_total - self.deposits
+    }
+
+    /// 📊 Margin over the lines whose cost is known
+    pub fn margin_summary(&self) -> MarginSummary {
+        let mut revenue = Money::zero();
+        let mut cost = Money::zero();
+        let mut unknown_cost_lines = 0;
+        for line in &self.items {
+            match line.cost {
+                Some(line_cost) => {
+                    revenue = revenue + line.net_revenue();
+                    cost = cost + line_cost;
+                }
+                None => unknown_cost_lines += 1,
+            }
+        }
+        let gross_margin = revenue - cost;
+        let known = unknown_cost_lines < self.items.len();
+        MarginSummary {
+            revenue,
+            cost,
+            gross_margin,
+            margin_pct: (known && !revenue.is_zero())
+                .then(|| gross_margin.amount as f64 / revenue.amount as f64 * 100.0),
+            unknown_cost_lines,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capped_engine(max_discount_percent: f64, strategy: CapStrategy) -> MixedScenarioEngine {
+        let rule = |id: &str, discount_type: DiscountType, priority: i32| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type,
+            priority,
+            conditions: Vec::new(),
+            stackable: true,
+        };
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.set_cap_strategy(strategy);
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![
+                rule("SALE20", DiscountType::Percentage(20.0), 10),
+                rule("COUPON15", DiscountType::FixedAmount(1500), 1),
+            ],
+            stackable: true,
+            max_discount_percent: Some(max_discount_percent),
+        }).unwrap();
+        engine
+    }
+
+    fn sku() -> Item {
+        let mut item = Item::new("SKU", Money::new(100, 0), 1.0);
+        item.id = "SKU".to_string();
+        item
+    }
+
+    #[test]
+    fn test_cap_prorated_across_lines() {
+        // Uncapped Rs.35 (20 + 15), cap 25% = Rs.25
+        let engine = capped_engine(25.0, CapStrategy::ProRate);
+        let item = sku();
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+
+        assert_eq!(result.discount_amount, Money::new(25, 0));
+        let amounts: Vec<i64> = result.discount_details.iter().map(|d| d.amount.amount).collect();
+        assert_eq!(amounts, vec![1429, 1071]);
+        assert_eq!(result.cap_adjustments.len(), 2);
+        assert!(result.cap_adjustments.iter().all(|a| !a.removed));
+    }
+
+    #[test]
+    fn test_cap_drops_lowest_priority() {
+        // Uncapped Rs.35, cap 20% = Rs.20 => the Rs.15 coupon goes
+        let engine = capped_engine(20.0, CapStrategy::DropLowestPriority);
+        let item = sku();
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+
+        assert_eq!(result.discount_amount, Money::new(20, 0));
+        assert_eq!(result.discount_details.len(), 1);
+        assert_eq!(result.discount_details[0].rule_id, "SALE20");
+        assert_eq!(
+            result.cap_adjustments,
+            vec![CapAdjustment {
+                rule_id: "COUPON15".to_string(),
+                name: "COUPON15".to_string(),
+                original_amount: Money::new(15, 0),
+                applied_amount: Money::zero(),
+                removed: true,
+            }]
+        );
+
+        // Cap 25% = Rs.25 => coupon trimmed to Rs.5 instead
+        let engine = capped_engine(25.0, CapStrategy::DropLowestPriority);
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+        assert_eq!(result.discount_details[1].amount, Money::new(5, 0));
+        assert!(!result.cap_adjustments[0].removed);
+    }
+
+    fn excise_engine(with_vat: bool) -> MixedScenarioEngine {
+        let mut rates = vec![TaxRate::per_unit(
+            "Excise",
+            Money::new(50, 0),
+            "ALL",
+            TaxAppliesTo::Product("ARRACK".to_string()),
+        )];
+        if with_vat {
+            rates.push(TaxRate::new("VAT", 18.0, "ALL", TaxAppliesTo::All).with_excise_in_base());
+        }
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "ARRACK".to_string(),
+            tax_rates: rates,
+            tax_exempt: false,
+            tax_included_in_price: false,
+        }).unwrap();
+        engine
+    }
+
+    fn arrack(liters: f64) -> Item {
+        let mut item = Item::new("Arrack", Money::new(1000, 0), liters);
+        item.id = "ARRACK".to_string();
+        item
+    }
+
+    #[test]
+    fn test_per_unit_excise_alone() {
+        let engine = excise_engine(false);
+        let item = arrack(3.0);
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+
+        // Rs.50 x 3 liters
+        assert_eq!(result.tax_amount, Money::new(150, 0));
+        assert_eq!(result.total, Money::new(3150, 0));
+    }
+
+    #[test]
+    fn test_per_unit_excise_with_vat_on_inclusive_base() {
+        let engine = excise_engine(true);
+        let item = arrack(3.0);
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+
+        // Excise 150 + VAT 18% of (3000 + 150) = 150 + 567
+        assert_eq!(result.tax_amount, Money::new(717, 0));
+    }
+
+    #[test]
+    fn test_inclusive_price_with_fractional_quantity_reconciles() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "RICE".to_string(),
+            tax_rates: vec![TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        let mut rice = Item::new("Rice (kg)", Money::new(99, 99), 2.5);
+        rice.id = "RICE".to_string();
+
+        let result = engine.calculate_item(&rice, std::slice::from_ref(&rice), &[], Some("LK")).unwrap();
+
+        // Rs.99.99 x 2.5 = Rs.249.975 -> Rs.249.98 once; VAT = 249.98 x 18/118 = 38.13
+        // (per-unit extraction would give 15.25 x 2.5 = 38.125 and drift from the shelf total)
+        assert_eq!(result.total, Money::new(249, 98));
+        assert_eq!(result.tax_amount, Money::new(38, 13));
+        assert_eq!(result.base_amount, Money::new(211, 85));
+        assert_eq!(result.base_amount + result.tax_amount, result.total);
+    }
+
+    #[test]
+    fn test_inclusive_price_keeps_excise_and_tax_limits() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "ARRACK".to_string(),
+            tax_rates: vec![
+                TaxRate::per_unit("Excise", Money::new(50, 0), "LK", TaxAppliesTo::Product("ARRACK".to_string())),
+                TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All).with_excise_in_base(),
+                TaxRate::new("Luxury", 10.0, "LK", TaxAppliesTo::All).with_limits(None, Some(Money::new(100, 0))),
+            ],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        let item = arrack(3.0);
+
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], Some("LK")).unwrap();
+
+        // Rs.3000 shelf = net + excise 150 + VAT on (net + excise) + Luxury (capped at Rs.100)
+        let tax = |name: &str| result.tax_details.iter().find(|d| d.name == name).unwrap().amount;
+        assert_eq!(tax("Excise"), Money::new(150, 0));
+        assert_eq!(tax("Luxury"), Money::new(100, 0));
+        // Net backed out of the percentage part: (3000 - 150 x 1.18) / 1.28 = 2205.47
+        assert_eq!(tax("VAT"), Money::new(423, 98));
+        // Each tax reports the base it was charged on: VAT includes the excise, Luxury does not
+        let base = |name: &str| result.tax_details.iter().find(|d| d.name == name).unwrap().taxable_base;
+        assert_eq!(base("VAT"), Money::new(2355, 47));
+        assert_eq!(base("Luxury"), Money::new(2205, 47));
+        assert_eq!(result.total, Money::new(3000, 0));
+        assert_eq!(result.base_amount + result.tax_amount, result.total);
+    }
+
+    #[test]
+    fn test_cart_separates_included_and_added_tax() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 20.0, "UK", TaxAppliesTo::All)).unwrap();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "SHELF".to_string(),
+            tax_rates: vec![TaxRate::new("VAT", 20.0, "UK", TaxAppliesTo::All)],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        let mut shelf = Item::new("Shelf-priced mug", Money::new(10, 0), 3.0);
+        shelf.id = "SHELF".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(shelf);
+        cart.add_item(Item::new("Trade-priced box", Money::new(25, 0), 1.0));
+
+        let result = engine.calculate_cart(&cart, &[], Some("UK")).unwrap();
+        // Gross £30.00 = £25.00 net + £5.00 VAT inside; £25.00 net + £5.00 VAT on top
+        assert!(result.items[0].tax_included && !result.items[1].tax_included);
+        assert_eq!(result.items[0].base_amount, Money::new(25, 0));
+        assert_eq!(result.included_tax, Money::new(5, 0));
+        assert_eq!(result.added_tax, Money::new(5, 0));
+        assert_eq!(result.included_tax + result.added_tax, result.total_tax);
+        assert_eq!(result.grand_total, Money::new(60, 0));
+    }
+
+    #[test]
+    fn test_quebec_qst_compounds_on_gst() {
+        // Pre-2013 Quebec: GST 5%, then QST 9.5% on price + GST (configured out of order)
+        let qst = TaxRate::new("QST", 9.5, "CA-QC", TaxAppliesTo::All).compounded(2);
+        let gst = TaxRate::new("GST", 5.0, "CA-QC", TaxAppliesTo::All).with_sequence(1);
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(qst.clone()).unwrap();
+        engine.add_global_tax(gst.clone()).unwrap();
+        let item = Item::new("Skates", Money::new(200, 0), 1.0);
+        let line = engine.calculate_item(&item, std::slice::from_ref(&item), &[], Some("CA-QC")).unwrap();
+
+        // GST $10.00; QST 9.5% of $210.00 = $19.95 (independently it would be $19.00)
+        let taxes: Vec<(&str, Money)> = line.tax_details.iter().map(|d| (d.name.as_str(), d.amount)).collect();
+        assert_eq!(taxes, vec![("GST", Money::new(10, 0)), ("QST", Money::new(19, 95))]);
+        assert_eq!(line.total, Money::new(229, 95));
+
+        // Same rates inside a gross price back out to the same split
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "SKATES".to_string(),
+            tax_rates: vec![qst, gst],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        let mut gross = Item::new("Skates", Money::new(229, 95), 1.0);
+        gross.id = "SKATES".to_string();
+        let line = engine.calculate_item(&gross, std::slice::from_ref(&gross), &[], Some("CA-QC")).unwrap();
+        assert_eq!(line.base_amount, Money::new(200, 0));
+        let taxes: Vec<(&str, Money)> = line.tax_details.iter().map(|d| (d.name.as_str(), d.amount)).collect();
+        assert_eq!(taxes, vec![("GST", Money::new(10, 0)), ("QST", Money::new(19, 95))]);
+    }
+
+    #[test]
+    fn test_reverse_charge_reports_vat_without_charging_registered_buyer() {
+        use crate::tax::remittance::{TaxRecord, TaxRemittance};
+
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 20.0, "ALL", TaxAppliesTo::All).reverse_charged()).unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Consulting", Money::new(1000, 0), 1.0));
+
+        // Unregistered buyer (or a malformed VAT ID): charged as usual
+        let retail = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+        assert_eq!(retail.total_tax, Money::new(200, 0));
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("DE12"));
+        assert_eq!(engine.calculate_cart(&cart, &[], Some("FR")).unwrap().total_tax, Money::new(200, 0));
+
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("DE 123 456 789"));
+        let b2b = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+        assert!(b2b.total_tax.is_zero());
+        assert_eq!(b2b.grand_total, Money::new(1000, 0));
+        let detail = &b2b.items[0].tax_details[0];
+        assert_eq!(detail.treatment, TaxTreatment::ReverseCharge);
+        assert_eq!(detail.reverse_charged, Money::new(200, 0));
+        assert_eq!(detail.to_string(), "VAT 20%: reverse charge (Rs.200.00)");
+
+        // Domestic B2B (French VAT ID buying in France) is taxed as usual; so is an unknown destination
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("FR 12 345678901"));
+        assert_eq!(engine.calculate_cart(&cart, &[], Some("FR")).unwrap().total_tax, Money::new(200, 0));
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("DE 123 456 789"));
+        assert_eq!(engine.calculate_cart(&cart, &[], None).unwrap().total_tax, Money::new(200, 0));
+
+        // Report: reverse charge on its own line, not payable; zero-rated stays standard
+        let at = Utc::now();
+        let mut sales = TaxRecord::from_calculation(&retail, "FR", at);
+        sales.extend(TaxRecord::from_calculation(&b2b, "FR", at));
+        sales.push(TaxRecord::new("VAT 0%", "FR", Money::zero(), at));
+        let summary = TaxRemittance::build(&sales, &[], at - chrono::Duration::hours(1), at + chrono::Duration::hours(1)).unwrap();
+        assert_eq!(summary.lines.len(), 3);
+        assert_eq!(summary.net_payable, Money::new(200, 0));
+        assert_eq!(summary.total_reverse_charged, Money::new(200, 0));
+    }
+
+    #[test]
+    fn test_reverse_charge_on_inclusive_price_and_small_orders() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "LICENCE".to_string(),
+            tax_rates: vec![TaxRate::new("VAT", 20.0, "FR", TaxAppliesTo::All).reverse_charged()],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        let mut licence = Item::new("Licence", Money::new(120, 0), 1.0);
+        licence.id = "LICENCE".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(licence);
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("DE 123 456 789"));
+
+        // Shelf price Rs.120 includes Rs.20 VAT; the German buyer pays the net and self-accounts
+        let b2b = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+        assert!(b2b.total_tax.is_zero());
+        assert_eq!(b2b.grand_total, Money::new(100, 0));
+        assert_eq!(b2b.items[0].tax_details[0].reverse_charged, Money::new(20, 0));
+
+        // A small-order flat rate does not re-tax the reverse-charged line
+        engine.set_small_order_tax(Some(SmallOrderTax {
+            threshold: Money::new(500, 0),
+            treatment: SmallOrderTreatment::FlatRate(2.0),
+        }));
+        let small = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+        assert!(small.total_tax.is_zero());
+        assert_eq!(small.items[0].tax_details.len(), 1);
+        assert_eq!(small.items[0].tax_details[0].treatment, TaxTreatment::ReverseCharge);
+    }
+
+    fn bounded_tax(rate: TaxRate, price: Money) -> Money {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(rate).unwrap();
+        let item = Item::new("Item", price, 1.0);
+        engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().tax_amount
+    }
+
+    #[test]
+    fn test_tax_suppressed_below_threshold() {
+        let luxury = TaxRate::new("Luxury", 10.0, "ALL", TaxAppliesTo::All).applies_above(Money::new(5000, 0));
+        assert!(bounded_tax(luxury.clone(), Money::new(4000, 0)).is_zero());
+        assert!(bounded_tax(luxury.clone(), Money::new(5000, 0)).is_zero());
+        assert_eq!(bounded_tax(luxury, Money::new(6000, 0)), Money::new(600, 0));
+    }
+
+    #[test]
+    fn test_min_tax_floor() {
+        let stamp = TaxRate::new("Stamp", 1.0, "ALL", TaxAppliesTo::All).with_limits(Some(Money::new(25, 0)), None);
+        // 1% of Rs.100 = Rs.1 => floored to Rs.25
+        assert_eq!(bounded_tax(stamp.clone(), Money::new(100, 0)), Money::new(25, 0));
+        // Nothing taxable, no floor
+        assert!(bounded_tax(stamp, Money::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_max_tax_cap() {
+        let levy = TaxRate::new("Levy", 5.0, "ALL", TaxAppliesTo::All).with_limits(None, Some(Money::new(200, 0)));
+        // 5% of Rs.10,000 = Rs.500 => capped at Rs.200
+        assert_eq!(bounded_tax(levy.clone(), Money::new(10_000, 0)), Money::new(200, 0));
+        assert_eq!(bounded_tax(levy, Money::new(1000, 0)), Money::new(50, 0));
+    }
+
+    #[test]
+    fn test_tax_follows_engine_rounding_mode() {
+        // Rs.3 × 7.5% = 22.5 cents
+        let tax_for = |mode: RoundingMode| {
+            let mut engine = MixedScenarioEngine::new();
+            engine.set_rounding_mode(mode);
+            engine.add_global_tax(TaxRate::new("VAT", 7.5, "ALL", TaxAppliesTo::All)).unwrap();
+            let item = Item::new("Item", Money::new(3, 0), 1.0);
+            engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().tax_amount.amount
+        };
+        assert_eq!(tax_for(RoundingMode::Standard), 23);
+        assert_eq!(tax_for(RoundingMode::Down), 22);
+        assert_eq!(tax_for(RoundingMode::Up), 23);
+        assert_eq!(tax_for(RoundingMode::Bankers), 22);
+    }
+
+    #[test]
+    fn test_tax_rounding_direction_overrides_engine_mode() {
+        let tax_for = |direction: &str, item: Item, rate: TaxRate| {
+            let mut engine = MixedScenarioEngine::new();
+            engine.set_rounding_mode(RoundingMode::Standard);
+            engine.set_tax_rounding(serde_json::from_str(&format!("\"{}\"", direction)).unwrap());
+            engine.add_global_tax(rate).unwrap();
+            engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().tax_amount.amount
+        };
+
+        // Rs.3 × 7.5% = 22.5 cents; Rs.0.33 excise × 1.5 kg = 49.5 cents
+        let vat = || TaxRate::new("VAT", 7.5, "ALL", TaxAppliesTo::All);
+        let excise = || TaxRate::per_unit("Excise", Money::from_cents(33), "ALL", TaxAppliesTo::All);
+        let expected = [("HalfUp", 23, 50), ("HalfEven", 22, 50), ("Down", 22, 49), ("Up", 23, 50)];
+        for (direction, vat_cents, excise_cents) in expected {
+            let item = Item::new("Item", Money::new(3, 0), 1.0);
+            assert_eq!(tax_for(direction, item, vat()), vat_cents, "{}", direction);
+            let loose = Item::new("Loose", Money::new(3, 0), 1.5);
+            assert_eq!(tax_for(direction, loose, excise()), excise_cents, "{}", direction);
+        }
+    }
+
+    #[test]
+    fn test_vat_on_net_and_levy_on_gross_in_same_item() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "ALL", TaxAppliesTo::All).on_base(TaxBase::PostDiscount)).unwrap();
+        engine.add_global_tax(TaxRate::new("Levy", 2.0, "ALL", TaxAppliesTo::All).on_base(TaxBase::PreDiscount)).unwrap();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "D10".to_string(),
+                name: "10% off".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+
+        // Rs.1,000 - 10% = Rs.900 net: VAT 18% of 900 = 162, levy 2% of 1,000 = 20
+        let item = Item {
+            price: Money::new(1000, 0),
+            ..sku()
+        };
+        for order in [CalculationOrder::DiscountFirst, CalculationOrder::Parallel] {
+            engine.set_calculation_order(order);
+            let calc = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+            assert_eq!(calc.discount_amount, Money::new(100, 0));
+            assert_eq!(calc.tax_amount, Money::new(182, 0), "{:?}", order);
+            assert_eq!(calc.total, Money::new(1082, 0));
+        }
+
+        // Tax first: both taxes on Rs.1,000 (Rs.200), then 10% off the taxed Rs.1,200 = Rs.120,
+        // of which Rs.20 is tax the customer no longer pays
+        engine.set_calculation_order(CalculationOrder::TaxFirst);
+        let calc = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+        assert_eq!(calc.total, Money::new(1080, 0));
+        assert_eq!(calc.discount_amount, Money::new(100, 0));
+        assert_eq!(calc.tax_amount, Money::new(180, 0));
+        let tax = |name: &str| calc.tax_details.iter().find(|d| d.name == name).unwrap().amount;
+        assert_eq!((tax("VAT"), tax("Levy")), (Money::new(162, 0), Money::new(18, 0)));
+        assert_eq!(calc.base_amount - calc.discount_amount + calc.tax_amount, calc.total);
+    }
+
+    #[test]
+    fn test_line_tax_details_one_per_applied_rate() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_global_tax(TaxRate::new("SSCL", 2.5, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "SHELF".to_string(),
+            tax_rates: vec![
+                TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All),
+                TaxRate::new("SSCL", 2.5, "LK", TaxAppliesTo::All),
+            ],
+            tax_exempt: false,
+            tax_included_in_price: true,
+        }).unwrap();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "TENOFF".to_string(),
+                name: "10% off".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+
+        let mut cart = Cart::new();
+        let mut item = sku();
+        item.price = Money::new(500, 0);
+        cart.add_item(item);
+        let mut shelf = Item::new("Shelf-priced", Money::new(1205, 0), 1.0);
+        shelf.id = "SHELF".to_string();
+        cart.add_item(shelf);
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+
+        // Exclusive line: Rs.450 after discount => VAT Rs.81.00, SSCL Rs.11.25
+        let line = &result.items[0];
+        assert_eq!(line.discount_details[0].rule_id, "TENOFF");
+        let receipt: Vec<String> = line.tax_details.iter().map(|d| d.to_string()).collect();
+        assert_eq!(receipt, vec!["VAT 18%: Rs.81.00", "SSCL 2.5%: Rs.11.25"]);
+
+        // Inclusive line: Rs.205 backed out of Rs.1205, split 18 : 2.5
+        let shelf = &result.items[1];
+        assert_eq!(shelf.tax_amount, Money::new(205, 0));
+        assert_eq!(shelf.tax_details[0].amount + shelf.tax_details[1].amount, shelf.tax_amount);
+        assert_eq!(shelf.tax_details[0].amount, Money::new(180, 0));
+
+        let breakdown = result.tax_breakdown();
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(breakdown[0].amount, Money::new(261, 0));
+        let total: Money = breakdown.iter().fold(Money::zero(), |acc, d| acc + d.amount);
+        assert_eq!(total, result.total_tax);
+    }
+
+    #[test]
+    fn test_calculate_cart_rejects_oversized_cart() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.set_cart_limits(CartLimits {
+            max_lines: 1,
+            ..CartLimits::default()
+        });
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("A", Money::new(10, 0), 1.0));
+        assert!(engine.calculate_cart(&cart, &[], None).is_ok());
+
+        cart.add_item(Item::new("B", Money::new(10, 0), 1.0));
+        assert!(matches!(
+            engine.calculate_cart(&cart, &[], None),
+            Err(crate::core::errors::EngineError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_calculate_cart_rejects_unsupported_currency() {
+        use crate::types::currency::{Currency, CurrencyInfo};
+
+        let mut engine = MixedScenarioEngine::new();
+        let mut registry = CurrencyRegistry::new();
+        registry.register(CurrencyInfo::new("LKR", "Rs.", 2)).unwrap();
+        registry.register(CurrencyInfo::new("USD", "$", 2).disabled()).unwrap();
+        engine.set_currency_registry(registry);
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(100, 0), 1.0));
+        assert!(engine.calculate_cart(&cart, &[], None).is_ok());
+
+        cart.currency = Currency::USD;
+        assert!(engine.calculate_cart(&cart, &[], None).is_err());
+    }
+
+    #[test]
+    fn test_mixed_currency_cart_needs_explicit_conversion() {
+        use crate::core::exchange::StaticExchangeRates;
+        use crate::types::currency::Currency;
+
+        let engine = MixedScenarioEngine::new();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Tea", Money::new(300, 0), 1.0));
+        let mut imported = Item::new("Imported jam", Money::new(2, 50), 2.0);
+        imported.currency = Currency::USD;
+        cart.add_item(imported);
+        // Rs.300 + $5.00 are never summed as 30,500 "cents"
+        assert!(engine.calculate_cart(&cart, &[], None).is_err());
+
+        let rates = StaticExchangeRates::new().with_rate(Currency::USD, Currency::LKR, 300.0).unwrap();
+        cart.convert_items(&rates, RoundingMode::Standard).unwrap();
+        let result = engine.calculate_cart(&cart, &[], None).unwrap();
+        assert_eq!(result.subtotal, Money::new(1800, 0));
+    }
+
+    #[test]
+    fn test_compare_10_vs_15_percent_promo() {
+        let promo = |pct: f64| {
+            let mut engine = MixedScenarioEngine::new();
+            engine.add_product_discount(ProductDiscountConfig {
+                product_id: "SKU".to_string(),
+                discounts: vec![DiscountRule {
+                    id: "PROMO".to_string(),
+                    name: "Promo".to_string(),
+                    discount_type: DiscountType::Percentage(pct),
+                    priority: 1,
+                    conditions: Vec::new(),
+                    stackable: true,
+                }],
+                stackable: true,
+                max_discount_percent: None,
+            }).unwrap();
+            engine
+        };
+        let mut cart = Cart::new();
+        let mut item = sku();
+        item.quantity = 2.0;
+        cart.add_item(item);
+
+        let current = promo(10.0);
+        let comparison = current.compare(&cart, &promo(15.0), &[], None).unwrap();
+
+        // Rs.200 line: Rs.20 => Rs.30 off
+        assert_eq!(comparison.baseline.total_discount, Money::new(20, 0));
+        assert_eq!(comparison.alternative.total_discount, Money::new(30, 0));
+        assert_eq!(comparison.discount_delta, Money::new(10, 0));
+        assert_eq!(comparison.grand_total_delta, Money::new(-10, 0));
+        assert_eq!(comparison.lines[0].total_delta, Money::new(-10, 0));
+        // Baseline engine unchanged
+        assert_eq!(current.calculate_cart(&cart, &[], None).unwrap().total_discount, Money::new(20, 0));
+    }
+
+    #[test]
+    fn test_auto_best_picks_largest_exclusive_discount() {
+        let exclusive = |id: &str, discount_type: DiscountType, priority: i32| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type,
+            priority,
+            conditions: Vec::new(),
+            stackable: false,
+        };
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![
+                exclusive("MEMBER5", DiscountType::Percentage(5.0), 10),
+                exclusive("COUPON20", DiscountType::FixedAmount(2000), 1),
+            ],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let item = sku();
+
+        // Priority: Rs.5 member discount wins, coupon is blocked
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+        assert_eq!(result.discount_amount, Money::new(5, 0));
+
+        // Auto-best: lower-priority Rs.20 coupon is better for the customer
+        engine.set_auto_best(true);
+        let result = engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap();
+        assert_eq!(result.discount_amount, Money::new(20, 0));
+        assert_eq!(result.discount_details[0].rule_id, "COUPON20");
+    }
+
+    #[test]
+    fn test_equally_good_coupons_resolve_by_rule_id() {
+        let coupon = |id: &str| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type: DiscountType::FixedAmount(1000),
+            priority: 5,
+            conditions: vec![DiscountCondition::PromoCode(id.to_string())],
+            stackable: false,
+        };
+        let codes = vec!["SPRING10".to_string(), "WELCOME10".to_string(), "AUTUMN10".to_string()];
+        let item = sku();
+
+        // Same Rs.10 benefit from every coupon: configured order must not decide which is consumed
+        let mut consumed = Vec::new();
+        for rotation in 0..codes.len() {
+            let mut ids = codes.clone();
+            ids.rotate_left(rotation);
+            for auto_best in [false, true] {
+                let mut engine = MixedScenarioEngine::new();
+                engine.set_auto_best(auto_best);
+                engine.add_product_discount(ProductDiscountConfig {
+                    product_id: "SKU".to_string(),
+                    discounts: ids.iter().map(|id| coupon(id)).collect(),
+                    stackable: true,
+                    max_discount_percent: None,
+                }).unwrap();
+                let result = engine.calculate_item(&item, std::slice::from_ref(&item), &codes, None).unwrap();
+                assert_eq!(result.discount_amount, Money::new(10, 0));
+                consumed.push(result.discount_details[0].rule_id.clone());
+            }
+        }
+        assert!(consumed.iter().all(|id| id == "AUTUMN10"), "{:?}", consumed);
+    }
+
+    #[test]
+    fn test_sale_window_in_store_timezone() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "FLASH".to_string(),
+                name: "Flash Sale".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: vec![DiscountCondition::DateRange {
+                    from: "2024-01-20".to_string(),
+                    to: "2024-01-22".to_string(),
+                }],
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        engine.set_store_timezone(StoreTimeZone::parse("Asia/Colombo").unwrap());
+        let item = sku();
+        let discount_at = |engine: &mut MixedScenarioEngine, h: u32, m: u32| {
+            use chrono::TimeZone;
+            engine.set_pricing_time(Some(Utc.with_ymd_and_hms(2024, 1, 22, h, m, 0).unwrap()));
+            engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().discount_amount
+        };
+
+        // 18:00 UTC = 23:30 Jan 22 in Colombo => still on sale
+        assert_eq!(discount_at(&mut engine, 18, 0), Money::new(10, 0));
+        // 18:30 UTC = 00:00 Jan 23 in Colombo => sale over (though still Jan 22 in UTC)
+        assert!(discount_at(&mut engine, 18, 30).is_zero());
+
+        engine.set_store_timezone(StoreTimeZone::default());
+        assert_eq!(discount_at(&mut engine, 18, 30), Money::new(10, 0));
+    }
+
+    #[test]
+    fn test_date_range_uses_clock_and_rejects_bad_ranges() {
+        use crate::core::clock::FixedClock;
+        use chrono::TimeZone;
+
+        let flash_sale = |from: &str, to: &str| ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "FLASH".to_string(),
+                name: "Flash Sale".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: vec![DiscountCondition::DateRange { from: from.to_string(), to: to.to_string() }],
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        };
+        let mut engine = MixedScenarioEngine::new();
+        assert!(engine.add_product_discount(flash_sale("2024-01-22", "2024-01-20")).is_err());
+        assert!(engine.add_product_discount(flash_sale("Jan 20", "2024-01-22")).is_err());
+        // Offset-qualified end: 18:00 in Colombo is 12:30 UTC
+        engine.add_product_discount(flash_sale("2024-01-20", "2024-01-22T18:00:00+05:30")).unwrap();
+
+        let item = sku();
+        let mut discount_at = |h: u32, m: u32| {
+            engine.set_clock(Arc::new(FixedClock(Utc.with_ymd_and_hms(2024, 1, 22, h, m, 0).unwrap())));
+            engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().discount_amount
+        };
+        assert_eq!(discount_at(12, 29), Money::new(10, 0));
+        assert!(discount_at(12, 30).is_zero());
+    }
+
+    #[test]
+    fn test_calculation_context_sets_time_customer_and_jurisdiction() {
+        use chrono::TimeZone;
+
+        let mut engine = group_engine(DiscountCondition::DateRange {
+            from: "2024-01-20".to_string(),
+            to: "2024-01-22".to_string(),
+        });
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_global_tax(TaxRate::new("GST", 10.0, "IN", TaxAppliesTo::All)).unwrap();
+        engine.set_pricing_time(Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+        let mut cart = Cart::new();
+        cart.add_item(sku());
+
+        // Engine time is outside the window; the context's timestamp is inside it
+        let context = CalculationContext::new()
+            .in_jurisdiction("LK")
+            .at(Utc.with_ymd_and_hms(2024, 1, 21, 9, 0, 0).unwrap());
+        let result = engine.calculate_cart_with_context(&cart, &[], &context).unwrap();
+        assert_eq!(result.total_discount, Money::new(30, 0));
+        assert_eq!(result.total_tax, Money::new(12, 60));
+        assert!(engine.calculate_cart(&cart, &[], Some("LK")).unwrap().total_discount.is_zero());
+
+        // Customer from the context; first purchase only
+        let first_order = group_engine(DiscountCondition::FirstPurchase);
+        let context = CalculationContext::new().for_customer(CustomerContext::new("C1"));
+        assert_eq!(first_order.calculate_cart_with_context(&cart, &[], &context).unwrap().total_discount, Money::new(30, 0));
+        assert!(first_order.calculate_cart(&cart, &[], None).unwrap().total_discount.is_zero());
+
+        let context = CalculationContext::new().in_currency(Currency::USD);
+        assert!(matches!(
+            engine.calculate_cart_with_context(&cart, &[], &context),
+            Err(EngineError::Validation { .. })
+        ));
+        assert!(engine.calculate_item_in_context(&sku(), &[], &[], &context).is_err());
+    }
+
+    #[test]
+    fn test_item_and_promo_check_follow_the_context_time() {
+        use crate::discount::promo::{PromoCode, PromoCodeManager};
+        use chrono::TimeZone;
+
+        let mut engine = group_engine(DiscountCondition::DateRange {
+            from: "2024-01-20".to_string(),
+            to: "2024-01-22".to_string(),
+        });
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.set_pricing_time(Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+        let promos = Arc::new(PromoCodeManager::new());
+        let until = Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+        promos.register(PromoCode::new("JAN").with_validity(None, Some(until))).unwrap();
+        engine.set_promo_manager(Some(promos));
+        let context = CalculationContext::new()
+            .in_jurisdiction("LK")
+            .at(Utc.with_ymd_and_hms(2024, 1, 21, 9, 0, 0).unwrap());
+
+        // Priced on 21 Jan: inside the discount window, VAT of LK
+        let line = engine.calculate_item_in_context(&sku(), &[], &[], &context).unwrap();
+        assert_eq!(line.discount_amount, Money::new(30, 0));
+        assert_eq!(line.tax_amount, Money::new(12, 60));
+
+        // The code expired after January: valid for a back-dated calculation only
+        let mut cart = Cart::new();
+        cart.add_item(sku());
+        let codes = vec!["jan".to_string()];
+        assert!(engine.check_promo_codes(&cart, &codes).is_err());
+        engine.check_promo_codes_with_context(&cart, &codes, &context).unwrap();
+    }
+
+    #[test]
+    fn test_item_entry_points_drop_unusable_promo_codes() {
+        use crate::discount::promo::{PromoCode, PromoCodeManager};
+
+        let mut engine = group_engine(DiscountCondition::PromoCode("BIG".to_string()));
+        let promos = Arc::new(PromoCodeManager::new());
+        promos.register(PromoCode::new("BIG").with_min_cart_value(Money::new(500, 0))).unwrap();
+        engine.set_promo_manager(Some(promos));
+        let codes = vec!["BIG".to_string()];
+        let item = sku();
+        let big_order = vec![item.clone(), Item::new("Kettle", Money::new(400, 0), 1.0)];
+
+        // Rs.100 alone is below the code's minimum: same answer as calculate_cart on that cart
+        assert!(engine.calculate_item(&item, std::slice::from_ref(&item), &codes, None).unwrap().discount_amount.is_zero());
+        assert!(engine.calculate_item_for_customer(&item, &[], &codes, None, Some("C1"), None).unwrap().discount_amount.is_zero());
+        let context = CalculationContext::new();
+        assert!(engine.calculate_item_in_context(&item, &[], &codes, &context).unwrap().discount_amount.is_zero());
+        let mut cart = Cart::new();
+        cart.add_item(item.clone());
+        assert!(engine.calculate_cart(&cart, &codes, None).unwrap().total_discount.is_zero());
+
+        // Rs.500 cart qualifies
+        let line = engine.calculate_item(&item, &big_order, &codes, None).unwrap();
+        assert_eq!(line.discount_amount, Money::new(30, 0));
+        let line = engine.calculate_item_in_context(&item, &big_order, &codes, &context).unwrap();
+        assert_eq!(line.discount_amount, Money::new(30, 0));
+    }
+
+    fn group_engine(condition: DiscountCondition) -> MixedScenarioEngine {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "STAFF30".to_string(),
+                name: "Staff 30% off".to_string(),
+                discount_type: DiscountType::Percentage(30.0),
+                priority: 1,
+                conditions: vec![condition],
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        engine
+    }
+
+    fn discount_for_group(engine: &MixedScenarioEngine, group: Option<&str>) -> Money {
+        let mut cart = Cart::new();
+        cart.customer_group = group.map(str::to_string);
+        cart.add_item(sku());
+        engine.calculate_cart(&cart, &[], None).unwrap().total_discount
+    }
+
+    #[test]
+    fn test_customer_group_discount_requires_exact_match() {
+        let engine = group_engine(DiscountCondition::CustomerGroup("staff".to_string()));
+
+        assert_eq!(discount_for_group(&engine, Some("staff")), Money::new(30, 0));
+        assert!(discount_for_group(&engine, Some("Staff")).is_zero());
+        assert!(discount_for_group(&engine, Some("retail")).is_zero());
+        assert!(discount_for_group(&engine, None).is_zero());
+    }
+
+    #[test]
+    fn test_any_of_customer_groups() {
+        let groups = vec!["staff".to_string(), "wholesale".to_string()];
+        let engine = group_engine(DiscountCondition::AnyOf(groups));
+
+        assert_eq!(discount_for_group(&engine, Some("wholesale")), Money::new(30, 0));
+        assert!(discount_for_group(&engine, Some("retail")).is_zero());
+        assert!(discount_for_group(&engine, None).is_zero());
+    }
+
+    #[test]
+    fn test_customer_context_gates_first_purchase_and_groups() {
+        let engine = group_engine(DiscountCondition::FirstPurchase);
+        let discount_for = |customer: Option<CustomerContext>| {
+            let mut cart = Cart::new();
+            cart.customer = customer;
+            cart.add_item(sku());
+            engine.calculate_cart(&cart, &[], None).unwrap().total_discount
+        };
+        assert_eq!(discount_for(Some(CustomerContext::new("C-1"))), Money::new(30, 0));
+        assert!(discount_for(Some(CustomerContext::new("C-1").with_purchase_count(3))).is_zero());
+        // Unknown customer, or one known by id only: no first-purchase discount
+        assert!(discount_for(None).is_zero());
+        let line = engine.calculate_item_for_customer(&sku(), &[], &[], None, Some("C-1"), None).unwrap();
+        assert!(line.discount_amount.is_zero());
+        let context = CalculationContext::new().for_customer(CustomerContext::new("C-1"));
+        let line = engine.calculate_item_in_context(&sku(), &[], &[], &context).unwrap();
+        assert_eq!(line.discount_amount, Money::new(30, 0));
+
+        // Any of the customer's groups satisfies a group condition
+        let engine = group_engine(DiscountCondition::CustomerGroup("staff".to_string()));
+        let member = CustomerContext::new("C-2").with_group("loyalty").with_group("staff");
+        let line = engine.calculate_item_in_context(&sku(), &[], &[], &CalculationContext::new().for_customer(member)).unwrap();
+        assert_eq!(line.discount_amount, Money::new(30, 0));
+    }
+
+    fn bogo_discount(buy: f64, get: f64, mode: BogoMode, unit_price: Money, quantity: f64) -> Money {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "BOGO".to_string(),
+                name: "BOGO".to_string(),
+                discount_type: DiscountType::BuyXGetY {
+                    buy,
+                    get,
+                    free_percent: 100.0,
+                    mode,
+                },
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let item = Item { price: unit_price, quantity, ..sku() };
+        engine.calculate_item(&item, std::slice::from_ref(&item), &[], None).unwrap().discount_amount
+    }
+
+    #[test]
+    fn test_discrete_buy_2_get_1() {
+        let price = Money::new(100, 0);
+        // 7 shirts = 2 full sets => 2 free; 6 => exactly 2 sets
+        assert_eq!(bogo_discount(2.0, 1.0, BogoMode::Discrete, price, 7.0), Money::new(200, 0));
+        assert_eq!(bogo_discount(2.0, 1.0, BogoMode::Discrete, price, 6.0), Money::new(200, 0));
+        assert!(bogo_discount(2.0, 1.0, BogoMode::Discrete, price, 2.0).is_zero());
+    }
+
+    #[test]
+    fn test_weighted_buy_2kg_get_half_kg_free() {
+        let per_kg = Money::new(400, 0);
+        // 5kg = 2 full 2.5kg sets => 1kg free
+        assert_eq!(bogo_discount(2.0, 0.5, BogoMode::Weighted, per_kg, 5.0), Money::new(400, 0));
+        // Proportional past the last full set: 6kg => 1.2kg free (discrete stays at 1kg)
+        assert_eq!(bogo_discount(2.0, 0.5, BogoMode::Weighted, per_kg, 6.0), Money::new(480, 0));
+        assert_eq!(bogo_discount(2.0, 0.5, BogoMode::Discrete, per_kg, 6.0), Money::new(400, 0));
+        // Fractional weight: 2.5kg = Rs.1000 line, 0.5kg free
+        assert_eq!(bogo_discount(2.0, 0.5, BogoMode::Weighted, per_kg, 2.5), Money::new(200, 0));
+        // 1.35kg => 0.27kg free of a Rs.540 line
+        assert_eq!(bogo_discount(2.0, 0.5, BogoMode::Weighted, per_kg, 1.35), Money::new(108, 0));
+    }
+
+    #[test]
+    fn test_snapshot_replay_ignores_later_config_changes() {
+        let mut engine = group_engine(DiscountCondition::MinQuantity(1.0));
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let item = sku();
+        let input = ReplayInput {
+            item: item.clone(),
+            cart_items: vec![item.clone()],
+            promo_codes: Vec::new(),
+            target_jurisdiction: Some("LK".to_string()),
+            customer_group: None,
+            customer_id: None,
+        };
+        let original = engine.calculate_item(&item, &input.cart_items, &[], Some("LK")).unwrap();
+        let json = serde_json::to_string(&engine.snapshot()).unwrap();
+
+        // Live engine changes afterwards
+        engine.add_global_tax(TaxRate::new("Levy", 5.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.set_calculation_order(CalculationOrder::TaxFirst);
+        engine.set_rounding_mode(RoundingMode::Up);
+        let changed = engine.calculate_item(&item, &input.cart_items, &[], Some("LK")).unwrap();
+        assert_ne!(changed.tax_amount, original.tax_amount);
+
+        let snapshot: EngineSnapshot = serde_json::from_str(&json).unwrap();
+        let replayed = MixedScenarioEngine::replay(&snapshot, &input).unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_replay_keeps_promo_acceptance_and_rate_ceiling() {
+        use crate::discount::promo::{PromoCode, PromoCodeManager};
+
+        let mut engine = group_engine(DiscountCondition::PromoCode("BIG".to_string()));
+        engine.set_max_rate_percent(300.0).unwrap();
+        let promos = Arc::new(PromoCodeManager::new());
+        promos.register(PromoCode::new("BIG").with_min_cart_value(Money::new(500, 0))).unwrap();
+        engine.set_promo_manager(Some(promos));
+        let item = sku();
+        let input = ReplayInput {
+            item: item.clone(),
+            cart_items: vec![item.clone()],
+            promo_codes: vec!["big".to_string()],
+            target_jurisdiction: None,
+            customer_group: None,
+            customer_id: None,
+        };
+
+        // Rs.100 cart: the code was rejected live, so replay must not apply it either
+        let original = engine.calculate_item(&item, &input.cart_items, &input.promo_codes, None).unwrap();
+        assert!(original.discount_amount.is_zero());
+        let json = serde_json::to_string(&engine.snapshot_for(&input)).unwrap();
+        let snapshot: EngineSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.accepted_promo_codes, Some(Vec::new()));
+        assert!(MixedScenarioEngine::replay(&snapshot, &input).unwrap().discount_amount.is_zero());
+
+        // An accepted code is still applied
+        let big_order = ReplayInput {
+            cart_items: vec![item.clone(), Item::new("Kettle", Money::new(400, 0), 1.0)],
+            ..input.clone()
+        };
+        let replayed = MixedScenarioEngine::replay(&engine.snapshot_for(&big_order), &big_order).unwrap();
+        assert_eq!(replayed.discount_amount, Money::new(30, 0));
+
+        // The raised rate ceiling survives the round trip
+        let mut restored = MixedScenarioEngine::from_snapshot(&snapshot);
+        restored.add_global_tax(TaxRate::new("Excise", 250.0, "LK", TaxAppliesTo::All)).unwrap();
+    }
+
+    #[test]
+    fn test_presorted_rules_give_priority_order_results() {
+        let rule = |id: &str, discount_type: DiscountType, priority: i32, stackable: bool| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type,
+            priority,
+            conditions: Vec::new(),
+            stackable,
+        };
+        let mut engine = MixedScenarioEngine::new();
+        // Configured out of priority order on purpose
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![
+                rule("LOW5", DiscountType::Percentage(5.0), 1, true),
+                rule("HIGH3", DiscountType::FixedAmount(300), 10, true),
+                rule("MID10", DiscountType::Percentage(10.0), 5, false),
+                rule("MID20", DiscountType::Percentage(20.0), 5, false),
+            ],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+
+        let mut cart = Cart::new();
+        for _ in 0..100 {
+            cart.add_item(sku());
+        }
+        let result = engine.calculate_cart(&cart, &[], None).unwrap();
+
+        // HIGH3 (Rs.3) + MID10 (Rs.10, exclusive - MID20 blocked as it ties but sorts after) + LOW5 (Rs.5)
+        for line in &result.items {
+            let ids: Vec<&str> = line.discount_details.iter().map(|d| d.rule_id.as_str()).collect();
+            assert_eq!(ids, vec!["HIGH3", "MID10", "LOW5"]);
+            assert_eq!(line.discount_amount, Money::new(18, 0));
+        }
+        assert_eq!(result.total_discount, Money::new(1800, 0));
+
+        // A snapshot stored with its rules out of order is re-sorted on load
+        let mut snapshot = engine.snapshot();
+        snapshot.product_discounts.get_mut("SKU").unwrap().discounts.reverse();
+        let replayed = MixedScenarioEngine::from_snapshot(&snapshot).calculate_cart(&cart, &[], None).unwrap();
+        assert_eq!(replayed.total_discount, Money::new(1800, 0));
+    }
+
+    #[test]
+    fn test_streaming_totals_match_eager() {
+        let mut engine = capped_engine(25.0, CapStrategy::ProRate);
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cart = Cart::new();
+        for n in 1..=200 {
+            cart.add_item(Item::new("Other", Money::new(n, 25), 2.0));
+            cart.add_item(sku());
+        }
+
+        let eager = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+
+        let mut stream = engine.calculate_cart_stream(&cart, &[], Some("LK")).unwrap();
+        let mut line_total = Money::zero();
+        let mut lines = 0;
+        for line in stream.by_ref() {
+            line_total = line_total + line.unwrap().total;
+            lines += 1;
+        }
+        let totals = stream.totals().unwrap();
+
+        assert_eq!(lines, eager.items.len());
+        assert_eq!(totals.subtotal, eager.subtotal);
+        assert_eq!(totals.total_discount, eager.total_discount);
+        assert_eq!(totals.total_tax, eager.total_tax);
+        assert_eq!(totals.grand_total, eager.grand_total);
+        assert_eq!(line_total, eager.grand_total);
+
+        // Same cart checks as the eager path; cart-level passes can't be streamed
+        engine.set_cart_limits(CartLimits { max_lines: 10, ..CartLimits::default() });
+        assert!(engine.calculate_cart_stream(&cart, &[], Some("LK")).is_err());
+        engine.set_cart_limits(CartLimits::default());
+        engine.set_small_order_tax(Some(SmallOrderTax {
+            threshold: Money::new(100, 0),
+            treatment: SmallOrderTreatment::Exempt,
+        }));
+        assert!(matches!(
+            engine.calculate_cart_stream(&cart, &[], Some("LK")),
+            Err(EngineError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bundle_discount_and_tax_split_reconcile() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_cart_bundle(CartBundle {
+            id: "WORKSTATION".to_string(),
+            name: "Laptop + Mouse".to_string(),
+            items: vec!["Laptop".to_string(), "Mouse".to_string()],
+            discount_percent: 15.0,
+        }).unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Laptop", Money::new(1999, 99), 1.0));
+        cart.add_item(Item::new("Mouse", Money::new(24, 99), 1.0));
+        cart.add_item(Item::new("Bag", Money::new(10, 0), 1.0));
+
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        let members = &result.items[..2];
+
+        // 15% of Rs.2024.98 rounded once on the bundle: Rs.303.75
+        let bundle_discount: Money = members.iter().fold(Money::zero(), |acc, l| acc + l.discount_amount);
+        assert_eq!(bundle_discount, Money::new(303, 75));
+        // Bundle VAT: Rs.364.50 - 15% (54.68) = Rs.309.82, the VAT on the discounted bundle
+        let bundle_tax: Money = members.iter().fold(Money::zero(), |acc, l| acc + l.tax_amount);
+        assert_eq!(bundle_tax, Money::new(309, 82));
+        assert!(members.iter().all(|l| l.discount_details.iter().any(|d| d.rule_id == "WORKSTATION")));
+        // Non-member untouched
+        assert_eq!(result.items[2].discount_amount, Money::zero());
+        assert_eq!(result.items[2].tax_amount, Money::new(1, 80));
+
+        assert_eq!(result.total_discount, bundle_discount);
+        assert_eq!(result.total_tax, bundle_tax + Money::new(1, 80));
+        let line_total = result.items.iter().fold(Money::zero(), |acc, l| acc + l.total);
+        assert_eq!(line_total, result.grand_total);
+        assert_eq!(result.grand_total, result.subtotal - result.total_discount + result.total_tax);
+    }
+
+    #[test]
+    fn test_bundle_recharges_excise_and_capped_tax_on_discounted_net() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "CIGAR".to_string(),
+            tax_rates: vec![
+                TaxRate::per_unit("Excise", Money::new(20, 0), "LK", TaxAppliesTo::Product("CIGAR".to_string())),
+                TaxRate::new("Luxury", 10.0, "LK", TaxAppliesTo::All).with_limits(None, Some(Money::new(5, 0))),
+            ],
+            tax_exempt: false,
+            tax_included_in_price: false,
+        }).unwrap();
+        engine.add_cart_bundle(CartBundle {
+            id: "SMOKER".to_string(),
+            name: "Cigar + Lighter".to_string(),
+            items: vec!["CIGAR".to_string(), "LIGHTER".to_string()],
+            discount_percent: 50.0,
+        }).unwrap();
+        let mut cart = Cart::new();
+        for id in ["CIGAR", "LIGHTER"] {
+            let mut item = Item::new(id, Money::new(100, 0), 1.0);
+            item.id = id.to_string();
+            cart.add_item(item);
+        }
+
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        // Half off: excise per unit and the capped luxury tax (10% of Rs.50 = Rs.5) do not move
+        let cigar = &result.items[0];
+        assert_eq!(cigar.discount_amount, Money::new(50, 0));
+        assert_eq!(cigar.tax_amount, Money::new(25, 0));
+        assert_eq!(cigar.tax_details.iter().find(|d| d.name == "Excise").unwrap().amount, Money::new(20, 0));
+        // VAT on the discounted Rs.50
+        assert_eq!(result.items[1].tax_amount, Money::new(9, 0));
+        assert_eq!(result.grand_total, Money::new(134, 0));
+        assert_eq!(result.grand_total, result.subtotal - result.total_discount + result.total_tax);
+    }
+
+    #[test]
+    fn test_bundle_rule_discounts_each_complete_set() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "CUP".to_string(),
+            discounts: vec![DiscountRule {
+                id: "CUP-SAUCER".to_string(),
+                name: "Cup & saucer set".to_string(),
+                discount_type: DiscountType::Bundle { items: vec!["SAUCER".to_string()], discount_percent: 10.0 },
+                priority: 1,
+                conditions: vec![],
+                stackable: false,
+            }],
+            stackable: false,
+            max_discount_percent: None,
+        }).unwrap();
+        let line = |id: &str, price: Money, quantity: f64| {
+            let mut item = Item::new(id, price, quantity);
+            item.id = id.to_string();
+            item
+        };
+        let mut cart = Cart::new();
+        cart.add_item(line("CUP", Money::new(100, 0), 2.0));
+        cart.add_item(line("SAUCER", Money::new(50, 0), 3.0));
+
+        // Two complete sets (Rs.300): Rs.30 split 200:100, the third saucer at full price
+        let result = engine.calculate_cart(&cart, &[], None).unwrap();
+        assert_eq!(result.items[0].discount_amount, Money::new(20, 0));
+        assert_eq!(result.items[1].discount_amount, Money::new(10, 0));
+        assert!(result.items.iter().all(|l| l.discount_details.len() == 1 && l.discount_details[0].rule_id == "CUP-SAUCER"));
+        assert_eq!(result.total_discount, Money::new(30, 0));
+
+        // No saucer, no set
+        let mut cups_only = Cart::new();
+        cups_only.add_item(line("CUP", Money::new(100, 0), 2.0));
+        assert_eq!(engine.calculate_cart(&cups_only, &[], None).unwrap().total_discount, Money::zero());
+    }
+
+    #[test]
+    fn test_kit_blends_component_vat_rates() {
+        let mut engine = MixedScenarioEngine::new();
+        let vat = |product_id: &str, rate: f64| ProductTaxConfig {
+            product_id: product_id.to_string(),
+            tax_rates: vec![TaxRate::new("VAT", rate, "LK", TaxAppliesTo::All)],
+            tax_exempt: false,
+            tax_included_in_price: false,
+        };
+        engine.add_product_tax(vat("TEA", 0.0)).unwrap();
+        engine.add_product_tax(vat("MUG", 18.0)).unwrap();
+        let component = |product_id: &str, price: Money, quantity: f64| KitComponent {
+            product_id: product_id.to_string(),
+            name: product_id.to_string(),
+            unit_price: price,
+            quantity,
+            unit_cost: Some(Money::from_cents(price.amount / 2)),
+        };
+        engine.add_kit(Kit {
+            id: "GIFT-BOX".to_string(),
+            name: "Tea gift box".to_string(),
+            components: vec![component("TEA", Money::new(300, 0), 2.0), component("MUG", Money::new(400, 0), 1.0)],
+        }).unwrap();
+
+        let mut kit = Item::new("Tea gift box", Money::zero(), 2.0);
+        kit.id = "GIFT-BOX".to_string();
+        let line = engine.calculate_item(&kit, &[kit.clone()], &[], Some("LK")).unwrap();
+
+        // 2 kits: tea Rs.1200 at 0%, mugs Rs.800 at 18% => Rs.144 VAT on Rs.2000 (7.2% blended)
+        assert_eq!(line.item_id, "GIFT-BOX");
+        assert_eq!(line.base_amount, Money::new(2000, 0));
+        assert_eq!(line.tax_amount, Money::new(144, 0));
+        assert_eq!(line.total, Money::new(2144, 0));
+        // Component costs (half the price here) summed for margin
+        assert_eq!(line.cost, Some(Money::new(1000, 0)));
+
+        // Fractional components (half a tin of tea per box) are priced, not truncated to zero
+        engine.add_kit(Kit {
+            id: "SAMPLER".to_string(),
+            name: "Tea sampler".to_string(),
+            components: vec![component("TEA", Money::new(300, 0), 0.5), component("MUG", Money::new(400, 0), 1.0)],
+        }).unwrap();
+        let mut sampler = Item::new("Tea sampler", Money::zero(), 1.0);
+        sampler.id = "SAMPLER".to_string();
+        let line = engine.calculate_item(&sampler, &[sampler.clone()], &[], Some("LK")).unwrap();
+        assert_eq!(line.base_amount, Money::new(550, 0));
+        assert_eq!(line.cost, Some(Money::new(275, 0)));
+
+        // Kits cannot nest
+        let nested = Kit {
+            id: "HAMPER".to_string(),
+            name: "Hamper".to_string(),
+            components: vec![component("GIFT-BOX", Money::new(700, 0), 1.0)],
+        };
+        assert!(engine.add_kit(nested).is_err());
+
+        // NaN or infinite component quantities are rejected like zero ones
+        for quantity in [0.0, f64::NAN, f64::INFINITY] {
+            let broken = Kit {
+                id: "BROKEN".to_string(),
+                name: "Broken".to_string(),
+                components: vec![component("TEA", Money::new(300, 0), quantity)],
+            };
+            assert!(engine.add_kit(broken).is_err(), "quantity {}", quantity);
+        }
+    }
+
+    #[test]
+    fn test_stacking_policy_suppresses_with_reasons() {
+        let rule = |id: &str, discount_type: DiscountType, priority: i32, stackable: bool| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type,
+            priority,
+            conditions: Vec::new(),
+            stackable,
+        };
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "TV".to_string(),
+            discounts: vec![
+                rule("A10", DiscountType::Percentage(10.0), 3, true),
+                rule("B5", DiscountType::Percentage(5.0), 2, true),
+                rule("C3", DiscountType::Percentage(3.0), 1, true),
+                rule("SHIP", DiscountType::FixedAmount(500), 0, false),
+            ],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        for (id, priority) in [("CART5", 2), ("CART2", 1)] {
+            engine.add_cart_discount(CartDiscountConfig {
+                id: id.to_string(),
+                name: id.to_string(),
+                discount_type: CartDiscountType::Percentage(if id == "CART5" { 5.0 } else { 2.0 }),
+                conditions: Vec::new(),
+                priority,
+                stackable: true,
+            }).unwrap();
+        }
+        engine.set_stacking_policy(StackingPolicy {
+            exclusive_groups: [("loyalty".to_string(), vec!["A10".to_string(), "B5".to_string()])].into(),
+            max_per_line: Some(1),
+            max_cart_discounts: Some(1),
+            always_stack: vec!["SHIP".to_string()],
+            category_exclusions: [("electronics".to_string(), vec!["CART5".to_string()])].into(),
+        });
+
+        let mut tv = Item::new("TV", Money::new(100, 0), 1.0).with_metadata("category", "electronics");
+        tv.id = "TV".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(tv);
+        cart.add_item(Item::new("Book", Money::new(200, 0), 1.0));
+        let result = engine.calculate_cart(&cart, &[], None).unwrap();
+
+        // A10 + always-stacking SHIP; B5 shares A10's group, C3 is over the one-per-line limit
+        let tv_line = &result.items[0];
+        assert_eq!(tv_line.discount_amount, Money::new(15, 0));
+        let reasons: Vec<(&str, &SuppressionReason)> =
+            tv_line.suppressed_discounts.iter().map(|s| (s.rule_id.as_str(), &s.reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("B5", &SuppressionReason::ExclusiveGroup { group: "loyalty".to_string(), applied: "A10".to_string() }),
+                ("C3", &SuppressionReason::MaxStacked { limit: 1 }),
+                ("CART5", &SuppressionReason::CategoryExcluded { category: "electronics".to_string() }),
+            ]
+        );
+        // CART5 on the book only; CART2 over the one-cart-coupon limit
+        assert_eq!(result.items[1].discount_amount, Money::new(10, 0));
+        assert_eq!(result.suppressed_discounts.len(), 1);
+        assert_eq!(result.suppressed_discounts[0].rule_id, "CART2");
+        assert_eq!(result.suppressed_discounts[0].reason.to_string(), "at most 1 discount(s) stack");
+    }
+
+    #[test]
+    fn test_cart_discounts_tier_and_allocate_to_lines() {
+        let cart_discount = |id: &str, discount_type: CartDiscountType, priority: i32, stackable: bool| {
+            CartDiscountConfig {
+                id: id.to_string(),
+                name: id.to_string(),
+                discount_type,
+                conditions: Vec::new(),
+                priority,
+                stackable,
+            }
+        };
+        let tier = |min: f64, discount_percent: f64| CartTier { min, discount_percent };
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_cart_discount(cart_discount(
+            "BILL-TIERS",
+            CartDiscountType::TieredByTotal(vec![tier(50_000.0, 5.0), tier(100_000.0, 10.0)]),
+            1,
+            true,
+        )).unwrap();
+        let mut welcome = cart_discount("WELCOME", CartDiscountType::FixedAmount(Money::new(50, 0)), 5, false);
+        welcome.conditions = vec![DiscountCondition::PromoCode("WELCOME".to_string())];
+        engine.add_cart_discount(welcome).unwrap();
+        assert!(engine
+            .add_cart_discount(cart_discount("BAD", CartDiscountType::TieredByQuantity(Vec::new()), 1, true))
+            .is_err());
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Kettle", Money::new(600, 0), 1.0));
+        cart.add_item(Item::new("Mug", Money::new(200, 0), 2.0));
+
+        // Rs.1000 net reaches the 10% tier: Rs.100 split 60 / 40 by line net, VAT follows
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        assert_eq!(result.items[0].discount_amount, Money::new(60, 0));
+        assert_eq!(result.items[1].discount_amount, Money::new(40, 0));
+        assert!(result.items.iter().all(|l| l.discount_details[0].rule_id == "BILL-TIERS"));
+        assert_eq!(result.total_tax, Money::new(162, 0));
+        assert_eq!(result.grand_total, Money::new(1062, 0));
+
+        // Higher-priority exclusive coupon wins and stops the tiers
+        let result = engine.calculate_cart(&cart, &["WELCOME".to_string()], Some("LK")).unwrap();
+        assert_eq!(result.total_discount, Money::new(50, 0));
+        assert!(result.items.iter().all(|l| l.discount_details.len() == 1 && l.discount_details[0].rule_id == "WELCOME"));
+
+        let mut by_quantity = MixedScenarioEngine::new();
+        by_quantity.add_cart_discount(cart_discount(
+            "3-FOR-5",
+            CartDiscountType::TieredByQuantity(vec![tier(3.0, 5.0)]),
+            1,
+            true,
+        )).unwrap();
+        assert_eq!(by_quantity.calculate_cart(&cart, &[], Some("LK")).unwrap().total_discount, Money::new(50, 0));
+    }
+
+    #[test]
+    fn test_return_line_nets_against_sale() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let rule = |id: &str, discount_type: DiscountType| DiscountRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            discount_type,
+            priority: 1,
+            conditions: Vec::new(),
+            stackable: true,
+        };
+        let tier = TierLevel {
+            min_qty: 2.0,
+            max_qty: None,
+            discount_percent: 5.0,
+        };
+        let mut tea = Item::new("Tea", Money::new(500, 0), 3.0);
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: tea.id.clone(),
+            discounts: vec![
+                rule("SALE10", DiscountType::Percentage(10.0)),
+                rule("BULK5", DiscountType::Tiered(vec![tier])),
+            ],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let mut cart = Cart::new();
+        cart.add_item(tea.clone());
+        tea.quantity = -2.0;
+        cart.add_item(tea);
+
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        // Sale: 1500 - (150 + 75) = 1275 + VAT 229.50 = 1504.50
+        assert_eq!(result.items[0].total, Money::new(1504, 50));
+        // Return: -(1000 - 100) - VAT 162; the bulk tier is not refunded
+        let returned = &result.items[1];
+        assert_eq!(returned.base_amount, Money::new(-1000, 0));
+        assert_eq!(returned.discount_amount, Money::new(-100, 0));
+        assert_eq!(returned.tax_amount, Money::new(-162, 0));
+        assert_eq!(returned.total, Money::new(-1062, 0));
+        assert!(returned.discount_details.iter().all(|d| d.rule_id == "SALE10"));
+
+        assert_eq!(result.subtotal, Money::new(500, 0));
+        assert_eq!(result.total_discount, Money::new(125, 0));
+        assert_eq!(result.total_tax, Money::new(67, 50));
+        assert_eq!(result.grand_total, Money::new(442, 50));
+    }
+
+    #[test]
+    fn test_gross_margin_on_discounted_line() {
+        let mut engine = capped_engine(100.0, CapStrategy::ProRate);
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        // SALE20 + Rs.15 coupon on Rs.100 × 2: revenue 200 - 55 = 145, cost 2 × 60
+        let item = Item { quantity: 2.0, ..sku() }.with_cost(Money::new(60, 0));
+        let mut cart = Cart::new();
+        cart.add_item(item);
+        cart.add_item(Item::new("Gift wrap", Money::new(50, 0), 1.0));
+
+        let result = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        let line = &result.items[0];
+        assert_eq!(line.net_revenue(), Money::new(145, 0));
+        assert_eq!(line.cost, Some(Money::new(120, 0)));
+        assert_eq!(line.gross_margin(), Some(Money::new(25, 0)));
+        assert!((line.margin_pct().unwrap() - 17.24).abs() < 0.01);
+        // No cost: unknown, not a 100% margin
+        assert_eq!(result.items[1].gross_margin(), None);
+        assert_eq!(result.items[1].margin_pct(), None);
+
+        let summary = result.margin_summary();
+        assert_eq!(summary.revenue, Money::new(145, 0));
+        assert_eq!(summary.gross_margin, Money::new(25, 0));
+        assert_eq!(summary.unknown_cost_lines, 1);
+    }
+
+    #[test]
+    fn test_small_order_tax_threshold() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.set_small_order_tax(Some(SmallOrderTax {
+            threshold: Money::new(100, 0),
+            treatment: SmallOrderTreatment::Exempt,
+        }));
+        let cart_of = |price: Money| {
+            let mut cart = Cart::new();
+            cart.add_item(Item::new("Pen", price, 1.0));
+            cart
+        };
+
+        let below = engine.calculate_cart(&cart_of(Money::new(99, 99)), &[], Some("LK")).unwrap();
+        assert_eq!(below.total_tax, Money::zero());
+        assert_eq!(below.items[0].tax_amount, Money::zero());
+        assert_eq!(below.grand_total, Money::new(99, 99));
+
+        let above = engine.calculate_cart(&cart_of(Money::new(100, 0)), &[], Some("LK")).unwrap();
+        assert_eq!(above.total_tax, Money::new(18, 0));
+        assert_eq!(above.grand_total, Money::new(118, 0));
+
+        engine.set_small_order_tax(Some(SmallOrderTax {
+            threshold: Money::new(100, 0),
+            treatment: SmallOrderTreatment::FlatRate(2.0),
+        }));
+        let flat = engine.calculate_cart(&cart_of(Money::new(50, 0)), &[], Some("LK")).unwrap();
+        assert_eq!(flat.total_tax, Money::new(1, 0));
+        assert_eq!(flat.items[0].total, Money::new(51, 0));
+        assert_eq!(flat.items[0].tax_details[0].rate, 2.0);
+
+        // The flat rate replaces taxes; it does not tax what carried none
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "BREAD".to_string(),
+            tax_rates: Vec::new(),
+            tax_exempt: true,
+            tax_included_in_price: false,
+        }).unwrap();
+        let mut cart = cart_of(Money::new(50, 0));
+        let mut bread = Item::new("Bread", Money::new(20, 0), 1.0);
+        bread.id = "BREAD".to_string();
+        cart.add_item(bread);
+        let mixed = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        assert_eq!(mixed.total_tax, Money::new(1, 0));
+        assert!(mixed.items[1].tax_details.is_empty());
+        assert_eq!(mixed.items[1].total, Money::new(20, 0));
+    }
+
+    #[test]
+    fn test_out_of_range_rates_rejected_at_config_time() {
+        let mut engine = MixedScenarioEngine::new();
+        for rate in [-5.0, f64::NAN, 1000.0] {
+            let err = engine.add_global_tax(TaxRate::new("VAT", rate, "LK", TaxAppliesTo::All));
+            assert!(matches!(err, Err(EngineError::Validation { .. })), "{}", rate);
+        }
+        let discount = |percent: f64| ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "BAD".to_string(),
+                name: "Bad".to_string(),
+                discount_type: DiscountType::Percentage(percent),
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        };
+        assert!(engine.add_product_discount(discount(-5.0)).is_err());
+        assert!(engine.add_product_discount(discount(f64::NAN)).is_err());
+        assert!(engine.add_product_discount(discount(1000.0)).is_err());
+        assert!(engine.snapshot().global_tax_rates.is_empty());
+        assert!(engine.snapshot().product_discounts.is_empty());
+
+        // A raised cap admits high excise rates, but still not 1000%
+        engine.set_max_rate_percent(300.0).unwrap();
+        engine.add_product_tax(ProductTaxConfig {
+            product_id: "ARRACK".to_string(),
+            tax_rates: vec![TaxRate::new("Excise", 250.0, "LK", TaxAppliesTo::All)],
+            tax_exempt: false,
+            tax_included_in_price: false,
+        })
+        .unwrap();
+        assert!(engine.add_global_tax(TaxRate::new("Excise", 1000.0, "LK", TaxAppliesTo::All)).is_err());
+        assert!(engine.set_max_rate_percent(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_rounding_residue_reconciles_cart_identity() {
+        // Upstream POS lines priced gross per line: base, tax and total each rounded on their own
+        let lines: Vec<ItemCalculation> = (0..60)
+            .map(|i| {
+                let exact_base = 1_000.0 + i as f64 * 37.0 + 0.4;
+                let round = |v: f64| Money::from_cents(v.round() as i64);
+                ItemCalculation {
+                    item_id: format!("L{}", i),
+                    base_amount: round(exact_base),
+                    discount_amount: Money::zero(),
+                    tax_amount: round(exact_base * 0.18),
+                    total: round(exact_base * 1.18),
+                    discount_details: Vec::new(),
+                    tax_details: Vec::new(),
+                    cap_adjustments: Vec::new(),
+                    suppressed_discounts: Vec::new(),
+                    tax_included: false,
+                    cost: None,
+                }
+            })
+            .collect();
+        let line_totals = lines.iter().fold(Money::zero(), |acc, l| acc + l.total);
+        let identity = |c: &CartCalculation| c.subtotal - c.total_discount + c.total_tax + c.rounding_adjustment;
+        let imported = CartCalculation {
+            items: lines,
+            subtotal: Money::zero(),
+            total_discount: Money::zero(),
+            total_tax: Money::zero(),
+            grand_total: line_totals,
+            rounding_adjustment: Money::zero(),
+            suppressed_discounts: Vec::new(),
+            included_tax: Money::zero(),
+            added_tax: Money::zero(),
+            deposits: Money::zero(),
+        };
+
+        let mut adjusted = imported.clone();
+        adjusted.reconcile(ResidueTarget::Adjustment, &TotalFormula::standard()).unwrap();
+        assert!(!adjusted.rounding_adjustment.is_zero(), "lines should not reconcile on their own");
+        assert_eq!(adjusted.grand_total, line_totals);
+        assert_eq!(identity(&adjusted), adjusted.grand_total);
+
+        let mut absorbed = imported.clone();
+        absorbed.reconcile(ResidueTarget::LargestLine, &TotalFormula::standard()).unwrap();
+        assert!(absorbed.rounding_adjustment.is_zero());
+        assert_eq!(absorbed.grand_total, line_totals);
+        assert_eq!(identity(&absorbed), absorbed.grand_total);
+        assert_eq!(absorbed.total_tax, adjusted.total_tax);
+        assert_eq!(absorbed.items[59].base_amount, imported.items[59].base_amount + adjusted.rounding_adjustment);
+
+        // The total is composed by the given formula: one leaving tax out rejects taxed lines
+        let mut untaxed = imported.clone();
+        let no_tax = TotalFormula::parse("subtotal - discount").unwrap();
+        assert!(untaxed.reconcile(ResidueTarget::Adjustment, &no_tax).is_err());
+        assert_eq!(untaxed.grand_total, imported.grand_total);
+
+        // Engine-priced lines reconcile with no adjustment
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cart = Cart::new();
+        for i in 0..60 {
+            cart.add_item(Item::new(&format!("I{}", i), Money::from_cents(1_003 + i * 37), 3.0));
+        }
+        let calc = engine.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        assert!(calc.rounding_adjustment.is_zero());
+        assert_eq!(calc.items.iter().fold(Money::zero(), |acc, l| acc + l.total), calc.grand_total);
+        assert_eq!(identity(&calc), calc.grand_total);
+    }
+
+    #[test]
+    fn test_contract_price_overrides_list_price_before_promo() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        engine.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "PROMO10".to_string(),
+                name: "Promo 10%".to_string(),
+                discount_type: DiscountType::Percentage(10.0),
+                priority: 1,
+                conditions: vec![DiscountCondition::PromoCode("SAVE10".to_string())],
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let list = |id: &str, scope: PriceListScope, price: i64, skip_promotions: bool| PriceList {
+            id: id.to_string(),
+            name: id.to_string(),
+            scope,
+            prices: BTreeMap::from([("SKU".to_string(), Money::new(price, 0))]),
+            skip_promotions,
+        };
+        engine.add_price_list(list("WHOLESALE", PriceListScope::Group("wholesale".to_string()), 85, false)).unwrap();
+        engine.add_price_list(list("ACME", PriceListScope::Customer("ACME".to_string()), 80, false)).unwrap();
+        engine.add_price_list(list("NET", PriceListScope::Customer("NETCO".to_string()), 70, true)).unwrap();
+        assert!(engine.add_price_list(list("BAD", PriceListScope::Group("x".to_string()), -1, false)).is_err());
+
+        let cart_for = |customer: Option<&str>, group: Option<&str>| {
+            let mut cart = Cart::new();
+            cart.customer_id = customer.map(str::to_string);
+            cart.customer_group = group.map(str::to_string);
+            cart.add_item(sku());
+            cart
+        };
+        let promo = vec!["SAVE10".to_string()];
+
+        // List price Rs.100: promo 10 off, VAT on 90
+        let retail = engine.calculate_cart(&cart_for(None, None), &promo, Some("LK")).unwrap();
+        assert_eq!(
+            (retail.subtotal, retail.total_discount, retail.total_tax),
+            (Money::new(100, 0), Money::new(10, 0), Money::new(16, 20))
+        );
+
+        // Customer's own contract (80) beats the group's (85); the promo works off the contract price
+        let acme = engine.calculate_cart(&cart_for(Some("ACME"), Some("wholesale")), &promo, Some("LK")).unwrap();
+        assert_eq!(acme.subtotal, Money::new(80, 0));
+        assert_eq!(acme.total_discount, Money::new(8, 0));
+        assert_eq!(acme.total_tax, Money::new(12, 96));
+        let wholesale = engine.calculate_cart(&cart_for(Some("OTHER"), Some("wholesale")), &[], Some("LK")).unwrap();
+        assert_eq!(wholesale.subtotal, Money::new(85, 0));
+
+        // Net-price contract: no promotions on top
+        let net = engine.calculate_cart(&cart_for(Some("NETCO"), None), &promo, Some("LK")).unwrap();
+        assert_eq!(
+            (net.subtotal, net.total_discount, net.total_tax),
+            (Money::new(70, 0), Money::zero(), Money::new(12, 60))
+        );
+    }
+}
+
+/// 🧪 Calculation order invariants (property tests)
+/// `CalculationOrder` අතර ලේඛනගත සම්බන්ධතා අහඹු මිල, බදු සහ වට්ටම් මත තහවුරු කරයි.
+#[cfg(test)]
+mod order_invariant_tests {
+    use super::*;
+    use quickcheck::{quickcheck, TestResult};
+
+    const ORDERS: [CalculationOrder; 3] = [
+        CalculationOrder::DiscountFirst,
+        CalculationOrder::TaxFirst,
+        CalculationOrder::Parallel,
+    ];
+
+    /// Line total of one unit at `price_cents` with `tax_percent` VAT and `discount` on the SKU
+    fn total(order: CalculationOrder, price_cents: i64, tax_percent: f64, discount: Option<DiscountType>) -> Money {
+        let mut engine = MixedScenarioEngine::new();
+        engine.set_calculation_order(order);
+        if tax_percent > 0.0 {
+            engine.add_global_tax(TaxRate::new("VAT", tax_percent, "LK", TaxAppliesTo::All)).unwrap();
+        }
+        if let Some(discount_type) = discount {
+            engine.add_product_discount(ProductDiscountConfig {
+                product_id: "SKU".to_string(),
+                discounts: vec![DiscountRule {
+                    id: "D".to_string(),
+                    name: "Discount".to_string(),
+                    discount_type,
+                    priority: 1,
+                    conditions: Vec::new(),
+                    stackable: true,
+                }],
+                stackable: true,
+                max_discount_percent: None,
+            }).unwrap();
+        }
+        let mut item = Item::new("SKU", Money::from_cents(price_cents), 1.0);
+        item.id = "SKU".to_string();
+        engine.calculate_item(&item, std::slice::from_ref(&item), &[], Some("LK")).unwrap().total
+    }
+
+    fn totals(price_cents: i64, tax_percent: f64, discount: Option<DiscountType>) -> [Money; 3] {
+        ORDERS.map(|order| total(order, price_cents, tax_percent, discount.clone()))
+    }
+
+    /// Rs.0.01 - Rs.10,000
+    fn price(seed: u32) -> i64 {
+        1 + (seed % 1_000_000) as i64
+    }
+
+    #[test]
+    fn prop_zero_tax_makes_orders_equal() {
+        fn prop(seed: u32, discount_percent: u8) -> bool {
+            let discount = DiscountType::Percentage((discount_percent % 101) as f64);
+            let [first, tax_first, parallel] = totals(price(seed), 0.0, Some(discount));
+            first == tax_first && tax_first == parallel
+        }
+        quickcheck(prop as fn(u32, u8) -> bool);
+    }
+
+    #[test]
+    fn prop_zero_discount_makes_orders_equal() {
+        fn prop(seed: u32, tax_percent: u8) -> bool {
+            let [first, tax_first, parallel] = totals(price(seed), (tax_percent % 51) as f64, None);
+            first == tax_first && tax_first == parallel
+        }
+        quickcheck(prop as fn(u32, u8) -> bool);
+    }
+
+    #[test]
+    fn prop_percentage_discount_orders() {
+        fn prop(seed: u32, tax_percent: u8, discount_percent: u8) -> TestResult {
+            let (tax, discount) = ((tax_percent % 51) as f64, (discount_percent % 101) as f64);
+            if tax == 0.0 || discount == 0.0 {
+                return TestResult::discard();
+            }
+            let [first, tax_first, parallel] = totals(price(seed), tax, Some(DiscountType::Percentage(discount)));
+            // Percentages commute: each order rounds twice, so they agree within 2 cents (tax <= 50%)
+            let commute = (first - tax_first).abs() <= Money::from_cents(2);
+            TestResult::from_bool(first <= parallel && tax_first <= parallel && commute)
+        }
+        quickcheck(prop as fn(u32, u8, u8) -> TestResult);
+    }
+
+    #[test]
+    fn prop_fixed_discount_orders() {
+        fn prop(seed: u32, tax_percent: u8, off: u32) -> TestResult {
+            let price_cents = price(seed);
+            let tax = (tax_percent % 51) as f64;
+            let discount = 1 + off as i64 % price_cents;
+            if tax == 0.0 {
+                return TestResult::discard();
+            }
+            let [first, tax_first, parallel] = totals(price_cents, tax, Some(DiscountType::FixedAmount(discount)));
+            // A fixed amount off the taxed total leaves the tax untouched
+            TestResult::from_bool(first <= tax_first && tax_first == parallel)
+        }
+        quickcheck(prop as fn(u32, u8, u32) -> TestResult);
+    }
+
+    #[test]
+    fn test_tax_first_discounts_the_taxed_amount() {
+        // Rs.1,000 + 18% VAT = 1,180; 10% off: DiscountFirst 900 + 162, TaxFirst 1,180 - 118, Parallel 1,180 - 100
+        let [first, tax_first, parallel] = totals(100_000, 18.0, Some(DiscountType::Percentage(10.0)));
+        assert_eq!(first, Money::new(1062, 0));
+        assert_eq!(tax_first, Money::new(1062, 0));
+        assert_eq!(parallel, Money::new(1080, 0));
+    }
 }
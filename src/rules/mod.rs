@@ -4,3 +4,6 @@ pub mod builder;
 pub mod traits;
 pub mod promotions;
 pub mod mixed_scenarios;
+pub mod csv_import;
+pub mod definition;
+pub mod multi_store;
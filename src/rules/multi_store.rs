@@ -0,0 +1,126 @@
+use crate::core::errors::EngineResult;
+use crate::rules::mixed_scenarios::{
+    CartCalculation, ItemCalculation, MixedScenarioEngine, ProductDiscountConfig, ProductTaxConfig,
+    TaxRate,
+};
+use crate::types::cart::Cart;
+use crate::types::item::Item;
+use std::collections::HashMap;
+
+/// ============================================================================
+/// 🏬 Multi-Store Pricing (බහු-වෙළඳසැල් මිලකරණය)
+/// ============================================================================
+/// එක deployment එකකින් වෙනස් බදු/වට්ටම් සැකසුම් සහිත stores/brands කිහිපයකට
+/// සේවය කරයි. සෑම store එකකටම තමන්ගේම `MixedScenarioEngine` config එකක් ඇති අතර
+/// config එකක් නොමැති (හෝ `None`) store එකක් default store එකට වැටේ.
+/// Store configs ස්වාධීනයි: default එකේ rules store එකකට උරුම නොවේ.
+#[derive(Clone)]
+pub struct MultiStoreEngine {
+    default_store: MixedScenarioEngine,
+    stores: HashMap<String, MixedScenarioEngine>,
+}
+
+impl Default for MultiStoreEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultiStoreEngine {
+    pub fn new() -> Self {
+        MultiStoreEngine {
+            default_store: MixedScenarioEngine::new(),
+            stores: HashMap::new(),
+        }
+    }
+
+    /// Config used for unknown stores and calls without a store
+    pub fn default_store_mut(&mut self) -> &mut MixedScenarioEngine {
+        &mut self.default_store
+    }
+
+    /// ⚙️ A store's config (created empty on first use) - all engine setters apply per store
+    pub fn store_mut(&mut self, store_id: &str) -> &mut MixedScenarioEngine {
+        self.stores.entry(store_id.to_string()).or_insert_with(MixedScenarioEngine::new)
+    }
+
+    pub fn has_store(&self, store_id: &str) -> bool {
+        self.stores.contains_key(store_id)
+    }
+
+    /// 🔍 Config selected for a store context
+    pub fn engine_for(&self, store_id: Option<&str>) -> &MixedScenarioEngine {
+        store_id
+            .and_then(|id| self.stores.get(id))
+            .unwrap_or(&self.default_store)
+    }
+
+    pub fn add_global_tax(&mut self, store_id: &str, tax: TaxRate) -> EngineResult<()> {
+        self.store_mut(store_id).add_global_tax(tax)
+    }
+
+    pub fn add_product_tax(&mut self, store_id: &str, config: ProductTaxConfig) -> EngineResult<()> {
+        self.store_mut(store_id).add_product_tax(config)
+    }
+
+    pub fn add_product_discount(&mut self, store_id: &str, config: ProductDiscountConfig) -> EngineResult<()> {
+        self.store_mut(store_id).add_product_discount(config)
+    }
+
+    /// 💰 Calculate a single item under a store's config
+    pub fn calculate_item(
+        &self,
+        store_id: Option<&str>,
+        item: &Item,
+        cart_items: &[Item],
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+    ) -> EngineResult<ItemCalculation> {
+        self.engine_for(store_id)
+            .calculate_item(item, cart_items, promo_codes, target_jurisdiction)
+    }
+
+    /// 📊 Calculate a cart under a store's config
+    pub fn calculate_cart(
+        &self,
+        store_id: Option<&str>,
+        cart: &Cart,
+        promo_codes: &[String],
+        target_jurisdiction: Option<&str>,
+    ) -> EngineResult<CartCalculation> {
+        self.engine_for(store_id)
+            .calculate_cart(cart, promo_codes, target_jurisdiction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_same_sku_different_vat_per_store() {
+        let vat = |rate: f64| ProductTaxConfig {
+            product_id: "TEA".to_string(),
+            tax_rates: vec![TaxRate::new("VAT", rate, "ALL", TaxAppliesTo::All)],
+            tax_exempt: false,
+            tax_included_in_price: false,
+        };
+        let mut engine = MultiStoreEngine::new();
+        engine.add_product_tax("colombo", vat(18.0)).unwrap();
+        engine.add_product_tax("duty-free", vat(0.0)).unwrap();
+        engine.default_store_mut().add_product_tax(vat(15.0)).unwrap();
+
+        let mut tea = Item::new("Tea", Money::new(1000, 0), 1.0);
+        tea.id = "TEA".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(tea);
+
+        let tax = |store: Option<&str>| engine.calculate_cart(store, &cart, &[], None).unwrap().total_tax;
+        assert_eq!(tax(Some("colombo")), Money::new(180, 0));
+        assert!(tax(Some("duty-free")).is_zero());
+        // Unknown store and no store fall back to the default config
+        assert_eq!(tax(Some("kandy")), Money::new(150, 0));
+        assert_eq!(tax(None), Money::new(150, 0));
+    }
+}
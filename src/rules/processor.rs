@@ -1,6 +1,9 @@
 use crate::types::cart::Cart;
 use crate::core::errors::EngineResult;
+use crate::rules::conditions::Condition;
 use crate::rules::traits::{Rule, RuleAction};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// ============================================================================
 /// ⚙️ Rule Processor (රීති ක්‍රියාත්මක කරන්නා)
@@ -22,8 +25,8 @@ impl RuleProcessor {
     /// 📥 රීතියක් එකතු කරන්න (Register Rule)
     pub fn register_rule(&mut self, rule: Box<dyn Rule>) {
         self.rules.push(rule);
-        // Sort by priority (descending)
-        self.rules.sort_by(|a, b| b.priority().cmp(&a.priority()));
+        // Sort by priority (descending), ties by name so registration order never decides
+        self.rules.sort_by(|a, b| b.priority().cmp(&a.priority()).then_with(|| a.name().cmp(b.name())));
     }
 
     /// 🚀 සියලු රීති ක්‍රියාත්මක කරන්න (Process All)
@@ -36,6 +39,9 @@ impl RuleProcessor {
                 match rule.apply(cart) {
                     Ok(mut rule_actions) => {
                         actions.append(&mut rule_actions);
+                        if !rule.stackable() {
+                            break;
+                        }
                     },
                     Err(e) => {
                         // Log error but maybe don't stop everything?
@@ -48,4 +54,144 @@ impl RuleProcessor {
 
         Ok(actions)
     }
+
+    /// 🔎 Promo setup sanity check (static - cart එකක් අවශ්‍ය නැත)
+    /// - Unreachable: an unconditional non-stackable rule ahead of it always wins
+    /// - Conflicting overrides: more than one rule overrides the same item's price
+    /// - Priority ties: order among equal priorities depends on registration order
+    pub fn analyze(&self) -> RuleAnalysis {
+        let mut analysis = RuleAnalysis::default();
+
+        // Rules are kept sorted by priority (stable), so "ahead" == earlier in the list
+        let blocker = self
+            .rules
+            .iter()
+            .position(|r| !r.stackable() && matches!(r.condition(), Some(Condition::Always)));
+        if let Some(index) = blocker {
+            let blocked_by = self.rules[index].name().to_string();
+            for rule in &self.rules[index + 1..] {
+                analysis.unreachable.push(UnreachableRule {
+                    rule: rule.name().to_string(),
+                    blocked_by: blocked_by.clone(),
+                });
+            }
+        }
+
+        let mut overrides: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for rule in &self.rules {
+            for item_id in rule.price_override_targets() {
+                overrides.entry(item_id).or_default().push(rule.name().to_string());
+            }
+        }
+        analysis.conflicting_overrides = overrides
+            .into_iter()
+            .filter(|(_, rules)| rules.len() > 1)
+            .map(|(item_id, rules)| OverrideConflict { item_id, rules })
+            .collect();
+
+        let mut by_priority: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+        for rule in &self.rules {
+            by_priority.entry(rule.priority()).or_default().push(rule.name().to_string());
+        }
+        analysis.priority_ties = by_priority
+            .into_iter()
+            .rev()
+            .filter(|(_, rules)| rules.len() > 1)
+            .map(|(priority, rules)| PriorityTie { priority, rules })
+            .collect();
+
+        analysis
+    }
+}
+
+/// 🚫 A rule that can never run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnreachableRule {
+    pub rule: String,
+    pub blocked_by: String,
+}
+
+/// ⚔️ Several rules overriding one item's price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverrideConflict {
+    pub item_id: String,
+    pub rules: Vec<String>,
+}
+
+/// 🟰 Rules sharing a priority
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorityTie {
+    pub priority: i32,
+    pub rules: Vec<String>,
+}
+
+/// 📋 Rule Analysis Report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleAnalysis {
+    pub unreachable: Vec<UnreachableRule>,
+    pub conflicting_overrides: Vec<OverrideConflict>,
+    pub priority_ties: Vec<PriorityTie>,
+}
+
+impl RuleAnalysis {
+    pub fn is_clean(&self) -> bool {
+        self.unreachable.is_empty() && self.conflicting_overrides.is_empty() && self.priority_ties.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn rule(json: &str) -> Box<dyn Rule> {
+        RuleDefinition::from_json(json).unwrap().compile().unwrap()
+    }
+
+    #[test]
+    fn test_stacked_rule_behind_non_stackable_is_unreachable() {
+        let mut processor = RuleProcessor::new();
+        processor.register_rule(rule(
+            r#"{ "name": "Staff 30%", "priority": 50, "stackable": false,
+                 "actions": [ { "PercentageDiscount": { "percent": 30.0 } } ] }"#,
+        ));
+        processor.register_rule(rule(
+            r#"{ "name": "Weekend 5%", "priority": 10,
+                 "actions": [ { "PercentageDiscount": { "percent": 5.0 } } ] }"#,
+        ));
+
+        let analysis = processor.analyze();
+        assert_eq!(
+            analysis.unreachable,
+            vec![UnreachableRule {
+                rule: "Weekend 5%".to_string(),
+                blocked_by: "Staff 30%".to_string(),
+            }]
+        );
+        assert!(analysis.conflicting_overrides.is_empty());
+
+        // Processing agrees: only the staff discount runs
+        assert_eq!(processor.process(&Cart::new()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_conflicting_price_overrides_and_ties() {
+        let mut processor = RuleProcessor::new();
+        processor.register_rule(rule(
+            r#"{ "name": "Clearance", "priority": 20,
+                 "actions": [ { "PriceOverride": { "item_id": "SKU-1", "price": { "amount": 5000 } } } ] }"#,
+        ));
+        processor.register_rule(rule(
+            r#"{ "name": "Member Price", "priority": 20,
+                 "actions": [ { "PriceOverride": { "item_id": "SKU-1", "price": { "amount": 4500 } } } ] }"#,
+        ));
+
+        let analysis = processor.analyze();
+        assert_eq!(analysis.conflicting_overrides.len(), 1);
+        assert_eq!(analysis.conflicting_overrides[0].item_id, "SKU-1");
+        assert_eq!(analysis.conflicting_overrides[0].rules, vec!["Clearance", "Member Price"]);
+        assert_eq!(analysis.priority_ties[0].priority, 20);
+        assert!(analysis.unreachable.is_empty());
+        assert!(!analysis.is_clean());
+    }
 }
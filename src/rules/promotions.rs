@@ -139,3 +139,77 @@ impl Rule for GlobalQtyThreshold {
         Ok(vec![RuleAction::Discount(self.discount_amount.clone())])
     }
 }
+
+/// 5. Shipping Fee with a Free-Shipping Threshold
+///
+/// Charges `shipping_fee` unless the goods subtotal reaches `free_above`.
+/// Shipping coupons (`DiscountTarget::Shipping`) then reduce whatever fee remains.
+pub struct FreeShippingThreshold {
+    pub name: String,
+    pub shipping_fee: Money,
+    pub free_above: Money,
+}
+
+impl Rule for FreeShippingThreshold {
+    fn name(&self) -> &str { &self.name }
+    // Before coupons in priority order (fees are resolved after all rules anyway)
+    fn priority(&self) -> i32 { 60 }
+
+    fn can_apply(&self, cart: &Cart) -> bool {
+        cart.subtotal() < self.free_above
+    }
+
+    fn apply(&self, _cart: &Cart) -> EngineResult<Vec<RuleAction>> {
+        Ok(vec![RuleAction::Fee(self.shipping_fee)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn checkout(goods: Money, coupon: &str) -> CalculationResult {
+        let mut rules = load_rules(&format!(r#"[ {{ "name": "Coupon", "actions": [ {} ] }} ]"#, coupon)).unwrap();
+        rules.push(Box::new(FreeShippingThreshold {
+            name: "Shipping".to_string(),
+            shipping_fee: Money::new(350, 0),
+            free_above: Money::new(5000, 0),
+        }));
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Book", goods, 1.0));
+        CalculationEngine::new().calculate(&cart, &rules).unwrap()
+    }
+
+    #[test]
+    fn test_goods_coupon_leaves_shipping_untouched() {
+        let result = checkout(Money::new(2000, 0), r#"{ "PercentageDiscount": { "percent": 10.0 } }"#);
+        assert_eq!(result.discount_total, Money::new(200, 0));
+        assert_eq!(result.non_taxable_fees, Money::new(350, 0));
+        assert!(result.shipping_discount.is_zero());
+        assert_eq!(result.grand_total, Money::new(2150, 0));
+    }
+
+    #[test]
+    fn test_shipping_coupon_reduces_only_the_fee() {
+        let coupon = r#"{ "FixedDiscount": { "amount": { "amount": 20000 }, "target": "Shipping" } }"#;
+        let result = checkout(Money::new(2000, 0), coupon);
+        assert!(result.discount_total.is_zero());
+        assert_eq!(result.shipping_discount, Money::new(200, 0));
+        assert_eq!(result.non_taxable_fees, Money::new(150, 0));
+        assert_eq!(result.grand_total, Money::new(2150, 0));
+
+        // Free shipping coupon
+        let free = checkout(
+            Money::new(2000, 0),
+            r#"{ "PercentageDiscount": { "percent": 100.0, "target": "Shipping" } }"#,
+        );
+        assert!(free.non_taxable_fees.is_zero());
+        assert_eq!(free.grand_total, Money::new(2000, 0));
+
+        // Above the threshold shipping is already free: the coupon has nothing to reduce
+        let above = checkout(Money::new(6000, 0), coupon);
+        assert!(above.shipping_discount.is_zero());
+        assert_eq!(above.grand_total, Money::new(6000, 0));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::types::cart::Cart;
 use crate::core::errors::EngineResult;
 use crate::core::money::Money;
+use crate::rules::conditions::Condition;
+use serde::{Deserialize, Serialize};
 
 /// ============================================================================
 /// 🔌 Rule Traits (රීති ගුණාංග) - Pluggable Architecture
@@ -8,17 +10,50 @@ use crate::core::money::Money;
 /// ඕනෑම කෙනෙකුට තමන්ගේම රීති එන්ජිමට ඇතුළත් කිරීමට මෙය ඉඩ දෙයි.
 
 pub enum RuleAction {
-    /// මිල අඩු කිරීමක් (Discount)
+    /// මිල අඩු කිරීමක් (Discount) - භාණ්ඩ මත
     Discount(Money),
+
+    /// නිශ්චිත ඉලක්කයකට වට්ටමක් (Ex: free-shipping coupon)
+    /// Shipping discounts reduce the fees emitted by other rules and never go below zero.
+    TargetedDiscount { value: DiscountValue, target: DiscountTarget },
     
     /// බද්දක් (Tax)
     Tax(Money),
+
+    /// Taxable subtotal එකේ ප්‍රතිශතයක් ලෙස බද්දක් - engine එකේ rounding mode එකෙන් වට කරයි
+    PercentageTax(f64),
     
-    /// ගාස්තුවක් එකතු කිරීමක් (Surcharge/Fee)
+    /// ගාස්තුවක් එකතු කිරීමක් (Surcharge/Fee) - බදු අය නොවේ
     Fee(Money),
+
+    /// බදු අය කළ හැකි ගාස්තුවක් (Ex: service fee where services are taxed)
+    /// Tax rules see it as part of the taxable base.
+    TaxableFee(Money),
     
     /// නොමිලේ භාණ්ඩයක් (Free Item)
     FreeItem { item_id: String, qty: f64 },
+
+    /// ඒකක මිල ප්‍රතිස්ථාපනය (Price Override)
+    PriceOverride { item_id: String, price: Money },
+}
+
+/// 🎯 What a discount reduces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DiscountTarget {
+    /// භාණ්ඩ (merchandise line totals)
+    #[default]
+    Goods,
+    /// Shipping / delivery fee
+    Shipping,
+    /// Goods first, any remainder off shipping (percentages apply to both)
+    Both,
+}
+
+/// 💵 Discount amount resolved against its target's base
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiscountValue {
+    Fixed(Money),
+    Percentage(f64),
 }
 
 pub trait Rule {
@@ -33,4 +68,19 @@ pub trait Rule {
     
     /// ප්‍රමුඛතාවය (Priority) - වැඩි අගයක් මුලින් ක්‍රියාත්මක වේ
     fn priority(&self) -> i32;
+
+    /// අනෙක් රීති සමඟ එකතු විය හැකිද? (false => පසු රීති ක්‍රියාත්මක නොවේ)
+    fn stackable(&self) -> bool {
+        true
+    }
+
+    /// Static condition (analysis සඳහා). None => unknown / code-only
+    fn condition(&self) -> Option<&Condition> {
+        None
+    }
+
+    /// Items whose price this rule overrides (analysis සඳහා)
+    fn price_override_targets(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
@@ -29,6 +29,10 @@ pub enum AuditAction {
     RateLimitExceeded,
     SuspiciousActivity,
     
+    // Inventory events
+    StockMoved,
+    StockAdjusted,
+
     // System events
     ConfigChanged,
     RuleAdded,
@@ -62,6 +66,9 @@ pub struct AuditEntry {
     pub amount: Option<Money>,
     pub description: String,
     pub metadata: std::collections::HashMap<String, String>,
+    /// HTTP request that caused this entry (`X-Request-Id`)
+    #[serde(default)]
+    pub correlation_id: Option<String>,
     pub checksum: String,
 }
 
@@ -90,6 +97,7 @@ impl AuditEntry {
             amount: None,
             description: description.to_string(),
             metadata: std::collections::HashMap::new(),
+            correlation_id: None,
             checksum: String::new(),
         };
         
@@ -135,11 +143,18 @@ impl AuditEntry {
         self
     }
 
+    /// Link to the originating request
+    pub fn with_correlation_id(mut self, correlation_id: &str) -> Self {
+        self.correlation_id = Some(correlation_id.to_string());
+        self.checksum = self.calculate_checksum();
+        self
+    }
+
     /// Calculate tamper-proof checksum
     fn calculate_checksum(&self) -> String {
         use sha2::{Sha256, Digest};
         
-        let data = format!(
+        let mut data = format!(
             "{}:{}:{:?}:{:?}:{:?}:{}",
             self.id,
             self.timestamp.timestamp(),
@@ -148,6 +163,10 @@ impl AuditEntry {
             self.amount.as_ref().map(|m| m.amount),
             self.description
         );
+        // Appended only when set so entries written before correlation ids still verify
+        if let Some(correlation_id) = &self.correlation_id {
+            data.push_str(&format!(":{}", correlation_id));
+        }
         
         let mut hasher = Sha256::new();
         hasher.update(data.as_bytes());
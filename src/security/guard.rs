@@ -1,26 +1,63 @@
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use crate::api::facade::FinancialEngine;
-use crate::core::errors::{EngineResult, EngineError};
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use crate::inventory::stock::{InventoryManager, MovementOutcome, MovementType, StockMovement};
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use crate::refund::types::{RefundDestination, RefundResult};
+use crate::rules::mixed_scenarios::CartCalculation;
+use crate::security::lock_order::{LockRank, OrderedMutex};
+use crate::types::cart::Cart;
 
 /// ============================================================================
 /// 🛡️ Iron Guard (ආරක්ෂිත කවචය)
 /// ============================================================================
 /// මෙය මධ්‍යගත ආරක්ෂක පද්ධතියයි. ගනුදෙනුවක් සිදුවන අතරතුර වෙනත් කිසිවෙකුට
 /// මැදිහත් විය නොහැකි ලෙස එන්ජිම "Lock" කරයි.
+/// Inventory සහ ledger වලට තමන්ගේම lock ඇති අතර, ඒවා දෙකම අවශ්‍ය
+/// `checkout`, `void`, `refund` සැමවිටම Inventory → Ledger පිළිවෙලට lock කරයි
+/// (`lock_order` බලන්න), එබැවින් සමගාමී checkout සහ refund deadlock නොවේ.
+/// Inventory සහ ledger හි එකම පිටපත guard එක සතුය; `execute_transaction` ඒවා
+/// closure එක ක්‍රියාත්මක වන තෙක් engine එකට ණයට දෙයි.
 /// (Centralized Transactional Guard)
 
 pub struct IronGuard {
-    engine: Arc<Mutex<FinancialEngine>>,
+    engine: Arc<OrderedMutex<FinancialEngine>>,
+    inventory: Arc<OrderedMutex<InventoryManager>>,
+    ledger: Arc<OrderedMutex<GeneralLedger>>,
+}
+
+/// 🏦 Accounts a sale posts to
+#[derive(Debug, Clone)]
+pub struct SaleAccounts {
+    /// Cash / receivable (debited with the grand total)
+    pub receivable: String,
+    pub revenue: String,
+    /// Tax name => tax payable account
+    pub tax_accounts: HashMap<String, String>,
+    /// Contra-revenue account for discounts (revenue is then posted gross); required for free orders
+    pub discounts: Option<String>,
 }
 
 impl IronGuard {
-    pub fn new(engine: FinancialEngine) -> Self {
+    /// The engine's inventory and ledger move behind their own locks; the guard owns the only copy
+    /// (use `inventory()` / `ledger()` for direct access)
+    pub fn new(mut engine: FinancialEngine) -> Self {
+        let inventory = std::mem::replace(&mut engine.inventory, InventoryManager::new());
+        let ledger = std::mem::replace(&mut engine.ledger, GeneralLedger::new());
         IronGuard {
-            engine: Arc::new(Mutex::new(engine)),
+            engine: Arc::new(OrderedMutex::new(LockRank::Engine, engine)),
+            inventory: Arc::new(OrderedMutex::new(LockRank::Inventory, inventory)),
+            ledger: Arc::new(OrderedMutex::new(LockRank::Ledger, ledger)),
         }
     }
 
     /// 🔒 Execute a Safe Transaction (ආරක්ෂිත ගනුදෙනුවක්)
+    /// Takes every lock (Engine → Inventory → Ledger) and lends the guard's inventory and ledger
+    /// to the engine for the duration of `action`.
     pub fn execute_transaction<F, R>(&self, action: F) -> EngineResult<R>
     where
         F: FnOnce(&mut FinancialEngine) -> EngineResult<R>,
@@ -30,15 +67,19 @@ impl IronGuard {
         LoggerEngine::log("🔒 IRON GUARD: එන්ජිම ලොක් කරන ලදී. (Engine Locked)");
 
         // 1. Lock the Engine (වෙනත් අයට ඇතුල් විය නොහැක)
-        let mut engine_lock = self.engine.lock().map_err(|_| EngineError::Validation { 
-            message: "IronGuard Lock Poisoned!".to_string() 
-        })?;
+        let mut engine_lock = self.engine.lock()?;
+        let mut inventory = self.inventory.lock()?;
+        let mut ledger = self.ledger.lock()?;
 
         LoggerEngine::log("⚙️ IRON GUARD: ගනුදෙනුව ක්‍රියාත්මක වෙමින් පවතී... (Processing)");
 
         // 2. Execute Action (ක්‍රියාව සිදු කිරීම)
-        let result = action(&mut *engine_lock);
-        
+        std::mem::swap(&mut engine_lock.inventory, &mut *inventory);
+        std::mem::swap(&mut engine_lock.ledger, &mut *ledger);
+        let result = action(&mut engine_lock);
+        std::mem::swap(&mut engine_lock.inventory, &mut *inventory);
+        std::mem::swap(&mut engine_lock.ledger, &mut *ledger);
+
         match &result {
             Ok(_) => LoggerEngine::log("✅ IRON GUARD: ගනුදෙනුව සාර්ථකයි. (Success)"),
             Err(e) => LoggerEngine::error(&format!("⚠️ IRON GUARD: ගනුදෙනුව අසාර්ථකයි! {:?}", e)),
@@ -49,11 +90,415 @@ impl IronGuard {
         // 3. Auto Unlock when scope ends
         result
     }
-    
+
+    /// 📦 Inventory only
+    pub fn inventory<R>(&self, action: impl FnOnce(&mut InventoryManager) -> R) -> EngineResult<R> {
+        let mut inventory = self.inventory.lock()?;
+        Ok(action(&mut inventory))
+    }
+
+    /// 🏦 Ledger only
+    pub fn ledger<R>(&self, action: impl FnOnce(&mut GeneralLedger) -> R) -> EngineResult<R> {
+        let mut ledger = self.ledger.lock()?;
+        Ok(action(&mut ledger))
+    }
+
+    /// 🛒 Checkout: ship every line from `warehouse_id`, then post the sale
+    /// A failure at either step leaves both stock and ledger untouched.
+    /// A free order (`CartCalculation::is_free`) debits nothing to `receivable`.
+    /// There is no payment to capture; the discount still reaches the ledger.
+    pub fn checkout(
+        &self,
+        order_id: &str,
+        cart: &Cart,
+        sale: &CartCalculation,
+        warehouse_id: &str,
+        accounts: &SaleAccounts,
+    ) -> EngineResult<Transaction> {
+        let mut inventory = self.inventory.lock()?;
+        let mut ledger = self.ledger.lock()?;
+
+        let lines: Vec<(&str, f64)> = cart.items.iter().map(|i| (i.id.as_str(), i.quantity)).collect();
+        let moved = move_stock(&mut inventory, warehouse_id, &lines, MovementType::Outbound, order_id)?;
+
+        ledger
+            .post_sale_with_discounts(
+                sale,
+                &accounts.receivable,
+                &accounts.revenue,
+                accounts.discounts.as_deref(),
+                &accounts.tax_accounts,
+            )
+            // The rollback releases `order_id`, so a retried checkout ships the stock again
+            .inspect_err(|_| undo_stock(&mut inventory, warehouse_id, &moved, MovementType::Outbound, order_id))
+    }
+
+    /// 🚫 Void: restock every line and reverse the posted sale
+    pub fn void(
+        &self,
+        order_id: &str,
+        cart: &Cart,
+        sale_transaction: &Transaction,
+        warehouse_id: &str,
+    ) -> EngineResult<Transaction> {
+        let mut inventory = self.inventory.lock()?;
+        let mut ledger = self.ledger.lock()?;
+
+        let reversal = sale_transaction.reversal();
+        let reference = format!("{}#void", order_id);
+        let lines: Vec<(&str, f64)> = cart.items.iter().map(|i| (i.id.as_str(), i.quantity)).collect();
+        let moved = move_stock(&mut inventory, warehouse_id, &lines, MovementType::Inbound, &reference)?;
+        ledger
+            .post_transaction(reversal.clone())
+            .inspect_err(|_| undo_stock(&mut inventory, warehouse_id, &moved, MovementType::Inbound, &reference))?;
+        Ok(reversal)
+    }
+
+    /// 🔄 Refund: restock the refunded lines, reverse their revenue and output tax, pay the refund
+    /// Refund amounts are tax-inclusive: each line's tax is taken from `sale` in proportion to the
+    /// refunded share of the sale line and debited to that tax's payable account.
+    /// Only original-tender refunds are paid back through `receivable`; store-credit and bank
+    /// refunds post through `GeneralLedger::post_refund` with their own accounts.
+    pub fn refund(
+        &self,
+        refund: &RefundResult,
+        sale: &CartCalculation,
+        warehouse_id: &str,
+        accounts: &SaleAccounts,
+    ) -> EngineResult<Transaction> {
+        if refund.destination != RefundDestination::OriginalTender {
+            return Err(EngineError::Validation {
+                message: format!("{:?} refunds need RefundAccounts (GeneralLedger::post_refund)", refund.destination),
+            });
+        }
+        let mut transaction = Transaction::new("Refund");
+        let mut tax_total = Money::zero();
+        for (name, amount) in refund_tax(refund, sale)? {
+            let account = accounts.tax_accounts.get(&name).ok_or_else(|| EngineError::Validation {
+                message: format!("No tax payable account configured for '{}'", name),
+            })?;
+            transaction = transaction.debit(account, amount);
+            tax_total = tax_total + amount;
+        }
+        let mut transaction = transaction
+            .debit(&accounts.revenue, refund.refund_amount - tax_total)
+            .credit(&accounts.receivable, refund.refund_amount);
+        transaction.metadata.insert("refund_id".to_string(), refund.id.clone());
+
+        let mut inventory = self.inventory.lock()?;
+        let mut ledger = self.ledger.lock()?;
+
+        let lines: Vec<(&str, f64)> = refund.lines.iter().map(|l| (l.item_id.as_str(), l.quantity)).collect();
+        let moved = move_stock(&mut inventory, warehouse_id, &lines, MovementType::Inbound, &refund.id)?;
+        ledger
+            .post_transaction(transaction.clone())
+            .inspect_err(|_| undo_stock(&mut inventory, warehouse_id, &moved, MovementType::Inbound, &refund.id))?;
+        Ok(transaction)
+    }
+
     /// 🔓 Get clone of internal engine for read-only checks (Testing only)
     /// In production, use execute_transaction for everything.
     pub fn get_snapshot(&self) -> EngineResult<crate::core::calculation::CalculationResult> {
-        let guard = self.engine.lock().unwrap();
+        let guard = self.engine.lock()?;
         guard.calculate()
     }
 }
+
+/// Apply one movement per line and return the lines actually moved (a line already recorded
+/// under `reference` is skipped); on failure the lines already moved are undone
+fn move_stock<'a>(
+    inventory: &mut InventoryManager,
+    warehouse_id: &str,
+    lines: &[(&'a str, f64)],
+    movement_type: MovementType,
+    reference: &str,
+) -> EngineResult<Vec<(&'a str, f64)>> {
+    let mut moved = Vec::with_capacity(lines.len());
+    for &(item_id, quantity) in lines {
+        let movement = StockMovement::new(item_id, warehouse_id, quantity, movement_type, reference);
+        match inventory.record_movement(movement) {
+            Ok(MovementOutcome::Duplicate) => {}
+            Ok(_) => moved.push((item_id, quantity)),
+            Err(e) => {
+                undo_stock(inventory, warehouse_id, &moved, movement_type, reference);
+                return Err(e);
+            }
+        }
+    }
+    Ok(moved)
+}
+
+/// Reverse `moved` and release `reference` so the same operation can be retried.
+/// The caller still holds the inventory lock, so the stock just moved is there to move back.
+fn undo_stock(
+    inventory: &mut InventoryManager,
+    warehouse_id: &str,
+    moved: &[(&str, f64)],
+    movement_type: MovementType,
+    reference: &str,
+) {
+    let undo = match movement_type {
+        MovementType::Outbound => MovementType::Inbound,
+        _ => MovementType::Outbound,
+    };
+    for &(item_id, quantity) in moved {
+        let movement = StockMovement::new(item_id, warehouse_id, quantity, undo, "")
+            .with_reason(&format!("rollback {}", reference));
+        let _ = inventory.record_movement(movement);
+        inventory.release_reference(reference, item_id, movement_type);
+    }
+}
+
+/// Output tax inside the refunded lines, per tax name
+fn refund_tax(refund: &RefundResult, sale: &CartCalculation) -> EngineResult<BTreeMap<String, Money>> {
+    let mut taxes = BTreeMap::new();
+    for line in &refund.lines {
+        let sold = sale.items.iter().find(|i| i.item_id == line.item_id).ok_or_else(|| EngineError::Validation {
+            message: format!("Refunded item '{}' is not on the sale", line.item_id),
+        })?;
+        if sold.total.is_zero() {
+            continue;
+        }
+        let share = line.amount.amount as f64 / sold.total.amount as f64;
+        for detail in sold.tax_details.iter().filter(|d| !d.amount.is_zero()) {
+            let amount = detail.amount.mul_ratio_rounded(share, RoundingMode::Standard);
+            let entry = taxes.entry(detail.name.clone()).or_insert_with(Money::zero);
+            *entry = *entry + amount;
+        }
+    }
+    taxes.retain(|_, amount| !amount.is_zero());
+    Ok(taxes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::refund::types::RefundLine;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_concurrent_checkouts_and_refunds_do_not_deadlock() {
+        let mut engine = FinancialEngine::new();
+        engine
+            .inventory
+            .record_movement(StockMovement::new("SKU", "WH", 1000.0, MovementType::Inbound, "PO-1"))
+            .unwrap();
+        engine.ledger.add_account(Account::new("cash", "Cash", AccountType::Asset));
+        engine.ledger.add_account(Account::new("sales", "Sales", AccountType::Income));
+        let guard = Arc::new(IronGuard::new(engine));
+
+        let mut cart = Cart::new();
+        let mut item = Item::new("Widget", Money::new(100, 0), 1.0);
+        item.id = "SKU".to_string();
+        cart.add_item(item);
+        let sale = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        let accounts = SaleAccounts {
+            receivable: "cash".to_string(),
+            revenue: "sales".to_string(),
+            tax_accounts: HashMap::new(),
+            discounts: None,
+        };
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                let (guard, cart, sale, accounts) = (guard.clone(), cart.clone(), sale.clone(), accounts.clone());
+                std::thread::spawn(move || {
+                    for n in 0..50 {
+                        let order_id = format!("ORD-{}-{}", worker, n);
+                        guard.checkout(&order_id, &cart, &sale, "WH", &accounts).unwrap();
+                        let refund = RefundResult {
+                            id: format!("RF-{}-{}", worker, n),
+                            transaction_id: order_id,
+                            timestamp: chrono::Utc::now(),
+                            refund_amount: Money::new(100, 0),
+                            refund_type: RefundType::Full,
+                            new_cart_state: None,
+                            lines: vec![RefundLine {
+                                item_id: "SKU".to_string(),
+                                quantity: 1.0,
+                                amount: Money::new(100, 0),
+                            }],
+                            reason: RefundReason::ChangedMind,
+                            note: None,
+                            destination: RefundDestination::OriginalTender,
+                        };
+                        guard.refund(&refund, &sale, "WH", &accounts).unwrap();
+                    }
+                })
+            })
+            .collect();
+        std::thread::spawn(move || {
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            done_tx.send(()).unwrap();
+        });
+        done_rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("checkouts and refunds deadlocked");
+
+        // Every sale was refunded: stock and cash are back where they started
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 1000.0);
+        assert_eq!(guard.ledger(|l| l.balance("cash")).unwrap(), Some(Money::zero()));
+        assert_eq!(guard.ledger(|l| l.journal().len()).unwrap(), 800);
+    }
+
+    #[test]
+    fn test_failed_checkout_leaves_stock_untouched() {
+        let guard = IronGuard::new(FinancialEngine::new());
+        guard
+            .inventory(|inv| inv.record_movement(StockMovement::new("SKU", "WH", 5.0, MovementType::Inbound, "")))
+            .unwrap()
+            .unwrap();
+
+        let mut cart = Cart::new();
+        let mut item = Item::new("Widget", Money::new(100, 0), 2.0);
+        item.id = "SKU".to_string();
+        cart.add_item(item);
+        let sale = MixedScenarioEngine::new().calculate_cart(&cart, &[], None).unwrap();
+        // No ledger accounts => posting fails after the stock moved
+        let accounts = SaleAccounts {
+            receivable: "cash".to_string(),
+            revenue: "sales".to_string(),
+            tax_accounts: HashMap::new(),
+            discounts: None,
+        };
+
+        assert!(guard.checkout("ORD-1", &cart, &sale, "WH", &accounts).is_err());
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 5.0);
+        // Transactions see the same inventory the guard holds
+        let seen = guard.execute_transaction(|engine| Ok(engine.inventory.get_stock("WH", "SKU"))).unwrap();
+        assert_eq!(seen, 5.0);
+
+        // Retrying the same order after fixing the accounts ships the stock this time
+        guard
+            .ledger(|l| {
+                l.add_account(Account::new("cash", "Cash", AccountType::Asset));
+                l.add_account(Account::new("sales", "Sales", AccountType::Income));
+            })
+            .unwrap();
+        guard.checkout("ORD-1", &cart, &sale, "WH", &accounts).unwrap();
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_refund_reverses_output_tax_and_rolls_back_stock_on_ledger_failure() {
+        use crate::rules::mixed_scenarios::{TaxAppliesTo, TaxRate};
+
+        let guard = IronGuard::new(FinancialEngine::new());
+        guard
+            .inventory(|inv| inv.record_movement(StockMovement::new("SKU", "WH", 5.0, MovementType::Inbound, "")))
+            .unwrap()
+            .unwrap();
+        guard
+            .ledger(|l| {
+                l.add_account(Account::new("cash", "Cash", AccountType::Asset));
+                l.add_account(Account::new("sales", "Sales", AccountType::Income));
+            })
+            .unwrap();
+        let mut pricing = MixedScenarioEngine::new();
+        pricing.add_global_tax(TaxRate::new("VAT", 18.0, "LK", TaxAppliesTo::All)).unwrap();
+        let mut cart = Cart::new();
+        let mut item = Item::new("Widget", Money::new(100, 0), 2.0);
+        item.id = "SKU".to_string();
+        cart.add_item(item);
+        let sale = pricing.calculate_cart(&cart, &[], Some("LK")).unwrap();
+        let mut accounts = SaleAccounts {
+            receivable: "cash".to_string(),
+            revenue: "sales".to_string(),
+            tax_accounts: HashMap::from([("VAT".to_string(), "vat".to_string())]),
+            discounts: None,
+        };
+        let refund = RefundResult {
+            id: "RF-1".to_string(),
+            transaction_id: "ORD-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            refund_amount: Money::new(118, 0),
+            refund_type: RefundType::Partial,
+            new_cart_state: None,
+            lines: vec![RefundLine {
+                item_id: "SKU".to_string(),
+                quantity: 1.0,
+                amount: Money::new(118, 0),
+            }],
+            reason: RefundReason::ChangedMind,
+            note: None,
+            destination: RefundDestination::OriginalTender,
+        };
+
+        // The VAT account does not exist yet: the restock is undone and can be retried
+        guard.checkout("ORD-1", &cart, &sale, "WH", &accounts).unwrap_err();
+        guard.ledger(|l| l.add_account(Account::new("vat", "VAT Payable", AccountType::Liability))).unwrap();
+        guard.checkout("ORD-1", &cart, &sale, "WH", &accounts).unwrap();
+        accounts.revenue = "missing".to_string();
+        assert!(guard.refund(&refund, &sale, "WH", &accounts).is_err());
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 3.0);
+
+        accounts.revenue = "sales".to_string();
+        let posted = guard.refund(&refund, &sale, "WH", &accounts).unwrap();
+        assert!(posted.is_balanced());
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 4.0);
+        // Half the sale is refunded: half the VAT comes back off the payable account
+        assert_eq!(guard.ledger(|l| l.balance("vat")).unwrap(), Some(Money::new(-18, 0)));
+        assert_eq!(guard.ledger(|l| l.balance("sales")).unwrap(), Some(Money::new(-100, 0)));
+    }
+
+    #[test]
+    fn test_free_order_posts_discount_without_payment() {
+        use crate::rules::mixed_scenarios::{DiscountRule, DiscountType, ProductDiscountConfig};
+
+        let mut engine = FinancialEngine::new();
+        engine
+            .inventory
+            .record_movement(StockMovement::new("SKU", "WH", 5.0, MovementType::Inbound, "PO-1"))
+            .unwrap();
+        engine.ledger.add_account(Account::new("cash", "Cash", AccountType::Asset));
+        engine.ledger.add_account(Account::new("sales", "Sales", AccountType::Income));
+        engine.ledger.add_account(Account::new("discounts", "Discounts", AccountType::Expense));
+        let guard = IronGuard::new(engine);
+
+        let mut pricing = MixedScenarioEngine::new();
+        pricing.add_product_discount(ProductDiscountConfig {
+            product_id: "SKU".to_string(),
+            discounts: vec![DiscountRule {
+                id: "FREE".to_string(),
+                name: "100% off".to_string(),
+                discount_type: DiscountType::Percentage(100.0),
+                priority: 1,
+                conditions: Vec::new(),
+                stackable: true,
+            }],
+            stackable: true,
+            max_discount_percent: None,
+        }).unwrap();
+        let mut cart = Cart::new();
+        let mut item = Item::new("Widget", Money::new(100, 0), 1.0);
+        item.id = "SKU".to_string();
+        cart.add_item(item);
+        let sale = pricing.calculate_cart(&cart, &[], None).unwrap();
+        assert!(sale.is_free());
+
+        let mut accounts = SaleAccounts {
+            receivable: "cash".to_string(),
+            revenue: "sales".to_string(),
+            tax_accounts: HashMap::new(),
+            discounts: None,
+        };
+        // Without a discount account the discount would vanish from the books
+        assert!(guard.checkout("ORD-0", &cart, &sale, "WH", &accounts).is_err());
+
+        accounts.discounts = Some("discounts".to_string());
+        let posted = guard.checkout("ORD-1", &cart, &sale, "WH", &accounts).unwrap();
+        assert!(posted.is_balanced());
+        assert_eq!(posted.metadata.get("free").map(String::as_str), Some("true"));
+        // No payment: nothing touches the cash account
+        assert!(posted.entries.iter().all(|e| e.account_id != "cash"));
+        assert_eq!(guard.ledger(|l| l.balance("cash")).unwrap(), Some(Money::zero()));
+        assert_eq!(guard.ledger(|l| l.balance("discounts")).unwrap(), Some(Money::new(100, 0)));
+        assert_eq!(guard.ledger(|l| l.balance("sales")).unwrap(), Some(Money::new(-100, 0)));
+        assert_eq!(guard.inventory(|inv| inv.get_stock("WH", "SKU")).unwrap(), 4.0);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::core::errors::{EngineError, EngineResult};
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+/// ============================================================================
+/// 🔐 Lock Ordering (අගුළු දැමීමේ අනුපිළිවෙල)
+/// ============================================================================
+/// Checkout එකක් inventory සහ ledger දෙකම lock කරන අතර refund එකක් එම
+/// lock ප්‍රතිවිරුද්ධ පිළිවෙලට ගත්තොත් deadlock වේ. එය වැළැක්වීමට සියලුම
+/// multi-subsystem operations එකම ගෝලීය පිළිවෙල අනුගමනය කරයි: Engine → Inventory → Ledger.
+/// ඉහළ rank එකක lock එකක් අල්ලාගෙන සිටියදී පහළ rank එකක් ගැනීම debug builds වල panic වේ.
+///
+/// 🪜 Lock Rank (lower ranks are always acquired first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LockRank {
+    Engine = 0,
+    Inventory = 1,
+    Ledger = 2,
+}
+
+thread_local! {
+    /// Highest rank currently held by this thread
+    static HELD: Cell<Option<LockRank>> = const { Cell::new(None) };
+}
+
+/// 🔒 Mutex that knows its place in the global order
+#[derive(Debug)]
+pub struct OrderedMutex<T> {
+    rank: LockRank,
+    inner: Mutex<T>,
+}
+
+impl<T> OrderedMutex<T> {
+    pub fn new(rank: LockRank, value: T) -> Self {
+        OrderedMutex {
+            rank,
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// Acquire the lock. Taking a rank at or below one already held is an ordering bug
+    pub fn lock(&self) -> EngineResult<OrderedGuard<'_, T>> {
+        let previous = HELD.with(Cell::get);
+        debug_assert!(
+            previous.is_none_or(|held| held < self.rank),
+            "lock order violated: acquiring {:?} while holding {:?}",
+            self.rank,
+            previous
+        );
+        let guard = self.inner.lock().map_err(|_| EngineError::System {
+            message: format!("{:?} lock poisoned", self.rank),
+        })?;
+        HELD.with(|held| held.set(Some(self.rank)));
+        Ok(OrderedGuard { guard, previous })
+    }
+}
+
+/// Guard restoring the thread's held rank when released
+pub struct OrderedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    previous: Option<LockRank>,
+}
+
+impl<T> Deref for OrderedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OrderedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for OrderedGuard<'_, T> {
+    fn drop(&mut self) {
+        HELD.with(|held| held.set(self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranks_released_in_order() {
+        let inventory = OrderedMutex::new(LockRank::Inventory, 1);
+        let ledger = OrderedMutex::new(LockRank::Ledger, 2);
+        {
+            let inv = inventory.lock().unwrap();
+            let led = ledger.lock().unwrap();
+            assert_eq!(*inv + *led, 3);
+        }
+        // Both released: the ledger may now be taken first on its own
+        assert_eq!(*ledger.lock().unwrap(), 2);
+        assert_eq!(*inventory.lock().unwrap(), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "lock order violated")]
+    fn test_reverse_order_is_caught() {
+        let inventory = OrderedMutex::new(LockRank::Inventory, ());
+        let ledger = OrderedMutex::new(LockRank::Ledger, ());
+        let _led = ledger.lock().unwrap();
+        let _inv = inventory.lock();
+    }
+}
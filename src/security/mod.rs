@@ -1,5 +1,7 @@
 pub mod audit_trail;
 pub mod encryption;
+#[cfg(feature = "server")]
 pub mod gateway;
 pub mod guard;
+pub(crate) mod lock_order;
 pub mod validator; // Added Secure Gateway middleware (WAF)
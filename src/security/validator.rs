@@ -1,5 +1,6 @@
 use crate::core::errors::{EngineError, EngineResult};
 use crate::core::money::Money;
+use crate::core::metrics;
 // HashSet removed
 
 /// ============================================================================
@@ -215,6 +216,7 @@ impl RateLimiter {
         timestamps.retain(|&ts| ts > cutoff);
 
         if timestamps.len() >= self.max_requests {
+            metrics::record_rate_limited();
             return Err(EngineError::Security {
                 code: "RATE_LIMIT_EXCEEDED".to_string(),
                 message: format!(
@@ -10,7 +10,7 @@ use crate::core::calculation::CalculationResult;
 /// - NoSQL: MongoDB, Redis, DynamoDB
 /// - ORM: Prisma, Diesel, SQLx
 /// - JSON file storage
-
+///
 /// 🔌 Storage Backend Trait (ගබඩා පසුබිම)
 pub trait StorageBackend: Send + Sync {
     /// Store a value
@@ -27,6 +27,17 @@ pub trait StorageBackend: Send + Sync {
     
     /// List keys with pattern
     fn keys(&self, pattern: &str) -> EngineResult<Vec<String>>;
+
+    /// Atomically add `by` to an integer counter (missing => 0) and return the new value
+    /// Must hold across processes sharing the backend: SQL backends use a single
+    /// `INSERT ... ON CONFLICT DO UPDATE ... RETURNING` (or a DB sequence), Redis `INCRBY`
+    fn increment(&self, key: &str, by: i64) -> EngineResult<i64>;
+}
+
+fn parse_counter(key: &str, value: &str) -> EngineResult<i64> {
+    value.trim().parse().map_err(|_| EngineError::Storage {
+        message: format!("Counter '{}' is not an integer: {}", key, value),
+    })
 }
 
 /// 📊 Repository Trait (දත්ත ගබඩාව)
@@ -109,19 +120,43 @@ impl StorageBackend for JsonFileStorage {
         })?;
 
         let mut keys = Vec::new();
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let name = entry.file_name().to_string_lossy().to_string();
-                if name.ends_with(".json") {
-                    let key = name.replace(".json", "").replace("_", ":");
-                    if pattern == "*" || key.contains(pattern) {
-                        keys.push(key);
-                    }
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".json") {
+                let key = name.replace(".json", "").replace("_", ":");
+                if pattern == "*" || key.contains(pattern) {
+                    keys.push(key);
                 }
             }
         }
         Ok(keys)
     }
+
+    /// Read-modify-write under an exclusive OS file lock (released when the file closes)
+    fn increment(&self, key: &str, by: i64) -> EngineResult<i64> {
+        use std::io::{Read, Seek, Write};
+
+        let path = self.get_file_path(key);
+        let io_error = |e: std::io::Error| EngineError::Storage {
+            message: format!("Failed to update counter file {}: {}", path, e),
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error)?;
+        file.lock().map_err(io_error)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content).map_err(io_error)?;
+        let current = if content.trim().is_empty() { 0 } else { parse_counter(key, &content)? };
+        let next = current + by;
+        file.set_len(0).map_err(io_error)?;
+        file.rewind().map_err(io_error)?;
+        file.write_all(next.to_string().as_bytes()).map_err(io_error)?;
+        Ok(next)
+    }
 }
 
 /// 🧠 In-Memory Storage (මතක ගබඩාව)
@@ -138,6 +173,12 @@ impl InMemoryStorage {
     }
 }
 
+impl Default for InMemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StorageBackend for InMemoryStorage {
     fn set(&self, key: &str, value: &str) -> EngineResult<()> {
         let mut data = self.data.write().map_err(|_| EngineError::Storage {
@@ -177,6 +218,54 @@ impl StorageBackend for InMemoryStorage {
             .cloned()
             .collect())
     }
+
+    fn increment(&self, key: &str, by: i64) -> EngineResult<i64> {
+        let mut data = self.data.write().map_err(|_| EngineError::Storage {
+            message: "Lock poisoned".to_string(),
+        })?;
+        let current = match data.get(key) {
+            Some(value) => parse_counter(key, value)?,
+            None => 0,
+        };
+        let next = current + by;
+        data.insert(key.to_string(), next.to_string());
+        Ok(next)
+    }
+}
+
+/// 🏷️ Current stored-JSON schema version
+/// v1: flat calculation blob (`serialize_calculation`), v2: `CalculationResult` shape
+pub const STORAGE_SCHEMA_VERSION: u32 = 2;
+
+/// 📦 Versioned Storage Envelope (සංස්කරණ සහිත ගබඩා ආවරණය)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageEnvelope {
+    pub schema_version: u32,
+    /// Entity kind used to pick migrations (Ex: "calculation")
+    pub kind: String,
+    pub payload: serde_json::Value,
+}
+
+/// 🔄 Upgrade a payload of `kind` from `version` to `version + 1`
+fn migrate_step(kind: &str, version: u32, payload: serde_json::Value) -> EngineResult<serde_json::Value> {
+    match (kind, version) {
+        ("calculation", 1) => {
+            // v1 stored cents as flat integers plus formatted strings
+            let cents = |field: &str| -> EngineResult<i64> {
+                payload.get(field).and_then(|v| v.as_i64()).ok_or_else(|| EngineError::Storage {
+                    message: format!("v1 calculation is missing '{}'", field),
+                })
+            };
+            Ok(serde_json::json!({
+                "subtotal": { "amount": cents("subtotal")? },
+                "discount_total": { "amount": cents("discount_total")? },
+                "tax_total": { "amount": cents("tax_total")? },
+                "grand_total": { "amount": cents("grand_total")? },
+            }))
+        }
+        // Shape unchanged for this kind/version
+        _ => Ok(payload),
+    }
 }
 
 /// 📝 Entity Serializer (object -> JSON)
@@ -198,12 +287,58 @@ impl EntitySerializer {
     }
 
     /// Deserialize from JSON
+    /// Versioned envelopes are migrated to `STORAGE_SCHEMA_VERSION` first; bare JSON is read as-is.
     pub fn from_json<T: for<'de> Deserialize<'de>>(json: &str) -> EngineResult<T> {
-        serde_json::from_str(json).map_err(|e| EngineError::Storage {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| EngineError::Storage {
+            message: format!("Deserialization failed: {}", e),
+        })?;
+
+        let is_envelope = value.get("schema_version").is_some()
+            && value.get("kind").is_some()
+            && value.get("payload").is_some();
+        let value = if is_envelope {
+            let envelope: StorageEnvelope = serde_json::from_value(value).map_err(|e| EngineError::Storage {
+                message: format!("Invalid storage envelope: {}", e),
+            })?;
+            Self::migrate(envelope)?.payload
+        } else {
+            value
+        };
+
+        serde_json::from_value(value).map_err(|e| EngineError::Storage {
             message: format!("Deserialization failed: {}", e),
         })
     }
 
+    /// 📦 Serialize inside a versioned envelope with sorted keys (deterministic output)
+    pub fn to_versioned_json<T: Serialize>(kind: &str, entity: &T) -> EngineResult<String> {
+        let payload = serde_json::to_value(entity).map_err(|e| EngineError::Storage {
+            message: format!("Serialization failed: {}", e),
+        })?;
+        Self::to_json(&StorageEnvelope {
+            schema_version: STORAGE_SCHEMA_VERSION,
+            kind: kind.to_string(),
+            payload,
+        })
+    }
+
+    /// 🔄 Run migrations until the envelope reaches the current version
+    pub fn migrate(mut envelope: StorageEnvelope) -> EngineResult<StorageEnvelope> {
+        if envelope.schema_version > STORAGE_SCHEMA_VERSION {
+            return Err(EngineError::Storage {
+                message: format!(
+                    "Stored schema v{} is newer than supported v{}",
+                    envelope.schema_version, STORAGE_SCHEMA_VERSION
+                ),
+            });
+        }
+        while envelope.schema_version < STORAGE_SCHEMA_VERSION {
+            envelope.payload = migrate_step(&envelope.kind, envelope.schema_version, envelope.payload)?;
+            envelope.schema_version += 1;
+        }
+        Ok(envelope)
+    }
+
     /// Serialize calculation result for API/DB
     pub fn serialize_calculation(result: &CalculationResult) -> EngineResult<String> {
         let dto = serde_json::json!({
@@ -475,6 +610,7 @@ impl SchemaGenerator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prelude::*;
 
     #[test]
     fn test_in_memory_storage() {
@@ -488,6 +624,32 @@ mod tests {
         assert!(!storage.exists("test:key").unwrap());
     }
 
+    #[test]
+    fn test_file_counter_increments_across_handles() {
+        let dir = std::env::temp_dir().join(format!("engine-counter-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+
+        // Separate storage handles stand in for separate processes on one directory
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let storage = JsonFileStorage::new(&dir);
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        storage.increment("seq:test", 1).unwrap();
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        let storage = JsonFileStorage::new(&dir);
+        assert_eq!(storage.get("seq:test").unwrap().as_deref(), Some("100"));
+
+        storage.set("seq:bad", "x").unwrap();
+        assert!(storage.increment("seq:bad", 1).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_entity_serialization() {
         let money = Money::new(100, 50);
@@ -501,4 +663,45 @@ mod tests {
         let conn = config.connection_string();
         assert!(conn.starts_with("postgres://"));
     }
+
+    #[test]
+    fn test_v1_calculation_blob_migrates() {
+        let v1 = r#"{
+            "schema_version": 1,
+            "kind": "calculation",
+            "payload": {
+                "subtotal": 10000, "discount_total": 1000, "tax_total": 1620, "grand_total": 10620,
+                "subtotal_formatted": "Rs.100.00", "total_formatted": "Rs.106.20"
+            }
+        }"#;
+
+        let result: CalculationResult = EntitySerializer::from_json(v1).unwrap();
+        assert_eq!(result.subtotal, Money::new(100, 0));
+        assert_eq!(result.discount_total, Money::new(10, 0));
+        assert_eq!(result.grand_total, Money::new(106, 20));
+    }
+
+    #[test]
+    fn test_versioned_round_trip() {
+        let result = CalculationResult {
+            subtotal: Money::new(50, 0),
+            discount_total: Money::zero(),
+            tax_total: Money::new(9, 0),
+            grand_total: Money::new(59, 0),
+            taxable_fees: Money::zero(),
+            non_taxable_fees: Money::zero(),
+            shipping_discount: Money::zero(),
+        };
+        let json = EntitySerializer::to_versioned_json("calculation", &result).unwrap();
+        assert!(json.contains(&format!("\"schema_version\":{}", STORAGE_SCHEMA_VERSION)));
+
+        let back: CalculationResult = EntitySerializer::from_json(&json).unwrap();
+        assert_eq!(back.grand_total, result.grand_total);
+
+        let future = json.replace(
+            &format!("\"schema_version\":{}", STORAGE_SCHEMA_VERSION),
+            "\"schema_version\":99",
+        );
+        assert!(EntitySerializer::from_json::<CalculationResult>(&future).is_err());
+    }
 }
@@ -1,4 +1,7 @@
 pub mod config;
+#[cfg(feature = "persistence")]
 pub mod connector;
 pub mod models;
+#[cfg(feature = "observability")]
 pub mod redis; // Added Redis module
+pub mod database;
@@ -1,7 +1,6 @@
 // use crate::core::money::Money;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 
 /// ============================================================================
 /// 🗄️ Database Models (දත්ත ආකෘති)
@@ -9,7 +8,8 @@ use sqlx::FromRow;
 /// මෙහි සියලුම ORM Models අර්ථ දක්වනු ලැබේ.
 /// PostgreSQL සහ අනෙකුත් DB සඳහා පොදු ආකෘති.
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "persistence", derive(sqlx::FromRow))]
 pub struct TransactionRecord {
     pub id: String,
     pub created_at: DateTime<Utc>,
@@ -19,4 +19,37 @@ pub struct TransactionRecord {
     pub status: String,
 }
 
+/// 🧾 One `transactions` row (`SchemaGenerator::postgres_schema`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRow {
+    pub id: String,
+    /// Ex: "order"
+    pub transaction_type: String,
+    pub subtotal: i64,
+    pub discount_total: i64,
+    pub tax_total: i64,
+    pub grand_total: i64,
+    pub currency: String,
+    pub customer_id: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub metadata: serde_json::Value,
+}
+
+/// 📦 One `transaction_items` row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionItemRow {
+    pub id: String,
+    pub transaction_id: String,
+    pub item_id: String,
+    pub item_name: String,
+    pub unit_price: i64,
+    pub quantity: f64,
+    pub discount: i64,
+    pub tax: i64,
+    pub total: i64,
+    pub metadata: serde_json::Value,
+}
+
 // TODO: Add more models here as the schema evolves
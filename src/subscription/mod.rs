@@ -1,3 +1,4 @@
 pub mod plan;
 pub mod billing;
 pub mod proration;
+pub mod schedule;
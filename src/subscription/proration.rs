@@ -1,6 +1,10 @@
+use crate::core::calendar::BusinessCalendar;
 use crate::core::errors::{EngineError, EngineResult};
+use crate::core::micro_money::MicroMoney;
 use crate::core::money::Money;
-use chrono::{DateTime, Datelike, Duration, Utc};
+use crate::core::rounding::RoundingMode;
+use crate::subscription::schedule::BillingSchedule;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// ============================================================================
@@ -21,6 +25,22 @@ pub struct ProrationRequest {
     pub billing_cycle_end: DateTime<Utc>,
     pub change_date: DateTime<Utc>,
     pub proration_method: ProrationMethod,
+    /// Decimal places of the reported `proration_factor` (charges use full precision)
+    #[serde(default = "default_factor_precision")]
+    pub factor_precision: u32,
+}
+
+/// Reported `proration_factor` precision: 0.4999999999 => 0.5
+pub const DEFAULT_FACTOR_PRECISION: u32 = 6;
+
+fn default_factor_precision() -> u32 {
+    DEFAULT_FACTOR_PRECISION
+}
+
+/// Display-only rounding of a proration factor
+fn round_factor(factor: f64, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals as i32);
+    (factor * scale).round() / scale
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -43,7 +63,7 @@ pub struct ProrationResult {
     pub net_amount: Money,    // Net amount to charge (can be negative = credit)
     pub days_remaining: i64,
     pub days_total: i64,
-    pub proration_factor: f64, // 0.0 to 1.0
+    pub proration_factor: f64, // 0.0 to 1.0, rounded to `factor_precision` for display
     pub effective_date: DateTime<Utc>,
     pub next_billing_date: DateTime<Utc>,
 }
@@ -80,12 +100,13 @@ impl ProrationEngine {
             });
         }
 
-        let proration_factor = match request.proration_method {
-            ProrationMethod::SecondBased => remaining_seconds as f64 / total_seconds as f64,
-            ProrationMethod::DayBased => remaining_days as f64 / total_days as f64,
-            ProrationMethod::None => 1.0,
-            ProrationMethod::CreditNext => remaining_seconds as f64 / total_seconds as f64,
-        };
+        let proration_factor = Self::remaining_factor(
+            &request.proration_method,
+            remaining_seconds,
+            total_seconds,
+            remaining_days,
+            total_days,
+        );
 
         // Calculate credit from old plan (unused portion)
         let credit_amount =
@@ -105,12 +126,28 @@ impl ProrationEngine {
             net_amount,
             days_remaining: remaining_days,
             days_total: total_days,
-            proration_factor,
+            proration_factor: round_factor(proration_factor, request.factor_precision),
             effective_date: request.change_date,
             next_billing_date: request.billing_cycle_end,
         })
     }
 
+    /// Share of the cycle still to run under a proration method
+    fn remaining_factor(
+        method: &ProrationMethod,
+        remaining_seconds: i64,
+        total_seconds: i64,
+        remaining_days: i64,
+        total_days: i64,
+    ) -> f64 {
+        match method {
+            ProrationMethod::SecondBased => remaining_seconds as f64 / total_seconds as f64,
+            ProrationMethod::DayBased => remaining_days as f64 / total_days as f64,
+            ProrationMethod::None => 1.0,
+            ProrationMethod::CreditNext => remaining_seconds as f64 / total_seconds as f64,
+        }
+    }
+
     /// Calculate prorated amount
     fn calculate_prorated_amount(amount: &Money, factor: f64) -> Money {
         let prorated = (amount.amount as f64 * factor).round() as i64;
@@ -156,18 +193,66 @@ impl ProrationEngine {
         actual_units: f64,
         overage_rate: Money, // Per unit overage cost
     ) -> EngineResult<UsageBillingResult> {
-        if actual_units <= included_units {
+        Self::usage_based_micro(
+            base_amount,
+            included_units,
+            actual_units,
+            MicroMoney::from_money(overage_rate),
+        )
+    }
+
+    /// 📈 Usage-based billing with a sub-cent per-unit rate (Ex: Rs.0.0035 per API call)
+    /// Overage accumulates in micros and is rounded to cents once, on the total.
+    pub fn usage_based_micro(
+        base_amount: Money,
+        included_units: f64,
+        actual_units: f64,
+        overage_rate: MicroMoney,
+    ) -> EngineResult<UsageBillingResult> {
+        Self::usage_based_capped(
+            base_amount,
+            included_units,
+            actual_units,
+            overage_rate,
+            &UsageCaps::default(),
+        )
+    }
+
+    /// 📈 Usage-based billing with runaway protection
+    /// Units beyond `hard_unit_cap` are not counted and the overage charge never exceeds
+    /// `overage_cap`; the result records which cap was hit.
+    pub fn usage_based_capped(
+        base_amount: Money,
+        included_units: f64,
+        actual_units: f64,
+        overage_rate: MicroMoney,
+        caps: &UsageCaps,
+    ) -> EngineResult<UsageBillingResult> {
+        let (counted_units, unit_cap_hit) = match caps.hard_unit_cap {
+            Some(limit) if actual_units > limit => (limit, true),
+            _ => (actual_units, false),
+        };
+
+        if counted_units <= included_units {
             return Ok(UsageBillingResult {
                 base_charge: base_amount,
                 overage_units: 0.0,
                 overage_charge: Money::zero(),
                 total_charge: base_amount,
-                units_remaining: included_units - actual_units,
+                units_remaining: included_units - counted_units,
+                unit_cap_hit,
+                overage_cap_hit: false,
             });
         }
 
-        let overage_units = actual_units - included_units;
-        let overage_charge = overage_rate * (overage_units.ceil() as i64);
+        let overage_units = counted_units - included_units;
+        let uncapped_charge = overage_rate
+            .times(overage_units.ceil() as i64)?
+            .to_money(RoundingMode::Standard);
+        let (overage_charge, overage_cap_hit) = match caps.overage_cap {
+            Some(cap) if uncapped_charge > cap => (cap, true),
+            _ => (uncapped_charge, false),
+        };
         let total_charge = base_amount + overage_charge;
 
         Ok(UsageBillingResult {
@@ -176,6 +261,96 @@ impl ProrationEngine {
             overage_charge,
             total_charge,
             units_remaining: 0.0,
+            unit_cap_hit,
+            overage_cap_hit,
+        })
+    }
+
+    /// 📆 Usage billing for a customer who joined mid-cycle
+    /// Both the base charge and the included allowance are prorated by the share of the
+    /// cycle remaining at `start_date`; overage is then measured against the prorated
+    /// allowance (Ex: joining half-way on 1,000 units gets 500 included).
+    pub fn prorated_usage_billing(request: &ProratedUsageRequest) -> EngineResult<ProratedUsageResult> {
+        if request.start_date < request.billing_cycle_start || request.start_date > request.billing_cycle_end {
+            return Err(EngineError::Validation {
+                message: "Start date must fall within the billing cycle".to_string(),
+            });
+        }
+        let total_seconds = (request.billing_cycle_end - request.billing_cycle_start).num_seconds();
+        if total_seconds <= 0 {
+            return Err(EngineError::Validation {
+                message: "Invalid billing cycle duration".to_string(),
+            });
+        }
+        let factor = Self::remaining_factor(
+            &request.proration_method,
+            (request.billing_cycle_end - request.start_date).num_seconds(),
+            total_seconds,
+            (request.billing_cycle_end - request.start_date).num_days(),
+            (request.billing_cycle_end - request.billing_cycle_start).num_days(),
+        );
+
+        let base_charge = Self::calculate_prorated_amount(&request.base_amount, factor);
+        let included_units = request.included_units * factor;
+        let usage = Self::usage_based_capped(
+            base_charge,
+            included_units,
+            request.actual_units,
+            request.overage_rate,
+            &request.caps,
+        )?;
+
+        Ok(ProratedUsageResult {
+            proration_factor: round_factor(factor, DEFAULT_FACTOR_PRECISION),
+            included_units,
+            usage,
+        })
+    }
+
+    /// 👥 Prorate a seat/quantity change on a per-unit plan
+    /// Adding seats charges the delta for the remaining cycle; removing seats credits it
+    /// (negative `net_amount`).
+    pub fn prorate_quantity_change(
+        unit_price: Money,
+        old_quantity: u32,
+        new_quantity: u32,
+        billing_cycle_start: DateTime<Utc>,
+        billing_cycle_end: DateTime<Utc>,
+        change_date: DateTime<Utc>,
+    ) -> EngineResult<QuantityChangeResult> {
+        if change_date < billing_cycle_start || change_date > billing_cycle_end {
+            return Err(EngineError::Validation {
+                message: "Change date must fall within the billing cycle".to_string(),
+            });
+        }
+
+        let total_seconds = (billing_cycle_end - billing_cycle_start).num_seconds();
+        if total_seconds <= 0 {
+            return Err(EngineError::Validation {
+                message: "Invalid billing cycle duration".to_string(),
+            });
+        }
+        let remaining_seconds = (billing_cycle_end - change_date).num_seconds();
+        let proration_factor = remaining_seconds as f64 / total_seconds as f64;
+
+        let quantity_delta = new_quantity as i64 - old_quantity as i64;
+        let full_delta = unit_price * quantity_delta.abs();
+        let prorated = Self::calculate_prorated_amount(&full_delta, proration_factor);
+        let net_amount = if quantity_delta < 0 {
+            Money::zero() - prorated
+        } else {
+            prorated
+        };
+
+        Ok(QuantityChangeResult {
+            old_quantity,
+            new_quantity,
+            quantity_delta,
+            net_amount,
+            days_remaining: (billing_cycle_end - change_date).num_days(),
+            days_total: (billing_cycle_end - billing_cycle_start).num_days(),
+            proration_factor: round_factor(proration_factor, DEFAULT_FACTOR_PRECISION),
+            effective_date: change_date,
         })
     }
 
@@ -231,6 +406,69 @@ pub struct UsageBillingResult {
     pub overage_charge: Money,
     pub total_charge: Money,
     pub units_remaining: f64,
+    /// Usage beyond `hard_unit_cap` was ignored
+    #[serde(default)]
+    pub unit_cap_hit: bool,
+    /// Overage charge was limited to `overage_cap`
+    #[serde(default)]
+    pub overage_cap_hit: bool,
+}
+
+/// 📆 Mid-cycle usage billing request (see `prorated_usage_billing`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProratedUsageRequest {
+    /// Full-cycle base charge
+    pub base_amount: Money,
+    /// Full-cycle included units
+    pub included_units: f64,
+    pub actual_units: f64,
+    pub overage_rate: MicroMoney,
+    pub billing_cycle_start: DateTime<Utc>,
+    pub billing_cycle_end: DateTime<Utc>,
+    /// When the customer joined
+    pub start_date: DateTime<Utc>,
+    pub proration_method: ProrationMethod,
+    #[serde(default)]
+    pub caps: UsageCaps,
+}
+
+/// 📆 Mid-cycle usage billing result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProratedUsageResult {
+    /// Rounded to `DEFAULT_FACTOR_PRECISION` for display (charges use full precision)
+    pub proration_factor: f64,
+    /// Prorated allowance overage was measured against
+    pub included_units: f64,
+    /// Prorated base, overage and total
+    pub usage: UsageBillingResult,
+}
+
+/// 🛑 Usage Caps (runaway client protection)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageCaps {
+    /// Maximum overage charge per cycle
+    pub overage_cap: Option<Money>,
+    /// Units counted at most (included units count towards it)
+    pub hard_unit_cap: Option<f64>,
+}
+
+/// 👥 Seat Change Result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantityChangeResult {
+    pub old_quantity: u32,
+    pub new_quantity: u32,
+    pub quantity_delta: i64,
+    pub net_amount: Money, // Positive = charge, negative = credit
+    pub days_remaining: i64,
+    pub days_total: i64,
+    pub proration_factor: f64,
+    pub effective_date: DateTime<Utc>,
+}
+
+impl QuantityChangeResult {
+    pub fn is_credit(&self) -> bool {
+        self.net_amount.is_negative()
+    }
 }
 
 /// 🚫 Cancellation Result
@@ -257,39 +495,29 @@ pub struct BillingCycleCalculator;
 
 impl BillingCycleCalculator {
     /// Calculate next billing date
+    /// Month-based cycles clamp to the last day of short months (Jan 31 => Feb 29).
     pub fn next_billing_date(current: DateTime<Utc>, cycle: BillingCycle) -> DateTime<Utc> {
-        match cycle {
-            BillingCycle::Daily => current + Duration::days(1),
-            BillingCycle::Weekly => current + Duration::weeks(1),
-            BillingCycle::Monthly => {
-                let month = current.month();
-                let year = current.year();
-                let (new_year, new_month) = if month == 12 {
-                    (year + 1, 1)
-                } else {
-                    (year, month + 1)
-                };
-                current
-                    .with_year(new_year)
-                    .unwrap()
-                    .with_month(new_month)
-                    .unwrap()
-            }
-            BillingCycle::Quarterly => {
-                let month = current.month();
-                let year = current.year();
-                let new_month = month + 3;
-                if new_month > 12 {
-                    current
-                        .with_year(year + 1)
-                        .unwrap()
-                        .with_month(new_month - 12)
-                        .unwrap()
-                } else {
-                    current.with_month(new_month).unwrap()
-                }
+        BillingSchedule::from(cycle).next_after(current)
+    }
+
+    /// Next billing date from an expressive schedule
+    pub fn next_billing_date_for_schedule(current: DateTime<Utc>, schedule: &BillingSchedule) -> DateTime<Utc> {
+        schedule.next_after(current)
+    }
+
+    /// Next billing date, optionally rolled forward to a business day (time of day kept)
+    pub fn next_billing_date_on_calendar(
+        current: DateTime<Utc>,
+        cycle: BillingCycle,
+        calendar: Option<&BusinessCalendar>,
+    ) -> DateTime<Utc> {
+        let next = Self::next_billing_date(current, cycle);
+        match calendar {
+            Some(calendar) => {
+                let shift = calendar.roll_forward(next.date_naive()) - next.date_naive();
+                next + shift
             }
-            BillingCycle::Yearly => current.with_year(current.year() + 1).unwrap(),
+            None => next,
         }
     }
 }
@@ -306,6 +534,7 @@ pub enum BillingCycle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Duration;
 
     #[test]
     fn test_proration_upgrade() {
@@ -317,6 +546,7 @@ mod tests {
             billing_cycle_end: Utc::now() + Duration::days(15),
             change_date: Utc::now(),
             proration_method: ProrationMethod::DayBased,
+            factor_precision: DEFAULT_FACTOR_PRECISION,
         };
 
         let result = ProrationEngine::calculate(&request).unwrap();
@@ -329,6 +559,31 @@ mod tests {
         assert_eq!(result.proration_factor, 0.5);
     }
 
+    #[test]
+    fn test_factor_rounded_for_display_only() {
+        use chrono::TimeZone;
+        // 2 of 3 days remaining => 0.666666...
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let request = ProrationRequest {
+            subscription_id: "sub_1".to_string(),
+            old_plan_amount: Money::zero(),
+            new_plan_amount: Money::new(1_000_000, 0),
+            billing_cycle_start: start,
+            billing_cycle_end: start + Duration::days(3),
+            change_date: start + Duration::days(1),
+            proration_method: ProrationMethod::SecondBased,
+            factor_precision: DEFAULT_FACTOR_PRECISION,
+        };
+
+        let result = ProrationEngine::calculate(&request).unwrap();
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"proration_factor\":0.666667,"));
+
+        // Charge from the exact factor: 100,000,000 × 2/3 = 66,666,666.67 cents
+        // (the rounded factor would have given 66,666,700)
+        assert_eq!(result.charge_amount, Money::from_cents(66_666_667));
+    }
+
     #[test]
     fn test_usage_billing() {
         let result = ProrationEngine::usage_based(
@@ -346,6 +601,95 @@ mod tests {
         assert_eq!(result.total_charge.amount, 10000);
     }
 
+    #[test]
+    fn test_prorated_usage_joining_half_way() {
+        use chrono::TimeZone;
+        let start = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        let request = ProratedUsageRequest {
+            base_amount: Money::new(1000, 0),
+            included_units: 1000.0,
+            actual_units: 800.0,
+            overage_rate: MicroMoney::from_money(Money::new(2, 0)),
+            billing_cycle_start: start,
+            billing_cycle_end: start + Duration::days(30),
+            start_date: start + Duration::days(15),
+            proration_method: ProrationMethod::DayBased,
+            caps: UsageCaps::default(),
+        };
+
+        let result = ProrationEngine::prorated_usage_billing(&request).unwrap();
+
+        // Half the base, half the allowance: 800 - 500 = 300 units over at Rs.2
+        assert_eq!(result.proration_factor, 0.5);
+        assert_eq!(result.included_units, 500.0);
+        assert_eq!(result.usage.base_charge, Money::new(500, 0));
+        assert_eq!(result.usage.overage_units, 300.0);
+        assert_eq!(result.usage.overage_charge, Money::new(600, 0));
+        assert_eq!(result.usage.total_charge, Money::new(1100, 0));
+
+        let early = ProratedUsageRequest {
+            start_date: start - Duration::days(1),
+            ..request
+        };
+        assert!(ProrationEngine::prorated_usage_billing(&early).is_err());
+    }
+
+    #[test]
+    fn test_usage_billing_sub_cent_rate() {
+        // 1,000,000 API calls at Rs.0.0035 = Rs.3,500 exactly
+        let rate = MicroMoney::parse("0.0035").unwrap();
+        let result =
+            ProrationEngine::usage_based_micro(Money::zero(), 0.0, 1_000_000.0, rate).unwrap();
+
+        assert_eq!(result.overage_charge, Money::new(3500, 0));
+        assert_eq!(result.total_charge, Money::new(3500, 0));
+    }
+
+    #[test]
+    fn test_usage_overage_hits_money_cap() {
+        let caps = UsageCaps {
+            overage_cap: Some(Money::new(500, 0)),
+            hard_unit_cap: None,
+        };
+        // 10,000 overage units × Rs.1 = Rs.10,000 => capped at Rs.500
+        let result = ProrationEngine::usage_based_capped(
+            Money::new(50, 0),
+            100.0,
+            10_100.0,
+            MicroMoney::from_money(Money::new(1, 0)),
+            &caps,
+        )
+        .unwrap();
+
+        assert!(result.overage_cap_hit);
+        assert!(!result.unit_cap_hit);
+        assert_eq!(result.overage_units, 10_000.0);
+        assert_eq!(result.overage_charge, Money::new(500, 0));
+        assert_eq!(result.total_charge, Money::new(550, 0));
+    }
+
+    #[test]
+    fn test_usage_stops_counting_at_unit_cap() {
+        let caps = UsageCaps {
+            overage_cap: None,
+            hard_unit_cap: Some(1_000.0),
+        };
+        let result = ProrationEngine::usage_based_capped(
+            Money::new(50, 0),
+            100.0,
+            1_000_000.0,
+            MicroMoney::from_money(Money::new(1, 0)),
+            &caps,
+        )
+        .unwrap();
+
+        // Only 1,000 units counted => 900 overage units
+        assert!(result.unit_cap_hit);
+        assert!(!result.overage_cap_hit);
+        assert_eq!(result.overage_units, 900.0);
+        assert_eq!(result.overage_charge, Money::new(900, 0));
+    }
+
     #[test]
     fn test_cancellation_prorated() {
         let result = ProrationEngine::cancellation_refund(
@@ -363,4 +707,94 @@ mod tests {
         assert_eq!(result.days_used, 10);
         assert_eq!(result.days_unused, 20);
     }
+
+    #[test]
+    fn test_mid_cycle_upgrade_and_cancellation_at_fixed_dates() {
+        use chrono::TimeZone;
+        // One instant for every date, so day counts cannot straddle a clock tick
+        let now = Utc.with_ymd_and_hms(2024, 4, 16, 12, 0, 0).unwrap();
+        let request = ProrationRequest {
+            subscription_id: "SUB001".to_string(),
+            old_plan_amount: Money::new(100, 0),
+            new_plan_amount: Money::new(200, 0),
+            billing_cycle_start: now - Duration::days(15),
+            billing_cycle_end: now + Duration::days(15),
+            change_date: now,
+            proration_method: ProrationMethod::DayBased,
+            factor_precision: DEFAULT_FACTOR_PRECISION,
+        };
+        let upgrade = ProrationEngine::calculate(&request).unwrap();
+        assert_eq!(upgrade.proration_factor, 0.5);
+        assert_eq!(upgrade.net_amount, Money::new(50, 0));
+
+        let cancellation = ProrationEngine::cancellation_refund(
+            Money::new(100, 0),
+            now - Duration::days(10),
+            now + Duration::days(20),
+            now,
+            RefundPolicy::Prorated,
+        )
+        .unwrap();
+        assert_eq!((cancellation.days_used, cancellation.days_unused), (10, 20));
+        assert_eq!(cancellation.refund_amount, Money::new(66, 67));
+    }
+
+    #[test]
+    fn test_billing_date_snaps_to_business_day() {
+        use chrono::{NaiveDate, TimeZone};
+
+        // 2024-02-09 (Fri) + 1 month = 2024-03-09 (Sat) => Mon 2024-03-11
+        let current = Utc.with_ymd_and_hms(2024, 2, 9, 10, 0, 0).unwrap();
+        let calendar = BusinessCalendar::new();
+
+        let plain = BillingCycleCalculator::next_billing_date_on_calendar(current, BillingCycle::Monthly, None);
+        assert_eq!(plain, Utc.with_ymd_and_hms(2024, 3, 9, 10, 0, 0).unwrap());
+
+        let snapped =
+            BillingCycleCalculator::next_billing_date_on_calendar(current, BillingCycle::Monthly, Some(&calendar));
+        assert_eq!(snapped, Utc.with_ymd_and_hms(2024, 3, 11, 10, 0, 0).unwrap());
+
+        let with_holiday = calendar.with_holiday(NaiveDate::from_ymd_opt(2024, 3, 11).unwrap());
+        let snapped =
+            BillingCycleCalculator::next_billing_date_on_calendar(current, BillingCycle::Monthly, Some(&with_holiday));
+        assert_eq!(snapped, Utc.with_ymd_and_hms(2024, 3, 12, 10, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_add_three_seats_mid_cycle() {
+        let now = Utc::now();
+        let result = ProrationEngine::prorate_quantity_change(
+            Money::new(10, 0), // Rs. 10/seat
+            5,
+            8,
+            now - Duration::days(15),
+            now + Duration::days(15),
+            now,
+        )
+        .unwrap();
+
+        // 3 seats * Rs. 10 * 0.5 = Rs. 15 charge
+        assert_eq!(result.quantity_delta, 3);
+        assert_eq!(result.net_amount, Money::new(15, 0));
+        assert!(!result.is_credit());
+    }
+
+    #[test]
+    fn test_remove_two_seats_credits() {
+        let now = Utc::now();
+        let result = ProrationEngine::prorate_quantity_change(
+            Money::new(12, 0),
+            5,
+            3,
+            now - Duration::days(10),
+            now + Duration::days(20),
+            now,
+        )
+        .unwrap();
+
+        // 2 seats * Rs. 12 * (20/30) = Rs. 16 credit
+        assert_eq!(result.quantity_delta, -2);
+        assert_eq!(result.net_amount, Money::new(-16, 0));
+        assert!(result.is_credit());
+    }
 }
@@ -0,0 +1,176 @@
+use crate::core::calendar::BusinessCalendar;
+use crate::core::errors::{EngineError, EngineResult};
+use crate::subscription::proration::BillingCycle;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 🔁 Recurring Billing Schedule (පුනරාවර්තන බිල් කාලසටහන)
+/// ============================================================================
+/// RRULE වැනි interval + unit + anchor කාලසටහනක්.
+/// Ex: "every 2 weeks", "monthly on the 15th", "last business day of month"
+/// කෙටි මාසවලදී anchor දිනය මාසයේ අවසාන දිනයට clamp කරයි (31 => Feb 29).
+///
+/// 📏 Schedule Unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// 📌 Anchor Rule (Month / Year units only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleAnchor {
+    /// Keep the current day of month (clamped)
+    SameDay,
+    /// Specific day of month, clamped to short months
+    DayOfMonth(u32),
+    LastDayOfMonth,
+    /// Last business day per the schedule's calendar (Sat/Sun weekend by default)
+    LastBusinessDayOfMonth,
+}
+
+/// 🔁 Billing Schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingSchedule {
+    pub interval: u32,
+    pub unit: ScheduleUnit,
+    pub anchor: ScheduleAnchor,
+    pub calendar: Option<BusinessCalendar>,
+}
+
+impl BillingSchedule {
+    pub fn new(interval: u32, unit: ScheduleUnit) -> EngineResult<Self> {
+        if interval == 0 {
+            return Err(EngineError::Validation {
+                message: "Schedule interval must be at least 1".to_string(),
+            });
+        }
+        Ok(BillingSchedule {
+            interval,
+            unit,
+            anchor: ScheduleAnchor::SameDay,
+            calendar: None,
+        })
+    }
+
+    pub fn with_anchor(mut self, anchor: ScheduleAnchor) -> EngineResult<Self> {
+        if let ScheduleAnchor::DayOfMonth(day) = anchor {
+            if !(1..=31).contains(&day) {
+                return Err(EngineError::Validation {
+                    message: format!("Day of month {} must be between 1 and 31", day),
+                });
+            }
+        }
+        self.anchor = anchor;
+        Ok(self)
+    }
+
+    pub fn with_calendar(mut self, calendar: BusinessCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// ⏭️ Next billing date after `current` (the previous billing date); time of day is kept
+    pub fn next_after(&self, current: DateTime<Utc>) -> DateTime<Utc> {
+        let interval = self.interval as i64;
+        let months = match self.unit {
+            ScheduleUnit::Day => return current + Duration::days(interval),
+            ScheduleUnit::Week => return current + Duration::weeks(interval),
+            ScheduleUnit::Month => interval,
+            ScheduleUnit::Year => interval * 12,
+        };
+
+        let month_index = current.year() as i64 * 12 + current.month0() as i64 + months;
+        let year = month_index.div_euclid(12) as i32;
+        let month = month_index.rem_euclid(12) as u32 + 1;
+        let last_day = days_in_month(year, month);
+
+        let date = match self.anchor {
+            ScheduleAnchor::SameDay => ymd(year, month, current.day().min(last_day)),
+            ScheduleAnchor::DayOfMonth(day) => ymd(year, month, day.min(last_day)),
+            ScheduleAnchor::LastDayOfMonth => ymd(year, month, last_day),
+            ScheduleAnchor::LastBusinessDayOfMonth => {
+                let calendar = self.calendar.clone().unwrap_or_default();
+                calendar.roll_backward(ymd(year, month, last_day))
+            }
+        };
+
+        current + (date - current.date_naive())
+    }
+}
+
+impl From<BillingCycle> for BillingSchedule {
+    fn from(cycle: BillingCycle) -> Self {
+        let (interval, unit) = match cycle {
+            BillingCycle::Daily => (1, ScheduleUnit::Day),
+            BillingCycle::Weekly => (1, ScheduleUnit::Week),
+            BillingCycle::Monthly => (1, ScheduleUnit::Month),
+            BillingCycle::Quarterly => (3, ScheduleUnit::Month),
+            BillingCycle::Yearly => (1, ScheduleUnit::Year),
+        };
+        BillingSchedule {
+            interval,
+            unit,
+            anchor: ScheduleAnchor::SameDay,
+            calendar: None,
+        }
+    }
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("day clamped to month length")
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    ymd(next_year, next_month, 1).pred_opt().map(|d| d.day()).unwrap_or(28)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_every_two_weeks() {
+        let schedule = BillingSchedule::new(2, ScheduleUnit::Week).unwrap();
+        assert_eq!(schedule.next_after(at(2024, 1, 1)), at(2024, 1, 15));
+        assert_eq!(schedule.next_after(at(2024, 12, 23)), at(2025, 1, 6));
+    }
+
+    #[test]
+    fn test_monthly_on_31st_clamps_short_months() {
+        let schedule = BillingSchedule::new(1, ScheduleUnit::Month)
+            .unwrap()
+            .with_anchor(ScheduleAnchor::DayOfMonth(31))
+            .unwrap();
+
+        let feb = schedule.next_after(at(2024, 1, 31));
+        assert_eq!(feb, at(2024, 2, 29));
+        let mar = schedule.next_after(feb);
+        assert_eq!(mar, at(2024, 3, 31));
+        assert_eq!(schedule.next_after(mar), at(2024, 4, 30));
+    }
+
+    #[test]
+    fn test_last_business_day_of_month() {
+        let schedule = BillingSchedule::new(1, ScheduleUnit::Month)
+            .unwrap()
+            .with_anchor(ScheduleAnchor::LastBusinessDayOfMonth)
+            .unwrap();
+
+        // 2024-03-31 is a Sunday => Friday 29th
+        assert_eq!(schedule.next_after(at(2024, 2, 29)), at(2024, 3, 29));
+
+        let holiday = BusinessCalendar::new().with_holiday(NaiveDate::from_ymd_opt(2024, 3, 29).unwrap());
+        let schedule = schedule.with_calendar(holiday);
+        assert_eq!(schedule.next_after(at(2024, 2, 29)), at(2024, 3, 28));
+    }
+}
@@ -33,7 +33,7 @@ impl TaxCalculator {
     /// 💰 බදු ගණනය කරන්න (Calculate Tax)
     pub fn calculate(&self, cart: &Cart) -> EngineResult<Money> {
         let mut total_tax = Money::zero();
-        let taxable_amount = cart.subtotal(); // Assuming subtotal is taxable base
+        let taxable_amount = cart.taxable_subtotal();
 
         for rule in &self.rules {
             // Simple VAT-style calculation
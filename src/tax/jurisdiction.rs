@@ -0,0 +1,239 @@
+use crate::core::errors::{EngineError, EngineResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// ============================================================================
+/// 🗺️ Tax Jurisdictions (බදු අධිකරණ බල ප්‍රදේශ)
+/// ============================================================================
+/// Ship-to / bill-to ලිපිනයක් country → state → city ලෙස ධුරාවලියක් (hierarchy) බවට
+/// පත් කරයි. `TaxRate.jurisdiction` හි "US" rate එකක් "US-CA" සහ "US-CA-LOS_ANGELES"
+/// යන දෙකටම අදාළ වේ; "US-CA-LOS_ANGELES" rate එකක් එම නගරයට පමණි.
+///
+/// Codes: `COUNTRY`, `COUNTRY-STATE`, `COUNTRY-STATE-CITY` (upper-cased; spaces and
+/// dashes inside a city become `_`). Matches `TaxSourcing::region_code`.
+/// Jurisdiction එකක් නොදුන් විට resolver එකේ home jurisdiction එක භාවිතා වේ.
+///
+/// 📮 Address the tax is sourced to
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TaxAddress {
+    /// ISO country code (Ex: "US")
+    pub country: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub postal_code: Option<String>,
+}
+
+impl TaxAddress {
+    pub fn new(country: &str) -> Self {
+        TaxAddress {
+            country: country.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_state(mut self, state: &str) -> Self {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    pub fn with_city(mut self, city: &str) -> Self {
+        self.city = Some(city.to_string());
+        self
+    }
+
+    pub fn with_postal_code(mut self, postal_code: &str) -> Self {
+        self.postal_code = Some(postal_code.to_string());
+        self
+    }
+
+    /// Country-first codes of the address (a city without a state is ignored)
+    fn codes(&self) -> Vec<String> {
+        let part = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(segment)
+        };
+        let mut codes = vec![segment(&self.country)];
+        if let Some(state) = part(&self.state) {
+            codes.push(format!("{}-{}", codes[0], state));
+            if let Some(city) = part(&self.city) {
+                codes.push(format!("{}-{}", codes[1], city));
+            }
+        }
+        codes
+    }
+}
+
+fn segment(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .map(|c| if c.is_whitespace() || c == '-' { '_' } else { c })
+        .collect()
+}
+
+/// 🧭 Resolved jurisdiction: country first, most specific last
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jurisdiction {
+    pub codes: Vec<String>,
+    /// False => the seller does not collect tax here
+    pub has_nexus: bool,
+}
+
+impl Jurisdiction {
+    /// Most specific code: the target to calculate with
+    pub fn code(&self) -> Option<&str> {
+        self.codes.last().map(String::as_str)
+    }
+}
+
+/// Rate applies to `target` or one of its ancestors ("US" covers "US-CA-LOS_ANGELES")
+pub fn covers(code: &str, target: &str) -> bool {
+    target
+        .strip_prefix(code)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+}
+
+/// Should a rate configured for `rate_jurisdiction` apply in `target`? ("ALL" rates apply everywhere)
+pub fn applies(rate_jurisdiction: &str, target: &str) -> bool {
+    rate_jurisdiction == "ALL" || covers(rate_jurisdiction, target)
+}
+
+/// ✏️ Postal codes taxed as a special jurisdiction (districts that cross city lines)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JurisdictionOverride {
+    pub country: String,
+    pub postal_prefix: String,
+    /// Replaces the resolved chain (Ex: ["US", "US-CA", "US-CA-BEVERLY_HILLS"])
+    pub codes: Vec<String>,
+}
+
+/// 🏛️ Address → jurisdiction, with overrides and nexus
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct JurisdictionResolver {
+    #[serde(default)]
+    pub overrides: Vec<JurisdictionOverride>,
+    /// Jurisdictions where the seller must collect; empty => everywhere
+    #[serde(default)]
+    pub nexus: BTreeSet<String>,
+    /// Used when a calculation names no jurisdiction (the store's own, Ex: "LK")
+    #[serde(default)]
+    pub home: Option<String>,
+}
+
+impl JurisdictionResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_override(mut self, country: &str, postal_prefix: &str, codes: &[&str]) -> EngineResult<Self> {
+        if postal_prefix.trim().is_empty() || codes.is_empty() {
+            return Err(EngineError::Validation {
+                message: format!("Jurisdiction override for '{}' needs a postal prefix and codes", country),
+            });
+        }
+        self.overrides.push(JurisdictionOverride {
+            country: segment(country),
+            postal_prefix: postal_prefix.trim().to_string(),
+            codes: codes.iter().map(|code| code.trim().to_uppercase()).collect(),
+        });
+        Ok(self)
+    }
+
+    /// Seller has nexus in `code` (and everything under it)
+    pub fn with_nexus(mut self, code: &str) -> Self {
+        self.nexus.insert(code.trim().to_uppercase());
+        self
+    }
+
+    pub fn with_home(mut self, code: &str) -> Self {
+        self.home = Some(code.trim().to_uppercase());
+        self
+    }
+
+    pub fn home(&self) -> Option<&str> {
+        self.home.as_deref()
+    }
+
+    /// Must the seller collect tax in `code`?
+    pub fn has_nexus(&self, code: &str) -> bool {
+        self.nexus.is_empty() || self.nexus.iter().any(|nexus| covers(nexus, code))
+    }
+
+    /// 🔍 Resolve an address; the longest matching postal prefix wins
+    pub fn resolve(&self, address: &TaxAddress) -> EngineResult<Jurisdiction> {
+        if address.country.trim().is_empty() {
+            return Err(EngineError::Validation {
+                message: "Tax address needs a country".to_string(),
+            });
+        }
+        let country = segment(&address.country);
+        let postal = address.postal_code.as_deref().map(str::trim).unwrap_or("");
+        let codes = self
+            .overrides
+            .iter()
+            .filter(|o| o.country == country && !postal.is_empty() && postal.starts_with(&o.postal_prefix))
+            .max_by_key(|o| o.postal_prefix.len())
+            .map(|o| o.codes.clone())
+            .unwrap_or_else(|| address.codes());
+        let has_nexus = codes.iter().any(|code| self.has_nexus(code));
+        Ok(Jurisdiction { codes, has_nexus })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_rates_follow_the_resolved_jurisdiction_chain() {
+        let mut engine = MixedScenarioEngine::new();
+        for (name, rate, code) in [
+            ("State", 6.0, "US-CA"),
+            ("City", 2.0, "US-CA-LOS_ANGELES"),
+            ("District", 1.5, "US-CA-BEVERLY_HILLS"),
+            ("State", 4.0, "US-NY"),
+        ] {
+            engine.add_global_tax(TaxRate::new(name, rate, code, TaxAppliesTo::All)).unwrap();
+        }
+        engine
+            .add_global_tax(TaxRate::new("Regional", 0.5, "ALL", TaxAppliesTo::Region("US-CA".to_string())))
+            .unwrap();
+        engine.set_jurisdiction_resolver(
+            JurisdictionResolver::new()
+                .with_override("us", "902", &["US", "US-CA", "US-CA-BEVERLY_HILLS"])
+                .unwrap()
+                .with_nexus("US-CA"),
+        );
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Lamp", Money::new(100, 0), 1.0));
+        let tax_at = |address: TaxAddress| engine.calculate_cart_for_address(&cart, &[], &address).unwrap().total_tax;
+
+        // State + city + the CA-only regional rate; never NY or another city's rate
+        let los_angeles = TaxAddress::new("us").with_state("ca").with_city("Los Angeles");
+        assert_eq!(tax_at(los_angeles.clone()), Money::new(8, 50));
+        assert_eq!(tax_at(TaxAddress::new("US").with_state("CA").with_city("Fresno")), Money::new(6, 50));
+        // Postal override replaces the city
+        assert_eq!(tax_at(los_angeles.with_postal_code("90210")), Money::new(8, 0));
+        // No nexus in New York: nothing collected
+        let new_york = TaxAddress::new("US").with_state("NY");
+        assert!(!engine.jurisdiction_resolver().resolve(&new_york).unwrap().has_nexus);
+        assert_eq!(tax_at(new_york), Money::zero());
+        assert!(engine.calculate_cart_for_address(&cart, &[], &TaxAddress::new(" ")).is_err());
+
+        // Nexus also applies to a jurisdiction passed as a code
+        assert_eq!(engine.calculate_cart(&cart, &[], Some("US-NY")).unwrap().total_tax, Money::zero());
+        // No jurisdiction and no home: untargeted, every configured rate applies
+        assert_eq!(engine.calculate_cart(&cart, &[], None).unwrap().total_tax, Money::new(14, 0));
+        engine.set_jurisdiction_resolver(engine.jurisdiction_resolver().clone().with_home("us-ca"));
+        assert_eq!(engine.calculate_cart(&cart, &[], None).unwrap().total_tax, Money::new(6, 50));
+    }
+}
@@ -1,3 +1,8 @@
 pub mod calculator;
 pub mod tax_rule;
 pub mod vat;
+pub mod remittance;
+pub mod sourcing;
+pub mod jurisdiction;
+pub mod withholding;
+pub mod reporting;
@@ -0,0 +1,198 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::rules::mixed_scenarios::{CartCalculation, TaxDetail, TaxTreatment};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// 🏛️ Tax Remittance (බදු ගෙවීම් සාරාංශය)
+/// ============================================================================
+/// Filing period එකක් සඳහා බදු වර්ගය සහ jurisdiction අනුව එකතු කළ බදු,
+/// ආපසු ගෙවූ (refund) බදු සහ අධිකාරියට ගෙවිය යුතු ශුද්ධ බදු ගණනය කරයි.
+///
+/// 🧾 One tax amount collected on a sale or given back on a refund
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxRecord {
+    pub tax_type: String,
+    pub jurisdiction: String,
+    pub amount: Money,
+    pub recorded_at: DateTime<Utc>,
+    /// Reverse-charge records carry the buyer's self-accounted tax
+    #[serde(default)]
+    pub treatment: TaxTreatment,
+    /// Rate of the tax (0 for per-unit taxes and records not built from details)
+    #[serde(default)]
+    pub rate: f64,
+    /// Amount the tax was charged on
+    #[serde(default)]
+    pub taxable_base: Money,
+}
+
+impl TaxRecord {
+    pub fn new(tax_type: &str, jurisdiction: &str, amount: Money, recorded_at: DateTime<Utc>) -> Self {
+        TaxRecord {
+            tax_type: tax_type.to_string(),
+            jurisdiction: jurisdiction.to_string(),
+            amount,
+            recorded_at,
+            treatment: TaxTreatment::Standard,
+            rate: 0.0,
+            taxable_base: Money::zero(),
+        }
+    }
+
+    /// Records for a list of tax details (Ex: the refunded lines' details)
+    pub fn from_details(details: &[TaxDetail], jurisdiction: &str, recorded_at: DateTime<Utc>) -> Vec<Self> {
+        details
+            .iter()
+            .map(|detail| {
+                let amount = match detail.treatment {
+                    TaxTreatment::Standard => detail.amount,
+                    TaxTreatment::ReverseCharge => detail.reverse_charged,
+                };
+                TaxRecord {
+                    treatment: detail.treatment,
+                    rate: detail.rate,
+                    taxable_base: detail.taxable_base,
+                    ..Self::new(&detail.name, jurisdiction, amount, recorded_at)
+                }
+            })
+            .collect()
+    }
+
+    /// Records for every line tax detail of a calculated cart
+    pub fn from_calculation(calc: &CartCalculation, jurisdiction: &str, recorded_at: DateTime<Utc>) -> Vec<Self> {
+        calc.items
+            .iter()
+            .flat_map(|item| Self::from_details(&item.tax_details, jurisdiction, recorded_at))
+            .collect()
+    }
+}
+
+/// 📄 Per tax type & jurisdiction line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemittanceLine {
+    pub tax_type: String,
+    pub jurisdiction: String,
+    #[serde(default)]
+    pub treatment: TaxTreatment,
+    pub collected: Money,
+    pub refunded: Money,
+    /// collected - refunded (negative = reclaimable)
+    pub net: Money,
+}
+
+/// 📊 Filing period summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemittanceSummary {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Sorted by jurisdiction, then tax type
+    pub lines: Vec<RemittanceLine>,
+    /// Standard-treatment totals only
+    pub total_collected: Money,
+    pub total_refunded: Money,
+    pub net_payable: Money,
+    /// Net reverse-charged tax: reported, but accounted for by the buyers
+    #[serde(default)]
+    pub total_reverse_charged: Money,
+}
+
+pub struct TaxRemittance;
+
+impl TaxRemittance {
+    /// 🧮 Net collected against refunded tax for `[from, to)`
+    /// Refund amounts count as given back whatever their sign (return lines carry negative tax).
+    /// Reverse-charged tax gets its own lines and is left out of `net_payable`.
+    pub fn build(
+        sales_tax_details: &[TaxRecord],
+        refund_tax_details: &[TaxRecord],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> EngineResult<RemittanceSummary> {
+        if from >= to {
+            return Err(EngineError::Validation {
+                message: format!("Filing period start {} must be before its end {}", from, to),
+            });
+        }
+        let in_period = |record: &&TaxRecord| record.recorded_at >= from && record.recorded_at < to;
+
+        let mut totals: BTreeMap<(String, String, TaxTreatment), (Money, Money)> = BTreeMap::new();
+        for record in sales_tax_details.iter().filter(in_period) {
+            let entry = totals
+                .entry((record.jurisdiction.clone(), record.tax_type.clone(), record.treatment))
+                .or_default();
+            entry.0 = entry.0 + record.amount;
+        }
+        for record in refund_tax_details.iter().filter(in_period) {
+            let entry = totals
+                .entry((record.jurisdiction.clone(), record.tax_type.clone(), record.treatment))
+                .or_default();
+            entry.1 = entry.1 + record.amount.abs();
+        }
+
+        let lines: Vec<RemittanceLine> = totals
+            .into_iter()
+            .map(|((jurisdiction, tax_type, treatment), (collected, refunded))| RemittanceLine {
+                tax_type,
+                jurisdiction,
+                treatment,
+                collected,
+                refunded,
+                net: collected - refunded,
+            })
+            .collect();
+        let standard = || lines.iter().filter(|l| l.treatment == TaxTreatment::Standard);
+        let total_collected = standard().fold(Money::zero(), |acc, l| acc + l.collected);
+        let total_refunded = standard().fold(Money::zero(), |acc, l| acc + l.refunded);
+        let total_reverse_charged = lines
+            .iter()
+            .filter(|l| l.treatment == TaxTreatment::ReverseCharge)
+            .fold(Money::zero(), |acc, l| acc + l.net);
+
+        Ok(RemittanceSummary {
+            from,
+            to,
+            lines,
+            total_collected,
+            total_refunded,
+            net_payable: total_collected - total_refunded,
+            total_reverse_charged,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_vat_refund_nets_against_collected_vat() {
+        let at = |month, day| Utc.with_ymd_and_hms(2024, month, day, 12, 0, 0).unwrap();
+        let sales = vec![
+            TaxRecord::new("VAT", "LK", Money::new(180, 0), at(3, 5)),
+            TaxRecord::new("VAT", "LK", Money::new(90, 0), at(3, 20)),
+            TaxRecord::new("NBT", "LK", Money::new(20, 0), at(3, 20)),
+            // Previous period: not in this filing
+            TaxRecord::new("VAT", "LK", Money::new(500, 0), at(2, 28)),
+        ];
+        let refunds = vec![
+            TaxRecord::new("VAT", "LK", Money::from_cents(-4_500), at(3, 25)),
+            // Next period
+            TaxRecord::new("VAT", "LK", Money::new(90, 0), at(4, 2)),
+        ];
+
+        let summary = TaxRemittance::build(&sales, &refunds, at(3, 1), at(4, 1)).unwrap();
+        assert_eq!(summary.lines.len(), 2);
+        let vat = summary.lines.iter().find(|l| l.tax_type == "VAT").unwrap();
+        assert_eq!(vat.collected, Money::new(270, 0));
+        assert_eq!(vat.refunded, Money::new(45, 0));
+        assert_eq!(vat.net, Money::new(225, 0));
+        assert_eq!(summary.total_collected, Money::new(290, 0));
+        assert_eq!(summary.net_payable, Money::new(245, 0));
+
+        assert!(TaxRemittance::build(&sales, &refunds, at(4, 1), at(3, 1)).is_err());
+    }
+}
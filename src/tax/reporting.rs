@@ -0,0 +1,331 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::rules::mixed_scenarios::{CartCalculation, TaxTreatment};
+use crate::tax::remittance::TaxRecord;
+use crate::storage::database::{EntitySerializer, StorageBackend};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// 📑 Tax Reporting (බදු වාර්තා)
+/// ============================================================================
+/// ගබඩා කළ (stored) විකුණුම් ගනුදෙනු කාල පරිච්ඡේදය, jurisdiction සහ බදු අනුපාතය අනුව
+/// එකතු කරයි: taxable base, එකතු කළ බද්ද, exempt මුදල සහ reverse-charged බද්ද.
+/// JSON (serde) සහ CSV ලෙස export කළ හැක. Periods are UTC calendar buckets.
+///
+/// Storage kind / key prefix of recorded transactions
+pub const TAX_TRANSACTION_KIND: &str = "tax_transaction";
+const KEY_PREFIX: &str = "tax-transaction:";
+
+/// Rate name of a line that carried no tax
+pub const EXEMPT: &str = "EXEMPT";
+
+/// 🧾 A completed sale as the tax report sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxTransaction {
+    pub id: String,
+    pub recorded_at: DateTime<Utc>,
+    pub jurisdiction: String,
+    pub calculation: CartCalculation,
+}
+
+impl TaxTransaction {
+    pub fn new(id: &str, jurisdiction: &str, calculation: CartCalculation, recorded_at: DateTime<Utc>) -> Self {
+        TaxTransaction {
+            id: id.to_string(),
+            recorded_at,
+            jurisdiction: jurisdiction.to_string(),
+            calculation,
+        }
+    }
+
+    /// 💾 Persist (replaces an earlier record with the same id)
+    pub fn store(&self, backend: &dyn StorageBackend) -> EngineResult<()> {
+        let json = EntitySerializer::to_versioned_json(TAX_TRANSACTION_KIND, self)?;
+        backend.set(&format!("{}{}", KEY_PREFIX, self.id), &json)
+    }
+
+    pub fn load(backend: &dyn StorageBackend, id: &str) -> EngineResult<Option<TaxTransaction>> {
+        match backend.get(&format!("{}{}", KEY_PREFIX, id))? {
+            Some(json) => EntitySerializer::from_json(&json).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 🗑️ Withdraw a record (sale cancelled); true if there was one
+    pub fn delete(backend: &dyn StorageBackend, id: &str) -> EngineResult<bool> {
+        backend.delete(&format!("{}{}", KEY_PREFIX, id))
+    }
+
+    /// Every recorded transaction, oldest first
+    pub fn load_all(backend: &dyn StorageBackend) -> EngineResult<Vec<TaxTransaction>> {
+        let mut transactions = Vec::new();
+        for key in backend.keys(KEY_PREFIX)? {
+            if let Some(json) = backend.get(&key)? {
+                transactions.push(EntitySerializer::from_json::<TaxTransaction>(&json)?);
+            }
+        }
+        transactions.sort_by(|a, b| a.recorded_at.cmp(&b.recorded_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(transactions)
+    }
+}
+
+/// 📆 Reporting bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGranularity {
+    Day,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl ReportGranularity {
+    pub fn parse(value: &str) -> EngineResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "day" | "daily" => Ok(Self::Day),
+            "month" | "monthly" => Ok(Self::Month),
+            "quarter" | "quarterly" => Ok(Self::Quarter),
+            "year" | "yearly" | "annual" => Ok(Self::Year),
+            other => Err(EngineError::Validation {
+                message: format!("Unknown report granularity '{}'", other),
+            }),
+        }
+    }
+
+    /// Ex: "2026-09-14", "2026-09", "2026-Q3", "2026"
+    fn label(&self, at: DateTime<Utc>) -> String {
+        match self {
+            Self::Day => at.format("%Y-%m-%d").to_string(),
+            Self::Month => at.format("%Y-%m").to_string(),
+            Self::Quarter => format!("{}-Q{}", at.year(), at.month0() / 3 + 1),
+            Self::Year => at.year().to_string(),
+        }
+    }
+}
+
+/// 📄 One period / jurisdiction / rate line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxReportRow {
+    pub period: String,
+    pub jurisdiction: String,
+    pub tax_name: String,
+    pub rate: f64,
+    /// Standard and reverse-charged amounts of one rate are reported on separate rows
+    #[serde(default)]
+    pub treatment: TaxTreatment,
+    /// Amount the tax was charged (or reverse-charged) on
+    pub taxable_base: Money,
+    pub tax_collected: Money,
+    /// Net amount of lines that carried no tax
+    pub exempt_amount: Money,
+    /// Reported but accounted for by business buyers
+    pub reverse_charged: Money,
+}
+
+/// 📊 Tax report for `[from, to)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub granularity: ReportGranularity,
+    /// Sorted by period, jurisdiction, tax name, rate, treatment
+    pub rows: Vec<TaxReportRow>,
+    pub total_collected: Money,
+    pub total_exempt: Money,
+    pub total_reverse_charged: Money,
+}
+
+impl TaxReport {
+    /// 🧮 Aggregate the transactions recorded in `[from, to)`
+    pub fn build(
+        transactions: &[TaxTransaction],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        granularity: ReportGranularity,
+    ) -> EngineResult<Self> {
+        if from >= to {
+            return Err(EngineError::Validation {
+                message: format!("Report start {} must be before its end {}", from, to),
+            });
+        }
+        let mut rows: BTreeMap<RowKey, TaxReportRow> = BTreeMap::new();
+        for transaction in transactions.iter().filter(|t| t.recorded_at >= from && t.recorded_at < to) {
+            let period = granularity.label(transaction.recorded_at);
+            for line in &transaction.calculation.items {
+                if line.tax_details.is_empty() {
+                    let exempt = row(&mut rows, &period, &transaction.jurisdiction, (EXEMPT, 0.0), TaxTreatment::Standard);
+                    exempt.exempt_amount = exempt.exempt_amount + line.total - line.tax_amount;
+                    continue;
+                }
+                let records = TaxRecord::from_details(&line.tax_details, &transaction.jurisdiction, transaction.recorded_at);
+                for record in records {
+                    let entry = row(&mut rows, &period, &record.jurisdiction, (&record.tax_type, record.rate), record.treatment);
+                    entry.taxable_base = entry.taxable_base + record.taxable_base;
+                    match record.treatment {
+                        TaxTreatment::Standard => entry.tax_collected = entry.tax_collected + record.amount,
+                        TaxTreatment::ReverseCharge => entry.reverse_charged = entry.reverse_charged + record.amount,
+                    }
+                }
+            }
+        }
+        let rows: Vec<TaxReportRow> = rows.into_values().collect();
+        let sum = |amount: fn(&TaxReportRow) -> Money| rows.iter().fold(Money::zero(), |acc, r| acc + amount(r));
+        Ok(TaxReport {
+            from,
+            to,
+            granularity,
+            total_collected: sum(|r| r.tax_collected),
+            total_exempt: sum(|r| r.exempt_amount),
+            total_reverse_charged: sum(|r| r.reverse_charged),
+            rows,
+        })
+    }
+
+    /// 📤 CSV export (amounts as decimals, Ex: "1234.50")
+    pub fn to_csv(&self) -> EngineResult<String> {
+        let failed = |e: &dyn std::fmt::Display| EngineError::System {
+            message: format!("Tax report CSV export failed: {}", e),
+        };
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record([
+                "period",
+                "jurisdiction",
+                "tax_name",
+                "rate",
+                "treatment",
+                "taxable_base",
+                "tax_collected",
+                "exempt_amount",
+                "reverse_charged",
+            ])
+            .map_err(|e| failed(&e))?;
+        for row in &self.rows {
+            writer
+                .write_record([
+                    row.period.clone(),
+                    row.jurisdiction.clone(),
+                    row.tax_name.clone(),
+                    row.rate.to_string(),
+                    treatment(row.treatment).to_string(),
+                    decimal(row.taxable_base),
+                    decimal(row.tax_collected),
+                    decimal(row.exempt_amount),
+                    decimal(row.reverse_charged),
+                ])
+                .map_err(|e| failed(&e))?;
+        }
+        let bytes = writer.into_inner().map_err(|e| failed(&e))?;
+        String::from_utf8(bytes).map_err(|e| failed(&e))
+    }
+}
+
+/// (period, jurisdiction, tax name, rate bits, treatment)
+type RowKey = (String, String, String, u64, TaxTreatment);
+
+fn row<'a>(
+    rows: &'a mut BTreeMap<RowKey, TaxReportRow>,
+    period: &str,
+    jurisdiction: &str,
+    (tax_name, rate): (&str, f64),
+    treatment: TaxTreatment,
+) -> &'a mut TaxReportRow {
+    let key = (period.to_string(), jurisdiction.to_string(), tax_name.to_string(), rate.to_bits(), treatment);
+    rows.entry(key).or_insert_with(|| TaxReportRow {
+        period: period.to_string(),
+        jurisdiction: jurisdiction.to_string(),
+        tax_name: tax_name.to_string(),
+        rate,
+        treatment,
+        taxable_base: Money::zero(),
+        tax_collected: Money::zero(),
+        exempt_amount: Money::zero(),
+        reverse_charged: Money::zero(),
+    })
+}
+
+fn treatment(treatment: TaxTreatment) -> &'static str {
+    match treatment {
+        TaxTreatment::Standard => "standard",
+        TaxTreatment::ReverseCharge => "reverse_charge",
+    }
+}
+
+fn decimal(amount: Money) -> String {
+    let sign = if amount.is_negative() { "-" } else { "" };
+    let cents = amount.amount.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::storage::database::InMemoryStorage;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_report_aggregates_stored_sales_by_period_jurisdiction_and_rate() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("VAT", 20.0, "ALL", TaxAppliesTo::All).reverse_charged()).unwrap();
+        engine
+            .add_product_tax(ProductTaxConfig {
+                product_id: "BOOK".to_string(),
+                tax_rates: Vec::new(),
+                tax_exempt: true,
+                tax_included_in_price: false,
+            })
+            .unwrap();
+        let mut book = Item::new("Book", Money::new(50, 0), 1.0);
+        book.id = "BOOK".to_string();
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Lamp", Money::new(100, 0), 1.0));
+        cart.add_item(book);
+        let retail = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+        cart.customer = Some(CustomerContext::new("ACME").with_vat_id("DE123456789"));
+        let b2b = engine.calculate_cart(&cart, &[], Some("FR")).unwrap();
+
+        let at = |month, day| Utc.with_ymd_and_hms(2026, month, day, 10, 0, 0).unwrap();
+        let storage = InMemoryStorage::new();
+        for transaction in [
+            TaxTransaction::new("S1", "FR", retail.clone(), at(7, 3)),
+            TaxTransaction::new("S2", "FR", retail.clone(), at(8, 20)),
+            TaxTransaction::new("S3", "FR", b2b, at(8, 21)),
+            // Outside the report range
+            TaxTransaction::new("S4", "FR", retail, at(10, 1)),
+        ] {
+            transaction.store(&storage).unwrap();
+        }
+        let stored = TaxTransaction::load_all(&storage).unwrap();
+        assert_eq!(stored.len(), 4);
+
+        let report = TaxReport::build(&stored, at(7, 1), at(10, 1), ReportGranularity::Month).unwrap();
+        let august: Vec<&TaxReportRow> = report.rows.iter().filter(|r| r.period == "2026-08").collect();
+        assert_eq!(august.len(), 3);
+        let vat = |treatment| august.iter().find(|r| r.tax_name == "VAT" && r.treatment == treatment).unwrap();
+        let standard = vat(TaxTreatment::Standard);
+        assert_eq!((standard.taxable_base, standard.tax_collected), (Money::new(100, 0), Money::new(20, 0)));
+        assert_eq!(standard.reverse_charged, Money::zero());
+        let reverse = vat(TaxTreatment::ReverseCharge);
+        assert_eq!((reverse.taxable_base, reverse.reverse_charged), (Money::new(100, 0), Money::new(20, 0)));
+        assert_eq!(reverse.tax_collected, Money::zero());
+        let exempt = august.iter().find(|r| r.tax_name == EXEMPT).unwrap();
+        assert_eq!(exempt.exempt_amount, Money::new(100, 0));
+        assert_eq!(report.total_collected, Money::new(40, 0));
+
+        let quarterly = TaxReport::build(&stored, at(7, 1), at(10, 1), ReportGranularity::Quarter).unwrap();
+        assert_eq!(quarterly.rows.len(), 3);
+        let csv = quarterly.to_csv().unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "period,jurisdiction,tax_name,rate,treatment,taxable_base,tax_collected,exempt_amount,reverse_charged"
+        );
+        assert_eq!(lines[2], "2026-Q3,FR,VAT,20,standard,200.00,40.00,0.00,0.00");
+        assert_eq!(lines[3], "2026-Q3,FR,VAT,20,reverse_charge,100.00,0.00,0.00,20.00");
+        assert!(TaxReport::build(&stored, at(10, 1), at(7, 1), ReportGranularity::Month).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 📍 Tax Sourcing (බදු මූලාශ්‍රය - origin vs destination)
+/// ============================================================================
+/// බදු ගණනය කළ යුත්තේ විකුණුම්කරුගේ ප්‍රදේශයට (origin) ද, නැතහොත් භාණ්ඩ
+/// යවන ලිපිනයේ ප්‍රදේශයට (destination - VAT, US sales tax) ද යන්න තීරණය කරයි.
+/// Destination sourcing යටතේ shipping ලිපිනයක් නැති විට origin region එක භාවිතා වේ.
+///
+/// Region codes follow `TaxRate.jurisdiction`: the country, or `COUNTRY-STATE`
+/// (Ex: "US-CA") when the address names a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TaxSourcing {
+    /// Seller's region (`CalculationRequest.tax_region`)
+    #[default]
+    Origin,
+    /// Shipping address region, falling back to origin
+    Destination,
+}
+
+impl TaxSourcing {
+    /// 🧭 Jurisdiction to calculate with
+    pub fn resolve(&self, origin: Option<&str>, destination: Option<&str>) -> Option<String> {
+        match (self, destination) {
+            (TaxSourcing::Destination, Some(region)) => Some(region.to_string()),
+            _ => origin.map(str::to_string),
+        }
+    }
+
+    /// Region code of an address (upper-cased, Ex: "us" + "ca" => "US-CA")
+    pub fn region_code(country: &str, state: Option<&str>) -> String {
+        let country = country.trim().to_uppercase();
+        match state.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(state) => format!("{}-{}", country, state.to_uppercase()),
+            None => country,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::api::rest::{AddressInput, ShippingInput};
+
+    fn shipping_to(country: &str, state: Option<&str>) -> ShippingInput {
+        ShippingInput {
+            method: "courier".to_string(),
+            address: AddressInput {
+                line1: "1 Main St".to_string(),
+                line2: None,
+                city: "Somewhere".to_string(),
+                state: state.map(str::to_string),
+                postal_code: "00000".to_string(),
+                country: country.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_origin_and_destination_sourcing_tax_the_same_cart_differently() {
+        let mut engine = MixedScenarioEngine::new();
+        engine.add_global_tax(TaxRate::new("Sales Tax", 6.0, "US-NV", TaxAppliesTo::All)).unwrap();
+        engine.add_global_tax(TaxRate::new("Sales Tax", 7.25, "US-CA", TaxAppliesTo::All)).unwrap();
+        engine.add_global_tax(TaxRate::new("Sales Tax", 4.0, "US-NY", TaxAppliesTo::All)).unwrap();
+
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Lamp", Money::new(100, 0), 1.0));
+        let origin = Some("US-NV");
+        let tax_for = |sourcing: TaxSourcing, shipping: Option<&ShippingInput>| {
+            let destination = shipping.map(ShippingInput::tax_region);
+            let jurisdiction = sourcing.resolve(origin, destination.as_deref());
+            engine.calculate_cart(&cart, &[], jurisdiction.as_deref()).unwrap().total_tax
+        };
+        let california = shipping_to("us", Some("ca"));
+        let new_york = shipping_to("US", Some("NY"));
+
+        // Origin: the seller's rate wherever the cart ships
+        assert_eq!(tax_for(TaxSourcing::Origin, Some(&california)), Money::new(6, 0));
+        assert_eq!(tax_for(TaxSourcing::Origin, Some(&new_york)), Money::new(6, 0));
+
+        // Destination: the shipping address's rate, origin when nothing ships
+        assert_eq!(tax_for(TaxSourcing::Destination, Some(&california)), Money::from_cents(725));
+        assert_eq!(tax_for(TaxSourcing::Destination, Some(&new_york)), Money::new(4, 0));
+        assert_eq!(tax_for(TaxSourcing::Destination, None), Money::new(6, 0));
+    }
+}
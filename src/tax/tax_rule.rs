@@ -2,7 +2,6 @@ use crate::core::errors::EngineResult;
 use crate::core::money::Money;
 use crate::rules::traits::{Rule, RuleAction};
 use crate::types::cart::Cart;
-use std::ops::{Div, Mul};
 
 /// ============================================================================
 /// 🏛️ Tax Rule (බදු රීති)
@@ -46,15 +45,11 @@ impl Rule for TaxRule {
         true // Applies generally, can be refined with conditions
     }
 
-    fn apply(&self, cart: &Cart) -> EngineResult<Vec<RuleAction>> {
+    fn apply(&self, _cart: &Cart) -> EngineResult<Vec<RuleAction>> {
         match &self.tax_type {
-            TaxType::Percentage(rate) => {
-                let subtotal = cart.subtotal();
-                // Subtotal * (rate / 100)
-                let tax_amount = subtotal.mul(*rate as i64).div(100);
-                Ok(vec![RuleAction::Tax(tax_amount)])
-            }
-            TaxType::Fixed(amount) => Ok(vec![RuleAction::Tax(amount.clone())]),
+            // Taxable subtotal × rate, rounded by the engine's rounding mode
+            TaxType::Percentage(rate) => Ok(vec![RuleAction::PercentageTax(*rate)]),
+            TaxType::Fixed(amount) => Ok(vec![RuleAction::Tax(*amount)]),
         }
     }
 
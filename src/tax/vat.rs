@@ -18,3 +18,37 @@ impl Vat {
         amount.mul(self.rate as i64).div(100)
     }
 }
+
+/// 🆔 Buyer VAT registration number, structure only (Ex: "DE123456789", "GB 123 4567 89")
+/// Country prefix + 8-12 letters/digits (GB: 9 or 12 digits, RO: 2-10). Whether the number is
+/// actually registered is a VIES / HMRC lookup the caller does before trusting it.
+pub fn is_valid_vat_id(vat_id: &str) -> bool {
+    let normalized: String = vat_id
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '.' && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+    if !normalized.is_ascii() || normalized.len() < 4 {
+        return false;
+    }
+    let (country, number) = normalized.split_at(2);
+    if !country.chars().all(|c| c.is_ascii_uppercase()) {
+        return false;
+    }
+    if country == "GB" {
+        return matches!(number.len(), 9 | 12) && number.chars().all(|c| c.is_ascii_digit());
+    }
+    let length = if country == "RO" { 2..=10 } else { 8..=12 };
+    length.contains(&number.len())
+        && number.chars().all(|c| c.is_ascii_alphanumeric())
+        && number.chars().any(|c| c.is_ascii_digit())
+}
+
+/// 🌍 Country a VAT ID is registered in (ISO code; Greece's "EL" prefix => "GR")
+pub fn vat_id_country(vat_id: &str) -> Option<String> {
+    if !is_valid_vat_id(vat_id) {
+        return None;
+    }
+    let prefix: String = vat_id.trim_start().chars().take(2).collect::<String>().to_uppercase();
+    Some(if prefix == "EL" { "GR".to_string() } else { prefix })
+}
@@ -0,0 +1,218 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
+use crate::core::rounding::RoundingMode;
+use crate::ledger::journal::GeneralLedger;
+use crate::ledger::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ============================================================================
+/// ✂️ Withholding Tax (රඳවා ගැනීමේ බද්ද - WHT)
+/// ============================================================================
+/// සැපයුම්කරුවන්ට ගෙවීමේදී (Ex: LK සේවා සඳහා 5%) ගෙවන්නා විසින් බද්දක් රඳවාගෙන
+/// රජයට ගෙවිය යුතුය. Invoice එක සැපයුම්කරුට ගෙවන ශුද්ධ මුදල සහ රජයට ගෙවිය යුතු
+/// WHT ලෙස බෙදා, ඒ සඳහා balanced ledger entries සාදයි.
+///
+/// 🏭 Supplier as far as withholding is concerned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplierProfile {
+    pub supplier_id: String,
+    /// Schedule category (Ex: "services", "rent", "goods")
+    pub category: String,
+    /// Holds an exemption certificate
+    #[serde(default)]
+    pub exempt: bool,
+    /// Supplier-specific percent (Ex: reduced treaty rate) instead of the category's
+    #[serde(default)]
+    pub rate_override: Option<f64>,
+}
+
+impl SupplierProfile {
+    pub fn new(supplier_id: &str, category: &str) -> Self {
+        SupplierProfile {
+            supplier_id: supplier_id.to_string(),
+            category: category.to_string(),
+            exempt: false,
+            rate_override: None,
+        }
+    }
+}
+
+/// 📋 Category => WHT percent; categories without a rate are not withheld
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct WithholdingSchedule {
+    pub rates: BTreeMap<String, f64>,
+    /// Invoices below this amount are paid in full
+    #[serde(default)]
+    pub threshold: Option<Money>,
+    pub rounding: RoundingMode,
+}
+
+impl WithholdingSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rate(mut self, category: &str, percent: f64) -> EngineResult<Self> {
+        check_percent(percent)?;
+        self.rates.insert(category.to_string(), percent);
+        Ok(self)
+    }
+
+    pub fn with_threshold(mut self, threshold: Money) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Percent withheld from this supplier's invoices
+    pub fn rate_for(&self, supplier: &SupplierProfile) -> EngineResult<f64> {
+        if supplier.exempt {
+            return Ok(0.0);
+        }
+        match supplier.rate_override {
+            Some(percent) => check_percent(percent).map(|_| percent),
+            None => Ok(self.rates.get(&supplier.category).copied().unwrap_or(0.0)),
+        }
+    }
+
+    /// 🔪 Split an invoice into what the supplier receives and what goes to government
+    pub fn split(&self, invoice_id: &str, amount: Money, supplier: &SupplierProfile) -> EngineResult<WithholdingSplit> {
+        if amount.is_negative() || amount.is_zero() {
+            return Err(EngineError::Validation {
+                message: format!("Invoice '{}' amount must be positive, got {}", invoice_id, amount),
+            });
+        }
+        let below_threshold = self.threshold.is_some_and(|threshold| amount < threshold);
+        let rate = if below_threshold { 0.0 } else { self.rate_for(supplier)? };
+        let withheld = amount.percentage_of_rounded(rate, self.rounding);
+        Ok(WithholdingSplit {
+            invoice_id: invoice_id.to_string(),
+            supplier_id: supplier.supplier_id.clone(),
+            gross: amount,
+            rate,
+            withheld,
+            net_payable: amount - withheld,
+        })
+    }
+}
+
+fn check_percent(percent: f64) -> EngineResult<()> {
+    if percent.is_finite() && (0.0..=100.0).contains(&percent) {
+        Ok(())
+    } else {
+        Err(EngineError::Validation {
+            message: format!("Withholding percent must be between 0 and 100, got {}", percent),
+        })
+    }
+}
+
+/// 🧾 gross == net_payable + withheld
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithholdingSplit {
+    pub invoice_id: String,
+    pub supplier_id: String,
+    pub gross: Money,
+    pub rate: f64,
+    pub withheld: Money,
+    pub net_payable: Money,
+}
+
+/// 🏦 Accounts a supplier payment posts to
+#[derive(Debug, Clone)]
+pub struct WithholdingAccounts {
+    /// The invoice's accounts payable
+    pub accounts_payable: String,
+    pub cash: String,
+    /// WHT owed to government until remitted
+    pub wht_payable: String,
+}
+
+impl WithholdingSplit {
+    /// Payment: Dr accounts payable (gross), Cr cash (net), Cr WHT payable (withheld)
+    pub fn post_payment(&self, ledger: &mut GeneralLedger, accounts: &WithholdingAccounts) -> EngineResult<Transaction> {
+        let mut transaction = Transaction::new(&format!("Supplier payment {}", self.invoice_id))
+            .debit(&accounts.accounts_payable, self.gross)
+            .credit(&accounts.cash, self.net_payable);
+        if !self.withheld.is_zero() {
+            transaction = transaction.credit(&accounts.wht_payable, self.withheld);
+        }
+        transaction.metadata.insert("invoice_id".to_string(), self.invoice_id.clone());
+        transaction.metadata.insert("supplier_id".to_string(), self.supplier_id.clone());
+        ledger.post_transaction(transaction.clone())?;
+        Ok(transaction)
+    }
+}
+
+/// 🏛️ Remit withheld tax to government: Dr WHT payable, Cr cash
+pub fn post_remittance(
+    ledger: &mut GeneralLedger,
+    accounts: &WithholdingAccounts,
+    reference: &str,
+    splits: &[WithholdingSplit],
+) -> EngineResult<Transaction> {
+    let total = splits.iter().fold(Money::zero(), |acc, split| acc + split.withheld);
+    if total.is_zero() {
+        return Err(EngineError::Validation {
+            message: format!("Nothing withheld to remit for '{}'", reference),
+        });
+    }
+    let mut transaction = Transaction::new(&format!("WHT remittance {}", reference))
+        .debit(&accounts.wht_payable, total)
+        .credit(&accounts.cash, total);
+    transaction.metadata.insert("reference".to_string(), reference.to_string());
+    ledger.post_transaction(transaction.clone())?;
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_wht_splits_payment_and_posts_balanced_entries() {
+        let schedule = WithholdingSchedule::new()
+            .with_rate("services", 5.0)
+            .unwrap()
+            .with_rate("rent", 10.0)
+            .unwrap()
+            .with_threshold(Money::new(100, 0));
+        assert!(WithholdingSchedule::new().with_rate("services", 120.0).is_err());
+
+        let consultant = SupplierProfile::new("SUP-1", "services");
+        let landlord = SupplierProfile::new("SUP-2", "rent");
+        let exempt = SupplierProfile { exempt: true, ..SupplierProfile::new("SUP-3", "services") };
+
+        let fee = schedule.split("INV-1", Money::new(10_000, 0), &consultant).unwrap();
+        assert_eq!(fee.withheld, Money::new(500, 0));
+        assert_eq!(fee.net_payable, Money::new(9_500, 0));
+        let rent = schedule.split("INV-2", Money::from_cents(123_455), &landlord).unwrap();
+        assert_eq!(rent.withheld + rent.net_payable, rent.gross);
+        assert!(schedule.split("INV-3", Money::new(5_000, 0), &exempt).unwrap().withheld.is_zero());
+        assert!(schedule.split("INV-4", Money::new(50, 0), &consultant).unwrap().withheld.is_zero());
+        assert!(schedule.split("INV-5", Money::zero(), &consultant).is_err());
+
+        let mut ledger = GeneralLedger::new();
+        for (id, kind) in [
+            ("1000", AccountType::Asset),
+            ("2000", AccountType::Liability),
+            ("2300", AccountType::Liability),
+        ] {
+            ledger.add_account(Account::new(id, id, kind));
+        }
+        let accounts = WithholdingAccounts {
+            accounts_payable: "2000".to_string(),
+            cash: "1000".to_string(),
+            wht_payable: "2300".to_string(),
+        };
+        assert!(fee.post_payment(&mut ledger, &accounts).unwrap().is_balanced());
+        assert!(rent.post_payment(&mut ledger, &accounts).unwrap().is_balanced());
+        let owed = fee.withheld + rent.withheld;
+        assert_eq!(ledger.balance("2300"), Some(Money::zero() - owed));
+
+        // Remitting clears the WHT liability
+        assert!(post_remittance(&mut ledger, &accounts, "2026-09", &[fee, rent]).unwrap().is_balanced());
+        assert_eq!(ledger.balance("2300"), Some(Money::zero()));
+        assert_eq!(ledger.balance("2000"), Some(Money::new(10_000, 0) + Money::from_cents(123_455)));
+    }
+}
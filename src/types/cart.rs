@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 use crate::types::item::Item;
 use crate::types::currency::Currency;
+use crate::types::customer::CustomerContext;
 use crate::core::money::Money;
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::exchange::{self, ExchangeRateProvider};
+use crate::core::rounding::RoundingMode;
+use chrono::{DateTime, Utc};
 
 /// ============================================================================
 /// 🛒 Cart (කරත්තය) - ගනුදෙනු එකතුව
@@ -15,11 +20,94 @@ pub struct Cart {
     /// පාරිභෝගිකයා (Customer ID - Optional)
     pub customer_id: Option<String>,
 
+    /// පාරිභෝගික කණ්ඩායම (Customer Group - Ex: "staff", "wholesale")
+    #[serde(default)]
+    pub customer_group: Option<String>,
+
+    /// පාරිභෝගික තොරතුරු (groups, purchase history - customer discount conditions)
+    #[serde(default)]
+    pub customer: Option<CustomerContext>,
+
     /// අයිතම ලැයිස්තුව (List of Items)
     pub items: Vec<Item>,
 
     /// මූලික මුදල් වර්ගය (Base Currency)
     pub currency: Currency,
+
+    /// ගනුදෙනු වේලාව (Transaction time - refund windows run from here; None if unknown)
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 🚧 Cart Limits (කරත්ත සීමා)
+/// Lines million ගණනක් හෝ 1e18 වැනි quantity එකක් memory නාස්ති කිරීම සහ
+/// money math overflow වීම වළක්වයි. Server එක (`EngineConfig::build`) defaults
+/// `CART_MAX_LINES`, `CART_MAX_LINE_QUANTITY`, `CART_MAX_TOTAL_QUANTITY` env vars වලින් override කරයි.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CartLimits {
+    pub max_lines: usize,
+    pub max_line_quantity: f64,
+    pub max_total_quantity: f64,
+}
+
+impl Default for CartLimits {
+    fn default() -> Self {
+        CartLimits {
+            max_lines: 10_000,
+            max_line_quantity: 1_000_000.0,
+            max_total_quantity: 10_000_000.0,
+        }
+    }
+}
+
+impl CartLimits {
+    /// ⚙️ Defaults overridden by environment
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+        let defaults = Self::default();
+        CartLimits {
+            max_lines: var("CART_MAX_LINES").unwrap_or(defaults.max_lines),
+            max_line_quantity: var("CART_MAX_LINE_QUANTITY").unwrap_or(defaults.max_line_quantity),
+            max_total_quantity: var("CART_MAX_TOTAL_QUANTITY").unwrap_or(defaults.max_total_quantity),
+        }
+    }
+
+    fn check_line(&self, item: &Item) -> EngineResult<()> {
+        if item.quantity.is_nan() || item.quantity.abs() > self.max_line_quantity {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Quantity {} of '{}' exceeds the per-line limit of {}",
+                    item.quantity, item.name, self.max_line_quantity
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// ✅ Whole-cart check (carts arriving over the API bypass `add_item_checked`)
+    pub fn check(&self, cart: &Cart) -> EngineResult<()> {
+        if cart.items.len() > self.max_lines {
+            return Err(EngineError::Validation {
+                message: format!("Cart has {} lines; the limit is {}", cart.items.len(), self.max_lines),
+            });
+        }
+        let mut total_quantity = 0.0;
+        for item in &cart.items {
+            self.check_line(item)?;
+            total_quantity += item.quantity.abs();
+        }
+        if total_quantity > self.max_total_quantity {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Cart total quantity {} exceeds the limit of {}",
+                    total_quantity, self.max_total_quantity
+                ),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Cart {
@@ -28,8 +116,11 @@ impl Cart {
         Cart {
             id: uuid::Uuid::new_v4().to_string(),
             customer_id: None,
+            customer_group: None,
+            customer: None,
             items: Vec::new(),
             currency: Currency::LKR,
+            created_at: Some(Utc::now()),
         }
     }
 
@@ -38,15 +129,113 @@ impl Cart {
         self.items.push(item);
     }
 
+    /// 💱 වෙනත් මුදල් වර්ගවලින් ඇති අයිතම cart currency එකට පරිවර්තනය කරන්න
+    /// (unit price and cost; the engine rejects carts that still mix currencies)
+    pub fn convert_items(&mut self, rates: &dyn ExchangeRateProvider, mode: RoundingMode) -> EngineResult<()> {
+        for item in self.items.iter_mut().filter(|item| item.currency != self.currency) {
+            item.price = exchange::convert(item.price, item.currency, self.currency, rates, mode)?;
+            item.cost = item
+                .cost
+                .map(|cost| exchange::convert(cost, item.currency, self.currency, rates, mode))
+                .transpose()?;
+            item.currency = self.currency;
+        }
+        Ok(())
+    }
+
+    /// ➕ සීමා පරීක්ෂා කර අයිතමයක් එකතු කරන්න (Add Item within limits)
+    pub fn add_item_checked(&mut self, item: Item, limits: &CartLimits) -> EngineResult<()> {
+        if self.items.len() >= limits.max_lines {
+            return Err(EngineError::Validation {
+                message: format!("Cart already has the maximum of {} lines", limits.max_lines),
+            });
+        }
+        limits.check_line(&item)?;
+        let total_quantity: f64 = self.items.iter().map(|i| i.quantity.abs()).sum::<f64>() + item.quantity.abs();
+        if total_quantity > limits.max_total_quantity {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "Cart total quantity {} would exceed the limit of {}",
+                    total_quantity, limits.max_total_quantity
+                ),
+            });
+        }
+        self.items.push(item);
+        Ok(())
+    }
+
     /// 💰 උප එකතුව (Subtotal without tax/discounts)
     pub fn subtotal(&self) -> Money {
+        self.subtotal_filtered(|_| true)
+    }
+
+    /// 🔍 තෝරාගත් අයිතම වල උප එකතුව (Subtotal of matching lines)
+    pub fn subtotal_filtered<F>(&self, predicate: F) -> Money
+    where
+        F: Fn(&Item) -> bool,
+    {
         let mut total = Money::zero();
         for item in &self.items {
             // Note: Currency conversion would happen here if mixed currencies
-            if item.currency == self.currency {
+            if item.currency == self.currency && predicate(item) {
                 total = total + item.total();
             }
         }
         total
     }
+
+    /// 🏷️ වට්ටම් සඳහා සුදුසු උප එකතුව (gift cards වැනි දේ හැර)
+    pub fn discountable_subtotal(&self) -> Money {
+        self.subtotal_filtered(Item::is_discountable)
+    }
+
+    /// 🏛️ බදු අය කළ හැකි උප එකතුව
+    pub fn taxable_subtotal(&self) -> Money {
+        self.subtotal_filtered(Item::is_taxable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gift_card_excluded_from_eligible_subtotals() {
+        let mut cart = Cart::new();
+        cart.add_item(Item::new("Shoes", Money::new(3000, 0), 1.0));
+        cart.add_item(
+            Item::new("Gift Card", Money::new(2500, 0), 1.0)
+                .with_metadata("discountable", "false")
+                .with_metadata("taxable", "false"),
+        );
+
+        assert_eq!(cart.subtotal(), Money::new(5500, 0));
+        assert_eq!(cart.discountable_subtotal(), Money::new(3000, 0));
+        assert_eq!(cart.taxable_subtotal(), Money::new(3000, 0));
+    }
+
+    #[test]
+    fn test_cart_limits_enforced() {
+        let limits = CartLimits {
+            max_lines: 2,
+            max_line_quantity: 100.0,
+            max_total_quantity: 150.0,
+        };
+        let mut cart = Cart::new();
+        cart.add_item_checked(Item::new("A", Money::new(1, 0), 10.0), &limits).unwrap();
+        cart.add_item_checked(Item::new("B", Money::new(1, 0), 10.0), &limits).unwrap();
+        // Third line over the line limit
+        assert!(cart.add_item_checked(Item::new("C", Money::new(1, 0), 1.0), &limits).is_err());
+        assert_eq!(cart.items.len(), 2);
+
+        let mut cart = Cart::new();
+        assert!(cart.add_item_checked(Item::new("Bulk", Money::new(1, 0), 1e18), &limits).is_err());
+        assert!(cart.add_item_checked(Item::new("Bad", Money::new(1, 0), f64::NAN), &limits).is_err());
+        cart.add_item_checked(Item::new("A", Money::new(1, 0), 100.0), &limits).unwrap();
+        assert!(cart.add_item_checked(Item::new("B", Money::new(1, 0), 60.0), &limits).is_err());
+
+        // Carts built without checks are caught at calculation time
+        cart.add_item(Item::new("B", Money::new(1, 0), 60.0));
+        assert!(limits.check(&cart).is_err());
+    }
 }
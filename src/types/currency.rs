@@ -1,4 +1,8 @@
+use crate::core::errors::{EngineError, EngineResult};
+use crate::core::money::Money;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
 
 /// ============================================================================
 /// 💱 Currency (මුදල් වර්ග) - සහය දක්වන මුදල් වර්ග
@@ -23,8 +27,238 @@ pub enum Currency {
     Other([char; 3]),
 }
 
+impl Currency {
+    /// 🔤 ISO 4217 කේතය (ISO code)
+    pub fn code(&self) -> String {
+        match self {
+            Currency::LKR => "LKR".to_string(),
+            Currency::USD => "USD".to_string(),
+            Currency::EUR => "EUR".to_string(),
+            Currency::GBP => "GBP".to_string(),
+            Currency::Other(code) => code.iter().collect(),
+        }
+    }
+
+    /// 🔤 ISO කේතයෙන් සාදන්න (Ex: "JPY")
+    pub fn from_code(code: &str) -> Option<Self> {
+        let upper = code.trim().to_uppercase();
+        match upper.as_str() {
+            "LKR" => Some(Currency::LKR),
+            "USD" => Some(Currency::USD),
+            "EUR" => Some(Currency::EUR),
+            "GBP" => Some(Currency::GBP),
+            _ => {
+                let chars: Vec<char> = upper.chars().collect();
+                match chars.as_slice() {
+                    [a, b, c] if chars.iter().all(|ch| ch.is_ascii_uppercase()) => {
+                        Some(Currency::Other([*a, *b, *c]))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// 🪙 Minor unit decimal places (ISO 4217) - JPY: 0, LKR: 2, BHD: 3
+    pub fn minor_units(&self) -> u32 {
+        match self.code().as_str() {
+            "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF"
+            | "UGX" | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+            "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+            _ => 2,
+        }
+    }
+
+    /// Minor units per major unit (10^minor_units)
+    pub fn minor_unit_factor(&self) -> i64 {
+        10_i64.pow(self.minor_units())
+    }
+}
+
 impl Default for Currency {
     fn default() -> Self {
         Currency::LKR
     }
 }
+
+/// ============================================================================
+/// 🗂️ Currency Registry (සහය දක්වන මුදල් ලේඛනය)
+/// ============================================================================
+/// Deployment එකක් ඇත්තටම සහය දක්වන මුදල් වර්ග. `CURRENCY_REGISTRY` env var එකෙන්
+/// (JSON list) පූරණය වී startup එකේදී වලංගු කරයි. Requests, Money සෑදීම ආදී
+/// currency එකක් පිළිගන්නා සෑම තැනකම මෙය පරීක්ෂා කර නොදන්නා/අක්‍රිය කේත ප්‍රතික්ෂේප කරයි.
+///
+/// 💱 One supported currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyInfo {
+    pub code: String,
+    pub symbol: String,
+    pub minor_units: u32,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl CurrencyInfo {
+    pub fn new(code: &str, symbol: &str, minor_units: u32) -> Self {
+        CurrencyInfo {
+            code: code.to_string(),
+            symbol: symbol.to_string(),
+            minor_units,
+            enabled: true,
+        }
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+}
+
+/// 🗂️ Registry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyRegistry {
+    currencies: BTreeMap<String, CurrencyInfo>,
+}
+
+impl CurrencyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// LKR, USD, EUR, GBP
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+        for info in [
+            CurrencyInfo::new("LKR", "Rs.", 2),
+            CurrencyInfo::new("USD", "$", 2),
+            CurrencyInfo::new("EUR", "€", 2),
+            CurrencyInfo::new("GBP", "£", 2),
+        ] {
+            registry.currencies.insert(info.code.clone(), info);
+        }
+        registry
+    }
+
+    /// ➕ Add a currency; its minor units must agree with ISO 4217 (what `Money` uses)
+    pub fn register(&mut self, info: CurrencyInfo) -> EngineResult<()> {
+        let currency = Currency::from_code(&info.code).ok_or_else(|| EngineError::Validation {
+            message: format!("'{}' is not a 3-letter ISO 4217 currency code", info.code),
+        })?;
+        if info.minor_units != currency.minor_units() {
+            return Err(EngineError::Validation {
+                message: format!(
+                    "{} has {} minor units, not {}",
+                    currency.code(),
+                    currency.minor_units(),
+                    info.minor_units
+                ),
+            });
+        }
+        if info.symbol.trim().is_empty() {
+            return Err(EngineError::Validation {
+                message: format!("{} needs a symbol", currency.code()),
+            });
+        }
+        let code = currency.code();
+        self.currencies.insert(code.clone(), CurrencyInfo { code, ..info });
+        Ok(())
+    }
+
+    /// 📥 JSON list, Ex: [{"code":"LKR","symbol":"Rs.","minor_units":2}]
+    pub fn from_json(json: &str) -> EngineResult<Self> {
+        let infos: Vec<CurrencyInfo> = serde_json::from_str(json).map_err(|e| EngineError::Validation {
+            message: format!("Invalid currency registry: {}", e),
+        })?;
+        let mut registry = Self::new();
+        for info in infos {
+            registry.register(info)?;
+        }
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// ⚙️ `CURRENCY_REGISTRY` (JSON) or the standard set
+    pub fn from_env() -> EngineResult<Self> {
+        match std::env::var("CURRENCY_REGISTRY") {
+            Ok(json) => Self::from_json(&json),
+            Err(_) => Ok(Self::standard()),
+        }
+    }
+
+    /// Deployment-wide registry (invalid config falls back to the standard set;
+    /// `main` validates `from_env()` at startup and refuses to start instead)
+    pub fn configured() -> &'static CurrencyRegistry {
+        static REGISTRY: OnceLock<CurrencyRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| Self::from_env().unwrap_or_else(|_| Self::standard()))
+    }
+
+    /// ✅ Startup check: at least one currency enabled
+    pub fn validate(&self) -> EngineResult<()> {
+        if !self.currencies.values().any(|c| c.enabled) {
+            return Err(EngineError::Validation {
+                message: "Currency registry has no enabled currencies".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, code: &str) -> Option<&CurrencyInfo> {
+        self.currencies.get(&code.trim().to_uppercase())
+    }
+
+    /// 🔍 Code => Currency, rejecting unknown or disabled codes
+    pub fn resolve(&self, code: &str) -> EngineResult<Currency> {
+        match self.get(code) {
+            Some(info) if info.enabled => Currency::from_code(&info.code).ok_or_else(|| EngineError::Validation {
+                message: format!("Unsupported currency '{}'", code),
+            }),
+            Some(info) => Err(EngineError::Validation {
+                message: format!("Currency '{}' is disabled", info.code),
+            }),
+            None => Err(EngineError::Validation {
+                message: format!("Unsupported currency '{}'", code),
+            }),
+        }
+    }
+
+    pub fn ensure_supported(&self, currency: Currency) -> EngineResult<()> {
+        self.resolve(&currency.code()).map(|_| ())
+    }
+
+    /// 💰 Money in a supported currency, Ex: (1500, 0, "JPY")
+    pub fn money(&self, major: i64, minor: i64, code: &str) -> EngineResult<Money> {
+        Money::from_major_minor(major, minor, self.resolve(code)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_accepted_disabled_and_unknown_rejected() {
+        let registry = CurrencyRegistry::from_json(
+            r#"[
+                { "code": "LKR", "symbol": "Rs.", "minor_units": 2 },
+                { "code": "jpy", "symbol": "¥", "minor_units": 0 },
+                { "code": "USD", "symbol": "$", "minor_units": 2, "enabled": false }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(registry.resolve("lkr").unwrap(), Currency::LKR);
+        assert_eq!(registry.money(1500, 0, "JPY").unwrap().amount, 1500);
+        assert!(registry.resolve("USD").unwrap_err().to_string().contains("disabled"));
+        assert!(registry.resolve("XYZ").is_err());
+        assert!(registry.ensure_supported(Currency::EUR).is_err());
+
+        // JPY has no minor units
+        assert!(CurrencyRegistry::from_json(r#"[{ "code": "JPY", "symbol": "¥", "minor_units": 2 }]"#).is_err());
+        let all_disabled = r#"[{ "code": "LKR", "symbol": "Rs.", "minor_units": 2, "enabled": false }]"#;
+        assert!(CurrencyRegistry::from_json(all_disabled).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::tax::vat::{is_valid_vat_id, vat_id_country};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// ============================================================================
+/// 👤 Customer Context (පාරිභෝගික තොරතුරු)
+/// ============================================================================
+/// Discount conditions (`CustomerGroup`, `AnyOf`, `FirstPurchase`) පරීක්ෂා කරන්නේ මෙම
+/// තොරතුරු මතය. Context එකක් නොමැති විට පාරිභෝගිකයා නොදන්නා කෙනෙකු ලෙස සැලකේ:
+/// first-purchase discounts ලබා නොදේ.
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CustomerContext {
+    pub customer_id: Option<String>,
+    /// Ex: ["staff", "wholesale"]
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Completed purchases before this one (0 => first purchase)
+    #[serde(default)]
+    pub purchase_count: u32,
+    #[serde(default)]
+    pub registration_date: Option<NaiveDate>,
+    /// Ex: "gold"
+    #[serde(default)]
+    pub loyalty_tier: Option<String>,
+    /// Business buyer's VAT registration (reverse-charge B2B sales)
+    #[serde(default)]
+    pub vat_id: Option<String>,
+}
+
+impl CustomerContext {
+    /// 🆕 Known customer with no purchase history yet
+    pub fn new(customer_id: &str) -> Self {
+        CustomerContext {
+            customer_id: Some(customer_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+
+    pub fn with_purchase_count(mut self, purchase_count: u32) -> Self {
+        self.purchase_count = purchase_count;
+        self
+    }
+
+    pub fn with_vat_id(mut self, vat_id: &str) -> Self {
+        self.vat_id = Some(vat_id.to_string());
+        self
+    }
+
+    /// Business buyer whose VAT ID is well-formed (reverse charge may apply)
+    pub fn has_valid_vat_id(&self) -> bool {
+        self.vat_id.as_deref().is_some_and(is_valid_vat_id)
+    }
+
+    /// Cross-border B2B sale: the buyer's VAT ID is registered outside the target
+    /// jurisdiction's country ("FR…" buying in "FR" is domestic and taxed as usual)
+    pub fn reverse_charge_applies(&self, target_jurisdiction: Option<&str>) -> bool {
+        let Some(target) = target_jurisdiction.and_then(|code| code.split('-').next()) else {
+            return false;
+        };
+        self.vat_id
+            .as_deref()
+            .and_then(vat_id_country)
+            .is_some_and(|country| !country.eq_ignore_ascii_case(target))
+    }
+
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+
+    pub fn is_first_purchase(&self) -> bool {
+        self.purchase_count == 0
+    }
+}
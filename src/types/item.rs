@@ -25,6 +25,10 @@ pub struct Item {
     /// මුදල් වර්ගය (Currency)
     pub currency: Currency,
 
+    /// ඒකක පිරිවැය (Unit Cost) - margin reporting; None = unknown
+    #[serde(default)]
+    pub cost: Option<Money>,
+
     /// අමතර දත්ත (Metadata)
     /// Ex: category, SKU, taxable status
     pub metadata: std::collections::HashMap<String, String>,
@@ -39,6 +43,7 @@ impl Item {
             price,
             quantity,
             currency: Currency::LKR, // Default to LKR
+            cost: None,
             metadata: std::collections::HashMap::new(),
         }
     }
@@ -48,4 +53,32 @@ impl Item {
     pub fn total(&self) -> Money {
         self.price.mul(self.quantity as i64)
     }
+
+    /// 💸 ඒකක පිරිවැය සකසන්න (Set unit cost)
+    pub fn with_cost(mut self, cost: Money) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// 🏷️ Metadata එකක් එකතු කරන්න
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Discount rules වලට සුදුසුද? (metadata `discountable` = "false" නම් නැත)
+    /// Ex: gift cards
+    pub fn is_discountable(&self) -> bool {
+        self.metadata.get("discountable").map(String::as_str) != Some("false")
+    }
+
+    /// කාණ්ඩය (metadata `category`, Ex: "electronics")
+    pub fn category(&self) -> Option<&str> {
+        self.metadata.get("category").map(String::as_str)
+    }
+
+    /// බදු අය කළ හැකිද? (metadata `taxable` = "false" නම් නැත)
+    pub fn is_taxable(&self) -> bool {
+        self.metadata.get("taxable").map(String::as_str) != Some("false")
+    }
 }
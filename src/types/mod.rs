@@ -1,5 +1,6 @@
 pub mod currency;
 pub mod cart;
+pub mod customer;
 pub mod item;
 
 pub use item::Item;